@@ -0,0 +1,193 @@
+//! Matches between two controllers, played as mirrored pairs of games.
+//!
+//! Each pair of games shares one setup (deck order, camps, hands, and which seat goes first)
+//! and one seed for the rest of the game's randomness. Controller A plays player 1 in both
+//! games, but the players' setups are swapped in the second, so each controller plays each side
+//! of the same deal (like duplicate bridge). This cancels out most of the luck of the deal when
+//! comparing the controllers.
+//!
+//! The controllers' own RNGs are seeded separately, from the AI seed, so with the same arena seed
+//! and AI seed, controllers with rollout budgets (e.g. `mcts:2000r`) play every game the same
+//! way. (Time-limited controllers search for as long as the machine allows, so they don't.)
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::decision_times::{DecisionTimes, GameTimes};
+use crate::play_to_end;
+use crate::radlands::camps::CampType;
+use crate::radlands::controllers::ControllerSpec;
+use crate::radlands::events::EventType;
+use crate::radlands::locations::Player;
+use crate::radlands::people::PersonType;
+use crate::radlands::rules::GameRules;
+use crate::radlands::{GameResult, GameState};
+
+/// The player that controller A plays in every game. Only the setups are swapped between the
+/// games of a pair (see `GameState::new_mirrorable`), so that A plays both sides of the deal.
+const A_PLAYER: Player = Player::Player1;
+
+/// The outcome of a single game, from controller A's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    AWins,
+    BWins,
+    Tie,
+}
+
+impl Outcome {
+    /// Returns the outcome of a game where controller A played the given player.
+    fn from_result(result: GameResult, a_player: Player) -> Self {
+        match (result, a_player) {
+            (GameResult::Tie, _) => Outcome::Tie,
+            (GameResult::P1Wins, Player::Player1) | (GameResult::P2Wins, Player::Player2) => {
+                Outcome::AWins
+            }
+            _ => Outcome::BWins,
+        }
+    }
+
+    /// Returns controller A's score for this outcome (1 for a win, 0.5 for a tie).
+    fn a_score(self) -> f64 {
+        match self {
+            Outcome::AWins => 1.0,
+            Outcome::BWins => 0.0,
+            Outcome::Tie => 0.5,
+        }
+    }
+}
+
+/// Plays `num_pairs` mirrored pairs of games between the controllers described by `a` and `b`,
+/// printing the result of each pair and then the totals (including each controller's decision
+/// times). The seeds for each pair are generated from `seed`, so the same arguments always
+/// produce the same deals, and the controllers' seeds are generated from `ai_seed`.
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    a: ControllerSpec,
+    b: ControllerSpec,
+    num_pairs: usize,
+    seed: u64,
+    ai_seed: u64,
+    rules: GameRules,
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+) {
+    println!("Controller A: {a}");
+    println!("Controller B: {b}");
+    println!(
+        "Playing {num_pairs} mirrored pairs of games (arena seed {seed}, AI seed {ai_seed})...\n"
+    );
+
+    let mut seed_rng = StdRng::seed_from_u64(seed);
+    let mut ai_seed_rng = StdRng::seed_from_u64(ai_seed);
+    let mut a_score = 0.0;
+    let mut games = [0; 3]; // A wins, B wins, ties
+    let mut pairs = [0; 3]; // A ahead, B ahead, even
+    let mut a_times = DecisionTimes::default();
+    let mut b_times = DecisionTimes::default();
+    for pair_index in 0..num_pairs {
+        let setup_seed = seed_rng.gen();
+        let play_seed = seed_rng.gen();
+        let [a_seed, b_seed]: [u64; 2] = ai_seed_rng.gen();
+
+        let mut play_game = |mirrored: bool| {
+            let mut controller_a = a.build(A_PLAYER, a_seed);
+            let mut controller_b = b.build(A_PLAYER.other(), b_seed);
+            let (mut game_state, choice) = GameState::new_mirrorable(
+                camp_types,
+                person_types,
+                event_types,
+                rules,
+                setup_seed,
+                play_seed,
+                mirrored,
+            );
+            let mut times = GameTimes::default();
+            let result = play_to_end(
+                &mut game_state,
+                choice,
+                controller_a.as_mut(),
+                controller_b.as_mut(),
+                Some(&mut times),
+            );
+            a_times.merge(times.player(A_PLAYER));
+            b_times.merge(times.player(A_PLAYER.other()));
+            Outcome::from_result(result, A_PLAYER)
+        };
+        let outcomes = [play_game(false), play_game(true)];
+
+        let pair_score: f64 = outcomes.iter().map(|outcome| outcome.a_score()).sum();
+        a_score += pair_score;
+        for outcome in outcomes {
+            games[outcome as usize] += 1;
+        }
+        let pair_outcome = match pair_score {
+            score if score > 1.0 => Outcome::AWins,
+            score if score < 1.0 => Outcome::BWins,
+            _ => Outcome::Tie,
+        };
+        pairs[pair_outcome as usize] += 1;
+
+        println!(
+            "Pair {:>4} (setup seed {setup_seed}, play seed {play_seed}): {:?} / {:?}",
+            pair_index + 1,
+            outcomes[0],
+            outcomes[1],
+        );
+    }
+
+    let num_games = num_pairs * 2;
+    println!(
+        "\nGames: A won {}, B won {}, {} tied",
+        games[0], games[1], games[2]
+    );
+    println!(
+        "Pairs: A ahead in {}, B ahead in {}, {} even",
+        pairs[0], pairs[1], pairs[2]
+    );
+    if num_games > 0 {
+        println!(
+            "Controller A's score: {a_score} / {num_games} ({:.1}%)",
+            a_score / num_games as f64 * 100.0
+        );
+    }
+    println!("Controller A's decisions: {a_times}");
+    println!("Controller B's decisions: {b_times}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::radlands::card_set::CardSet;
+    use crate::radlands::choices::Choice;
+
+    #[test]
+    fn controller_a_plays_both_sides_of_a_pair() {
+        let card_set = CardSet::standard();
+        let new_game = |mirrored| {
+            let (mut game_state, mut choice) = GameState::new_mirrorable(
+                &card_set.camp_types,
+                &card_set.person_types,
+                &card_set.event_types,
+                GameRules::default(),
+                12,
+                34,
+                mirrored,
+            );
+            // both players keep the first camps they were dealt, putting them on the boards
+            while let Choice::CampSelect(_) = choice {
+                choice = choice.choose(&mut game_state, 0).unwrap();
+            }
+            game_state
+        };
+        let (game, mirrored_game) = (new_game(false), new_game(true));
+
+        // A's starting position in the mirrored game is B's in the first game, and vice versa
+        let b_player = A_PLAYER.other();
+        assert_ne!(game.player(A_PLAYER), game.player(b_player));
+        assert_eq!(mirrored_game.player(A_PLAYER), game.player(b_player));
+        assert_eq!(mirrored_game.player(b_player), game.player(A_PLAYER));
+        assert_ne!(mirrored_game.cur_player, game.cur_player);
+    }
+}
@@ -0,0 +1,464 @@
+//! Autosaving of the game in progress, so that an interrupted game can be resumed.
+//!
+//! The autosave file stores the game's seed, the seed for the AI controllers' RNGs, its rules
+//! (whether mulligans were asked for and the rules fidelity), each player's controller spec, and
+//! the option chosen for every choice so far. Since games created with the same seed play out
+//! identically given the same choices, replaying the saved choices recovers the exact game state
+//! and history. The file is rewritten after every choice and removed when
+//! the game ends, so if it exists at launch, the last game was interrupted.
+//!
+//! The game is saved on one line, followed by a line for each note attached to it:
+//!
+//! ```text
+//! seed=42 ai_seed=7 mulligans=false fidelity=casual p1=mcts:3 p2=human moves=0,3,1,2
+//! note 1 Playing a person here leaves the left camp open.
+//! ```
+//!
+//! A note line gives the index of the move that the note is attached to (in the `moves` list),
+//! then the note's text. The same format is written by `--export-game` and read by
+//! `--load-game`. Fields that were added to the format later can be left out, so that games
+//! saved by older versions can still be loaded; they default to how those versions played
+//! (`ai_seed=0` and `fidelity=casual`).
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
+
+use crate::golden::parse_fields_with_defaults;
+use crate::radlands::camps::CampType;
+use crate::radlands::choices::Choice;
+use crate::radlands::controllers::ControllerSpec;
+use crate::radlands::events::EventType;
+use crate::radlands::locations::Player;
+use crate::radlands::people::PersonType;
+use crate::radlands::rules::GameRules;
+use crate::radlands::{GameResult, GameState};
+
+/// Returns the path of the autosave file.
+fn autosave_path() -> PathBuf {
+    std::env::temp_dir().join("radbot-autosave.txt")
+}
+
+/// A game in progress, as recorded in the autosave file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedGame {
+    /// The seed the game was created with.
+    pub seed: u64,
+
+    /// The seed that the controllers' own RNGs are derived from (see `controller_seed`). It's
+    /// separate from the game's seed, so either can be fixed without the other.
+    pub ai_seed: u64,
+
+    /// The rules the game was created with.
+    pub rules: GameRules,
+
+    /// The controller for player 1.
+    pub p1: ControllerSpec,
+
+    /// The controller for player 2.
+    pub p2: ControllerSpec,
+
+    /// The index of the option chosen for each choice so far.
+    pub moves: Vec<usize>,
+
+    /// The notes attached to the moves. They're saved on lines
+    /// of their own (see `to_file_contents`), so they aren't part of the game's line.
+    pub notes: Vec<Note>,
+}
+
+/// A free-text note attached to a move of a game, e.g. to annotate it for study.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    /// The index of the move (in `SavedGame::moves`) that the note is attached to.
+    pub move_index: usize,
+
+    /// The text of the note, on one line.
+    pub text: String,
+}
+
+impl SavedGame {
+    /// Returns a new game with random seeds and no moves made yet.
+    pub fn new_game(rules: GameRules, p1: ControllerSpec, p2: ControllerSpec) -> Self {
+        SavedGame {
+            seed: thread_rng().gen(),
+            ai_seed: thread_rng().gen(),
+            rules,
+            p1,
+            p2,
+            moves: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    /// Returns the seed for the given player's controller (see `ControllerSpec::build`). With
+    /// the same seeds, a game between controllers with rollout budgets is played out the same
+    /// way every time.
+    pub fn controller_seed(&self, player: Player) -> u64 {
+        let [p1_seed, p2_seed]: [u64; 2] = StdRng::seed_from_u64(self.ai_seed).gen();
+        match player {
+            Player::Player1 => p1_seed,
+            Player::Player2 => p2_seed,
+        }
+    }
+
+    /// Creates the game and replays the saved moves, calling `on_move` with the state, choice,
+    /// and chosen option before each move. Returns the resulting state and choice (or the result,
+    /// if the game has ended), or an error if the moves aren't valid for the game.
+    pub fn replay<'ctype>(
+        &self,
+        camp_types: &'ctype [CampType],
+        person_types: &'ctype [PersonType],
+        event_types: &'ctype [EventType],
+        mut on_move: impl FnMut(&GameState<'ctype>, &Choice<'ctype>, usize),
+    ) -> Result<(GameState<'ctype>, Result<Choice<'ctype>, GameResult>), String> {
+        let (mut game_state, choice) =
+            GameState::new_seeded(camp_types, person_types, event_types, self.rules, self.seed);
+
+        let mut cur_choice = Ok(choice);
+        for (ply, &option) in self.moves.iter().enumerate() {
+            let choice = match &cur_choice {
+                Ok(choice) => choice,
+                Err(_) => return Err(format!("the game ended before move {ply}")),
+            };
+            let num_options = choice.num_options();
+            if option >= num_options {
+                return Err(format!(
+                    "move {ply} chose option {option}, but there are only {num_options} options"
+                ));
+            }
+
+            on_move(&game_state, choice, option);
+            cur_choice = choice.choose(&mut game_state, option);
+        }
+
+        Ok((game_state, cur_choice))
+    }
+
+    /// Returns the contents of a file saving the game: the game's line, followed by a line for
+    /// each note.
+    pub fn to_file_contents(&self) -> String {
+        let mut contents = format!("{self}\n");
+        for note in &self.notes {
+            contents += &format!("note {} {}\n", note.move_index, note.text);
+        }
+        contents
+    }
+
+    /// Parses the contents of a file saving a game (see `to_file_contents`).
+    pub fn from_file_contents(contents: &str) -> Result<Self, String> {
+        let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+        let mut saved_game: SavedGame = lines.next().ok_or("the file is empty")?.parse()?;
+        for line in lines {
+            let (move_index, text) = line
+                .strip_prefix("note ")
+                .and_then(|note| note.split_once(' '))
+                .ok_or_else(|| format!("invalid note line: {line:?}"))?;
+            let move_index = move_index
+                .parse()
+                .ok()
+                .filter(|&index| index < saved_game.moves.len())
+                .ok_or_else(|| format!("invalid move index for a note: {move_index:?}"))?;
+            saved_game.notes.push(Note {
+                move_index,
+                text: text.to_string(),
+            });
+        }
+        Ok(saved_game)
+    }
+
+    /// Reads the game saved in the given file (e.g. by `--export-game`).
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        Self::from_file_contents(&contents)
+            .map_err(|message| format!("Invalid saved game in {}: {message}", path.display()))
+    }
+}
+
+impl fmt::Display for SavedGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "seed={} ai_seed={} mulligans={} fidelity={} p1={} p2={} moves={}",
+            self.seed,
+            self.ai_seed,
+            self.rules.mulligans,
+            self.rules.fidelity,
+            self.p1,
+            self.p2,
+            self.moves.iter().join(","),
+        )
+    }
+}
+
+impl FromStr for SavedGame {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let [seed, ai_seed, mulligans, fidelity, p1, p2, moves] = parse_fields_with_defaults(
+            line.trim(),
+            [
+                ("seed", None),
+                // games saved before the AIs had their own seeds
+                ("ai_seed", Some("0")),
+                ("mulligans", None),
+                // games saved before there was a strict fidelity
+                ("fidelity", Some("casual")),
+                ("p1", None),
+                ("p2", None),
+                ("moves", None),
+            ],
+        )?;
+
+        Ok(SavedGame {
+            seed: seed
+                .parse()
+                .map_err(|_| format!("invalid seed: {seed:?}"))?,
+            ai_seed: ai_seed
+                .parse()
+                .map_err(|_| format!("invalid AI seed: {ai_seed:?}"))?,
+            rules: GameRules {
+                mulligans: mulligans
+                    .parse()
+                    .map_err(|_| format!("invalid mulligans flag: {mulligans:?}"))?,
+                fidelity: fidelity.parse()?,
+            },
+            p1: p1.parse()?,
+            p2: p2.parse()?,
+            moves: moves
+                .split(',')
+                .filter(|m| !m.is_empty())
+                .map(|m| m.parse().map_err(|_| format!("invalid move: {m:?}")))
+                .collect::<Result<_, _>>()?,
+            notes: Vec::new(),
+        })
+    }
+}
+
+/// The game being autosaved, as of its last save.
+static CURRENT_GAME: Mutex<Option<SavedGame>> = Mutex::new(None);
+
+/// Returns the game being autosaved (with the moves made so far), if any. While a controller is
+/// choosing, this is the game as it was when the choice was reached.
+pub fn current_game() -> Option<SavedGame> {
+    CURRENT_GAME.lock().unwrap().clone()
+}
+
+/// Keeps the autosave file up to date with a game in progress. (Only one game is autosaved at a
+/// time; it's kept in `CURRENT_GAME`, so that notes can be added to it from the UI.)
+pub struct Autosave {
+    _private: (),
+}
+
+impl Autosave {
+    /// Starts autosaving the given game, and saves it immediately.
+    pub fn start(saved_game: SavedGame) -> Self {
+        update_current_game(|current_game| *current_game = Some(saved_game));
+        Autosave { _private: () }
+    }
+
+    /// Records that the given option was chosen, and saves the game.
+    pub fn record_move(&mut self, option: usize) {
+        update_current_game(|current_game| {
+            if let Some(saved_game) = current_game {
+                saved_game.moves.push(option);
+            }
+        });
+    }
+
+    /// Stops autosaving because the game has ended, and removes the autosave file.
+    pub fn finish(self) {
+        *CURRENT_GAME.lock().unwrap() = None;
+        let _ = fs::remove_file(autosave_path());
+    }
+}
+
+/// Attaches a note to the game being autosaved, and saves it. Does nothing if no game is being
+/// autosaved.
+pub fn add_note(note: Note) {
+    update_current_game(|current_game| {
+        if let Some(saved_game) = current_game {
+            saved_game.notes.push(note);
+        }
+    });
+}
+
+/// Updates the game being autosaved, and saves it (if there still is one).
+fn update_current_game(update: impl FnOnce(&mut Option<SavedGame>)) {
+    let mut current_game = CURRENT_GAME.lock().unwrap();
+    update(&mut current_game);
+    let saved_game = match current_game.as_ref() {
+        Some(saved_game) => saved_game,
+        None => return,
+    };
+
+    // write to a temporary file and rename it, so that the autosave file is never left
+    // half-written; autosaving is best-effort, so errors are ignored
+    let path = autosave_path();
+    let temp_path = path.with_extension("tmp");
+    if fs::write(&temp_path, saved_game.to_file_contents()).is_ok() {
+        let _ = fs::rename(temp_path, path);
+    }
+}
+
+/// If the autosave file contains an interrupted game, asks the user whether to resume it.
+/// Returns the game to resume, if any. An autosave that is declined or can't be resumed is
+/// removed.
+pub fn offer_resume(
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+) -> Option<SavedGame> {
+    let path = autosave_path();
+    let contents = fs::read_to_string(&path).ok()?;
+
+    let saved_game =
+        SavedGame::from_file_contents(&contents).and_then(|saved_game| {
+            match saved_game.replay(camp_types, person_types, event_types, |_, _, _| {})? {
+                (_, Ok(_)) => Ok(saved_game),
+                (_, Err(_)) => Err("the game already ended".to_string()),
+            }
+        });
+    let saved_game = match saved_game {
+        Ok(saved_game) => saved_game,
+        Err(message) => {
+            println!("Discarding an autosaved game that can't be resumed: {message}\n");
+            let _ = fs::remove_file(&path);
+            return None;
+        }
+    };
+
+    print!(
+        "Found an interrupted game ({} vs. {}, {} choices made). Resume it? [Y/n] ",
+        saved_game.p1,
+        saved_game.p2,
+        saved_game.moves.len(),
+    );
+    io::stdout().flush().expect("Failed to flush stdout");
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .expect("Failed to read from stdin");
+    println!();
+
+    match answer.trim().to_lowercase().as_str() {
+        "" | "y" | "yes" => Some(saved_game),
+        _ => {
+            let _ = fs::remove_file(&path);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::radlands::controllers::auto_actions::{AutoActions, AutoEndTurn};
+    use crate::radlands::controllers::SearchBudget;
+    use crate::radlands::end_turn_warnings::EndTurnWarnings;
+    use crate::radlands::rules::RulesFidelity;
+
+    #[test]
+    fn saved_games_round_trip() {
+        let line = "seed=42 ai_seed=7 mulligans=true fidelity=strict p1=mcts:3 p2=human moves=0,3";
+        let saved_game: SavedGame = line.parse().unwrap();
+        assert_eq!(saved_game.to_string(), line);
+    }
+
+    #[test]
+    fn saved_games_with_notes_round_trip_through_files() {
+        let mut saved_game: SavedGame =
+            "seed=42 ai_seed=7 mulligans=false p1=mcts:3 p2=human moves=0,3,1,2"
+                .parse()
+                .unwrap();
+        saved_game.notes = vec![
+            Note {
+                move_index: 1,
+                text: "Playing a person here leaves the left camp open.".to_string(),
+            },
+            Note {
+                move_index: 1,
+                text: "A second note on the same move".to_string(),
+            },
+            Note {
+                move_index: 3,
+                text: "note 0 looks like a note line".to_string(),
+            },
+        ];
+        let contents = saved_game.to_file_contents();
+        assert_eq!(contents.lines().count(), 4);
+        assert_eq!(SavedGame::from_file_contents(&contents), Ok(saved_game));
+
+        // a note must be attached to one of the game's moves
+        let contents = "seed=42 mulligans=false p1=mcts:3 p2=human moves=0,3\nnote 2 Too late\n";
+        assert!(SavedGame::from_file_contents(contents).is_err());
+    }
+
+    #[test]
+    fn fields_added_later_can_be_left_out() {
+        let saved_game: SavedGame = "seed=42 mulligans=false p1=mcts:3 p2=human moves=0,3,1,2"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            saved_game,
+            SavedGame {
+                seed: 42,
+                ai_seed: 0,
+                rules: GameRules {
+                    mulligans: false,
+                    fidelity: RulesFidelity::Casual,
+                },
+                p1: ControllerSpec::Mcts {
+                    budget: SearchBudget::Time(Duration::from_secs(3)),
+                    early_stop: true,
+                },
+                p2: ControllerSpec::Human {
+                    auto_actions: AutoActions::NONE,
+                },
+                moves: vec![0, 3, 1, 2],
+                notes: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn human_specs_without_end_turn_warnings_have_none() {
+        let saved_game: SavedGame = "seed=42 mulligans=false p1=human:prompt:skip p2=random moves="
+            .parse()
+            .unwrap();
+        let expected = AutoActions {
+            end_turn: AutoEndTurn::Prompt,
+            skip_unusable_icon_effects: true,
+            end_turn_warnings: EndTurnWarnings::NONE,
+        };
+        assert_eq!(
+            saved_game.p1,
+            ControllerSpec::Human {
+                auto_actions: expected
+            }
+        );
+
+        let spec = "human:prompt:skip:water,lethal";
+        assert_eq!(spec.parse::<ControllerSpec>().unwrap().to_string(), spec);
+        assert!("human:prompt:skip:water:more"
+            .parse::<ControllerSpec>()
+            .is_err());
+    }
+
+    #[test]
+    fn fields_must_be_in_order() {
+        let line = "ai_seed=7 seed=42 mulligans=true fidelity=strict p1=random p2=human moves=";
+        assert!(line.parse::<SavedGame>().is_err());
+        let line = "seed=42 mulligans=true fidelity=strict p1=random p2=human ai_seed=7 moves=";
+        assert!(line.parse::<SavedGame>().is_err());
+    }
+}
@@ -0,0 +1,431 @@
+//! Structured descriptions of every card type, for documentation and external tools (such as
+//! deck builders and wikis).
+//!
+//! Run RadBot with `--dump-cards json` or `--dump-cards markdown` to print them. The JSON is an
+//! array with one object per card type:
+//!
+//! ```text
+//! {"kind":"person","name":"Vanguard","cost":1,"num_in_deck":2,"junk_effect":"Raid",
+//!  "abilities":[{"cost":1,"description":"Damage, then opponent does damage back to you",
+//!  "optional":false}],"enter_play_effect":"Punk","traits":[],"on_junk":null}
+//! ```
+//!
+//! An `optional` ability is one whose user may decline its effect after paying for it.
+//!
+//! A card with its own behavior when junked has an `on_junk` object instead of null, with the
+//! behavior's `description` and whether it `replaces_junk_effect` (rather than following it).
+//! Camps have `num_initial_cards` instead of a cost, count in the deck, and junk effect, and
+//! their traits include what they do when damaged or destroyed. Events have `resolve_turns` and
+//! an `effect` instead of abilities. Every description is in English, regardless of the loaded
+//! locale.
+
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::json::json_string;
+use crate::radlands::camps::CampType;
+use crate::radlands::events::EventType;
+use crate::radlands::people::PersonType;
+use crate::radlands::{IconEffect, OnJunk, RAIDERS_EVENT};
+
+/// The format to dump the card descriptions in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    Json,
+    Markdown,
+}
+
+/// What kind of card a card type is, with the details that only that kind of card has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CardKind {
+    Camp {
+        /// The number of cards the camp grants at the start of the game.
+        num_initial_cards: u32,
+    },
+    Person {
+        /// The text of the person's enter-play effect, if any.
+        enter_play_effect: Option<&'static str>,
+    },
+    Event {
+        /// The number of turns the event resolves in. (Zero is immediate.)
+        resolve_turns: u8,
+
+        /// The text of the event's effect when it resolves.
+        effect: &'static str,
+    },
+}
+
+impl CardKind {
+    /// Returns the name of this kind of card, as used in the dump.
+    fn name(&self) -> &'static str {
+        match self {
+            CardKind::Camp { .. } => "camp",
+            CardKind::Person { .. } => "person",
+            CardKind::Event { .. } => "event",
+        }
+    }
+}
+
+/// A card's own behavior when junked (see `OnJunk`), as printed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnJunkDescription {
+    pub description: &'static str,
+    pub replaces_junk_effect: bool,
+}
+
+impl From<OnJunk> for OnJunkDescription {
+    fn from(on_junk: OnJunk) -> Self {
+        OnJunkDescription {
+            description: on_junk.description,
+            replaces_junk_effect: on_junk.replaces_junk_effect,
+        }
+    }
+}
+
+/// An ability on a card, as printed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbilityDescription {
+    pub cost: u32,
+    pub description: String,
+    pub optional: bool,
+}
+
+/// Everything printed on (or implied by) a card type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardDescription {
+    pub name: &'static str,
+    pub kind: CardKind,
+
+    /// The water cost to play the card (None for camps, which aren't played).
+    pub cost: Option<u32>,
+
+    /// How many of the card are in the deck (None for camps, which aren't in the deck).
+    pub num_in_deck: Option<u32>,
+
+    /// The card's junk effect (None for camps, which can't be junked).
+    pub junk_effect: Option<IconEffect>,
+
+    /// The card's own behavior when junked, if any.
+    pub on_junk: Option<OnJunkDescription>,
+
+    pub abilities: Vec<AbilityDescription>,
+
+    /// The card's traits, which apply without being used (e.g. entering play ready).
+    pub traits: Vec<&'static str>,
+}
+
+/// Returns the descriptions of every camp, person, and event type, in that order (with
+/// Raiders, which isn't in the deck, after the other events). Must be called before a locale is
+/// loaded, so that the ability descriptions are in English.
+pub fn describe_all(
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+) -> Vec<CardDescription> {
+    let camps = camp_types.iter().map(|camp_type| CardDescription {
+        name: camp_type.name,
+        kind: CardKind::Camp {
+            num_initial_cards: camp_type.num_initial_cards,
+        },
+        cost: None,
+        num_in_deck: None,
+        junk_effect: None,
+        on_junk: None,
+        abilities: camp_type
+            .abilities
+            .iter()
+            .map(|ability| AbilityDescription {
+                cost: ability.printed_cost(),
+                description: ability.description(),
+                optional: ability.is_optional(),
+            })
+            .collect(),
+        traits: (camp_type.special_type.trait_description().into_iter())
+            .chain(camp_type.on_damaged.map(|trigger| trigger.description))
+            .chain(camp_type.on_destroyed.map(|trigger| trigger.description))
+            .collect(),
+    });
+
+    let people = person_types.iter().map(|person_type| {
+        let mut traits = Vec::new();
+        if person_type.enters_play_ready {
+            traits.push("Enters play ready");
+        }
+        if person_type.enter_play_optional {
+            traits.push("Enter-play effect is optional");
+        }
+        traits.extend(
+            person_type
+                .passive_effects
+                .iter()
+                .map(|passive_effect| passive_effect.description()),
+        );
+        CardDescription {
+            name: person_type.name,
+            kind: CardKind::Person {
+                enter_play_effect: person_type.enter_play_effect,
+            },
+            cost: Some(person_type.cost),
+            num_in_deck: Some(person_type.num_in_deck),
+            junk_effect: Some(person_type.junk_effect),
+            on_junk: person_type.on_junk.map(OnJunkDescription::from),
+            abilities: person_type
+                .abilities
+                .iter()
+                .map(|ability| AbilityDescription {
+                    cost: ability.printed_cost(),
+                    description: ability.description(),
+                    optional: ability.is_optional(),
+                })
+                .collect(),
+            traits,
+        }
+    });
+
+    let events = event_types
+        .iter()
+        .chain([&RAIDERS_EVENT])
+        .map(|event_type| {
+            let is_raiders = event_type == &RAIDERS_EVENT;
+            CardDescription {
+                name: event_type.name,
+                kind: CardKind::Event {
+                    resolve_turns: event_type.resolve_turns,
+                    effect: event_type.description,
+                },
+                // Raiders is only ever put into play by a Raid effect, so it has no cost or
+                // junk effect
+                cost: (!is_raiders).then_some(event_type.cost),
+                num_in_deck: Some(event_type.num_in_deck),
+                junk_effect: (!is_raiders).then_some(event_type.junk_effect),
+                on_junk: event_type.on_junk.map(OnJunkDescription::from),
+                abilities: Vec::new(),
+                traits: event_type
+                    .optional
+                    .then_some("Effect is optional")
+                    .into_iter()
+                    .collect(),
+            }
+        });
+
+    camps.chain(people).chain(events).collect()
+}
+
+/// Returns the card descriptions in the given format.
+pub fn format(cards: &[CardDescription], format: DumpFormat) -> String {
+    match format {
+        DumpFormat::Json => to_json(cards),
+        DumpFormat::Markdown => to_markdown(cards),
+    }
+}
+
+/// Returns the card descriptions as a JSON array, on one line per card.
+fn to_json(cards: &[CardDescription]) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "null".to_string());
+
+    let mut json = String::from("[\n");
+    for (i, card) in cards.iter().enumerate() {
+        let abilities = card
+            .abilities
+            .iter()
+            .map(|ability| {
+                format!(
+                    r#"{{"cost":{},"description":{},"optional":{}}}"#,
+                    ability.cost,
+                    json_string(&ability.description),
+                    ability.optional,
+                )
+            })
+            .join(",");
+        let traits = card.traits.iter().map(|text| json_string(text)).join(",");
+        let on_junk = optional(card.on_junk.as_ref().map(|on_junk| {
+            format!(
+                r#"{{"description":{},"replaces_junk_effect":{}}}"#,
+                json_string(on_junk.description),
+                on_junk.replaces_junk_effect
+            )
+        }));
+        write!(
+            json,
+            concat!(
+                r#"{{"kind":"{}","name":{},"cost":{},"num_in_deck":{},"junk_effect":{},"#,
+                r#""abilities":[{}],"#,
+            ),
+            card.kind.name(),
+            json_string(card.name),
+            optional(card.cost.map(|cost| cost.to_string())),
+            optional(card.num_in_deck.map(|count| count.to_string())),
+            optional(
+                card.junk_effect
+                    .map(|effect| json_string(&format!("{effect:?}")))
+            ),
+            abilities,
+        )
+        .unwrap();
+        match &card.kind {
+            CardKind::Camp { num_initial_cards } => {
+                write!(json, r#""num_initial_cards":{num_initial_cards},"#).unwrap();
+            }
+            CardKind::Person { enter_play_effect } => {
+                let enter_play_effect = optional(enter_play_effect.map(json_string));
+                write!(json, r#""enter_play_effect":{enter_play_effect},"#).unwrap();
+            }
+            CardKind::Event {
+                resolve_turns,
+                effect,
+            } => {
+                write!(
+                    json,
+                    r#""resolve_turns":{resolve_turns},"effect":{},"#,
+                    json_string(effect)
+                )
+                .unwrap();
+            }
+        }
+        write!(json, r#""traits":[{traits}],"on_junk":{on_junk}}}"#).unwrap();
+        json.push_str(if i + 1 < cards.len() { ",\n" } else { "\n" });
+    }
+    json.push_str("]\n");
+    json
+}
+
+/// Escapes text for a cell of a Markdown table, which ends at a `|` or a line break.
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// Returns the card descriptions as a Markdown document, with a table for each kind of card.
+fn to_markdown(cards: &[CardDescription]) -> String {
+    let abilities = |card: &CardDescription| {
+        card.abilities
+            .iter()
+            .map(|ability| {
+                let optional = if ability.optional { ", optional" } else { "" };
+                format!("{} ({} water{optional})", ability.description, ability.cost)
+            })
+            .chain(card.traits.iter().map(|text| format!("*Trait:* {text}")))
+            .map(|text| markdown_cell(&text))
+            .join("<br>")
+    };
+    let junk_effect = |card: &CardDescription| match (card.junk_effect, &card.on_junk) {
+        (_, Some(on_junk)) if on_junk.replaces_junk_effect => markdown_cell(on_junk.description),
+        (Some(effect), Some(on_junk)) => {
+            format!("{effect:?}, then {}", markdown_cell(on_junk.description))
+        }
+        (Some(effect), None) => format!("{effect:?}"),
+        (None, _) => "-".to_string(),
+    };
+    let cost = |card: &CardDescription| match card.cost {
+        Some(cost) => cost.to_string(),
+        None => "-".to_string(),
+    };
+
+    let mut markdown = String::from("# Cards\n");
+
+    markdown.push_str(
+        "\n## Camps\n\n\
+         | Name | Initial cards | Abilities |\n\
+         | --- | --- | --- |\n",
+    );
+    for card in cards {
+        if let CardKind::Camp { num_initial_cards } = card.kind {
+            writeln!(
+                markdown,
+                "| {} | {num_initial_cards} | {} |",
+                markdown_cell(card.name),
+                abilities(card)
+            )
+            .unwrap();
+        }
+    }
+
+    markdown.push_str(
+        "\n## People\n\n\
+         | Name | Cost | In deck | Junk | Abilities | Enter play |\n\
+         | --- | --- | --- | --- | --- | --- |\n",
+    );
+    for card in cards {
+        if let CardKind::Person { enter_play_effect } = card.kind {
+            writeln!(
+                markdown,
+                "| {} | {} | {} | {} | {} | {} |",
+                markdown_cell(card.name),
+                cost(card),
+                card.num_in_deck.unwrap_or(0),
+                junk_effect(card),
+                abilities(card),
+                markdown_cell(enter_play_effect.unwrap_or("-")),
+            )
+            .unwrap();
+        }
+    }
+
+    markdown.push_str(
+        "\n## Events\n\n\
+         | Name | Cost | In deck | Junk | Resolves in | Effect |\n\
+         | --- | --- | --- | --- | --- | --- |\n",
+    );
+    for card in cards {
+        if let CardKind::Event {
+            resolve_turns,
+            effect,
+        } = card.kind
+        {
+            let resolve_turns = match resolve_turns {
+                0 => "immediately".to_string(),
+                1 => "1 turn".to_string(),
+                turns => format!("{turns} turns"),
+            };
+            writeln!(
+                markdown,
+                "| {} | {} | {} | {} | {resolve_turns} | {} |",
+                markdown_cell(card.name),
+                cost(card),
+                card.num_in_deck.unwrap_or(0),
+                junk_effect(card),
+                markdown_cell(effect),
+            )
+            .unwrap();
+        }
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_cells_escape_pipes_and_line_breaks() {
+        let card = CardDescription {
+            name: "Pipe | Dream",
+            kind: CardKind::Person {
+                enter_play_effect: Some("Draw\nthen discard"),
+            },
+            cost: Some(1),
+            num_in_deck: Some(2),
+            junk_effect: Some(IconEffect::Water),
+            on_junk: None,
+            abilities: vec![AbilityDescription {
+                cost: 1,
+                description: "Damage | Restore".to_string(),
+                optional: false,
+            }],
+            traits: vec!["Either | or"],
+        };
+        let markdown = to_markdown(&[card]);
+        let row = markdown
+            .lines()
+            .find(|line| line.starts_with("| Pipe"))
+            .unwrap();
+        assert_eq!(
+            row,
+            "| Pipe \\| Dream | 1 | 2 | Water \
+             | Damage \\| Restore (1 water)<br>*Trait:* Either \\| or | Draw<br>then discard |"
+        );
+        // every unescaped '|' is a cell boundary, so the row has the table's 6 columns
+        let num_boundaries = row.matches('|').count() - row.matches("\\|").count();
+        assert_eq!(num_boundaries, 7);
+    }
+}
@@ -0,0 +1,56 @@
+/// A discard pile: an ordered stack of cards, where the most recently discarded card is on top.
+/// Which cards are in the pile and the order they were discarded in are public information.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiscardPile<CardType> {
+    /// The cards in the pile, from the bottom (the first discarded) to the top.
+    cards: Vec<CardType>,
+}
+
+impl<CardType: Copy> DiscardPile<CardType> {
+    /// Creates a new, empty [`DiscardPile`].
+    pub fn new() -> Self {
+        Self { cards: Vec::new() }
+    }
+
+    /// Puts the given card on top of the pile. All discarding and junking goes through this.
+    pub fn push(&mut self, card_type: CardType) {
+        self.cards.push(card_type);
+    }
+
+    /// Returns the card on top of the pile (the most recently discarded), if any.
+    pub fn top(&self) -> Option<CardType> {
+        self.cards.last().copied()
+    }
+
+    /// Returns an iterator over the cards in the pile from the top down, i.e. from the most
+    /// recently discarded to the first discarded.
+    pub fn iter_recent(&self) -> impl Iterator<Item = CardType> + '_ {
+        self.cards.iter().rev().copied()
+    }
+
+    /// Returns the cards in the pile, in the order they were discarded (the top card is last).
+    pub fn as_slice(&self) -> &[CardType] {
+        &self.cards
+    }
+
+    /// Returns the number of cards in the pile.
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// Returns `true` if the pile contains no cards.
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// Removes all the cards from the pile, returning them in the order they were discarded.
+    pub fn take_all(&mut self) -> Vec<CardType> {
+        std::mem::take(&mut self.cards)
+    }
+}
+
+impl<CardType: Copy> Default for DiscardPile<CardType> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,4 @@
 use std::cmp;
-use std::hash::Hash;
 
 use super::Cards;
 
@@ -25,7 +24,7 @@ struct CardTypeState<CardType> {
     num_drawn: usize,
 }
 
-impl<CardType: Hash + Eq + Copy> Draws<CardType> {
+impl<CardType: Ord + Copy> Draws<CardType> {
     pub(super) fn new(cards: &Cards<CardType>, n: usize) -> Self {
         if cards.is_empty() {
             return Self {
@@ -43,14 +42,14 @@ impl<CardType: Hash + Eq + Copy> Draws<CardType> {
 
         Self {
             prob_denom_recip: 1.0 / (prob_denom as f64),
-            states: cards.cards.iter()
-                .map(|(&card_type, &count)| {
-                    CardTypeState {
-                        card_type,
-                        num_in_deck: count,
-                        n_remaining: n,
-                        num_drawn: 0,
-                    }
+            states: cards
+                .cards
+                .iter()
+                .map(|(&card_type, &count)| CardTypeState {
+                    card_type,
+                    num_in_deck: count,
+                    n_remaining: n,
+                    num_drawn: 0,
                 })
                 .collect(),
             index: 0,
@@ -73,7 +72,7 @@ impl<CardType: Hash + Eq + Copy> Draws<CardType> {
             let b = num_integer::binomial(state.num_in_deck, state.num_drawn);
             prob_numerator *= b as f64;
         }
-        for state in &self.states[i+1..] {
+        for state in &self.states[i + 1..] {
             reduced_deck.add(state.card_type, state.num_in_deck);
         }
         let prob = prob_numerator * self.prob_denom_recip;
@@ -96,7 +95,7 @@ impl<CardType: Hash + Eq + Copy> Draws<CardType> {
     }
 }
 
-impl<CardType: Hash + Eq + Copy> Iterator for Draws<CardType> {
+impl<CardType: Ord + Copy> Iterator for Draws<CardType> {
     type Item = (Cards<CardType>, Cards<CardType>, f64);
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -1,23 +1,26 @@
+mod discard_pile;
 mod draws;
 
 use rand::seq::SliceRandom;
-use std::collections::{hash_map::Entry, HashMap};
-use std::hash::Hash;
+use std::collections::{btree_map::Entry, BTreeMap};
 
+pub use self::discard_pile::DiscardPile;
 use self::draws::Draws;
 
 /// A multiset of cards.
-#[derive(Debug, PartialEq, Eq)]
-pub struct Cards<CardType: Hash + Eq> {
+/// Iteration is always in the order defined by the [`CardType`]'s [`Ord`] implementation, so
+/// that anything built from a [`Cards`] (e.g. a list of options) is deterministic.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Cards<CardType: Ord> {
     /// A mapping from card types to the number of cards of that type.
-    cards: HashMap<CardType, usize>,
+    cards: BTreeMap<CardType, usize>,
 }
 
-impl<CardType: Hash + Eq + Copy> Cards<CardType> {
+impl<CardType: Ord + Copy> Cards<CardType> {
     /// Creates a new, empty [`Cards`].
     pub fn new() -> Self {
         Self {
-            cards: HashMap::new(),
+            cards: BTreeMap::new(),
         }
     }
 
@@ -139,20 +142,18 @@ impl<CardType: Hash + Eq + Copy> Cards<CardType> {
         Draws::new(self, n)
     }
 
-    /// Returns an iterator over the unique card types in the [`Cards`].
-    /// The order of the types is not defined.
+    /// Returns an iterator over the unique card types in the [`Cards`], in sorted order.
     pub fn iter_unique(&self) -> impl Iterator<Item = CardType> + '_ {
         self.cards.keys().copied()
     }
 
-    /// Returns an iterator over (`CardType`, count) pairs.
-    /// The order is not defined.
+    /// Returns an iterator over (`CardType`, count) pairs, sorted by card type.
     pub fn iter(&self) -> impl Iterator<Item = (CardType, usize)> + '_ {
         self.cards.iter().map(|(key, count)| (*key, *count))
     }
 }
 
-impl<CardType: Hash + Eq + Clone> Clone for Cards<CardType> {
+impl<CardType: Ord + Clone> Clone for Cards<CardType> {
     fn clone(&self) -> Self {
         Self {
             cards: self.cards.clone(),
@@ -160,13 +161,13 @@ impl<CardType: Hash + Eq + Clone> Clone for Cards<CardType> {
     }
 }
 
-impl<CardType: Hash + Eq + Copy> Default for Cards<CardType> {
+impl<CardType: Ord + Copy> Default for Cards<CardType> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<'iter, CardType: 'iter + Hash + Eq + Copy> FromIterator<&'iter CardType> for Cards<CardType> {
+impl<'iter, CardType: 'iter + Ord + Copy> FromIterator<&'iter CardType> for Cards<CardType> {
     fn from_iter<I>(iter: I) -> Self
     where
         I: IntoIterator<Item = &'iter CardType>,
@@ -179,7 +180,7 @@ impl<'iter, CardType: 'iter + Hash + Eq + Copy> FromIterator<&'iter CardType> fo
     }
 }
 
-impl<CardType: Hash + Eq + Copy> FromIterator<CardType> for Cards<CardType> {
+impl<CardType: Ord + Copy> FromIterator<CardType> for Cards<CardType> {
     fn from_iter<I>(iter: I) -> Self
     where
         I: IntoIterator<Item = CardType>,
@@ -0,0 +1,73 @@
+//! The client for playing a game hosted by another RadBot over the network.
+//! See `radlands::controllers::remote` for the protocol.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpStream};
+
+use crossterm::{
+    cursor::MoveTo,
+    execute,
+    terminal::{Clear, ClearType},
+};
+
+/// Connects to the server at the given address and lets the user make choices until the
+/// server closes the connection.
+pub fn main(addr: SocketAddr) -> io::Result<()> {
+    println!("Connecting to {addr}...");
+    let mut stream = TcpStream::connect(addr)?;
+    let reader = BufReader::new(stream.try_clone()?);
+    println!("Connected; waiting for the game to start...");
+
+    let mut view = Vec::new();
+    let mut options = Vec::new();
+    let mut shown_choice = false;
+    for line in reader.lines() {
+        let line = line?;
+        let (kind, content) = line.split_once(' ').unwrap_or((&line, ""));
+        match kind {
+            "VIEW" | "OPTION" => {
+                if shown_choice {
+                    // this is the start of the next choice
+                    view.clear();
+                    options.clear();
+                    shown_choice = false;
+                }
+                match kind {
+                    "VIEW" => view.push(content.to_string()),
+                    _ => options.push(content.to_string()),
+                }
+            }
+            "CHOOSE" => {
+                // show the game view and options, then ask for an option number
+                execute!(io::stdout(), Clear(ClearType::All), MoveTo(0, 0))?;
+                for line in &view {
+                    println!("{line}");
+                }
+                println!();
+                for option in &options {
+                    let (number, text) = option.split_once(' ').unwrap_or((option, ""));
+                    println!("{:>5}  {text}", format!("({number})"));
+                }
+                shown_choice = true;
+
+                let input = loop {
+                    print!("\nChoose an option ({content} options): ");
+                    io::stdout().flush()?;
+                    let mut input = String::new();
+                    if io::stdin().read_line(&mut input)? == 0 {
+                        return Ok(()); // stdin was closed
+                    }
+                    if !input.trim().is_empty() {
+                        break input;
+                    }
+                };
+                write!(stream, "{input}")?;
+                println!("Waiting for the other player...");
+            }
+            _ => {} // ignore unknown messages
+        }
+    }
+
+    println!("Disconnected from the server.");
+    Ok(())
+}
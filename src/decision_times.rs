@@ -0,0 +1,85 @@
+//! Statistics on how many decisions each controller made and how long it took to make them, for
+//! checking that the AIs keep to their time limits and comparing the cost of different settings.
+//!
+//! Only choices with more than one option count as decisions, since controllers answer forced
+//! choices immediately. The time includes everything a controller does in `choose_option`, so a
+//! human's time is their thinking time.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::radlands::locations::Player;
+
+/// The number of decisions made by a controller, and the shortest, total, and longest time they
+/// took.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecisionTimes {
+    count: u32,
+    total: Duration,
+    min: Duration,
+    max: Duration,
+}
+
+impl DecisionTimes {
+    /// Records a decision that took the given time.
+    pub fn record(&mut self, time: Duration) {
+        self.min = if self.count == 0 { time } else { self.min.min(time) };
+        self.max = self.max.max(time);
+        self.total += time;
+        self.count += 1;
+    }
+
+    /// Adds the decisions recorded in `other` to these.
+    pub fn merge(&mut self, other: &DecisionTimes) {
+        if other.count == 0 {
+            return;
+        }
+        self.min = if self.count == 0 { other.min } else { self.min.min(other.min) };
+        self.max = self.max.max(other.max);
+        self.total += other.total;
+        self.count += other.count;
+    }
+
+    /// Returns the mean time per decision, or None if no decisions were recorded.
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| self.total / self.count)
+    }
+}
+
+impl fmt::Display for DecisionTimes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mean() {
+            Some(mean) => write!(
+                f,
+                "{} decisions, {:.2?} min / {mean:.2?} mean / {:.2?} max",
+                self.count, self.min, self.max
+            ),
+            None => write!(f, "no decisions"),
+        }
+    }
+}
+
+/// The decision times of both players' controllers in a game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GameTimes {
+    pub p1: DecisionTimes,
+    pub p2: DecisionTimes,
+}
+
+impl GameTimes {
+    /// Returns the decision times of the given player's controller.
+    pub fn player(&self, player: Player) -> &DecisionTimes {
+        match player {
+            Player::Player1 => &self.p1,
+            Player::Player2 => &self.p2,
+        }
+    }
+
+    /// Returns the decision times of the given player's controller as mutable.
+    pub fn player_mut(&mut self, player: Player) -> &mut DecisionTimes {
+        match player {
+            Player::Player1 => &mut self.p1,
+            Player::Player2 => &mut self.p2,
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! The prerecorded game played by `--demo`, for recording terminal casts of the UI (e.g. with
+//! asciinema).
+//!
+//! The demo script (`demo/demo.txt`) gives the seed of the game, then the index of the option
+//! chosen for every choice in the game (as in the autosave and golden games files), with
+//! captions to show in the log along the way:
+//!
+//! ```text
+//! seed 2024
+//! caption Each player has three camps to protect.
+//! moves 0,3,1
+//! caption Player 1 plays a person to protect a camp.
+//! moves 2,0
+//! ```
+//!
+//! Each caption is shown just before the move after it. Both players are played by
+//! `ScriptedController`s, which pause before every move that wasn't forced. The moves are only
+//! valid for the rules they were recorded with, so like the golden games, the demo has to be
+//! re-recorded (e.g. from the moves in an autosave file) when the options in the game change.
+
+use std::fs;
+use std::time::Duration;
+
+use itertools::Itertools;
+
+use crate::autosave::SavedGame;
+use crate::radlands::camps::CampType;
+use crate::radlands::controllers::scripted::ScriptStep;
+use crate::radlands::controllers::ControllerSpec;
+use crate::radlands::events::EventType;
+use crate::radlands::people::PersonType;
+use crate::radlands::rules::GameRules;
+
+/// The file containing the demo script.
+pub const DEMO_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/demo/demo.txt");
+
+/// A parsed demo script.
+#[derive(Debug, Clone)]
+pub struct Demo {
+    /// The seed the game is created with.
+    pub seed: u64,
+
+    /// The captions and moves of the game, in order.
+    pub steps: Vec<ScriptStep>,
+
+    /// How long to pause before each move that wasn't forced.
+    pub pace: Duration,
+}
+
+impl Demo {
+    /// Reads and parses the demo script, to be played at the given pace, and checks that its
+    /// moves play out a whole game. Returns a message describing the problem if they don't.
+    pub fn load(
+        pace: Duration,
+        camp_types: &[CampType],
+        person_types: &[PersonType],
+        event_types: &[EventType],
+    ) -> Result<Self, String> {
+        let contents = fs::read_to_string(DEMO_PATH)
+            .map_err(|err| format!("Failed to read {DEMO_PATH}: {err}"))?;
+
+        let mut seed = None;
+        let mut steps = Vec::new();
+        for (line_index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| format!("Line {}: {message}", line_index + 1);
+
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            match keyword {
+                "seed" => {
+                    let value = rest
+                        .trim()
+                        .parse()
+                        .map_err(|_| error("invalid seed".into()))?;
+                    seed = Some(value);
+                }
+                "caption" => steps.push(ScriptStep::Caption(rest.trim().to_string())),
+                "moves" => {
+                    for option in rest.split(',').map(str::trim).filter(|m| !m.is_empty()) {
+                        let option = option
+                            .parse()
+                            .map_err(|_| error(format!("invalid move: {option:?}")))?;
+                        steps.push(ScriptStep::Move(option));
+                    }
+                }
+                _ => return Err(error(format!("unknown keyword: {keyword:?}"))),
+            }
+        }
+
+        let demo = Demo {
+            seed: seed.ok_or("The demo script has no seed")?,
+            steps,
+            pace,
+        };
+        let mut saved_game = demo.saved_game();
+        saved_game.moves = demo.moves().collect_vec();
+        match saved_game.replay(camp_types, person_types, event_types, |_, _, _| {})? {
+            (_, Err(_)) => Ok(demo),
+            (_, Ok(_)) => Err("The demo script ends before the game does".to_string()),
+        }
+    }
+
+    /// Returns the moves of the game, in order.
+    fn moves(&self) -> impl Iterator<Item = usize> + '_ {
+        self.steps.iter().filter_map(|step| match step {
+            ScriptStep::Move(option) => Some(*option),
+            ScriptStep::Caption(_) => None,
+        })
+    }
+
+    /// Returns the demo's game as a new game, with no moves made yet. (The controller specs
+    /// are placeholders, since both players are played from the script.)
+    pub fn saved_game(&self) -> SavedGame {
+        SavedGame {
+            seed: self.seed,
+            ai_seed: 0,
+            rules: GameRules::default(),
+            p1: ControllerSpec::Random,
+            p2: ControllerSpec::Random,
+            moves: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::radlands::sandbox::testing::card_set;
+
+    #[test]
+    fn the_demo_script_plays_a_whole_game() {
+        let card_set = card_set();
+        let demo = Demo::load(
+            Duration::ZERO,
+            &card_set.camp_types,
+            &card_set.person_types,
+            &card_set.event_types,
+        )
+        .unwrap_or_else(|message| panic!("{message}; the demo needs to be re-recorded"));
+
+        // the script starts with a caption, so there's something to read from the first move
+        assert!(matches!(demo.steps.first(), Some(ScriptStep::Caption(_))));
+        assert!(demo.moves().count() > 0);
+    }
+}
@@ -0,0 +1,328 @@
+//! Evaluating a batch of positions with the AI (with `--evaluate`), for building suites of
+//! puzzle positions and regression-testing the AI's strength on known tactical spots.
+//!
+//! A positions file describes each position as a `[name]` header followed by the sandbox
+//! commands (see `radlands::sandbox`) that set it up from the setup of a new game:
+//!
+//! ```text
+//! # Lines starting with '#' are comments.
+//! [finish the last camp]
+//! seed 7
+//! turn p1
+//! water 4
+//! status p2 1 destroyed
+//! status p2 2 destroyed
+//! hand p1 add Gunner
+//! ```
+//!
+//! The optional `seed` line (only allowed right after the header; 0 by default) chooses the new
+//! game that the position is edited from, which decides the hands and deck order that the
+//! commands don't change. Every position is checked before any is evaluated. Each one is then
+//! searched by the MCTS AI for the given time, and a CSV row is written with the AI's best
+//! option, its win rate for the player to move, and the number of rollouts that chose it.
+//!
+//! A position can also give its known best move with an `expect <move>` line, where the move is
+//! written as it's shown in the options list (e.g. `expect Use Railgun's ability: Damage (costs
+//! 2 water)`). There may be several `expect` lines if more than one move is correct. Such
+//! positions are puzzles: `--check-puzzles` runs the puzzles in `puzzles/tactics.txt` several
+//! times each and fails if the AI finds an expected move too rarely, to catch regressions in its
+//! strength.
+
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use itertools::Itertools;
+use rand::rngs::StdRng;
+
+use crate::radlands::camps::CampType;
+use crate::radlands::choices::Choice;
+use crate::radlands::controllers::mcts::MCTSController;
+use crate::radlands::controllers::random::RandomController;
+use crate::radlands::controllers::{PlayerController, SearchBudget};
+use crate::radlands::events::EventType;
+use crate::radlands::locations::Player;
+use crate::radlands::people::PersonType;
+use crate::radlands::rules::GameRules;
+use crate::radlands::sandbox::Sandbox;
+use crate::radlands::GameState;
+
+/// The file containing the puzzle suite checked by `--check-puzzles`.
+pub const PUZZLES_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/puzzles/tactics.txt");
+
+/// How long the AI may think about each puzzle. This is a modest budget, so that the check runs
+/// quickly, but the puzzles are simple enough to be solved reliably within it.
+const PUZZLE_TIME_LIMIT: Duration = Duration::from_millis(500);
+
+/// How many times each puzzle is attempted, since the AI's search is randomized.
+const PUZZLE_ATTEMPTS: usize = 3;
+
+/// The fraction of all puzzle attempts that must find an expected move to pass the check.
+const MIN_PUZZLE_SOLVE_RATE: f64 = 0.8;
+
+/// The header row of the results CSV.
+const CSV_HEADER: &str = "position,player,num_options,best_move,win_rate,rollouts,solved";
+
+/// A position from a positions file, ready to be evaluated.
+struct Position<'ctype> {
+    name: String,
+    game_state: GameState<'ctype>,
+    choice: Choice<'ctype>,
+
+    /// The moves that are known to be best (as shown in the options list), if any.
+    expected_moves: Vec<String>,
+}
+
+impl Position<'_> {
+    /// Returns whether the given move is one of the expected moves, or None if the position
+    /// doesn't expect any.
+    fn is_solved_by(&self, best_move: &str) -> Option<bool> {
+        (!self.expected_moves.is_empty()).then(|| {
+            self.expected_moves
+                .iter()
+                .any(|expected| expected == best_move)
+        })
+    }
+}
+
+/// The AI's evaluation of a position.
+struct Evaluation {
+    /// The AI's best move, as shown in the options list.
+    best_move: String,
+
+    /// The best move's win rate for the player to move, and the number of rollouts it's based
+    /// on (or None if the choice has only one option, so it wasn't searched).
+    stats: Option<(f64, u32)>,
+}
+
+/// Has the MCTS AI (with its RNG seeded with `seed`) search a position for `time_limit` and
+/// returns its evaluation.
+fn evaluate(position: &Position, time_limit: Duration, seed: u64) -> Evaluation {
+    let chooser = position.choice.chooser(&position.game_state);
+    let game_view = position.game_state.view_for(chooser);
+    let mut ai = MCTSController::new(
+        chooser,
+        SearchBudget::Time(time_limit),
+        |_, rng: &mut StdRng| RandomController::from_rng(rng),
+        seed,
+    );
+    let best_option = ai.choose_option(&game_view, &position.choice);
+    let best_move = position
+        .choice
+        .option_label(best_option, &position.game_state);
+    let stats = ai
+        .search_tree(&game_view, &position.choice, 0, 1)
+        .map(|tree| {
+            let option = &tree.options[best_option];
+            (option.win_rate, option.num_rollouts)
+        });
+    Evaluation { best_move, stats }
+}
+
+/// Evaluates every position in the positions file at `positions_path`, searching each one for
+/// `time_limit` with the AI's RNG seeded with `ai_seed`, and writes the results to a CSV file at
+/// `output_path`. Returns a message describing the problem if the positions file is invalid or
+/// either file can't be accessed.
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    positions_path: &Path,
+    output_path: &Path,
+    time_limit: Duration,
+    ai_seed: u64,
+    rules: GameRules,
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+) -> Result<(), String> {
+    let contents = fs::read_to_string(positions_path)
+        .map_err(|err| format!("Failed to read {}: {err}", positions_path.display()))?;
+    let positions = parse_positions(&contents, rules, camp_types, person_types, event_types)?;
+    println!(
+        "Evaluating {} positions for {time_limit:?} each...",
+        positions.len()
+    );
+
+    let mut csv = format!("{CSV_HEADER}\n");
+    for position in positions {
+        let evaluation = evaluate(&position, time_limit, ai_seed);
+        let (win_rate, rollouts) = match evaluation.stats {
+            Some((win_rate, rollouts)) => (format!("{win_rate:.4}"), rollouts.to_string()),
+            None => (String::new(), String::new()),
+        };
+        let solved = match position.is_solved_by(&evaluation.best_move) {
+            Some(true) => "yes",
+            Some(false) => "no",
+            None => "",
+        };
+        println!(
+            "{}: {} (win rate: {win_rate})",
+            position.name, evaluation.best_move
+        );
+
+        let player = match position.choice.chooser(&position.game_state) {
+            Player::Player1 => "p1",
+            Player::Player2 => "p2",
+        };
+        let row = [
+            csv_field(&position.name),
+            player.to_string(),
+            position.choice.num_options().to_string(),
+            csv_field(&evaluation.best_move),
+            win_rate,
+            rollouts,
+            solved.to_string(),
+        ];
+        writeln!(csv, "{}", row.iter().join(",")).unwrap();
+    }
+
+    fs::write(output_path, csv)
+        .map_err(|err| format!("Failed to write {}: {err}", output_path.display()))?;
+    println!("Wrote the results to {}", output_path.display());
+    Ok(())
+}
+
+/// Has the AI attempt every puzzle in the puzzle suite several times, printing the ones it
+/// didn't always solve. Returns whether it found an expected move often enough overall.
+pub fn check_puzzles(
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+) -> bool {
+    let contents = fs::read_to_string(PUZZLES_PATH).expect("Failed to read the puzzles file");
+    let rules = GameRules::default();
+    let puzzles = match parse_positions(&contents, rules, camp_types, person_types, event_types) {
+        Ok(puzzles) => puzzles,
+        Err(message) => {
+            println!("Invalid puzzles file: {message}");
+            return false;
+        }
+    };
+    if let Some(puzzle) = puzzles.iter().find(|p| p.expected_moves.is_empty()) {
+        println!("Puzzle {:?} has no expected move", puzzle.name);
+        return false;
+    }
+
+    let mut num_solved = 0;
+    for puzzle in &puzzles {
+        let mut missed_moves = Vec::new();
+        for attempt in 0..PUZZLE_ATTEMPTS {
+            let evaluation = evaluate(puzzle, PUZZLE_TIME_LIMIT, attempt as u64);
+            if puzzle.is_solved_by(&evaluation.best_move) == Some(true) {
+                num_solved += 1;
+            } else {
+                missed_moves.push(evaluation.best_move);
+            }
+        }
+        if !missed_moves.is_empty() {
+            println!(
+                "Puzzle {:?} was missed {} of {PUZZLE_ATTEMPTS} times, with: {}",
+                puzzle.name,
+                missed_moves.len(),
+                missed_moves.iter().unique().join("; ")
+            );
+        }
+    }
+
+    let num_attempts = puzzles.len() * PUZZLE_ATTEMPTS;
+    let solve_rate = num_solved as f64 / num_attempts as f64;
+    println!(
+        "Solved {num_solved} of {num_attempts} puzzle attempts ({:.0}%; at least {:.0}% is required)",
+        solve_rate * 100.0,
+        MIN_PUZZLE_SOLVE_RATE * 100.0
+    );
+    solve_rate >= MIN_PUZZLE_SOLVE_RATE
+}
+
+/// Parses a positions file, setting up every position in it. Returns a message giving the line
+/// number of the first problem, if any.
+fn parse_positions<'ctype>(
+    contents: &str,
+    rules: GameRules,
+    camp_types: &'ctype [CampType],
+    person_types: &'ctype [PersonType],
+    event_types: &'ctype [EventType],
+) -> Result<Vec<Position<'ctype>>, String> {
+    // the name, sandbox, expected moves, and whether any commands have been applied, of each
+    // position so far
+    let mut sandboxes: Vec<(String, Sandbox, Vec<String>, bool)> = Vec::new();
+    for (line_index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: String| format!("Line {}: {message}", line_index + 1);
+
+        if let Some(name) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            let sandbox = Sandbox::new(camp_types, person_types, event_types, rules, 0);
+            sandboxes.push((name.trim().to_string(), sandbox, Vec::new(), false));
+            continue;
+        }
+
+        let (name, sandbox, expected_moves, edited) = match sandboxes.last_mut() {
+            Some(current) => current,
+            None => {
+                return Err(error(
+                    "expected a [name] header before the first command".into(),
+                ))
+            }
+        };
+        if let Some(seed) = line.strip_prefix("seed ") {
+            if *edited {
+                return Err(error(
+                    "the seed must come before the position's commands".into(),
+                ));
+            }
+            let seed = seed
+                .trim()
+                .parse()
+                .map_err(|_| error(format!("invalid seed: {:?}", seed.trim())))?;
+            *sandbox = Sandbox::new(camp_types, person_types, event_types, rules, seed);
+        } else if let Some(expected_move) = line.strip_prefix("expect ") {
+            expected_moves.push(expected_move.trim().to_string());
+        } else {
+            sandbox
+                .apply(line)
+                .map_err(|message| error(format!("in position {name:?}: {message}")))?;
+            *edited = true;
+        }
+    }
+
+    if sandboxes.is_empty() {
+        return Err("The positions file has no positions".to_string());
+    }
+    sandboxes
+        .into_iter()
+        .map(|(name, sandbox, expected_moves, _)| {
+            let (game_state, choice) = sandbox
+                .start()
+                .map_err(|message| format!("Position {name:?}: {message}"))?;
+
+            // catch expected moves that can never match, such as ones with typos
+            let labels = (0..choice.num_options())
+                .map(|option| choice.option_label(option, &game_state))
+                .collect_vec();
+            if let Some(expected) = expected_moves.iter().find(|m| !labels.contains(m)) {
+                return Err(format!(
+                    "Position {name:?}: the expected move {expected:?} isn't one of the options"
+                ));
+            }
+
+            Ok(Position {
+                name,
+                game_state,
+                choice,
+                expected_moves,
+            })
+        })
+        .collect()
+}
+
+/// Returns the value as a CSV field, quoting it if needed.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
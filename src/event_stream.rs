@@ -0,0 +1,434 @@
+//! A stream of game updates as newline-delimited JSON, for external UIs.
+//!
+//! Started with `--event-stream <ADDR>`, which listens for subscribers on the given address.
+//! A subscriber connects over TCP and sends one line naming the view it wants:
+//!
+//! - `player1` or `player2`: what that player can see (the opponent's hand is hidden)
+//! - `spectator`: what neither player's hand reveals (both hands are hidden)
+//! - `full`: everything except the order of the deck
+//!
+//! It then receives the latest update right away, and one line for every update after that:
+//!
+//! ```text
+//! {"type":"game_update","seq":3,"view":"player2","state":{...},"choice":{...},"result":null}
+//! ```
+//!
+//! `state` has the current player, their water, the deck size, the discard pile (in the order
+//! it was discarded), and both players' hands, boards, and event queues. `choice` is null once
+//! the game is over; otherwise it names the chooser, what the choice is part of resolving (such as
+//! a person's enter-play effect or an event, or null), and, if the view belongs to the chooser (or is
+//! `full`), the labels of the options. `result` is `p1_wins`, `p2_wins`, or `tie` once the
+//! game is over. Subscribers that stop reading are disconnected.
+//!
+//! A subscriber that adds ` binary` to its line (e.g. `player1 binary`) gets the same updates
+//! in the compact binary format of `radlands::wire` instead, each as a frame of its length in
+//! bytes (a 4-byte little-endian integer) followed by the encoded observation.
+
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::mem;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use itertools::Itertools;
+
+use crate::json::json_string;
+use crate::radlands::choices::Choice;
+use crate::radlands::locations::Player;
+use crate::radlands::player_state::{CampStatus, CardColumn, NonPunkStatus, Person, PlayerState};
+use crate::radlands::wire::Observation;
+use crate::radlands::{GameResult, GameState};
+
+/// How long a write to a subscriber may block before the subscriber is disconnected.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// What a subscriber can see of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    Full,
+    Player(Player),
+    Spectator,
+}
+
+impl View {
+    const ALL: [View; 4] = [
+        View::Full,
+        View::Player(Player::Player1),
+        View::Player(Player::Player2),
+        View::Spectator,
+    ];
+
+    /// Returns the name of this view in the protocol.
+    fn name(self) -> &'static str {
+        match self {
+            View::Full => "full",
+            View::Player(Player::Player1) => "player1",
+            View::Player(Player::Player2) => "player2",
+            View::Spectator => "spectator",
+        }
+    }
+
+    /// Returns whether this view shows the given player's hand.
+    fn shows_hand_of(self, player: Player) -> bool {
+        match self {
+            View::Full => true,
+            View::Player(viewer) => viewer == player,
+            View::Spectator => false,
+        }
+    }
+}
+
+/// An update as sent to subscribers of one view: a JSON line, and a binary frame.
+struct Update {
+    json: String,
+    frame: Vec<u8>,
+}
+
+impl Update {
+    /// Sends this update to a subscriber in the format it asked for.
+    fn send(&self, stream: &mut TcpStream, binary: bool) -> io::Result<()> {
+        if binary {
+            stream.write_all(&self.frame)
+        } else {
+            writeln!(stream, "{}", self.json)
+        }
+    }
+}
+
+/// A subscriber, with its view and whether it asked for binary frames.
+type Subscriber = (View, bool, TcpStream);
+
+struct StreamState {
+    /// The subscribers that aren't being sent an update right now.
+    subscribers: Vec<Subscriber>,
+    /// The number of updates published so far.
+    seq: u64,
+    /// The latest update as seen from each view (in the order of `View::ALL`), if any.
+    latest: Option<Arc<[Update]>>,
+}
+
+/// The state of the event stream. It's only locked to read or replace the latest update and the
+/// subscribers, never while writing to a subscriber, so a slow subscriber can't hold up others.
+static STREAM: Mutex<Option<StreamState>> = Mutex::new(None);
+
+/// Returns the index of the given view in `View::ALL`.
+fn view_index(view: View) -> usize {
+    View::ALL.iter().position(|&v| v == view).unwrap()
+}
+
+/// Starts listening for subscribers on the given address. Updates are only published after
+/// this is called.
+pub fn start(addr: SocketAddr) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    *STREAM.lock().unwrap() = Some(StreamState {
+        subscribers: Vec::new(),
+        seq: 0,
+        latest: None,
+    });
+
+    thread::Builder::new()
+        .name("event stream listener".into())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // read the handshake on its own thread, so a slow client doesn't hold up others
+                let _ = thread::Builder::new()
+                    .name("event stream subscriber".into())
+                    .spawn(move || subscribe(stream));
+            }
+        })?;
+    Ok(())
+}
+
+/// Reads a new subscriber's choice of view, then sends it the latest update and adds it to
+/// the subscribers.
+fn subscribe(mut stream: TcpStream) -> io::Result<()> {
+    let mut line = String::new();
+    BufReader::new(stream.try_clone()?).read_line(&mut line)?;
+    let (name, binary) = match line.trim().strip_suffix(" binary") {
+        Some(name) => (name, true),
+        None => (line.trim(), false),
+    };
+    let view = match View::ALL.into_iter().find(|view| view.name() == name) {
+        Some(view) => view,
+        None => {
+            let views = View::ALL.iter().map(|view| view.name()).join(", ");
+            return writeln!(
+                stream,
+                "unknown view {:?}; expected one of: {views}",
+                line.trim()
+            );
+        }
+    };
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+    // send the latest update (outside the lock), until no newer one was published meanwhile
+    let mut sent_seq = 0;
+    loop {
+        let latest = {
+            let mut guard = STREAM.lock().unwrap();
+            let state = guard.as_mut().expect("the event stream was not started");
+            if state.seq == sent_seq {
+                state.subscribers.push((view, binary, stream));
+                return Ok(());
+            }
+            sent_seq = state.seq;
+            state.latest.clone()
+        };
+        if let Some(latest) = latest {
+            latest[view_index(view)].send(&mut stream, binary)?;
+        }
+    }
+}
+
+/// Publishes an update to all subscribers, if the event stream was started. Updates are
+/// published by one thread (the one playing the game), so they can't overtake each other.
+pub fn publish<'ctype>(
+    game_state: &GameState<'ctype>,
+    choice: &Result<Choice<'ctype>, GameResult>,
+) {
+    let seq = match STREAM.lock().unwrap().as_ref() {
+        Some(state) => state.seq + 1,
+        None => return,
+    };
+    let updates: Arc<[Update]> = View::ALL
+        .into_iter()
+        .map(|view| view_update(seq, view, game_state, choice))
+        .collect();
+
+    // make this the latest update, and take the subscribers to send it to
+    let mut subscribers = {
+        let mut guard = STREAM.lock().unwrap();
+        let state = guard.as_mut().unwrap();
+        state.seq = seq;
+        state.latest = Some(updates.clone());
+        mem::take(&mut state.subscribers)
+    };
+
+    subscribers.retain_mut(|(view, binary, stream)| {
+        updates[view_index(*view)].send(stream, *binary).is_ok()
+    });
+
+    // put them back with any that subscribed meanwhile (who were sent this update already)
+    let mut guard = STREAM.lock().unwrap();
+    guard.as_mut().unwrap().subscribers.extend(subscribers);
+}
+
+/// Returns the update as seen from the given view.
+fn view_update<'ctype>(
+    seq: u64,
+    view: View,
+    game_state: &GameState<'ctype>,
+    choice: &Result<Choice<'ctype>, GameResult>,
+) -> Update {
+    let shows_hand = [Player::Player1, Player::Player2].map(|p| view.shows_hand_of(p));
+    let observation = Observation::new(game_state, choice, shows_hand).encode();
+    let mut frame = (observation.len() as u32).to_le_bytes().to_vec();
+    frame.extend(observation);
+    Update {
+        json: update_json(seq, view, game_state, choice),
+        frame,
+    }
+}
+
+/// Returns the JSON line for an update as seen from the given view.
+fn update_json<'ctype>(
+    seq: u64,
+    view: View,
+    game_state: &GameState<'ctype>,
+    choice: &Result<Choice<'ctype>, GameResult>,
+) -> String {
+    let mut json = String::new();
+    write!(
+        json,
+        r#"{{"type":"game_update","seq":{seq},"view":"{}","state":"#,
+        view.name()
+    )
+    .unwrap();
+    write_state(&mut json, view, game_state);
+
+    json.push_str(r#","choice":"#);
+    match choice {
+        Ok(choice) => {
+            let chooser = choice.chooser(game_state);
+            let context = match choice.context() {
+                Some(context) => json_string(&context.label()),
+                None => "null".to_string(),
+            };
+            write!(
+                json,
+                r#"{{"chooser":{},"context":{context},"num_options":{},"options":"#,
+                chooser.number(),
+                choice.num_options(),
+            )
+            .unwrap();
+            if matches!(view, View::Full) || view == View::Player(chooser) {
+                let labels = (0..choice.num_options())
+                    .map(|option| json_string(&choice.option_label(option, game_state)))
+                    .join(",");
+                write!(json, "[{labels}]}}").unwrap();
+            } else {
+                json.push_str("null}");
+            }
+        }
+        Err(_) => json.push_str("null"),
+    }
+
+    let result = match choice {
+        Ok(_) => "null",
+        Err(GameResult::P1Wins) => r#""p1_wins""#,
+        Err(GameResult::P2Wins) => r#""p2_wins""#,
+        Err(GameResult::Tie) => r#""tie""#,
+    };
+    write!(json, r#","result":{result}}}"#).unwrap();
+    json
+}
+
+fn write_state(json: &mut String, view: View, game_state: &GameState) {
+    let discard = game_state
+        .discard()
+        .as_slice()
+        .iter()
+        .map(|card| json_string(card.name()))
+        .join(",");
+    write!(
+        json,
+        r#"{{"cur_player":{},"cur_player_water":{},"deck_size":{},"discard":[{discard}],"players":["#,
+        game_state.cur_player.number(),
+        game_state.cur_player_water,
+        game_state.deck().len(),
+    )
+    .unwrap();
+    for (i, player) in [Player::Player1, Player::Player2].into_iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write_player(json, game_state.player(player), view.shows_hand_of(player));
+    }
+    json.push_str("]}");
+}
+
+fn write_player(json: &mut String, player_state: &PlayerState, show_hand: bool) {
+    let hand = if show_hand {
+        let cards = player_state
+            .hand
+            .iter()
+            .flat_map(|(card, count)| std::iter::repeat(json_string(card.name())).take(count))
+            .join(",");
+        format!("[{cards}]")
+    } else {
+        "null".to_string()
+    };
+    let events = player_state
+        .events
+        .iter()
+        .map(|event| match event {
+            Some(event) => json_string(event.name),
+            None => "null".to_string(),
+        })
+        .join(",");
+    write!(
+        json,
+        r#"{{"hand":{hand},"hand_size":{},"has_water_silo":{},"events":[{events}],"columns":["#,
+        player_state.hand.count(),
+        player_state.has_water_silo,
+    )
+    .unwrap();
+    for (i, column) in player_state.columns.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write_column(json, column);
+    }
+    json.push_str("]}");
+}
+
+fn write_column(json: &mut String, column: &CardColumn) {
+    let camp_status = match column.camp.status {
+        CampStatus::Undamaged => "undamaged",
+        CampStatus::Damaged => "damaged",
+        CampStatus::Destroyed => "destroyed",
+    };
+    write!(
+        json,
+        r#"{{"camp":{{"name":{},"status":"{camp_status}","ready":{},"position":{}}},"people":["#,
+        json_string(column.camp.camp_type.name),
+        column.camp.is_ready(),
+        column.camp.position(),
+    )
+    .unwrap();
+    for (i, slot) in column.person_slots.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        match slot {
+            None => json.push_str("null"),
+            Some(Person::Punk { is_ready, .. }) => {
+                let status = if *is_ready { "ready" } else { "not_ready" };
+                write!(json, r#"{{"name":"Punk","punk":true,"status":"{status}"}}"#).unwrap();
+            }
+            Some(Person::NonPunk {
+                person_type,
+                status,
+                ..
+            }) => {
+                let status = match status {
+                    NonPunkStatus::Ready => "ready",
+                    NonPunkStatus::NotReady => "not_ready",
+                    NonPunkStatus::Injured => "injured",
+                };
+                write!(
+                    json,
+                    r#"{{"name":{},"punk":false,"status":"{status}"}}"#,
+                    json_string(person_type.name),
+                )
+                .unwrap();
+            }
+        }
+    }
+    json.push_str("]}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::radlands::sandbox::testing::*;
+
+    #[test]
+    fn each_view_masks_the_hands_it_cant_see() {
+        let (game_state, choice) =
+            position(&["turn p1", "hand p1 add Looter", "hand p2 add Vigilante"]);
+        let choice = Ok(choice);
+        let card_set = card_set();
+
+        for view in View::ALL {
+            let shows_p1 = view.shows_hand_of(Player::Player1);
+            let shows_p2 = view.shows_hand_of(Player::Player2);
+            let update = view_update(1, view, &game_state, &choice);
+
+            // the JSON lists only the hands the view shows, but always their sizes
+            let json = &update.json;
+            assert_eq!(json.contains(r#""hand":["Looter"]"#), shows_p1, "{json}");
+            assert_eq!(json.contains(r#""hand":["Vigilante"]"#), shows_p2, "{json}");
+            let num_hidden = [shows_p1, shows_p2].iter().filter(|&&shown| !shown).count();
+            assert_eq!(json.matches(r#""hand":null"#).count(), num_hidden, "{json}");
+            assert_eq!(json.matches(r#""hand_size":1"#).count(), 2, "{json}");
+
+            // only the chooser (Player 1) and the full view see the option labels
+            assert_eq!(!json.contains(r#""options":null"#), shows_p1, "{json}");
+
+            // so does the binary frame
+            let observation = Observation::decode(
+                &update.frame[4..],
+                &card_set.camp_types,
+                &card_set.person_types,
+                &card_set.event_types,
+            )
+            .unwrap();
+            let [p1, p2] = &observation.players;
+            assert_eq!(p1.hand.is_some(), shows_p1, "{}", view.name());
+            assert_eq!(p2.hand.is_some(), shows_p2, "{}", view.name());
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Tracing a single seeded game between AIs (with `--trace-game`), as the starting point for
+//! investigating a questionable decision ("why did the AI do that on turn 14?").
+//!
+//! Both players are played by AIs with the same spec, and the game seed and AI seed are fixed,
+//! so with a rollout budget (e.g. `mcts:2000r`) the game is played out the same way every time.
+//! For every ply, the trace gives the turn, the choosing player, the fingerprints of the game
+//! state (see `GameState::fingerprint`) and of the game's RNG (see `GameState::rng_fingerprint`,
+//! which changes whenever the game has used its RNG since the last ply), and the options. For a
+//! decision (a choice with more than one option), it then gives the AI's evaluation of the
+//! options, as it would be shown in the UI's stats pane, and how long the AI took. Finally, it
+//! gives the option chosen, the changes it made, and what the game's randomness decided (the
+//! cards drawn from the deck, and the new order of the deck whenever it was shuffled):
+//!
+//! ```text
+//! ply 7, turn 2: Player 2 to choose (state 5c1e03a9d2b7f468, RNG 0e4d7a91c3b52f60)
+//!   0: Play Looter at <column: 0, row: 0>
+//!   1: End turn, taking Water Silo if possible
+//!   evaluation (412.31ms):
+//!     ...
+//!   chose 0: Play Looter at <column: 0, row: 0>
+//!     Player2's water 3 → 2
+//! ```
+//!
+//! Before the first ply, the trace gives the randomness of the set-up: which player goes first,
+//! the opening hands (unless camps are drafted first), and the order of the shuffled deck.
+//!
+//! The trace ends with the game in the saved game format, which can be put in a file and loaded
+//! with `--load-game` to look at any of its positions in the UI.
+
+use std::collections::BTreeMap;
+use std::iter;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use itertools::Itertools;
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+
+use crate::autosave::SavedGame;
+use crate::decision_times::GameTimes;
+use crate::radlands::camps::CampType;
+use crate::radlands::controllers::{ControllerSpec, ControllerStats};
+use crate::radlands::events::EventType;
+use crate::radlands::locations::Player;
+use crate::radlands::people::PersonType;
+use crate::radlands::rules::GameRules;
+use crate::radlands::{GameResult, GameState, PersonOrEventType};
+
+/// The name of the thread that plays the traced game. Stats shown from this thread are kept for
+/// the trace, rather than sent to the UI.
+pub const TRACE_THREAD_NAME: &str = "game trace";
+
+/// The size of the area that the AI's stats are rendered into for the trace.
+const STATS_AREA: Rect = Rect {
+    x: 0,
+    y: 0,
+    width: 120,
+    height: 60,
+};
+
+/// The stats last shown by the AI that is choosing in the traced game.
+static LAST_STATS: Mutex<Option<Box<dyn ControllerStats + Send>>> = Mutex::new(None);
+
+/// Returns whether the current thread is playing a traced game.
+pub fn is_trace_thread() -> bool {
+    thread::current().name() == Some(TRACE_THREAD_NAME)
+}
+
+/// Keeps the given stats, shown by the AI that is choosing in the traced game.
+pub fn record_stats(stats: Option<Box<dyn ControllerStats + Send>>) {
+    *LAST_STATS.lock().unwrap() = stats;
+}
+
+/// Returns the lines of text that the given stats show, without trailing blank lines.
+fn stats_lines(stats: &mut dyn ControllerStats) -> Vec<String> {
+    let mut buf = Buffer::empty(STATS_AREA);
+    stats.render(STATS_AREA, &mut buf);
+    let mut lines = (0..STATS_AREA.height)
+        .map(|y| {
+            let line: String = (0..STATS_AREA.width)
+                .map(|x| buf.get(x, y).symbol.as_str())
+                .collect();
+            line.trim_end().to_string()
+        })
+        .collect::<Vec<_>>();
+    while lines.last().map_or(false, |line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Plays a game with the given seed between two AIs with the given spec (whose RNGs are derived
+/// from `ai_seed`), and prints a trace of it.
+pub fn run(
+    seed: u64,
+    ai_seed: u64,
+    spec: ControllerSpec,
+    rules: GameRules,
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+) {
+    let mut saved_game = SavedGame::new_game(rules, spec, spec);
+    saved_game.seed = seed;
+    saved_game.ai_seed = ai_seed;
+    println!("Tracing game seed {seed} (AI seed {ai_seed}), with {spec} playing both sides\n");
+
+    let result = thread::scope(|scope| {
+        thread::Builder::new()
+            .name(TRACE_THREAD_NAME.into())
+            .spawn_scoped(scope, || {
+                play_traced(&mut saved_game, camp_types, person_types, event_types)
+            })
+            .expect("Failed to spawn the trace thread")
+            .join()
+            .expect("The traced game panicked")
+    });
+
+    println!(
+        "\nGame ended after {} plies; {}",
+        saved_game.moves.len(),
+        match result.0 {
+            GameResult::P1Wins => "player 1 wins!",
+            GameResult::P2Wins => "player 2 wins!",
+            GameResult::Tie => "tie!",
+        }
+    );
+    println!("Player 1's decisions: {}", result.1.p1);
+    println!("Player 2's decisions: {}", result.1.p2);
+    println!("\nThe game, for --load-game:\n{saved_game}");
+}
+
+/// Plays the saved game's controllers against each other from the start, printing the trace and
+/// recording the moves in the saved game. Returns the result and the decision times.
+fn play_traced(
+    saved_game: &mut SavedGame,
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+) -> (GameResult, GameTimes) {
+    let mut controllers = [Player::Player1, Player::Player2].map(|player| {
+        let spec = match player {
+            Player::Player1 => saved_game.p1,
+            Player::Player2 => saved_game.p2,
+        };
+        spec.build(player, saved_game.controller_seed(player))
+    });
+    let (mut game_state, choice) = GameState::new_seeded(
+        camp_types,
+        person_types,
+        event_types,
+        saved_game.rules,
+        saved_game.seed,
+    );
+
+    println!("Player {} goes first", game_state.cur_player.number());
+    for player in [Player::Player1, Player::Player2] {
+        let hand = &game_state.player(player).hand;
+        if !hand.is_empty() {
+            println!(
+                "Player {}'s opening hand: {}",
+                player.number(),
+                card_list(
+                    hand.iter()
+                        .flat_map(|(card, count)| iter::repeat(card).take(count))
+                )
+            );
+        }
+    }
+    println!(
+        "Deck (top first): {}\n",
+        card_list(game_state.deck().iter().rev().copied())
+    );
+
+    let mut times = GameTimes::default();
+    let mut cur_choice = Ok(choice);
+    let result = loop {
+        let choice = match &cur_choice {
+            Ok(choice) => choice,
+            Err(game_result) => break *game_result,
+        };
+        let chooser = choice.chooser(&game_state);
+        println!(
+            "ply {}, turn {}: Player {} to choose (state {:016x}, RNG {:016x})",
+            saved_game.moves.len(),
+            game_state.turn_number,
+            chooser.number(),
+            game_state.fingerprint(),
+            game_state.rng_fingerprint(),
+        );
+        for option in 0..choice.num_options() {
+            println!("  {option}: {}", choice.option_label(option, &game_state));
+        }
+
+        // have the AI choose, keeping the stats it shows while it does
+        let controller = match chooser {
+            Player::Player1 => &mut controllers[0],
+            Player::Player2 => &mut controllers[1],
+        };
+        record_stats(None);
+        let start_time = Instant::now();
+        let option = controller.choose_option(&game_state.view_for(chooser), choice);
+        let elapsed = start_time.elapsed();
+        if choice.num_options() > 1 {
+            times.player_mut(chooser).record(elapsed);
+            println!("  evaluation ({elapsed:.2?}):");
+            match LAST_STATS.lock().unwrap().as_mut() {
+                Some(stats) => {
+                    for line in stats_lines(stats.as_mut()) {
+                        if line.is_empty() {
+                            println!();
+                        } else {
+                            println!("    {line}");
+                        }
+                    }
+                }
+                None => println!("    (none shown)"),
+            }
+        }
+
+        // make the move, and show what it changed
+        let state_before = game_state.clone();
+        println!(
+            "  chose {option}: {}",
+            choice.option_label(option, &state_before)
+        );
+        cur_choice = choice.choose(&mut game_state, option);
+        for change in state_before.diff(&game_state).changes() {
+            println!("    {change}");
+        }
+        for draw in deck_draws(&state_before, &game_state) {
+            println!("    {draw}");
+        }
+        saved_game.moves.push(option);
+    };
+    (result, times)
+}
+
+/// Returns the given cards' names, separated by commas.
+fn card_list<'ctype>(cards: impl Iterator<Item = PersonOrEventType<'ctype>>) -> String {
+    cards.map(|card| card.name()).join(", ")
+}
+
+/// Returns lines describing what the deck's randomness decided between the given states: the
+/// cards drawn from the top of the deck or, if the deck was shuffled (by a reshuffle of the
+/// discard pile or a mulligan), its new order and the cards that were added to each hand.
+fn deck_draws(before: &GameState, after: &GameState) -> Vec<String> {
+    let (deck_before, deck_after) = (before.deck(), after.deck());
+    if deck_before.starts_with(deck_after) {
+        let drawn = &deck_before[deck_after.len()..];
+        if drawn.is_empty() {
+            return Vec::new();
+        }
+        return vec![format!(
+            "drew from the deck: {}",
+            card_list(drawn.iter().rev().copied())
+        )];
+    }
+
+    let mut lines = vec![format!(
+        "deck shuffled, now (top first): {}",
+        card_list(deck_after.iter().rev().copied())
+    )];
+    for player in [Player::Player1, Player::Player2] {
+        let hand_before: BTreeMap<_, _> = before.player(player).hand.iter().collect();
+        let gained = after.player(player).hand.iter().flat_map(|(card, count)| {
+            let count_before = hand_before.get(&card).copied().unwrap_or(0);
+            iter::repeat(card).take(count.saturating_sub(count_before))
+        });
+        let gained = card_list(gained);
+        if !gained.is_empty() {
+            lines.push(format!(
+                "Player {}'s hand gained: {gained}",
+                player.number()
+            ));
+        }
+    }
+    lines
+}
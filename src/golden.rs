@@ -0,0 +1,376 @@
+//! A regression suite of recorded ("golden") games.
+//!
+//! Each golden game is played with the casual rules, and is stored as its seed, whether mulligans
+//! were allowed, the list of chosen option indices, the game's result and final state
+//! fingerprint (the `Hash` of the complete game state), and a digest of the options offered at
+//! every choice. Replaying a game must reproduce the same result, fingerprint, and options
+//! exactly, so any unintended rules change or source of nondeterminism (such as building options
+//! by iterating over a `HashSet`) makes the check fail. Replaying also checks that no card is
+//! lost or duplicated along the way. After an *intended* rules change, the games must be
+//! re-recorded with `--record-golden`.
+
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::radlands::camps::CampType;
+use crate::radlands::choices::Choice;
+use crate::radlands::events::EventType;
+use crate::radlands::people::PersonType;
+use crate::radlands::rules::GameRules;
+use crate::radlands::{GameResult, GameState};
+use crate::stable_hash::StableHasher;
+
+/// The file containing the recorded golden games, one per line.
+pub const GOLDEN_GAMES_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/golden/games.txt");
+
+/// A recorded game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenGame {
+    /// The seed the game was created with.
+    seed: u64,
+
+    /// Whether the game was created with a mulligan phase.
+    allow_mulligans: bool,
+
+    /// The index of the option chosen for each choice in the game.
+    moves: Vec<usize>,
+
+    /// The result of the game.
+    result: GameResult,
+
+    /// The fingerprint of the final game state.
+    fingerprint: u64,
+
+    /// The digest of the options offered at every choice (see `hash_options`).
+    options: u64,
+}
+
+/// Adds the labels of the given choice's options, in order, to an options digest. Two games
+/// offer the same options at every choice only if their digests match.
+fn hash_options<'ctype>(
+    hasher: &mut StableHasher,
+    choice: &Choice<'ctype>,
+    game_state: &GameState<'ctype>,
+) {
+    choice.num_options().hash(hasher);
+    for option in 0..choice.num_options() {
+        choice.option_label(option, game_state).hash(hasher);
+    }
+}
+
+impl GoldenGame {
+    /// Plays and records a game with uniformly random moves, which are chosen by an RNG seeded
+    /// with the same seed as the game.
+    pub fn record(
+        seed: u64,
+        allow_mulligans: bool,
+        camp_types: &[CampType],
+        person_types: &[PersonType],
+        event_types: &[EventType],
+    ) -> Self {
+        let mut move_rng = StdRng::seed_from_u64(seed);
+        let (mut game_state, mut choice) = GameState::new_seeded(
+            camp_types,
+            person_types,
+            event_types,
+            GameRules::casual(allow_mulligans),
+            seed,
+        );
+
+        let mut moves = Vec::new();
+        let mut options_hasher = StableHasher::new();
+        let result = loop {
+            hash_options(&mut options_hasher, &choice, &game_state);
+            let option = move_rng.gen_range(0..choice.num_options());
+            moves.push(option);
+            match choice.choose(&mut game_state, option) {
+                Ok(next_choice) => choice = next_choice,
+                Err(game_result) => break game_result,
+            }
+        };
+
+        GoldenGame {
+            seed,
+            allow_mulligans,
+            moves,
+            result,
+            fingerprint: game_state.fingerprint(),
+            options: options_hasher.finish(),
+        }
+    }
+
+    /// Replays this game's moves and checks that the game ends the same way it did when it
+    /// was recorded. Returns a description of the first discrepancy, if any.
+    pub fn check(
+        &self,
+        camp_types: &[CampType],
+        person_types: &[PersonType],
+        event_types: &[EventType],
+    ) -> Result<(), String> {
+        let (mut game_state, mut choice) = GameState::new_seeded(
+            camp_types,
+            person_types,
+            event_types,
+            GameRules::casual(self.allow_mulligans),
+            self.seed,
+        );
+
+        // every card stays somewhere in the game (see `GameState::all_cards`), which is checked
+        // whenever no effect is in the middle of moving one
+        let all_cards = game_state.all_cards();
+
+        let mut options_hasher = StableHasher::new();
+        for (ply, &option) in self.moves.iter().enumerate() {
+            let num_options = choice.num_options();
+            if option >= num_options {
+                return Err(format!(
+                    "move {ply} chose option {option}, but there are only {num_options} options"
+                ));
+            }
+            if matches!(choice, Choice::Action(_)) && game_state.all_cards() != all_cards {
+                return Err(format!(
+                    "before move {ply}, the cards in the game are not the ones it started with"
+                ));
+            }
+
+            hash_options(&mut options_hasher, &choice, &game_state);
+            match choice.choose(&mut game_state, option) {
+                Ok(next_choice) => choice = next_choice,
+                Err(game_result) if ply == self.moves.len() - 1 => {
+                    // the game ended after the last move, as expected
+                    if game_result != self.result {
+                        return Err(format!(
+                            "expected result {:?}, but got {game_result:?}",
+                            self.result
+                        ));
+                    }
+                    let fingerprint = game_state.fingerprint();
+                    if fingerprint != self.fingerprint {
+                        return Err(format!(
+                            "expected final fingerprint {:016x}, but got {fingerprint:016x}",
+                            self.fingerprint
+                        ));
+                    }
+                    let options = options_hasher.finish();
+                    if options != self.options {
+                        return Err(format!(
+                            "expected options digest {:016x}, but got {options:016x} (the options \
+                             offered at some choice changed, or are in a different order)",
+                            self.options
+                        ));
+                    }
+                    // the fingerprint doesn't cover the RNG, so also check that replaying the
+                    // game again reaches exactly the same state
+                    if self.replay(camp_types, person_types, event_types) != game_state {
+                        return Err(
+                            "replaying the game again reached a different state".to_string()
+                        );
+                    }
+                    return Ok(());
+                }
+                Err(game_result) => {
+                    return Err(format!("game ended early at move {ply} ({game_result:?})"));
+                }
+            }
+        }
+
+        Err("game did not end after the last recorded move".to_string())
+    }
+
+    /// Replays all of this game's moves (which must be valid) and returns the final state.
+    fn replay<'ctype>(
+        &self,
+        camp_types: &'ctype [CampType],
+        person_types: &'ctype [PersonType],
+        event_types: &'ctype [EventType],
+    ) -> GameState<'ctype> {
+        let (mut game_state, mut choice) = GameState::new_seeded(
+            camp_types,
+            person_types,
+            event_types,
+            GameRules::casual(self.allow_mulligans),
+            self.seed,
+        );
+        for &option in &self.moves {
+            match choice.choose(&mut game_state, option) {
+                Ok(next_choice) => choice = next_choice,
+                Err(_) => break,
+            }
+        }
+        game_state
+    }
+}
+
+impl fmt::Display for GoldenGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "seed={} mulligans={} result={:?} fingerprint={:016x} options={:016x} moves={}",
+            self.seed,
+            self.allow_mulligans,
+            self.result,
+            self.fingerprint,
+            self.options,
+            self.moves.iter().join(","),
+        )
+    }
+}
+
+impl FromStr for GoldenGame {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let [seed, allow_mulligans, result, fingerprint, options, moves] = parse_fields(
+            line,
+            [
+                "seed",
+                "mulligans",
+                "result",
+                "fingerprint",
+                "options",
+                "moves",
+            ],
+        )?;
+
+        Ok(GoldenGame {
+            seed: seed
+                .parse()
+                .map_err(|_| format!("invalid seed: {seed:?}"))?,
+            allow_mulligans: allow_mulligans
+                .parse()
+                .map_err(|_| format!("invalid mulligans flag: {allow_mulligans:?}"))?,
+            result: match result {
+                "P1Wins" => GameResult::P1Wins,
+                "P2Wins" => GameResult::P2Wins,
+                "Tie" => GameResult::Tie,
+                _ => return Err(format!("invalid result: {result:?}")),
+            },
+            fingerprint: u64::from_str_radix(fingerprint, 16)
+                .map_err(|_| format!("invalid fingerprint: {fingerprint:?}"))?,
+            options: u64::from_str_radix(options, 16)
+                .map_err(|_| format!("invalid options digest: {options:?}"))?,
+            moves: moves
+                .split(',')
+                .map(|m| m.parse().map_err(|_| format!("invalid move: {m:?}")))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Parses a line of whitespace-separated `key=value` fields, which must have exactly the given
+/// keys in order. Returns the values.
+pub fn parse_fields<'a, const N: usize>(
+    line: &'a str,
+    keys: [&str; N],
+) -> Result<[&'a str; N], String> {
+    parse_fields_with_defaults(line, keys.map(|key| (key, None)))
+}
+
+/// Like `parse_fields`, but a key that's given a default value may be left out of the line (e.g.
+/// because the line was written before the field existed), in which case the default is its
+/// value.
+pub fn parse_fields_with_defaults<'a, const N: usize>(
+    line: &'a str,
+    keys: [(&str, Option<&'a str>); N],
+) -> Result<[&'a str; N], String> {
+    let mut fields = line
+        .split_whitespace()
+        .map(|field| {
+            field
+                .split_once('=')
+                .ok_or_else(|| format!("malformed field: {field:?}"))
+        })
+        .peekable();
+    let mut values = [""; N];
+    for (value, (expected_key, default)) in values.iter_mut().zip(keys) {
+        *value = match (fields.peek().cloned().transpose()?, default) {
+            (Some((key, field_value)), _) if key == expected_key => {
+                fields.next();
+                field_value
+            }
+            (_, Some(default)) => default,
+            (Some((key, _)), None) => {
+                return Err(format!("expected field {expected_key:?}, found {key:?}"))
+            }
+            (None, None) => return Err(format!("missing field: {expected_key}")),
+        };
+    }
+    match fields.next().transpose()? {
+        Some((key, value)) => Err(format!("unexpected field: \"{key}={value}\"")),
+        None => Ok(values),
+    }
+}
+
+/// Records `num_games` new golden games (with seeds `0..num_games`), and overwrites the golden
+/// games file with them.
+pub fn record_all(
+    num_games: u64,
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+) {
+    let lines = (0..num_games)
+        .map(|seed| {
+            let allow_mulligans = seed % 2 == 1; // cover games with and without mulligans
+            GoldenGame::record(seed, allow_mulligans, camp_types, person_types, event_types)
+        })
+        .join("\n");
+    fs::write(GOLDEN_GAMES_PATH, lines + "\n").expect("Failed to write the golden games file");
+    println!("Recorded {num_games} golden games to {GOLDEN_GAMES_PATH}");
+}
+
+/// Replays every game in the golden games file, printing any discrepancies.
+/// Returns whether all the games matched.
+pub fn check_all(
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+) -> bool {
+    let contents =
+        fs::read_to_string(GOLDEN_GAMES_PATH).expect("Failed to read the golden games file");
+
+    let mut num_games = 0;
+    let mut num_failures = 0;
+    for (line_index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        num_games += 1;
+
+        let check_result = line
+            .parse::<GoldenGame>()
+            .and_then(|game| game.check(camp_types, person_types, event_types));
+        if let Err(message) = check_result {
+            num_failures += 1;
+            println!("Golden game on line {} failed: {message}", line_index + 1);
+        }
+    }
+
+    println!(
+        "{} of {num_games} golden games matched",
+        num_games - num_failures
+    );
+    num_failures == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::radlands::card_set::CardSet;
+
+    #[test]
+    fn golden_games_match() {
+        let card_set = CardSet::standard();
+        assert!(check_all(
+            &card_set.camp_types,
+            &card_set.person_types,
+            &card_set.event_types,
+        ));
+    }
+}
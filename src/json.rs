@@ -0,0 +1,19 @@
+//! Helpers for writing JSON by hand (the project doesn't depend on a serialization library).
+
+use std::fmt::Write;
+
+/// Returns the string as a JSON string literal.
+pub fn json_string(s: &str) -> String {
+    let mut json = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
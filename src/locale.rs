@@ -0,0 +1,251 @@
+//! Localization of the text shown to the user.
+//!
+//! Every localizable string is identified by its English text (e.g. a card's name), which is
+//! also what's shown when no translation is loaded. Since card types keep their English names
+//! as identifiers, translating them doesn't affect anything that is saved or compared.
+//!
+//! A locale file is a list of translations, grouped into sections:
+//!
+//! ```text
+//! # Lines starting with '#' are comments; blank lines are ignored.
+//! [person]
+//! Looter = Plünderer
+//!
+//! [ui]
+//! Hand = Hand
+//! ```
+//!
+//! The sections are `camp`, `person`, and `event` (card names), `ability` (ability descriptions
+//! and icon effects), and `ui` (labels in the UI). Each line of a section has the form
+//! `<English text> = <translation>`. Strings that aren't translated are shown in English. Run
+//! RadBot with `--locale-template` to print every localizable string in this format.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::sync::RwLock;
+
+use itertools::Itertools;
+
+use crate::radlands::camps::CampType;
+use crate::radlands::events::EventType;
+use crate::radlands::people::PersonType;
+use crate::radlands::{IconEffect, RAIDERS_EVENT};
+
+/// A group of localizable strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    Camp,
+    Person,
+    Event,
+    Ability,
+    Ui,
+}
+
+impl Section {
+    /// All the sections, in the order they appear in the template.
+    pub const ALL: [Section; 5] = [
+        Section::Camp,
+        Section::Person,
+        Section::Event,
+        Section::Ability,
+        Section::Ui,
+    ];
+
+    /// Returns the name of this section in locale files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Section::Camp => "camp",
+            Section::Person => "person",
+            Section::Event => "event",
+            Section::Ability => "ability",
+            Section::Ui => "ui",
+        }
+    }
+}
+
+/// The strings in the `ui` section. Every UI string that is translated must be listed here, so
+/// that the template includes it.
+pub const UI_STRINGS: &[&str] = &[
+    "Log",
+    "Options",
+    "Sandbox",
+    "Input",
+    "Note",
+    "Game State",
+    "Stats",
+    "Stats (Player 1)",
+    "Stats (Player 2)",
+    "Analysis (Player 1)",
+    "Analysis (Player 2)",
+    "Unseen by Player 1",
+    "Unseen by Player 2",
+    "The terminal is too small for RadBot; please enlarge it to at least",
+    "Player 1 wins!",
+    "Player 2 wins!",
+    "The game ends in a tie!",
+    "Match",
+    "best of",
+    "game",
+    "Player",
+    "wins the match",
+    "The match was left unfinished at",
+    "win",
+    "wins",
+    "going first",
+    "games",
+    "tied",
+    "by agreement",
+    "resigned",
+    "choices per game on average",
+    "water",
+    "Hand",
+    "Events",
+    "Board",
+    "hidden cards",
+    "<none>",
+    "<destroyed>",
+    "<choosing>",
+    "Discard pile",
+    "Deck",
+    "cards",
+    "Filter:",
+    "(no cards)",
+    "Type to filter, arrows/PgUp/PgDn to scroll, Tab to switch piles, Esc to close",
+    "Type to filter, arrows/PgUp/PgDn to scroll, Esc to close",
+];
+
+type Translations = HashMap<Section, HashMap<String, &'static str>>;
+
+/// The translations of the loaded locale, if any. The translated strings are leaked so that
+/// looking them up gives `&'static str`s, like the English strings; a locale is only loaded once.
+static TRANSLATIONS: RwLock<Option<Translations>> = RwLock::new(None);
+
+/// Returns the translation of the given English text in the loaded locale, or the text itself if
+/// it isn't translated.
+pub fn translate(section: Section, text: &str) -> &str {
+    debug_assert!(
+        section != Section::Ui || UI_STRINGS.contains(&text),
+        "UI string {text:?} is missing from UI_STRINGS",
+    );
+
+    let translations = TRANSLATIONS.read().unwrap();
+    let translation = translations
+        .as_ref()
+        .and_then(|translations| translations.get(&section))
+        .and_then(|section_translations| section_translations.get(text));
+    match translation {
+        Some(&translation) => translation,
+        None => text,
+    }
+}
+
+/// Loads the locale file at the given path, so that its translations are used from now on.
+/// Returns an error message if the file can't be read or parsed.
+pub fn load(path: &Path) -> Result<(), String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("couldn't read {}: {err}", path.display()))?;
+    let translations = parse(&contents)?;
+
+    let translations = translations
+        .into_iter()
+        .map(|(section, section_translations)| {
+            let leaked = section_translations
+                .into_iter()
+                .map(|(text, translation)| {
+                    let translation: &'static str = Box::leak(translation.into_boxed_str());
+                    (text, translation)
+                })
+                .collect();
+            (section, leaked)
+        })
+        .collect();
+    *TRANSLATIONS.write().unwrap() = Some(translations);
+    Ok(())
+}
+
+/// Parses the contents of a locale file.
+fn parse(contents: &str) -> Result<HashMap<Section, HashMap<String, String>>, String> {
+    let mut translations: HashMap<Section, HashMap<String, String>> = HashMap::new();
+    let mut section = None;
+    for (line_index, line) in contents.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = Some(
+                Section::ALL
+                    .into_iter()
+                    .find(|section| section.name() == name.trim())
+                    .ok_or_else(|| format!("line {line_number}: unknown section {name:?}"))?,
+            );
+            continue;
+        }
+
+        let section = section
+            .ok_or_else(|| format!("line {line_number}: translation outside of a section"))?;
+        let (text, translation) = line.split_once('=').ok_or_else(|| {
+            format!("line {line_number}: expected `<English text> = <translation>`")
+        })?;
+        translations
+            .entry(section)
+            .or_default()
+            .insert(text.trim().to_string(), translation.trim().to_string());
+    }
+    Ok(translations)
+}
+
+/// Returns a locale file containing every localizable string, translated to itself.
+/// Must be called before a locale is loaded, so that the ability descriptions are in English.
+pub fn template(
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+) -> String {
+    let abilities = camp_types
+        .iter()
+        .flat_map(|camp_type| &camp_type.abilities)
+        .chain(
+            person_types
+                .iter()
+                .flat_map(|person_type| &person_type.abilities),
+        )
+        .map(|ability| ability.description());
+    let icon_effects = IconEffect::ALL
+        .into_iter()
+        .map(|icon_effect| format!("{icon_effect:?}"));
+
+    let mut template = String::from(
+        "# RadBot locale file. Each line is `<English text> = <translation>`.\n\
+         # Untranslated lines can be removed; they will be shown in English.\n",
+    );
+    for section in Section::ALL {
+        let texts = match section {
+            Section::Camp => camp_types.iter().map(|t| t.name.to_string()).collect_vec(),
+            Section::Person => std::iter::once("Punk".to_string())
+                .chain(person_types.iter().map(|t| t.name.to_string()))
+                .collect_vec(),
+            Section::Event => event_types
+                .iter()
+                .chain([&RAIDERS_EVENT])
+                .map(|t| t.name.to_string())
+                .collect_vec(),
+            Section::Ability => icon_effects
+                .clone()
+                .chain(abilities.clone())
+                .unique()
+                .collect_vec(),
+            Section::Ui => UI_STRINGS.iter().map(|text| text.to_string()).collect_vec(),
+        };
+
+        write!(template, "\n[{}]\n", section.name()).unwrap();
+        for text in texts {
+            writeln!(template, "{text} = {text}").unwrap();
+        }
+    }
+    template
+}
@@ -1,20 +1,52 @@
+mod arena;
+mod autosave;
+mod card_dump;
 mod cards;
+mod client;
+mod decision_times;
+mod demo;
+mod evaluate;
+mod event_stream;
+mod game_trace;
+mod golden;
+mod json;
+mod locale;
+mod metrics;
+mod prelude;
 mod radlands;
+mod search_trace;
+mod stable_hash;
 mod ui;
 
+use autosave::{Autosave, SavedGame};
+use card_dump::DumpFormat;
 use clap::Parser;
+use decision_times::GameTimes;
+use demo::Demo;
+use golden::GoldenGame;
 use radlands::events::EventType;
-use std::time::Duration;
+use rand::{thread_rng, Rng};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::net::SocketAddr;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 use radlands::camps::CampType;
 use radlands::choices::Choice;
+use radlands::end_turn_warnings::EndTurnWarnings;
 use radlands::locations::Player;
 use radlands::people::PersonType;
+use radlands::rules::{GameRules, RulesFidelity};
+use radlands::turn_status::TurnStatus;
 use radlands::*;
 
 use radlands::controllers::{
-    human::HumanController, monte_carlo::MonteCarloController, random::RandomController,
-    PlayerController,
+    auto_actions::{AutoActions, AutoEndTurn},
+    monte_carlo::{Determinization, WorldAggregation},
+    search_tree::{set_tree_dump, TreeDumpSettings},
+    ControllerSpec, EndGameRequest, PlayerController, SearchBudget,
 };
 
 fn validate_secs(s: &str) -> Result<(), String> {
@@ -26,6 +58,24 @@ fn validate_secs(s: &str) -> Result<(), String> {
     }
 }
 
+fn validate_fps(s: &str) -> Result<(), String> {
+    let fps = s.parse::<u32>().map_err(|_| "invalid number".to_string())?;
+    if fps > 0 {
+        Ok(())
+    } else {
+        Err("the frame rate must be positive".to_string())
+    }
+}
+
+fn validate_best_of(s: &str) -> Result<(), String> {
+    let games = s.parse::<u32>().map_err(|_| "invalid number".to_string())?;
+    if games % 2 == 1 {
+        Ok(())
+    } else {
+        Err("a match must be best of an odd number of games".to_string())
+    }
+}
+
 #[derive(Parser, Debug)]
 #[clap(
     name = "RadBot",
@@ -41,6 +91,87 @@ struct Args {
     #[clap(short, long, conflicts_with = "humans")]
     random: bool,
 
+    /// With --random, play only the game with the given seed (e.g. one from a quarantine file)
+    #[clap(long, requires = "random")]
+    seed: Option<u64>,
+
+    /// With --random, catch panics in the game logic, record the seeds of the games that
+    /// panicked in the given file, and continue with the next game
+    #[clap(
+        long,
+        value_name = "FILE",
+        requires = "random",
+        conflicts_with = "seed"
+    )]
+    quarantine: Option<PathBuf>,
+
+    /// Play the given number of mirrored pairs of games between the controllers given by
+    /// --arena-a and --arena-b (e.g. "mcts:1" or "random"), with sides swapped within each pair
+    #[clap(
+        long,
+        value_name = "PAIRS",
+        requires_all = &["arena-a", "arena-b"],
+        conflicts_with_all = &["ui", "random", "humans"],
+    )]
+    arena: Option<usize>,
+
+    /// With --arena, the spec of the first controller
+    #[clap(long, value_name = "SPEC", requires = "arena")]
+    arena_a: Option<ControllerSpec>,
+
+    /// With --arena, the spec of the second controller
+    #[clap(long, value_name = "SPEC", requires = "arena")]
+    arena_b: Option<ControllerSpec>,
+
+    /// With --arena, the seed that every pair's setup and play seeds are generated from (by
+    /// default, a random one)
+    #[clap(long, value_name = "SEED", requires = "arena")]
+    arena_seed: Option<u64>,
+
+    /// The seed for a new game's deal and the rest of its randomness, e.g. deck reshuffles (by
+    /// default, a random one)
+    #[clap(long, value_name = "SEED", conflicts_with_all = &["random", "arena", "evaluate"])]
+    game_seed: Option<u64>,
+
+    /// The seed for the AI controllers' own RNGs, which are separate from the game's (by
+    /// default, a random one). With the game seed (or --arena-seed) and the AI seed fixed, AIs
+    /// with rollout budgets (e.g. "mcts:2000r") make the same choices every time
+    #[clap(long, value_name = "SEED", conflicts_with = "random")]
+    ai_seed: Option<u64>,
+
+    /// Have the AI evaluate every position in the given positions file (positions are set up
+    /// with the --sandbox commands), thinking for --ai-time-limit seconds on each, and write
+    /// its best moves and win rates to the CSV file given by --evaluate-output
+    #[clap(
+        long,
+        value_name = "FILE",
+        requires = "evaluate-output",
+        conflicts_with_all = &["ui", "random", "humans", "arena"],
+    )]
+    evaluate: Option<PathBuf>,
+
+    /// With --evaluate, the CSV file to write the results to
+    #[clap(long, value_name = "FILE", requires = "evaluate")]
+    evaluate_output: Option<PathBuf>,
+
+    /// Replay the recorded golden games and check that they end identically
+    #[clap(long)]
+    check_golden: bool,
+
+    /// Have the AI attempt the puzzles in puzzles/tactics.txt (positions with known best moves)
+    /// and check that it finds the expected moves often enough
+    #[clap(long, conflicts_with = "locale")]
+    check_puzzles: bool,
+
+    /// Re-record the golden games file with the given number of random games
+    #[clap(long, value_name = "COUNT", conflicts_with = "check-golden")]
+    record_golden: Option<u64>,
+
+    /// Check that every observation of the given number of random games round-trips through
+    /// the binary wire format, and that the decoder rejects corrupted data
+    #[clap(long, value_name = "GAMES")]
+    fuzz_wire: Option<u64>,
+
     /// Run a game with 2 human/manual players
     #[clap(short, long, conflicts_with = "random")]
     humans: bool,
@@ -53,91 +184,739 @@ struct Args {
         validator = validate_secs,
     )]
     ai_time_limit: f64,
+
+    /// Have the AI evaluate every option in the same set of randomized worlds (hidden card
+    /// orders) for each decision, instead of a new randomization for every rollout
+    #[clap(short = 'w', long, value_name = "COUNT")]
+    ai_worlds: Option<usize>,
+
+    /// Have the AI compare options using paired rollouts that share the same randomized
+    /// worlds (deck orders and reshuffles), reducing the noise in the comparison
+    #[clap(long, conflicts_with = "ai-worlds")]
+    ai_paired_rollouts: bool,
+
+    /// With --ai-worlds, let each world vote for an option instead of averaging the win rates
+    #[clap(long, requires = "ai-worlds")]
+    ai_world_voting: bool,
+
+    /// How often (in seconds) the AI updates its live stats in the UI while it thinks
+    #[clap(
+        long,
+        value_name = "SECONDS",
+        default_value = "0.1",
+        validator = validate_secs,
+    )]
+    stats_interval: f64,
+
+    /// The most frames per second that the UI draws (updates that arrive faster are drawn
+    /// together)
+    #[clap(long, value_name = "FPS", default_value = "30", validator = validate_fps)]
+    max_fps: u32,
+
+    /// Make the AI of a new game use its whole budget for every decision, even once the
+    /// most-visited option can't be overtaken (the specs given to --arena and --trace-game can
+    /// end with :no-early-stop instead)
+    #[clap(long)]
+    no_early_stop: bool,
+
+    /// After each of the AI's decisions, write its search tree to JSON and Graphviz files in
+    /// the given directory (only the MCTS AI used with --ui supports this)
+    #[clap(long, value_name = "DIR")]
+    dump_search_tree: Option<PathBuf>,
+
+    /// With --dump-search-tree, the maximum depth of the dumped trees
+    #[clap(
+        long,
+        value_name = "DEPTH",
+        default_value = "3",
+        requires = "dump-search-tree"
+    )]
+    dump_max_depth: usize,
+
+    /// With --dump-search-tree, the maximum number of nodes in the dumped trees
+    #[clap(
+        long,
+        value_name = "COUNT",
+        default_value = "200",
+        requires = "dump-search-tree"
+    )]
+    dump_max_nodes: usize,
+
+    /// Load a search state file written by the MCTS AI with --dump-search-tree, and print a
+    /// trace of the PUCT computations in --trace-iterations search iterations from it
+    #[clap(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = &["ui", "random", "humans", "arena", "evaluate", "demo"],
+    )]
+    trace_search: Option<PathBuf>,
+
+    /// With --trace-search, the number of search iterations to trace
+    #[clap(
+        long,
+        value_name = "COUNT",
+        default_value = "1",
+        requires = "trace-search"
+    )]
+    trace_iterations: usize,
+
+    /// With --trace-search, the seed for randomizing the hidden information and rollouts
+    #[clap(
+        long,
+        value_name = "SEED",
+        default_value = "0",
+        requires = "trace-search"
+    )]
+    trace_seed: u64,
+
+    /// Play one game between AIs (--trace-controller on both sides) with the given game seed and
+    /// --ai-seed (0 by default), and print a trace of every ply: the options, the AI's evaluation
+    /// of them, the changes made, and fingerprints of the game state and RNG
+    #[clap(
+        long,
+        value_name = "SEED",
+        conflicts_with_all = &["ui", "random", "humans", "arena", "evaluate", "demo", "trace-search"],
+    )]
+    trace_game: Option<u64>,
+
+    /// With --trace-game, the spec of the AI that plays both sides (with a rollout budget, the
+    /// game is the same every time)
+    #[clap(
+        long,
+        value_name = "SPEC",
+        default_value = "mcts:2000r",
+        requires = "trace-game"
+    )]
+    trace_controller: ControllerSpec,
+
+    /// With --ui, play against a remote human (instead of the AI), who connects to the given
+    /// address with --connect
+    #[clap(long, value_name = "ADDR", requires = "ui")]
+    listen: Option<SocketAddr>,
+
+    /// Publish every update of the game being played (with --ui or in a plain game) as
+    /// newline-delimited JSON (or binary frames) to subscribers that connect to the given address
+    #[clap(long, value_name = "ADDR")]
+    event_stream: Option<SocketAddr>,
+
+    /// Connect to a game hosted with --listen at the given address, and play as player 2
+    #[clap(long, value_name = "ADDR", conflicts_with_all = &["ui", "listen"])]
+    connect: Option<SocketAddr>,
+
+    /// What to do for human players when ending the turn is the only meaningful action: choose
+    /// it as usual ("manual"), ask, offering to automate it from then on ("prompt"), or end the
+    /// turn automatically ("auto")
+    #[clap(
+        long,
+        value_name = "MODE",
+        default_value = "prompt",
+        possible_values = ["manual", "prompt", "auto"],
+    )]
+    auto_end_turn: String,
+
+    /// Don't make any choices automatically for human players (by default, turns with nothing
+    /// left to do are handled according to --auto-end-turn, and icon effects that can't be used
+    /// are declined)
+    #[clap(long)]
+    no_auto_actions: bool,
+
+    /// The warnings that human players are asked to confirm before ending their turn while
+    /// there's still something else to do: a comma-separated list of "water" (water left
+    /// unspent), "restore" (a damaged camp that could still be restored), and "lethal" (the
+    /// damage abilities on the opponent's board could destroy all of their camps), or "none"
+    #[clap(long, value_name = "WARNINGS", default_value = "water,restore,lethal")]
+    end_turn_warnings: EndTurnWarnings,
+
+    /// With --ui, set up the position to play from by editing a new game's setup (the game
+    /// starts once the position is valid, and isn't autosaved)
+    #[clap(long, requires = "ui")]
+    sandbox: bool,
+
+    /// Play the prerecorded demo game in demo/demo.txt in the UI, with captions in the log (for
+    /// recording terminal casts)
+    #[clap(
+        long,
+        conflicts_with_all = &["sandbox", "random", "humans", "arena", "evaluate", "listen"],
+    )]
+    demo: bool,
+
+    /// With --demo, the number of seconds to pause before each move
+    #[clap(
+        long,
+        value_name = "SECONDS",
+        default_value = "1.5",
+        validator = validate_secs,
+        requires = "demo",
+    )]
+    demo_pace: f64,
+
+    /// With --ui, play a best-of-N match (e.g. 3 or 5) instead of a single game, alternating
+    /// which player goes first
+    #[clap(
+        long,
+        value_name = "GAMES",
+        validator = validate_best_of,
+        requires = "ui",
+        conflicts_with_all = &["sandbox", "demo", "listen"],
+    )]
+    best_of: Option<u32>,
+
+    /// With --ui, write the game (with the notes attached to its moves) to the given file when
+    /// the UI exits, to be reviewed later with --load-game
+    #[clap(
+        long,
+        value_name = "FILE",
+        requires = "ui",
+        conflicts_with_all = &["sandbox", "demo", "best-of"],
+    )]
+    export_game: Option<PathBuf>,
+
+    /// With --ui, resume the game saved in the given file by --export-game (or review it, if it
+    /// is over), with its notes, instead of starting a new game
+    #[clap(
+        long,
+        value_name = "FILE",
+        requires = "ui",
+        conflicts_with_all = &["sandbox", "demo", "best-of", "game-seed", "ai-seed"],
+    )]
+    load_game: Option<PathBuf>,
+
+    /// Let each player choose whether to mulligan their opening hand
+    #[clap(short, long)]
+    mulligan: bool,
+
+    /// In a plain game (without --ui), print the status of the turn and the option chosen at
+    /// every choice
+    #[clap(short, long, conflicts_with = "ui")]
+    verbose: bool,
+
+    /// Play by the official rules only, without house conveniences such as mulligans (for
+    /// tournament play and AI training)
+    #[clap(long, conflicts_with_all = &["mulligan", "random"])]
+    strict_rules: bool,
+
+    /// Show card names, ability descriptions, and UI labels translated by the given locale file
+    #[clap(long, value_name = "FILE")]
+    locale: Option<PathBuf>,
+
+    /// Play with the given card set: "base" (the default) for the base game, "no-events" for the
+    /// base game without events, or the path of a card set file, which changes, adds, or removes
+    /// cards from the base game (see src/radlands/card_set.rs for the format)
+    #[clap(
+        long,
+        value_name = "SET",
+        default_value = "base",
+        conflicts_with_all = &["check-golden", "record-golden", "check-puzzles"],
+    )]
+    card_set: String,
+
+    /// Print a locale file containing every localizable string (in English), to start a
+    /// translation from
+    #[clap(long, conflicts_with = "locale")]
+    locale_template: bool,
+
+    /// Print a description of every card (name, cost, junk effect, abilities, count in the
+    /// deck, and traits) as JSON or Markdown, for documentation and external tools
+    #[clap(
+        long,
+        value_name = "FORMAT",
+        min_values = 0,
+        default_missing_value = "json",
+        possible_values = ["json", "markdown"],
+        conflicts_with = "locale",
+    )]
+    dump_cards: Option<String>,
 }
 
 fn main() {
     let args = Args::parse();
 
+    let card_set = card_set::select_card_set(&args.card_set).unwrap_or_else(|message| {
+        eprintln!("Failed to load the card set: {message}");
+        std::process::exit(1);
+    });
+    let camp_types = &card_set.camp_types[..];
+    let person_types = &card_set.person_types[..];
+    let event_types = &card_set.event_types[..];
+
+    if args.locale_template {
+        print!(
+            "{}",
+            locale::template(camp_types, person_types, event_types)
+        );
+        return;
+    }
+
+    if let Some(format) = &args.dump_cards {
+        let format = match format.as_str() {
+            "json" => DumpFormat::Json,
+            "markdown" => DumpFormat::Markdown,
+            _ => unreachable!("clap only allows the possible values"),
+        };
+        let cards = card_dump::describe_all(camp_types, person_types, event_types);
+        print!("{}", card_dump::format(&cards, format));
+        return;
+    }
+
     println!("RadBot, version {}\n", env!("CARGO_PKG_VERSION"));
 
-    let camp_types = camps::get_camp_types();
-    let person_types = people::get_person_types();
-    let event_types = events::get_event_types();
+    if let Some(path) = &args.locale {
+        if let Err(message) = locale::load(path) {
+            eprintln!("Failed to load the locale: {message}");
+            std::process::exit(1);
+        }
+    }
+
+    ui::set_stats_interval(Duration::from_secs_f64(args.stats_interval));
+    ui::set_max_fps(args.max_fps);
+
+    if let Some(dir) = &args.dump_search_tree {
+        fs::create_dir_all(dir).expect("Failed to create the search tree directory");
+        set_tree_dump(TreeDumpSettings {
+            dir: dir.clone(),
+            max_depth: args.dump_max_depth,
+            max_nodes: args.dump_max_nodes,
+        });
+    }
+
+    if let Some(addr) = args.event_stream {
+        if let Err(err) = event_stream::start(addr) {
+            eprintln!("Failed to start the event stream on {addr}: {err}");
+            std::process::exit(1);
+        }
+    }
 
-    if args.ui {
-        ui::main().expect("UI error");
+    if let Some(addr) = args.connect {
+        client::main(addr).expect("Remote client error");
+    } else if args.check_golden {
+        if !golden::check_all(camp_types, person_types, event_types) {
+            std::process::exit(1);
+        }
+    } else if args.check_puzzles {
+        if !evaluate::check_puzzles(camp_types, person_types, event_types) {
+            std::process::exit(1);
+        }
+    } else if let Some(num_games) = args.record_golden {
+        golden::record_all(num_games, camp_types, person_types, event_types);
+    } else if let Some(num_games) = args.fuzz_wire {
+        if !wire::fuzz_round_trip(num_games, camp_types, person_types, event_types) {
+            std::process::exit(1);
+        }
+    } else if let Some(num_pairs) = args.arena {
+        let specs = [args.arena_a, args.arena_b].map(|spec| spec.expect("required by --arena"));
+        for spec in specs {
+            if !spec.is_ai() {
+                eprintln!("Only AI controllers can play in the arena, not {spec}");
+                std::process::exit(1);
+            }
+        }
+        let seed = args.arena_seed.unwrap_or_else(|| thread_rng().gen());
+        let ai_seed = args.ai_seed.unwrap_or_else(|| thread_rng().gen());
+        arena::run(
+            specs[0],
+            specs[1],
+            num_pairs,
+            seed,
+            ai_seed,
+            game_rules(&args),
+            camp_types,
+            person_types,
+            event_types,
+        );
+    } else if let Some(positions_path) = &args.evaluate {
+        let output_path = args
+            .evaluate_output
+            .as_ref()
+            .expect("required by --evaluate");
+        let result = evaluate::run(
+            positions_path,
+            output_path,
+            Duration::from_secs_f64(args.ai_time_limit),
+            args.ai_seed.unwrap_or_else(|| thread_rng().gen()),
+            game_rules(&args),
+            camp_types,
+            person_types,
+            event_types,
+        );
+        if let Err(message) = result {
+            eprintln!("{message}");
+            std::process::exit(1);
+        }
+    } else if let Some(path) = &args.trace_search {
+        let result = search_trace::run(
+            path,
+            args.trace_iterations,
+            args.trace_seed,
+            camp_types,
+            person_types,
+            event_types,
+        );
+        if let Err(message) = result {
+            eprintln!("{message}");
+            std::process::exit(1);
+        }
+    } else if let Some(seed) = args.trace_game {
+        if !args.trace_controller.is_ai() {
+            eprintln!(
+                "Only AI controllers can play a traced game, not {}",
+                args.trace_controller
+            );
+            std::process::exit(1);
+        }
+        game_trace::run(
+            seed,
+            args.ai_seed.unwrap_or(0),
+            args.trace_controller,
+            game_rules(&args),
+            camp_types,
+            person_types,
+            event_types,
+        );
+    } else if args.demo {
+        let pace = Duration::from_secs_f64(args.demo_pace);
+        let demo = match Demo::load(pace, camp_types, person_types, event_types) {
+            Ok(demo) => demo,
+            Err(message) => {
+                eprintln!("Invalid demo script: {message}");
+                std::process::exit(1);
+            }
+        };
+        ui::main(demo.saved_game(), false, Some(demo), None, None, &card_set).expect("UI error");
     } else if args.random {
-        let num_games = 100_000;
-        println!("Running {} random games...", num_games);
-        for _ in 0..num_games {
-            do_game(&camp_types, &person_types, &event_types, &args);
+        if let Some(seed) = args.seed {
+            println!("Running the random game with seed {seed}...");
+            play_random_game(camp_types, person_types, event_types, &args, seed);
+        } else {
+            run_random_games(camp_types, person_types, event_types, &args);
         }
     } else {
-        do_game(&camp_types, &person_types, &event_types, &args);
+        // load or resume a game, or start a new one (a sandbox or match always starts a new one)
+        let resumed_game = if let Some(path) = &args.load_game {
+            let saved_game = SavedGame::load(path).and_then(|saved_game| {
+                match saved_game.replay(camp_types, person_types, event_types, |_, _, _| {}) {
+                    Ok(_) => Ok(saved_game),
+                    Err(message) => Err(format!("Can't replay the game in the file: {message}")),
+                }
+            });
+            match saved_game {
+                Ok(saved_game) => Some(saved_game),
+                Err(message) => {
+                    eprintln!("{message}");
+                    std::process::exit(1);
+                }
+            }
+        } else if args.sandbox || args.best_of.is_some() {
+            None
+        } else {
+            autosave::offer_resume(camp_types, person_types, event_types)
+        };
+        let saved_game = resumed_game.unwrap_or_else(|| {
+            let (p1, p2) = controller_specs(&args);
+            let mut saved_game = SavedGame::new_game(game_rules(&args), p1, p2);
+            if let Some(seed) = args.game_seed {
+                saved_game.seed = seed;
+            }
+            if let Some(ai_seed) = args.ai_seed {
+                saved_game.ai_seed = ai_seed;
+            }
+            saved_game
+        });
+
+        if args.ui {
+            ui::main(
+                saved_game,
+                args.sandbox,
+                None,
+                args.best_of,
+                args.export_game.clone(),
+                &card_set,
+            )
+            .expect("UI error");
+        } else {
+            do_game(
+                camp_types,
+                person_types,
+                event_types,
+                saved_game,
+                args.verbose,
+            );
+        }
     }
+
+    metrics::print_report();
 }
 
-fn do_game(
+/// Plays a game with uniformly random moves. The moves are chosen by an RNG seeded with the same
+/// seed as the game, so the game can be reproduced from its seed.
+fn play_random_game(
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+    args: &Args,
+    seed: u64,
+) {
+    GoldenGame::record(seed, args.mulligan, camp_types, person_types, event_types);
+}
+
+/// Plays a bunch of random games to fuzz-test the game logic. With --quarantine, a game that
+/// panics is recorded in the quarantine file instead of ending the run.
+fn run_random_games(
     camp_types: &[CampType],
     person_types: &[PersonType],
     event_types: &[EventType],
     args: &Args,
 ) {
-    let mut p1: Box<dyn PlayerController>;
-    let mut p2: Box<dyn PlayerController>;
-    if args.random {
-        p1 = Box::new(RandomController);
-        p2 = Box::new(RandomController);
+    let num_games = 100_000;
+    println!("Running {} random games...", num_games);
+
+    let mut num_panics = 0;
+    for _ in 0..num_games {
+        let seed = thread_rng().gen();
+        let quarantine_path = match &args.quarantine {
+            Some(quarantine_path) => quarantine_path,
+            None => {
+                play_random_game(camp_types, person_types, event_types, args, seed);
+                continue;
+            }
+        };
+
+        // each game has its own state, so nothing is left inconsistent by a panic
+        let game_result = panic::catch_unwind(AssertUnwindSafe(|| {
+            play_random_game(camp_types, person_types, event_types, args, seed)
+        }));
+        if let Err(payload) = game_result {
+            num_panics += 1;
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "<unknown panic payload>".to_string());
+            let line = format!(
+                "seed={seed} mulligans={} panic={message:?}\n",
+                args.mulligan
+            );
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(quarantine_path)
+                .and_then(|mut file| file.write_all(line.as_bytes()))
+                .expect("Failed to write to the quarantine file");
+        }
+    }
+
+    if let Some(quarantine_path) = &args.quarantine {
+        println!(
+            "{num_panics} of {num_games} games panicked; their seeds were recorded in {}",
+            quarantine_path.display()
+        );
+    }
+}
+
+/// Returns the rules for a new game, based on the command-line arguments.
+fn game_rules(args: &Args) -> GameRules {
+    GameRules {
+        mulligans: args.mulligan,
+        fidelity: if args.strict_rules { RulesFidelity::Strict } else { RulesFidelity::Casual },
+    }
+}
+
+/// Returns the specs for the controllers of a new game, based on the command-line arguments.
+fn controller_specs(args: &Args) -> (ControllerSpec, ControllerSpec) {
+    let human = ControllerSpec::Human {
+        auto_actions: if args.no_auto_actions {
+            AutoActions {
+                end_turn_warnings: args.end_turn_warnings,
+                ..AutoActions::NONE
+            }
+        } else {
+            AutoActions {
+                end_turn: match args.auto_end_turn.as_str() {
+                    "manual" => AutoEndTurn::Manual,
+                    "prompt" => AutoEndTurn::Prompt,
+                    "auto" => AutoEndTurn::Auto,
+                    _ => unreachable!("clap only allows the possible values"),
+                },
+                skip_unusable_icon_effects: true,
+                end_turn_warnings: args.end_turn_warnings,
+            }
+        },
+    };
+
+    if let Some(addr) = args.listen {
+        (human, ControllerSpec::Remote { addr })
+    } else if args.ui {
+        let ai = ControllerSpec::Mcts {
+            budget: SearchBudget::Time(Duration::from_secs_f64(3.0)),
+            early_stop: !args.no_early_stop,
+        };
+        (ai, human)
     } else if args.humans {
-        p1 = Box::new(HumanController);
-        p2 = Box::new(HumanController);
+        (human, human)
     } else {
-        let ai_time_limit = Duration::from_secs_f64(args.ai_time_limit);
-        println!("AI time limit: {:?}", ai_time_limit);
-        p1 = Box::new(MonteCarloController {
-            player: Player::Player1,
-            choice_time_limit: ai_time_limit,
-            make_rollout_controller: |_| RandomController,
-        });
-        p2 = Box::new(HumanController);
+        let ai = ControllerSpec::MonteCarlo {
+            budget: SearchBudget::Time(Duration::from_secs_f64(args.ai_time_limit)),
+            determinization: match args.ai_worlds {
+                None if args.ai_paired_rollouts => Determinization::Paired,
+                None => Determinization::Independent,
+                Some(num_worlds) => Determinization::Worlds {
+                    num_worlds,
+                    aggregation: if args.ai_world_voting {
+                        WorldAggregation::Vote
+                    } else {
+                        WorldAggregation::Average
+                    },
+                },
+            },
+            early_stop: !args.no_early_stop,
+        };
+        (ai, human)
     }
+}
 
-    let (mut game_state, choice) = GameState::new(camp_types, person_types, event_types);
+/// Plays the given (new or resumed) game to the end, autosaving it after every choice.
+fn do_game(
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+    saved_game: SavedGame,
+    verbose: bool,
+) {
+    println!("Player 1: {}", saved_game.p1);
+    println!("Player 2: {}", saved_game.p2);
+    let mut p1 = saved_game
+        .p1
+        .build(Player::Player1, saved_game.controller_seed(Player::Player1));
+    let mut p2 = saved_game
+        .p2
+        .build(Player::Player2, saved_game.controller_seed(Player::Player2));
 
-    let result = play_to_end(&mut game_state, choice, p1.as_mut(), p2.as_mut());
+    let (mut game_state, mut cur_choice) = saved_game
+        .replay(camp_types, person_types, event_types, |_, _, _| {})
+        .expect("Failed to replay the saved game");
 
-    if !args.random {
-        println!(
-            "\nGame ended; {}",
-            match result {
-                GameResult::P1Wins => "player 1 wins!",
-                GameResult::P2Wins => "player 2 wins!",
-                GameResult::Tie => "tie!",
-            }
+    let mut autosave = Autosave::start(saved_game);
+    let mut times = GameTimes::default();
+    event_stream::publish(&game_state, &cur_choice);
+    let result = loop {
+        let choice = match &cur_choice {
+            Ok(choice) => choice,
+            Err(game_result) => break *game_result,
+        };
+        // the option is described in the state it was chosen in
+        let state_before = verbose.then(|| {
+            let status = TurnStatus::new(&game_state, choice);
+            println!("\n{}", ui::spans_to_string(&status.format()));
+            game_state.clone()
+        });
+        let (chosen_option, choice_result) = do_one_choice(
+            &mut game_state,
+            choice,
+            p1.as_mut(),
+            p2.as_mut(),
+            Some(&mut times),
         );
-    }
+        if let Some(state_before) = &state_before {
+            println!(
+                "{:?}: {}",
+                choice.chooser(state_before),
+                choice.option_label(chosen_option, state_before)
+            );
+            for change in state_before.diff(&game_state).changes() {
+                println!("    {change}");
+            }
+        }
+        autosave.record_move(chosen_option);
+        cur_choice = choice_result;
+        event_stream::publish(&game_state, &cur_choice);
+    };
+    autosave.finish();
+
+    println!(
+        "\nGame ended; {}",
+        match result {
+            GameResult::P1Wins => "player 1 wins!",
+            GameResult::P2Wins => "player 2 wins!",
+            GameResult::Tie => "tie!",
+        }
+    );
+    println!("Player 1's decisions: {}", times.p1);
+    println!("Player 2's decisions: {}", times.p2);
 }
 
+/// Plays the game to the end from the given choice. If `times` is given, the controllers'
+/// decision times are recorded in it.
 pub fn play_to_end<'ctype>(
     game_state: &mut GameState<'ctype>,
     mut choice: Choice<'ctype>,
     p1: &mut dyn PlayerController<'ctype>,
     p2: &mut dyn PlayerController<'ctype>,
+    mut times: Option<&mut GameTimes>,
 ) -> GameResult {
     loop {
-        match do_one_choice(game_state, &choice, p1, p2).1 {
+        match do_one_choice(game_state, &choice, p1, p2, times.as_deref_mut()).1 {
             Ok(new_choice) => choice = new_choice,
             Err(game_result) => return game_result,
         }
     }
 }
 
+/// Has the choosing player's controller make the given choice, and applies it. If `times` is
+/// given and the choice has more than one option, the time the controller took is recorded in
+/// it. (The AI's rollouts don't time their choices, to keep them fast.)
 fn do_one_choice<'c, 'ctype>(
     game_state: &mut GameState<'ctype>,
     choice: &Choice<'ctype>,
     p1: &'c mut dyn PlayerController<'ctype>,
     p2: &'c mut dyn PlayerController<'ctype>,
+    times: Option<&mut GameTimes>,
 ) -> (usize, Result<Choice<'ctype>, GameResult>) {
+    let decision = do_one_decision(
+        game_state,
+        choice,
+        p1,
+        p2,
+        times,
+        |controller, game_view| Ok(controller.choose_option(game_view, choice)),
+    );
+    match decision {
+        Ok(decision) => decision,
+        Err(_) => unreachable!("the controller was only asked to choose"),
+    }
+}
+
+/// Like `do_one_choice`, but the choosing player may ask to end the game early instead (see
+/// `PlayerController::choose_option_or_end_game`), in which case the game state is left as it
+/// was and the request is returned.
+fn do_one_choice_or_end_game<'c, 'ctype>(
+    game_state: &mut GameState<'ctype>,
+    choice: &Choice<'ctype>,
+    p1: &'c mut dyn PlayerController<'ctype>,
+    p2: &'c mut dyn PlayerController<'ctype>,
+    times: Option<&mut GameTimes>,
+) -> Result<(usize, Result<Choice<'ctype>, GameResult>), EndGameRequest> {
+    do_one_decision(
+        game_state,
+        choice,
+        p1,
+        p2,
+        times,
+        |controller, game_view| controller.choose_option_or_end_game(game_view, choice),
+    )
+}
+
+/// Has the choosing player's controller decide on the given choice with `decide`, timing it,
+/// and applies the chosen option (if any).
+fn do_one_decision<'c, 'ctype>(
+    game_state: &mut GameState<'ctype>,
+    choice: &Choice<'ctype>,
+    p1: &'c mut dyn PlayerController<'ctype>,
+    p2: &'c mut dyn PlayerController<'ctype>,
+    times: Option<&mut GameTimes>,
+    decide: impl FnOnce(
+        &mut dyn PlayerController<'ctype>,
+        &GameView<'_, 'ctype>,
+    ) -> Result<usize, EndGameRequest>,
+) -> Result<(usize, Result<Choice<'ctype>, GameResult>), EndGameRequest> {
     // get the choosing player and their controller
     let chooser = choice.chooser(game_state);
     let controller = match chooser {
@@ -145,11 +924,21 @@ fn do_one_choice<'c, 'ctype>(
         Player::Player2 => p2,
     };
 
-    // have the controller choose an option
-    let chosen_option = controller.choose_option(&game_state.view_for(chooser), choice);
+    // make sure that the controller has something to choose from
+    assert!(
+        choice.num_options() > 0,
+        "{chooser:?} was given a choice with no options",
+    );
+
+    // have the controller choose an option, timing it if requested
+    let start_time = (times.is_some() && choice.num_options() > 1).then(Instant::now);
+    let chosen_option = decide(controller, &game_state.view_for(chooser))?;
+    if let (Some(times), Some(start_time)) = (times, start_time) {
+        times.player_mut(chooser).record(start_time.elapsed());
+    }
 
     // apply the choice to the game state
     let choice_result = choice.choose(game_state, chosen_option);
 
-    (chosen_option, choice_result)
+    Ok((chosen_option, choice_result))
 }
@@ -0,0 +1,114 @@
+//! Counters for the engine's hot paths, for measuring performance changes and spotting
+//! pathological behavior (like runaway choice chains).
+//!
+//! The counters are only kept when the `metrics` feature is enabled; otherwise, counting compiles
+//! to nothing.
+
+#[cfg(feature = "metrics")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A quantity that is counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Counter {
+    /// Games created.
+    Games,
+    /// Clones of a `GameState`.
+    GameStateClones,
+    /// Choices created.
+    Choices,
+    /// Clones of a `Choice` (which share its options, rather than copying them).
+    ChoiceClones,
+    /// Rollouts performed by the AI.
+    Rollouts,
+    /// Cards damaged or destroyed.
+    Damages,
+    /// Reshuffles of the discard pile into the deck.
+    Reshuffles,
+}
+
+impl Counter {
+    /// All the counters, in the order they are reported.
+    pub const ALL: [Counter; 7] = [
+        Counter::Games,
+        Counter::GameStateClones,
+        Counter::Choices,
+        Counter::ChoiceClones,
+        Counter::Rollouts,
+        Counter::Damages,
+        Counter::Reshuffles,
+    ];
+
+    /// Returns the name of this counter, for display.
+    pub fn name(self) -> &'static str {
+        match self {
+            Counter::Games => "games",
+            Counter::GameStateClones => "game state clones",
+            Counter::Choices => "choices",
+            Counter::ChoiceClones => "choice clones",
+            Counter::Rollouts => "rollouts",
+            Counter::Damages => "damages",
+            Counter::Reshuffles => "reshuffles",
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(feature = "metrics")]
+static COUNTS: [AtomicU64; Counter::ALL.len()] = [ZERO; Counter::ALL.len()];
+
+/// Increments the given counter.
+#[inline]
+pub fn increment(counter: Counter) {
+    #[cfg(feature = "metrics")]
+    COUNTS[counter as usize].fetch_add(1, Ordering::Relaxed);
+
+    #[cfg(not(feature = "metrics"))]
+    let _ = counter;
+}
+
+/// Returns the current value of the given counter (always 0 if metrics are disabled).
+pub fn get(counter: Counter) -> u64 {
+    #[cfg(feature = "metrics")]
+    return COUNTS[counter as usize].load(Ordering::Relaxed);
+
+    #[cfg(not(feature = "metrics"))]
+    {
+        let _ = counter;
+        0
+    }
+}
+
+/// Returns whether the counters are being kept (i.e. the `metrics` feature is enabled).
+pub fn enabled() -> bool {
+    cfg!(feature = "metrics")
+}
+
+/// Returns a report of all the counters, one per line, including per-game averages.
+pub fn report() -> Vec<String> {
+    let num_games = get(Counter::Games);
+    Counter::ALL
+        .into_iter()
+        .map(|counter| {
+            let count = get(counter);
+            if counter != Counter::Games && num_games > 0 {
+                let per_game = count as f64 / num_games as f64;
+                format!("{}: {count} ({per_game:.1} per game)", counter.name())
+            } else {
+                format!("{}: {count}", counter.name())
+            }
+        })
+        .collect()
+}
+
+/// Prints the report of all the counters, if they are being kept.
+pub fn print_report() {
+    if enabled() {
+        println!("\nEngine metrics:");
+        for line in report() {
+            println!("  {line}");
+        }
+    }
+}
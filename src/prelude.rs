@@ -0,0 +1,16 @@
+//! The types needed to write a player controller, re-exported in one place, so that a controller
+//! can start with `use crate::prelude::*;`. See `radlands::controllers::template` for an example.
+//!
+//! A controller is given a `GameView` of the game from its player's perspective and a `Choice`,
+//! and returns the index of one of the choice's options (from 0 to `Choice::num_options`). The
+//! options can be described with `GameView::option_label`, grouped as the UI shows them with
+//! `GameView::option_groups`, and matched to the cards they target with
+//! `GameView::option_target`. The view doesn't give access to the whole game state, which holds
+//! the opponent's hand and the order of the deck.
+//!
+//! Since RadBot is a binary, a re-export that no controller uses is an unused import, so only
+//! what the template uses is exported here. Add other types as controllers come to need them.
+
+pub use crate::radlands::choices::Choice;
+pub use crate::radlands::controllers::PlayerController;
+pub use crate::radlands::GameView;
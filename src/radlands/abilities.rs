@@ -1,19 +1,67 @@
-use super::choices::ChoiceFuture;
+use super::choices::{perform_optional_effect, ChoiceFuture};
 use super::locations::CardLocation;
+use super::people::PersonType;
 use super::{GameResult, GameView, GameViewMut, IconEffect};
 
+/// Identifies one of a person's abilities by the ID of the person type it's printed on and its
+/// index among that person's abilities, for finding the same ability reached by different routes
+/// (e.g. copied by Mimic, or given by Argo Yesky).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AbilityId {
+    person_type_id: usize,
+    index: usize,
+}
+
+impl AbilityId {
+    /// Returns the ID of the ability at the given index in the given person's abilities.
+    pub fn new(person_type: &PersonType, index: usize) -> Self {
+        AbilityId {
+            person_type_id: person_type.id,
+            index,
+        }
+    }
+}
+
 /// An ability on a camp or person.
 pub trait Ability: Sync {
-    /// Returns a description of this ability for display.
+    /// Returns a description of this ability for display, in the loaded locale.
     fn description(&self) -> String;
 
-    /// Returns the water cost of this ability.
-    fn cost<'v, 'g: 'v, 'ctype: 'g>(&self, game_view: &'v GameView<'g, 'ctype>) -> u32;
+    /// Returns the icon effect that this ability performs, if it's a plain icon ability.
+    fn icon_effect(&self) -> Option<IconEffect> {
+        None
+    }
+
+    /// Returns the water cost printed on the card, which doesn't depend on the game state.
+    fn printed_cost(&self) -> u32;
+
+    /// Returns whether this ability's effect is optional ("may"), so that once it's paid for,
+    /// its user is asked whether to resolve it (see `perform_ability`).
+    fn is_optional(&self) -> bool {
+        false
+    }
+
+    /// Returns the water cost of this ability given the game state. By default, this is the
+    /// printed cost.
+    fn cost<'v, 'g: 'v, 'ctype: 'g>(&self, _game_view: &'v GameView<'g, 'ctype>) -> u32 {
+        self.printed_cost()
+    }
 
     /// Returns whether this ability can be used given the game state.
     /// Does not need to check for the water cost.
     fn can_perform<'v, 'g: 'v, 'ctype: 'g>(&self, game_view: &'v GameView<'g, 'ctype>) -> bool;
 
+    /// Returns whether this ability can be used by the card at the given location.
+    /// Only needs to be overridden by abilities that depend on the state of their own card.
+    /// Does not need to check for the water cost.
+    fn can_perform_at<'v, 'g: 'v, 'ctype: 'g>(
+        &self,
+        game_view: &'v GameView<'g, 'ctype>,
+        _card_loc: CardLocation,
+    ) -> bool {
+        self.can_perform(game_view)
+    }
+
     /// Performs this ability.
     fn perform<'g, 'ctype: 'g>(
         &self,
@@ -28,6 +76,37 @@ pub trait Ability: Sync {
     ) -> bool {
         game_view.game_state.cur_player_water >= self.cost(game_view) && self.can_perform(game_view)
     }
+
+    /// Returns whether this ability can be afforded and used by the card at the given location.
+    fn can_afford_and_perform_at<'v, 'g: 'v, 'ctype: 'g>(
+        &self,
+        game_view: &'v GameView<'g, 'ctype>,
+        card_loc: CardLocation,
+    ) -> bool {
+        game_view.game_state.cur_player_water >= self.cost(game_view)
+            && self.can_perform_at(game_view, card_loc)
+    }
+}
+
+/// Performs the given ability for the card at the given location, first asking its user whether
+/// to resolve it if it's optional (see `perform_optional_effect`). Abilities should always be
+/// performed through this, rather than `Ability::perform`.
+pub fn perform_ability<'g, 'ctype: 'g>(
+    ability: &'ctype dyn Ability,
+    game_view: GameViewMut<'g, 'ctype>,
+    card_loc: CardLocation,
+) -> Result<ChoiceFuture<'g, 'ctype>, GameResult> {
+    if !ability.is_optional() {
+        return ability.perform(game_view, card_loc);
+    }
+    let player = game_view.player;
+    let source = game_view
+        .game_state
+        .player(card_loc.player())
+        .card_name_at(card_loc.player_loc());
+    perform_optional_effect(game_view.game_state, player, source, move |game_state| {
+        ability.perform(game_state.view_for_mut(player), card_loc)
+    })
 }
 
 /// An ability that performs an IconEffect.
@@ -38,10 +117,14 @@ struct IconAbility {
 
 impl Ability for IconAbility {
     fn description(&self) -> String {
-        format!("{:?}", self.effect)
+        self.effect.display_name().to_string()
     }
 
-    fn cost<'v, 'g: 'v, 'ctype: 'g>(&self, _game_view: &'v GameView<'g, 'ctype>) -> u32 {
+    fn icon_effect(&self) -> Option<IconEffect> {
+        Some(self.effect)
+    }
+
+    fn printed_cost(&self) -> u32 {
         self.cost
     }
 
@@ -63,12 +146,63 @@ pub fn icon_ability(cost: u32, effect: IconEffect) -> Box<dyn Ability> {
     Box::new(IconAbility { cost, effect })
 }
 
+/// An ability that performs several IconEffects, one after another (for card sets loaded from
+/// a file; see `card_set`).
+struct IconSequenceAbility {
+    cost: u32,
+    effects: Vec<IconEffect>,
+}
+
+impl Ability for IconSequenceAbility {
+    fn description(&self) -> String {
+        self.effects
+            .iter()
+            .map(|effect| effect.display_name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn printed_cost(&self) -> u32 {
+        self.cost
+    }
+
+    fn can_perform<'v, 'g: 'v, 'ctype: 'g>(&self, game_view: &'v GameView<'g, 'ctype>) -> bool {
+        // every effect must be possible now, since the earlier ones rarely make later ones possible
+        self.effects
+            .iter()
+            .all(|effect| effect.can_perform(game_view))
+    }
+
+    fn perform<'g, 'ctype: 'g>(
+        &self,
+        game_view: GameViewMut<'g, 'ctype>,
+        _card_loc: CardLocation,
+    ) -> Result<ChoiceFuture<'g, 'ctype>, GameResult> {
+        IconEffect::perform_sequence(&self.effects, game_view)
+    }
+}
+
+/// Creates an ability that performs the given IconEffects in order (just an icon ability, if
+/// there is only one).
+///
+/// Panics if there are no effects.
+pub fn icon_sequence_ability(cost: u32, effects: Vec<IconEffect>) -> Box<dyn Ability> {
+    match effects[..] {
+        [] => panic!("Tried to create an ability with no effects"),
+        [effect] => icon_ability(cost, effect),
+        _ => Box::new(IconSequenceAbility { cost, effects }),
+    }
+}
+
 /// Macro for easily creating custom abilities.
 macro_rules! ability {
     {
         description => $description:literal;
         cost => $cost:expr;
+        $(cost($game_view_4:ident) => $dynamic_cost:expr;)?
+        $(optional => $optional:literal;)?
         can_perform($game_view_1:ident) => $can_perform:expr;
+        $(can_perform_at($game_view_3:ident, $card_loc_3:ident) => $can_perform_at:expr;)?
         perform($game_view_2_1:ident $($game_view_2_2:ident)?, $card_loc:ident) => $perform:expr;
     } => {{
         use $crate::radlands::{GameView, GameViewMut, GameResult};
@@ -79,13 +213,28 @@ macro_rules! ability {
         struct MacroAbility;
         impl $crate::abilities::Ability for MacroAbility {
             fn description(&self) -> String {
-                $description.to_string()
+                $crate::locale::translate($crate::locale::Section::Ability, $description).to_string()
             }
 
-            fn cost<'v, 'g: 'v, 'ctype: 'g>(&self, _game_view: &'v GameView<'g, 'ctype>) -> u32 {
+            fn printed_cost(&self) -> u32 {
                 $cost
             }
 
+            $(
+                fn cost<'v, 'g: 'v, 'ctype: 'g>(
+                    &self,
+                    $game_view_4: &'v GameView<'g, 'ctype>,
+                ) -> u32 {
+                    $dynamic_cost
+                }
+            )?
+
+            $(
+                fn is_optional(&self) -> bool {
+                    $optional
+                }
+            )?
+
             fn can_perform<'v, 'g: 'v, 'ctype: 'g>(
                 &self,
                 $game_view_1: &'v GameView<'g, 'ctype>,
@@ -93,6 +242,16 @@ macro_rules! ability {
                 $can_perform
             }
 
+            $(
+                fn can_perform_at<'v, 'g: 'v, 'ctype: 'g>(
+                    &self,
+                    $game_view_3: &'v GameView<'g, 'ctype>,
+                    $card_loc_3: CardLocation,
+                ) -> bool {
+                    $can_perform_at
+                }
+            )?
+
             fn perform<'g, 'ctype: 'g>(
                 &self,
                 $game_view_2_1 $($game_view_2_2)?: GameViewMut<'g, 'ctype>,
@@ -1,29 +1,88 @@
+use std::fmt;
 use std::hash::{Hash, Hasher};
 
 use by_address::ByAddress;
 use itertools::Itertools;
+use tui::text::Span;
 
-use super::choices::DamageChoice;
+use crate::locale::{translate, Section};
+
+use super::choices::{AdvanceEventChoice, DamageChoice, DiscardDrawnChoice};
+use super::locations::{CardLocation, Player};
+use super::player_state::CampStatus;
+use super::styles::*;
 
 use super::abilities::*;
-use super::IconEffect;
+use super::{GameResult, GameState, IconEffect};
+
+/// Enum for identifying "special" camps that require special handling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpecialCampType {
+    None,
+    Obelisk,
+    Oasis,
+    Bonfire,
+}
+
+impl SpecialCampType {
+    /// Returns the text of the trait that this special handling implements (in English), if
+    /// any.
+    pub fn trait_description(self) -> Option<&'static str> {
+        match self {
+            SpecialCampType::None => None,
+            SpecialCampType::Obelisk => Some(
+                "When the last card of the deck is drawn, if this card isn't destroyed, you win",
+            ),
+            SpecialCampType::Oasis => {
+                Some("People cost 1 less to play into this column while it has no people")
+            }
+            SpecialCampType::Bonfire => Some("This card can't be restored"),
+        }
+    }
+}
+
+/// Card-specific behavior for when a camp is damaged or destroyed. It can't ask for choices,
+/// since it happens in the middle of damaging cards; it resolves once the whole batch of damage
+/// that triggered it is done (see `GameState::damage_cards_at`).
+#[derive(Clone, Copy)]
+pub struct CampTrigger {
+    /// The text of the behavior (in English), including when it happens.
+    pub description: &'static str,
+
+    /// The handler function containing the logic of the behavior.
+    /// Takes the location of the camp that triggered it.
+    pub handler: for<'ctype> fn(&mut GameState<'ctype>, CardLocation) -> Result<(), GameResult>,
+}
 
 /// A type of camp card.
 pub struct CampType {
-    /// The camp's name.
+    /// The camp's name in English, which never changes (see `display_name`).
     pub name: &'static str,
 
+    /// The index of this camp type in the list of camp types, which identifies it.
+    pub id: usize,
+
     /// The number of cards this camp grants at the start of the game.
     pub num_initial_cards: u32,
 
     /// The camp's abilities.
     pub abilities: Vec<Box<dyn Ability>>,
+
+    /// Whether this camp needs special handling (e.g. a trait).
+    pub special_type: SpecialCampType,
+
+    /// The camp's behavior when it's damaged (but not destroyed), if any.
+    pub on_damaged: Option<CampTrigger>,
+
+    /// The camp's behavior when it's destroyed, if any.
+    pub on_destroyed: Option<CampTrigger>,
 }
 
-// hash references by address
+// hash references by ID, which is cheap, stable across runs of the program, and (within a list
+// of card types) agrees with comparing by address
 impl Hash for &CampType {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        ByAddress(*self).hash(state);
+        self.id.hash(state);
     }
 }
 
@@ -35,30 +94,63 @@ impl PartialEq for &CampType {
 }
 impl Eq for &CampType {}
 
+impl fmt::Debug for CampType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CampType[{}]", self.name)
+    }
+}
+
+impl CampType {
+    /// Returns the camp's name in the loaded locale.
+    pub fn display_name(&self) -> &'static str {
+        translate(Section::Camp, self.name)
+    }
+}
+
+impl StyledName for CampType {
+    /// Returns this camp's name, styled for display.
+    fn styled_name(&self) -> Span<'static> {
+        Span::styled(self.display_name(), *CAMP)
+    }
+}
+
 pub fn get_camp_types() -> Vec<CampType> {
-    vec![
+    let mut camp_types = vec![
         CampType {
+            id: 0, // assigned below
             name: "Outpost",
             num_initial_cards: 1,
             abilities: vec![
                 icon_ability(2, IconEffect::Raid),
                 icon_ability(2, IconEffect::Restore),
             ],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
         },
         CampType {
+            id: 0, // assigned below
             name: "Railgun",
             num_initial_cards: 0,
             abilities: vec![icon_ability(2, IconEffect::Damage)],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
         },
         CampType {
+            id: 0, // assigned below
             name: "Victory Totem",
             num_initial_cards: 1,
             abilities: vec![
                 icon_ability(2, IconEffect::Injure),
                 icon_ability(2, IconEffect::Raid),
             ],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
         },
         CampType {
+            id: 0, // assigned below
             name: "Scud Launcher",
             num_initial_cards: 0,
             abilities: vec![ability! {
@@ -71,28 +163,679 @@ pub fn get_camp_types() -> Vec<CampType> {
                         .card_locs()
                         .map(|loc| loc.for_player(game_view.player.other()))
                         .collect_vec();
+                    if target_locs.is_empty() {
+                        // nothing to damage (the game should already be over)
+                        return Ok(game_view.immediate_future());
+                    }
                     let damage_future = DamageChoice::future(game_view.player.other(), false, target_locs);
                     Ok(damage_future.ignore_result())
                 };
             }],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
         },
         CampType {
+            id: 0, // assigned below
             name: "Cannon",
             num_initial_cards: 1,
             abilities: vec![ability! {
-                description => "Damage this card, then damage";
+                description => "If this card is undamaged, damage it, then damage";
                 cost => 1;
-                can_perform => true;
+                can_perform(_game_view) => true;
+                can_perform_at(game_view, card_loc) => {
+                    game_view.my_state().column(card_loc.column()).camp.status
+                        == CampStatus::Undamaged
+                };
                 perform(game_view, card_loc) => {
-                    game_view.game_state.damage_card_at(card_loc, false, false)?;
-                    IconEffect::Damage.perform(game_view)
+                    game_view.damage_self_then(card_loc, |game_view| {
+                        IconEffect::Damage.perform(game_view)
+                    })
                 };
             }],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
         },
         CampType {
+            id: 0, // assigned below
             name: "Garage",
             num_initial_cards: 0,
             abilities: vec![icon_ability(1, IconEffect::Raid)],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Juggernaut",
+            num_initial_cards: 0,
+            abilities: vec![ability! {
+                description => "Move this card forward one space; on its third move, your opponent destroys one of their camps";
+                cost => 1;
+                can_perform => true;
+                perform(mut game_view, card_loc) => {
+                    let camp = &mut game_view.my_state_mut().column_mut(card_loc.column()).camp;
+                    if camp.advance() {
+                        Ok(game_view.opponent_damages_own_camp(true))
+                    } else {
+                        Ok(game_view.immediate_future())
+                    }
+                };
+            }],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Omen Clock",
+            num_initial_cards: 1,
+            abilities: vec![ability! {
+                description => "Advance an event in either queue one slot";
+                cost => 1;
+                can_perform(game_view) => !game_view.advanceable_events().is_empty();
+                perform(game_view) => {
+                    let events = game_view.advanceable_events();
+                    Ok(AdvanceEventChoice::future(game_view.player, events))
+                };
+            }],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Resonator",
+            num_initial_cards: 1,
+            abilities: vec![ability! {
+                description => "Damage, if this is your only ability this turn";
+                cost => 1;
+                can_perform(game_view) => {
+                    !game_view.game_state.turn_state().has_used_ability()
+                        && IconEffect::Damage.can_perform(game_view)
+                };
+                perform(game_view) => {
+                    // no more abilities can be used this turn (see `PlayerState::actions`)
+                    game_view.game_state.turn_state.abilities_locked = true;
+                    IconEffect::Damage.perform(game_view)
+                };
+            }],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Obelisk",
+            num_initial_cards: 1,
+            abilities: Vec::new(),
+            special_type: SpecialCampType::Obelisk,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Reactor",
+            num_initial_cards: 1,
+            abilities: vec![ability! {
+                description => "Destroy this card";
+                cost => 2;
+                can_perform => true;
+                perform(game_view, card_loc) => {
+                    // destroying it triggers its own effect (see `on_destroyed`)
+                    game_view.game_state.damage_card_at(card_loc, true, true)?;
+                    Ok(game_view.immediate_future())
+                };
+            }],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: Some(CampTrigger {
+                description: "When this card is destroyed, destroy all people",
+                handler: |game_state, _camp_loc| {
+                    let person_locs = [Player::Player1, Player::Player2]
+                        .into_iter()
+                        .flat_map(|player| {
+                            game_state
+                                .player(player)
+                                .person_locs()
+                                .map(move |loc| loc.for_player(player))
+                        })
+                        .collect_vec();
+                    game_state.damage_cards_at(person_locs, true)
+                },
+            }),
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Atomic Garden",
+            num_initial_cards: 1,
+            abilities: vec![ability! {
+                description => "Restore a damaged person; they're ready";
+                cost => 2;
+                can_perform(game_view) => {
+                    game_view
+                        .my_state()
+                        .restorable_card_locs()
+                        .any(|loc| !loc.row().is_camp())
+                };
+                perform(game_view) => Ok(game_view.restore_and_ready_person());
+            }],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Pillbox",
+            num_initial_cards: 1,
+            abilities: vec![ability! {
+                description => "Damage; costs 1 less per destroyed camp you have";
+                cost => 3;
+                cost(game_view) => {
+                    let discount = game_view.my_state().num_destroyed_camps();
+                    3u32.saturating_sub(discount)
+                };
+                can_perform(game_view) => IconEffect::Damage.can_perform(game_view);
+                perform(game_view, _card_loc) => IconEffect::Damage.perform(game_view);
+            }],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Construction Yard",
+            num_initial_cards: 2,
+            abilities: vec![
+                ability! {
+                    description => "Move any person to another slot on its side";
+                    cost => 1;
+                    can_perform(game_view) => !game_view.movable_person_locs().is_empty();
+                    perform(game_view) => Ok(game_view.move_any_person());
+                },
+                icon_ability(2, IconEffect::Raid),
+            ],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Arcade",
+            num_initial_cards: 1,
+            abilities: vec![ability! {
+                description => "Gain a punk, if you have 0 or 1 people";
+                cost => 1;
+                can_perform(game_view) => {
+                    // punks count as people here
+                    game_view.my_state().people().count() <= 1
+                        && IconEffect::GainPunk.can_perform(game_view)
+                };
+                perform => IconEffect::GainPunk;
+            }],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Training Camp",
+            num_initial_cards: 2,
+            abilities: vec![ability! {
+                description => "Damage, if this column has 2 people";
+                cost => 2;
+                can_perform(game_view) => IconEffect::Damage.can_perform(game_view);
+                can_perform_at(game_view, card_loc) => {
+                    game_view.my_state().column(card_loc.column()).people().count() == 2
+                        && IconEffect::Damage.can_perform(game_view)
+                };
+                perform(game_view, _card_loc) => IconEffect::Damage.perform(game_view);
+            }],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Supply Depot",
+            num_initial_cards: 2,
+            abilities: vec![ability! {
+                description => "Draw 2, then discard 1 of them";
+                cost => 2;
+                can_perform => true;
+                perform(mut game_view) => {
+                    // only the drawn cards can be discarded
+                    let drawn = game_view.draw_cards_into_hand(2)?;
+                    Ok(DiscardDrawnChoice::from_drawn(game_view.player, &drawn).ignore_result())
+                };
+            }],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Oasis",
+            num_initial_cards: 1,
+            abilities: Vec::new(),
+            special_type: SpecialCampType::Oasis,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Bonfire",
+            num_initial_cards: 1,
+            abilities: vec![ability! {
+                description => "Damage this card, then restore any number of cards";
+                cost => 0;
+                can_perform(game_view) => game_view.my_state().has_restorable_card();
+                perform(game_view, card_loc) => {
+                    game_view.damage_self_then(card_loc, |game_view| {
+                        Ok(game_view.restore_any_number())
+                    })
+                };
+            }],
+            special_type: SpecialCampType::Bonfire,
+            on_damaged: None,
+            on_destroyed: None,
         },
-    ]
+        CampType {
+            id: 0, // assigned below
+            name: "Cache",
+            num_initial_cards: 1,
+            abilities: vec![icon_sequence_ability(
+                2,
+                vec![IconEffect::Raid, IconEffect::GainPunk],
+            )],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+        CampType {
+            id: 0, // assigned below
+            name: "Watchtower",
+            num_initial_cards: 0,
+            abilities: vec![ability! {
+                description => "Damage, if any event resolved this turn";
+                cost => 1;
+                can_perform(game_view) => {
+                    game_view.game_state.turn_state().event_resolved()
+                        && IconEffect::Damage.can_perform(game_view)
+                };
+                perform => IconEffect::Damage;
+            }],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
+        },
+    ];
+    for (id, camp_type) in camp_types.iter_mut().enumerate() {
+        camp_type.id = id;
+    }
+    camp_types
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cards::Cards;
+
+    use super::super::choices::Choice;
+    use super::super::locations::Player::{Player1, Player2};
+    use super::super::locations::PlayerCardLocation;
+    use super::super::sandbox::testing::*;
+    use super::super::PersonOrEventType;
+    use super::*;
+
+    #[test]
+    fn cannon_cannot_be_used_while_damaged() {
+        let (game_state, choice) = position(&["turn p1", "water 3", "camp p1 1 Cannon"]);
+        assert!(labels(&game_state, &choice)
+            .iter()
+            .any(|label| label.starts_with("Use Cannon's ability")));
+
+        let (game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "camp p1 1 Cannon",
+            "status p1 1 damaged",
+        ]);
+        assert!(!labels(&game_state, &choice)
+            .iter()
+            .any(|label| label.starts_with("Use Cannon's ability")));
+    }
+
+    #[test]
+    fn cannon_damages_itself_before_damaging() {
+        let (mut game_state, choice) = position(&["turn p1", "water 3", "camp p1 1 Cannon"]);
+
+        // by the time its owner chooses what to damage, Cannon is already damaged
+        let choice = choose(&mut game_state, &choice, "Use Cannon's ability").unwrap();
+        assert!(matches!(choice, Choice::Damage(_)));
+        assert_eq!(
+            game_state.player(Player1).columns[0].camp.status,
+            CampStatus::Damaged
+        );
+
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 2, 0)).unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(
+            game_state.player(Player2).columns[1].camp.status,
+            CampStatus::Damaged
+        );
+    }
+
+    #[test]
+    fn damage_self_then_ends_the_game_when_it_destroys_the_last_camp() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "camp p1 1 Bonfire",
+            "status p1 1 damaged",
+            "status p1 2 destroyed",
+            "status p1 3 destroyed",
+            "person p1 2 1 Looter",
+            "status p1 2 1 injured",
+        ]);
+
+        // Bonfire destroys itself, so the rest of its ability never happens
+        let result = choose(&mut game_state, &choice, "Use Bonfire's ability");
+        assert_eq!(result.err(), Some(GameResult::P2Wins));
+        assert!(game_state.player(Player1).columns[1].person_slots[0]
+            .as_ref()
+            .unwrap()
+            .is_injured());
+    }
+
+    /// The rows (0 for the camp, or 1 or 2 for a person slot) of the unprotected card and person
+    /// in a column (if any).
+    type UnprotectedRows = (Option<usize>, Option<usize>);
+
+    /// Returns the unprotected rows in Player 2's first column.
+    fn unprotected_rows_in_column_1(game_state: &GameState) -> UnprotectedRows {
+        let player_state = game_state.player(Player2);
+        let row = |loc: PlayerCardLocation| match loc.row().to_person_index() {
+            Ok(person_row) => person_row.as_usize() + 1,
+            Err(()) => 0,
+        };
+        let in_column_1 = |loc: &PlayerCardLocation| loc.column().as_usize() == 0;
+        (
+            player_state
+                .unprotected_card_locs()
+                .find(in_column_1)
+                .map(row),
+            player_state
+                .unprotected_person_locs()
+                .find(in_column_1)
+                .map(row),
+        )
+    }
+
+    #[test]
+    fn juggernaut_protects_only_the_people_in_front_of_it() {
+        // (the people in the column, and the unprotected card and person at each position)
+        let cases: [(&[&str], [UnprotectedRows; 3]); 2] = [
+            (
+                &["person p2 1 1 punk"],
+                [(Some(1), Some(1)), (Some(0), None), (Some(0), None)],
+            ),
+            (
+                &["person p2 1 1 punk", "person p2 1 2 Looter"],
+                [(Some(2), Some(2)), (Some(2), Some(2)), (Some(0), None)],
+            ),
+        ];
+        for (people_commands, expected) in cases {
+            let mut commands = vec!["turn p1", "camp p2 1 Juggernaut"];
+            commands.extend_from_slice(people_commands);
+            let (mut game_state, _) = position(&commands);
+            for (camp_position, expected) in expected.into_iter().enumerate() {
+                let camp = &game_state.player(Player2).columns[0].camp;
+                assert_eq!(camp.position(), camp_position);
+                assert_eq!(
+                    unprotected_rows_in_column_1(&game_state),
+                    expected,
+                    "{people_commands:?}, position {camp_position}"
+                );
+                game_state.player_mut(Player2).columns[0].camp.advance();
+            }
+        }
+    }
+
+    #[test]
+    fn juggernaut_moves_back_and_makes_the_opponent_destroy_a_camp_on_its_third_move() {
+        let (mut game_state, choice) = position(&["turn p1", "water 3", "camp p1 1 Juggernaut"]);
+
+        // the first move just moves it forward
+        let mut first_move_state = game_state.clone();
+        let first_move_choice =
+            choose(&mut first_move_state, &choice, "Use Juggernaut's ability").unwrap();
+        assert!(matches!(first_move_choice, Choice::Action(_)));
+        assert_eq!(
+            first_move_state.player(Player1).columns[0].camp.position(),
+            1
+        );
+
+        // (as if it had been moved on earlier turns)
+        for _ in 0..2 {
+            game_state.player_mut(Player1).columns[0].camp.advance();
+        }
+        let choice = choose(&mut game_state, &choice, "Use Juggernaut's ability").unwrap();
+        assert_eq!(game_state.player(Player1).columns[0].camp.position(), 0);
+        assert!(matches!(choice, Choice::Damage(_)));
+        assert_eq!(choice.chooser(&game_state), Player2);
+        assert_eq!(choice.num_options(), 3);
+
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 2, 0)).unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(
+            game_state.player(Player2).columns[1].camp.status,
+            CampStatus::Destroyed
+        );
+    }
+
+    #[test]
+    fn a_destroyed_juggernaut_moves_back() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "person p1 1 1 Looter",
+            "camp p2 1 Juggernaut",
+            "status p2 1 damaged",
+            "person p2 1 1 punk",
+        ]);
+        for _ in 0..2 {
+            game_state.player_mut(Player2).columns[0].camp.advance();
+        }
+
+        // at the front of its column, it's the card to damage, not the punk
+        let choice = choose(&mut game_state, &choice, "Use Looter's ability").unwrap();
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 1, 0)).unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        let column = &game_state.player(Player2).columns[0];
+        assert_eq!(column.camp.status, CampStatus::Destroyed);
+        assert_eq!(column.camp.position(), 0);
+        assert_eq!(
+            unprotected_rows_in_column_1(&game_state),
+            (Some(1), Some(1))
+        );
+    }
+
+    #[test]
+    fn omen_clock_advances_an_event_in_either_queue() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "camp p1 1 Omen Clock",
+            "event p1 3 Napalm",
+            "event p2 1 Raiders",
+            "event p2 2 Bombardment",
+        ]);
+
+        // Bombardment is blocked by Raiders in front of it
+        let choice = choose(&mut game_state, &choice, "Use Omen Clock's ability").unwrap();
+        assert_eq!(
+            labels(&game_state, &choice),
+            [
+                "Advance your Napalm from event slot 3",
+                "Resolve opponent's Raiders from event slot 1",
+            ]
+        );
+
+        let napalm = game_state.player(Player1).events[2];
+        let choice = choose(&mut game_state, &choice, "Advance your Napalm").unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(game_state.player(Player1).events, [None, napalm, None]);
+    }
+
+    #[test]
+    fn omen_clock_resolves_an_event_in_the_first_slot_for_its_owner() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "camp p1 1 Omen Clock",
+            "event p2 1 Napalm",
+            "person p1 2 1 Looter",
+        ]);
+        let discard_len = game_state.discard.len();
+
+        // the opponent's Napalm leaves play, and they choose where it destroys people
+        let choice = choose(&mut game_state, &choice, "Use Omen Clock's ability").unwrap();
+        let choice = choose(&mut game_state, &choice, "Resolve opponent's Napalm").unwrap();
+        assert_eq!(game_state.player(Player2).events, [None, None, None]);
+        assert_eq!(game_state.discard.len(), discard_len + 1);
+        assert_eq!(choice.chooser(&game_state), Player2);
+        let choice = choose(&mut game_state, &choice, "Destroy people").unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert!(game_state.player(Player1).people().next().is_none());
+    }
+
+    #[test]
+    fn supply_depot_discards_one_of_the_drawn_cards() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "camp p1 1 Supply Depot",
+            "hand p1 add Looter",
+        ]);
+        let looter = person_type("Looter");
+
+        // only the drawn cards are offered, even though Looter is also in the hand
+        let choice = choose(&mut game_state, &choice, "Use Supply Depot's ability").unwrap();
+        let discard_drawn_choice = match &choice {
+            Choice::DiscardDrawn(discard_drawn_choice) => discard_drawn_choice.clone(),
+            _ => panic!("Expected a DiscardDrawn choice"),
+        };
+        let drawn = discard_drawn_choice.drawn().to_vec();
+        assert_eq!(drawn.len(), 2);
+        assert_eq!(
+            labels(&game_state, &choice),
+            drawn
+                .iter()
+                .unique()
+                .map(|card| format!("Discard the drawn {}", card.name()))
+                .collect_vec(),
+        );
+
+        // discarding one of them keeps the other and the rest of the hand
+        let discarded = discard_drawn_choice.cards()[0];
+        let discard_len = game_state.discard.len();
+        let choice = choose(&mut game_state, &choice, "Discard the drawn").unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        let mut expected_hand: Cards<_> = drawn.iter().collect();
+        expected_hand.add_one(PersonOrEventType::Person(looter));
+        expected_hand.remove_one(discarded);
+        assert_eq!(game_state.player(Player1).hand, expected_hand);
+        assert_eq!(game_state.discard.len(), discard_len + 1);
+    }
+
+    #[test]
+    fn an_optional_ability_can_be_declined_after_paying_for_it() {
+        let camp_type = CampType {
+            id: card_set().camp_types.len(),
+            name: "Test Well",
+            num_initial_cards: 0,
+            abilities: vec![ability! {
+                description => "You may draw";
+                cost => 1;
+                optional => true;
+                can_perform(_game_view) => true;
+                perform(game_view, _card_loc) => IconEffect::Draw.perform(game_view);
+            }],
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: None,
+        };
+        let camp_type: &'static CampType = Box::leak(Box::new(camp_type));
+
+        for (answer, hand_size) in [("Decline", 0), ("Resolve", 1)] {
+            let (mut game_state, _) = position(&["turn p1", "water 3"]);
+            game_state.player_mut(Player1).columns[0].camp.camp_type = camp_type;
+            let choice = Choice::new_actions(&mut game_state);
+            let choice = choose(&mut game_state, &choice, "Use Test Well's ability").unwrap();
+            assert_eq!(
+                labels(&game_state, &choice),
+                [
+                    "Decline Test Well's optional effect",
+                    "Resolve Test Well's optional effect",
+                ]
+            );
+            assert_eq!(game_state.cur_player_water, 2);
+            let choice = choose(&mut game_state, &choice, answer).unwrap();
+            assert!(matches!(choice, Choice::Action(_)));
+            assert_eq!(
+                game_state.player(Player1).hand.count(),
+                hand_size,
+                "{answer}"
+            );
+        }
+    }
+
+    /// Returns whether any of the choice's options uses an ability.
+    fn has_ability_option<'ctype>(game_state: &GameState<'ctype>, choice: &Choice<'ctype>) -> bool {
+        labels(game_state, choice)
+            .iter()
+            .any(|label| label.starts_with("Use "))
+    }
+
+    #[test]
+    fn resonator_cant_be_used_after_another_ability() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 5",
+            "camp p1 1 Resonator",
+            "person p1 2 1 Looter",
+        ]);
+        assert!(labels(&game_state, &choice)
+            .iter()
+            .any(|label| label.starts_with("Use Resonator's ability")));
+
+        let choice = choose(&mut game_state, &choice, "Use Looter's ability").unwrap();
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 1, 0)).unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert!(!labels(&game_state, &choice)
+            .iter()
+            .any(|label| label.starts_with("Use Resonator's ability")));
+    }
+
+    #[test]
+    fn resonator_locks_abilities_for_the_rest_of_the_turn() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 5",
+            "camp p1 1 Resonator",
+            "person p1 2 1 Looter",
+            "person p2 2 1 Looter",
+        ]);
+
+        let choice = choose(&mut game_state, &choice, "Use Resonator's ability").unwrap();
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 1, 0)).unwrap();
+        assert!(game_state.turn_state().abilities_locked());
+        assert!(!has_ability_option(&game_state, &choice));
+
+        // the lock ends with the turn
+        let choice = choose(&mut game_state, &choice, "End turn").unwrap();
+        assert_eq!(choice.chooser(&game_state), Player2);
+        assert!(!game_state.turn_state().abilities_locked());
+        assert!(has_ability_option(&game_state, &choice));
+    }
 }
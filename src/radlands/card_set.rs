@@ -0,0 +1,969 @@
+//! Card sets, for trying out expansions, custom cards, and house-rule variants of the standard
+//! cards without recompiling.
+//!
+//! `--card-set` selects either one of the built-in card sets in [`BUILT_IN_CARD_SETS`] by
+//! name, or a card set file. A card set file is a TOML file that starts from the standard cards
+//! and changes them, with a table per card (`card_sets` has an example):
+//!
+//! ```toml
+//! # Lines starting with '#' are comments.
+//! [person.Looter]
+//! count = 3
+//!
+//! [camp.Outpost]
+//! removed = true
+//!
+//! [person."Field Medic"]
+//! count = 2
+//! cost = 1
+//! junk = "Restore"
+//! abilities = [
+//!     { cost = 1, effects = ["Restore"] },
+//!     { cost = 2, effects = ["Draw", "Damage"] },
+//! ]
+//!
+//! [event."Carpet Bombing"]
+//! based_on = "Strafe"
+//! cost = 4
+//! ```
+//!
+//! Each card's table is `[<kind>.<name>]`, where the kind is `camp`, `person`, or `event` (and
+//! a name with spaces is quoted). A table for a standard card changes just the properties it
+//! gives, and any other table adds a new card. The properties are:
+//!
+//! - `based_on = "<name>"`: makes a new card a copy of the standard card of the same kind with
+//!   the given name, including the behavior that can't be written in the file (such as a
+//!   person's enter-play effect, or an event's effect)
+//! - `removed = true`: leaves the card out of the set
+//! - `count = <n>` (people and events): how many copies are in the deck
+//! - `cost = <n>` (people and events): the water cost to play the card
+//! - `junk = "<effect>"` (people and events): the card's junk effect
+//! - `ready = true` (people): makes the person enter play ready
+//! - `traits = ["<trait>", ...]` (people): the person's traits, replacing the card's existing
+//!   traits (an empty list removes them)
+//! - `resolve_turns = <n>` (events): how many turns the event takes to resolve
+//! - `initial_cards = <n>` (camps): how many cards the camp adds to the opening hand
+//! - `abilities = [{ cost = <n>, effects = ["<effect>", ...] }, ...]` (camps and people): the
+//!   card's abilities, replacing its existing ones, each of which performs the given icon
+//!   effects in order
+//! - `on_junk = { effects = ["<effect>", ...], replaces_junk = <bool> }` (people and events):
+//!   icon effects performed in order when the card is junked from hand, after its junk effect
+//!   (or instead of it, if `replaces_junk = true`)
+//!
+//! The effects are the icon effects: `Damage`, `Injure`, `Restore`, `Draw`, `Water`,
+//! `GainPunk`, and `Raid`. The traits are `FreeInDestroyedColumn` (Holdout's), `CopyAbility`
+//! (Mimic's), `GrantAbility` (Argo Yesky's, which gives the person's first ability),
+//! `InstantFirstEvent` (Zeto Khan's), `PeopleEnterReady` (Karli Blaze's), and
+//! `FirstUseStaysReady` (Vera Vosh's). A new card that isn't based on a standard card needs the
+//! properties that have no sensible default: `initial_cards` for a camp, and `count`, `cost`,
+//! and `junk` for a person. A new event must be based on a standard event, since its effect
+//! can't be written in the file.
+//!
+//! Cards are identified by their positions in the lists of card types (the standard cards in
+//! their usual order, then the new cards in the order they appear in the file), so saved games
+//! and remote games must be played with the same card set they were started with.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use itertools::Itertools;
+use serde::Deserialize;
+use toml::Value;
+
+use super::abilities::{icon_sequence_ability, Ability};
+use super::camps::{get_camp_types, CampType, SpecialCampType};
+use super::events::{get_event_types, EventType};
+use super::people::{get_person_types, PassiveEffect, PersonType};
+use super::{IconEffect, OnJunk};
+
+/// The lists of camp, person, and event types that games are played with.
+pub struct CardSet {
+    pub camp_types: Vec<CampType>,
+    pub person_types: Vec<PersonType>,
+    pub event_types: Vec<EventType>,
+}
+
+impl CardSet {
+    /// Returns the standard cards.
+    pub fn standard() -> Self {
+        CardSet {
+            camp_types: get_camp_types(),
+            person_types: get_person_types(),
+            event_types: get_event_types(),
+        }
+    }
+
+    /// Returns an error if a game can't be played with this card set.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.camp_types.len() < 6 {
+            return Err(format!(
+                "a card set needs at least 6 camps, but this one has {}",
+                self.camp_types.len(),
+            ));
+        }
+        if self.person_types.is_empty() {
+            return Err("a card set needs at least one person".to_string());
+        }
+
+        // cards are looked up by name in locale files and card set files
+        let duplicate_name = (self.camp_types.iter().map(|c| ("camp", c.name)))
+            .chain(self.person_types.iter().map(|p| ("person", p.name)))
+            .chain(self.event_types.iter().map(|e| ("event", e.name)))
+            .duplicates()
+            .next();
+        if let Some((kind, name)) = duplicate_name {
+            return Err(format!("there's more than one {kind} named {name:?}"));
+        }
+
+        // the deck must hold both opening hands (with the largest camps) and the first turn's draw
+        let largest_hand: u32 = self
+            .camp_types
+            .iter()
+            .map(|camp_type| camp_type.num_initial_cards)
+            .sorted()
+            .rev()
+            .take(3)
+            .sum();
+        let deck_size: u32 = self
+            .person_types
+            .iter()
+            .map(|person_type| person_type.num_in_deck)
+            .chain(self.event_types.iter().map(|e| e.num_in_deck))
+            .sum();
+        if deck_size < 2 * largest_hand + 1 {
+            return Err(format!(
+                "a deck of {deck_size} cards is too small for two opening hands of up to {largest_hand}"
+            ));
+        }
+
+        // a person that gives its ability to the others needs one to give
+        if let Some(person_type) = self.person_types.iter().find(|person_type| {
+            person_type.has_passive_effect(PassiveEffect::GrantAbility)
+                && person_type.abilities.is_empty()
+        }) {
+            return Err(format!(
+                "the person {:?} has the GrantAbility trait, but no ability to give",
+                person_type.name,
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A card set that's built into the program.
+pub struct BuiltInCardSet {
+    /// The name that selects this card set.
+    pub name: &'static str,
+
+    /// What the card set contains (in English).
+    pub description: &'static str,
+
+    /// Builds the lists of card types.
+    pub build: fn() -> CardSet,
+}
+
+/// The card sets built into the program. The first one is the default.
+pub static BUILT_IN_CARD_SETS: &[BuiltInCardSet] = &[
+    BuiltInCardSet {
+        name: "base",
+        description: "The cards of the base game",
+        build: CardSet::standard,
+    },
+    BuiltInCardSet {
+        name: "no-events",
+        description: "The base game without events",
+        build: no_events,
+    },
+];
+
+/// Returns the base game's cards without the events, from the card set file in `card_sets`.
+fn no_events() -> CardSet {
+    parse(include_str!("../../card_sets/no-events.toml"))
+        .expect("The built-in card set file should be valid")
+}
+
+/// Returns the built-in card set with the given name or, if there isn't one, loads the card set
+/// file at the given path. Returns a message describing the problem if the file can't be loaded
+/// or a game can't be played with the card set.
+pub fn select_card_set(name_or_path: &str) -> Result<CardSet, String> {
+    let card_set = match BUILT_IN_CARD_SETS
+        .iter()
+        .find(|built_in| built_in.name == name_or_path)
+    {
+        Some(built_in) => (built_in.build)(),
+        None if Path::new(name_or_path).exists() => load_card_set(Path::new(name_or_path))?,
+        None => {
+            return Err(format!(
+                "there's no built-in card set or file named {name_or_path:?} (the built-in card \
+                 sets are {})",
+                BUILT_IN_CARD_SETS
+                    .iter()
+                    .map(|built_in| format!("{}: {}", built_in.name, built_in.description))
+                    .join("; "),
+            ))
+        }
+    };
+    card_set.validate()?;
+    Ok(card_set)
+}
+
+/// Loads the card set in the given file. Returns a message describing the problem if the file
+/// can't be read or is invalid.
+pub fn load_card_set(path: &Path) -> Result<CardSet, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("couldn't read {}: {err}", path.display()))?;
+    parse(&contents)
+}
+
+/// Parses the contents of a card set file.
+fn parse(contents: &str) -> Result<CardSet, String> {
+    let mut card_set = CardSet::standard();
+    let mut removed = Vec::new();
+    for section in parse_sections(contents)? {
+        if section.properties.removed {
+            check_removal(&card_set, &section)?;
+            removed.push((section.kind, section.name.clone()));
+            continue;
+        }
+        match section.kind {
+            CardKind::Camp => apply_camp_section(&mut card_set.camp_types, &section)?,
+            CardKind::Person => apply_person_section(&mut card_set.person_types, &section)?,
+            CardKind::Event => apply_event_section(&mut card_set.event_types, &section)?,
+        }
+    }
+
+    let is_removed = |kind, name: &str| {
+        removed
+            .iter()
+            .any(|(removed_kind, removed_name)| *removed_kind == kind && removed_name == name)
+    };
+    card_set
+        .camp_types
+        .retain(|camp_type| !is_removed(CardKind::Camp, camp_type.name));
+    card_set
+        .person_types
+        .retain(|person_type| !is_removed(CardKind::Person, person_type.name));
+    card_set
+        .event_types
+        .retain(|event_type| !is_removed(CardKind::Event, event_type.name));
+    for (id, camp_type) in card_set.camp_types.iter_mut().enumerate() {
+        camp_type.id = id;
+    }
+    for (id, person_type) in card_set.person_types.iter_mut().enumerate() {
+        person_type.id = id;
+    }
+    for (id, event_type) in card_set.event_types.iter_mut().enumerate() {
+        event_type.id = id;
+    }
+
+    Ok(card_set)
+}
+
+/// Returns an error unless the given section removes a standard card and says nothing else.
+fn check_removal(card_set: &CardSet, section: &Section) -> Result<(), String> {
+    let is_standard = match section.kind {
+        CardKind::Camp => card_set.camp_types.iter().any(|c| c.name == section.name),
+        CardKind::Person => card_set.person_types.iter().any(|p| p.name == section.name),
+        CardKind::Event => card_set.event_types.iter().any(|e| e.name == section.name),
+    };
+    if !is_standard {
+        return Err(section.error("there's no standard card by this name to remove"));
+    }
+    if section.keys.len() > 1 {
+        return Err(section.error("a removed card can't have other properties"));
+    }
+    Ok(())
+}
+
+/// Applies a camp's section, changing a standard camp or adding a new one.
+fn apply_camp_section(camp_types: &mut Vec<CampType>, section: &Section) -> Result<(), String> {
+    let index = match camp_types.iter().position(|c| c.name == section.name) {
+        Some(index) => {
+            section.forbid_based_on()?;
+            index
+        }
+        None => {
+            let camp_type = match section.based_on(get_camp_types(), |c| c.name)? {
+                Some(base) => CampType {
+                    name: section.leaked_name(),
+                    ..base
+                },
+                None => {
+                    section.require(&["initial_cards"])?;
+                    CampType {
+                        name: section.leaked_name(),
+                        id: 0, // assigned later
+                        num_initial_cards: 0,
+                        abilities: Vec::new(),
+                        special_type: SpecialCampType::None,
+                        on_damaged: None,
+                        on_destroyed: None,
+                    }
+                }
+            };
+            camp_types.push(camp_type);
+            camp_types.len() - 1
+        }
+    };
+
+    let camp_type = &mut camp_types[index];
+    if let Some(num_initial_cards) = section.properties.initial_cards {
+        camp_type.num_initial_cards = num_initial_cards;
+    }
+    if let Some(abilities) = section.abilities()? {
+        camp_type.abilities = abilities;
+    }
+    Ok(())
+}
+
+/// Applies a person's section, changing a standard person or adding a new one.
+fn apply_person_section(
+    person_types: &mut Vec<PersonType>,
+    section: &Section,
+) -> Result<(), String> {
+    let index = match person_types.iter().position(|p| p.name == section.name) {
+        Some(index) => {
+            section.forbid_based_on()?;
+            index
+        }
+        None => {
+            let person_type = match section.based_on(get_person_types(), |p| p.name)? {
+                Some(base) => PersonType {
+                    name: section.leaked_name(),
+                    ..base
+                },
+                None => {
+                    section.require(&["count", "cost", "junk"])?;
+                    PersonType {
+                        name: section.leaked_name(),
+                        id: 0, // assigned later
+                        num_in_deck: 0,
+                        junk_effect: IconEffect::Draw,
+                        on_junk: None,
+                        cost: 0,
+                        abilities: Vec::new(),
+                        on_enter_play: None,
+                        enter_play_effect: None,
+                        enter_play_optional: false,
+                        enters_play_ready: false,
+                        passive_effects: Vec::new(),
+                    }
+                }
+            };
+            person_types.push(person_type);
+            person_types.len() - 1
+        }
+    };
+
+    let person_type = &mut person_types[index];
+    if let Some(num_in_deck) = section.properties.count {
+        person_type.num_in_deck = num_in_deck;
+    }
+    if let Some(cost) = section.properties.cost {
+        person_type.cost = cost;
+    }
+    if let Some(junk_effect) = section.junk_effect()? {
+        person_type.junk_effect = junk_effect;
+    }
+    if let Some(on_junk) = section.on_junk()? {
+        person_type.on_junk = Some(on_junk);
+    }
+    if let Some(enters_play_ready) = section.properties.ready {
+        person_type.enters_play_ready = enters_play_ready;
+    }
+    if let Some(passive_effects) = section.passive_effects()? {
+        person_type.passive_effects = passive_effects;
+    }
+    if let Some(abilities) = section.abilities()? {
+        person_type.abilities = abilities;
+    }
+    Ok(())
+}
+
+/// Applies an event's section, changing a standard event or adding a new one.
+fn apply_event_section(event_types: &mut Vec<EventType>, section: &Section) -> Result<(), String> {
+    let index = match event_types.iter().position(|e| e.name == section.name) {
+        Some(index) => {
+            section.forbid_based_on()?;
+            index
+        }
+        None => {
+            let base = section
+                .based_on(get_event_types(), |e| e.name)?
+                .ok_or_else(|| section.error("a new event must be based_on a standard event"))?;
+            event_types.push(EventType {
+                name: section.leaked_name(),
+                ..base
+            });
+            event_types.len() - 1
+        }
+    };
+
+    let event_type = &mut event_types[index];
+    if let Some(num_in_deck) = section.properties.count {
+        event_type.num_in_deck = num_in_deck;
+    }
+    if let Some(cost) = section.properties.cost {
+        event_type.cost = cost;
+    }
+    if let Some(junk_effect) = section.junk_effect()? {
+        event_type.junk_effect = junk_effect;
+    }
+    if let Some(on_junk) = section.on_junk()? {
+        event_type.on_junk = Some(on_junk);
+    }
+    if let Some(resolve_turns) = section.properties.resolve_turns {
+        event_type.resolve_turns = resolve_turns;
+    }
+    Ok(())
+}
+
+/// The kind of card that a section describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum CardKind {
+    Camp,
+    Person,
+    Event,
+}
+
+impl CardKind {
+    /// Returns the name of this kind in section headers.
+    fn name(self) -> &'static str {
+        match self {
+            CardKind::Camp => "camp",
+            CardKind::Person => "person",
+            CardKind::Event => "event",
+        }
+    }
+
+    /// Returns the keys of the properties that a card of this kind can have.
+    fn keys(self) -> &'static [&'static str] {
+        match self {
+            CardKind::Camp => &["based_on", "removed", "initial_cards", "abilities"],
+            CardKind::Person => &[
+                "based_on",
+                "removed",
+                "count",
+                "cost",
+                "junk",
+                "ready",
+                "traits",
+                "abilities",
+                "on_junk",
+            ],
+            CardKind::Event => &[
+                "based_on",
+                "removed",
+                "count",
+                "cost",
+                "junk",
+                "resolve_turns",
+                "on_junk",
+            ],
+        }
+    }
+}
+
+/// One card's table in a card set file.
+struct Section {
+    kind: CardKind,
+    name: String,
+
+    /// The keys of the properties that are given, in order.
+    keys: Vec<String>,
+
+    properties: Properties,
+}
+
+/// The properties of a card's table (see the module documentation). Only the ones that a card
+/// of the table's kind can have are allowed (see `CardKind::keys`).
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Properties {
+    based_on: Option<String>,
+    #[serde(default)]
+    removed: bool,
+    count: Option<u32>,
+    cost: Option<u32>,
+    junk: Option<String>,
+    ready: Option<bool>,
+    traits: Option<Vec<String>>,
+    resolve_turns: Option<u8>,
+    initial_cards: Option<u32>,
+    abilities: Option<Vec<AbilityProperties>>,
+    on_junk: Option<OnJunkProperties>,
+}
+
+/// One of the abilities in a card's table.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AbilityProperties {
+    cost: u32,
+    effects: Vec<String>,
+}
+
+/// A card's behavior when junked, in its table.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OnJunkProperties {
+    effects: Vec<String>,
+    #[serde(default)]
+    replaces_junk: bool,
+}
+
+impl Section {
+    /// Returns an error message about this card.
+    fn error(&self, message: impl fmt::Display) -> String {
+        format!("{} {:?}: {message}", self.kind.name(), self.name)
+    }
+
+    /// Parses the junk effect, if it's given.
+    fn junk_effect(&self) -> Result<Option<IconEffect>, String> {
+        self.properties
+            .junk
+            .as_deref()
+            .map(|name| parse_effect(name).map_err(|message| self.error(message)))
+            .transpose()
+    }
+
+    /// Parses the behavior when junked, if it's given.
+    fn on_junk(&self) -> Result<Option<OnJunk>, String> {
+        self.properties
+            .on_junk
+            .as_ref()
+            .map(|on_junk| {
+                if on_junk.effects.is_empty() {
+                    return Err(self.error("on_junk needs at least one effect"));
+                }
+                let effects = on_junk
+                    .effects
+                    .iter()
+                    .map(|name| parse_effect(name).map_err(|message| self.error(message)))
+                    .collect::<Result<_, _>>()?;
+                Ok(OnJunk::icon_sequence(effects, on_junk.replaces_junk))
+            })
+            .transpose()
+    }
+
+    /// Parses the traits, if they're given.
+    fn passive_effects(&self) -> Result<Option<Vec<PassiveEffect>>, String> {
+        self.properties
+            .traits
+            .as_ref()
+            .map(|names| {
+                names
+                    .iter()
+                    .map(|name| parse_passive_effect(name).map_err(|message| self.error(message)))
+                    .collect()
+            })
+            .transpose()
+    }
+
+    /// Parses the abilities, if they're given.
+    fn abilities(&self) -> Result<Option<Vec<Box<dyn Ability>>>, String> {
+        self.properties
+            .abilities
+            .as_ref()
+            .map(|abilities| {
+                abilities
+                    .iter()
+                    .map(|ability| {
+                        if ability.effects.is_empty() {
+                            return Err(self.error("an ability needs at least one effect"));
+                        }
+                        let effects = ability
+                            .effects
+                            .iter()
+                            .map(|name| parse_effect(name).map_err(|message| self.error(message)))
+                            .collect::<Result<_, _>>()?;
+                        Ok(icon_sequence_ability(ability.cost, effects))
+                    })
+                    .collect()
+            })
+            .transpose()
+    }
+
+    /// Returns the standard card this new card is based on, if it's given.
+    fn based_on<T>(
+        &self,
+        standard_types: Vec<T>,
+        name_of: fn(&T) -> &'static str,
+    ) -> Result<Option<T>, String> {
+        match &self.properties.based_on {
+            None => Ok(None),
+            Some(base_name) => standard_types
+                .into_iter()
+                .find(|card_type| name_of(card_type) == base_name)
+                .map(Some)
+                .ok_or_else(|| {
+                    self.error(format!(
+                        "there's no standard {} named {base_name:?} to base it on",
+                        self.kind.name(),
+                    ))
+                }),
+        }
+    }
+
+    /// Returns an error if this section, for a card that already exists, says what to base it
+    /// on.
+    fn forbid_based_on(&self) -> Result<(), String> {
+        match self.properties.based_on {
+            Some(_) => Err(self.error("a standard card can't be based_on another")),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the name of the new card this section adds, which lives for the rest of the
+    /// program like the standard cards' names.
+    fn leaked_name(&self) -> &'static str {
+        Box::leak(self.name.clone().into_boxed_str())
+    }
+
+    /// Returns an error unless each of the given properties is given, for a new card that isn't
+    /// based on a standard card.
+    fn require(&self, keys: &[&str]) -> Result<(), String> {
+        match keys
+            .iter()
+            .find(|&&key| !self.keys.iter().any(|given| given == key))
+        {
+            Some(key) => Err(self.error(format!(
+                "a new card needs a {key} (or a standard card to be based_on)"
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parses an icon effect by its name.
+fn parse_effect(name: &str) -> Result<IconEffect, String> {
+    IconEffect::ALL
+        .into_iter()
+        .find(|effect| format!("{effect:?}") == name)
+        .ok_or_else(|| {
+            format!(
+                "unknown effect {name:?} (the effects are {})",
+                IconEffect::ALL
+                    .iter()
+                    .map(|effect| format!("{effect:?}"))
+                    .join(", "),
+            )
+        })
+}
+
+/// Parses a trait by its name.
+fn parse_passive_effect(name: &str) -> Result<PassiveEffect, String> {
+    PassiveEffect::ALL
+        .into_iter()
+        .find(|passive_effect| format!("{passive_effect:?}") == name)
+        .ok_or_else(|| {
+            format!(
+                "unknown trait {name:?} (the traits are {})",
+                PassiveEffect::ALL
+                    .iter()
+                    .map(|passive_effect| format!("{passive_effect:?}"))
+                    .join(", "),
+            )
+        })
+}
+
+/// Parses the contents of a card set file into its cards' sections, in the order they appear
+/// for each kind of card.
+fn parse_sections(contents: &str) -> Result<Vec<Section>, String> {
+    // TOML itself rejects a table that's given twice
+    let file: toml::value::Table = toml::from_str(contents).map_err(|err| err.to_string())?;
+    let mut sections = Vec::new();
+    for (kind_name, cards) in file {
+        let kind = [CardKind::Camp, CardKind::Person, CardKind::Event]
+            .into_iter()
+            .find(|card_kind| card_kind.name() == kind_name)
+            .ok_or_else(|| format!("unknown card kind {kind_name:?}"))?;
+        let cards = match cards {
+            Value::Table(cards) => cards,
+            _ => return Err(format!("expected a table of cards for {kind_name:?}")),
+        };
+        for (name, properties) in cards {
+            let keys = match &properties {
+                Value::Table(properties) => properties.keys().cloned().collect_vec(),
+                _ => {
+                    return Err(format!(
+                        "{kind_name} {name:?}: expected a table of properties"
+                    ))
+                }
+            };
+            if let Some(key) = keys.iter().find(|key| !kind.keys().contains(&key.as_str())) {
+                return Err(format!(
+                    "{kind_name} {name:?}: a {kind_name} can't have a {key:?} (it can have {})",
+                    kind.keys().join(", "),
+                ));
+            }
+            let properties = properties
+                .try_into()
+                .map_err(|err| format!("{kind_name} {name:?}: {err}"))?;
+            sections.push(Section {
+                kind,
+                name,
+                keys,
+                properties,
+            });
+        }
+    }
+    Ok(sections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::radlands::choices::Choice;
+    use crate::radlands::locations::Player;
+    use crate::radlands::rules::GameRules;
+    use crate::radlands::sandbox::testing::{choose, labels};
+    use crate::radlands::sandbox::Sandbox;
+
+    /// Returns the person with the given name in the card set.
+    fn person<'a>(card_set: &'a CardSet, name: &str) -> &'a PersonType {
+        card_set
+            .person_types
+            .iter()
+            .find(|person_type| person_type.name == name)
+            .unwrap_or_else(|| panic!("no person named {name:?}"))
+    }
+
+    /// Parses the card set file, expecting an error that contains the given text.
+    fn parse_error(contents: &str, expected: &str) {
+        match parse(contents) {
+            Ok(_) => panic!("parsed without an error:\n{contents}"),
+            Err(message) => assert!(
+                message.contains(expected),
+                "{message:?} doesn't contain {expected:?}"
+            ),
+        }
+    }
+
+    #[test]
+    fn built_in_card_sets_are_valid() {
+        for built_in in BUILT_IN_CARD_SETS {
+            let card_set = (built_in.build)();
+            assert_eq!(card_set.validate(), Ok(()), "{}", built_in.name);
+        }
+        let no_events = select_card_set("no-events").unwrap();
+        assert!(no_events.event_types.is_empty());
+        assert_eq!(
+            no_events.person_types.len(),
+            CardSet::standard().person_types.len()
+        );
+    }
+
+    #[test]
+    fn overrides_change_only_the_given_properties() {
+        let standard = CardSet::standard();
+        let card_set = parse(
+            r#"
+            [person.Looter]
+            count = 3
+            junk = "Water"
+            traits = ["PeopleEnterReady"]
+            "#,
+        )
+        .unwrap();
+        let (looter, standard_looter) = (person(&card_set, "Looter"), person(&standard, "Looter"));
+        assert_eq!(looter.num_in_deck, 3);
+        assert_eq!(looter.junk_effect, IconEffect::Water);
+        assert_eq!(looter.passive_effects, [PassiveEffect::PeopleEnterReady]);
+        assert_eq!(looter.cost, standard_looter.cost);
+        assert_eq!(looter.id, standard_looter.id);
+        assert_eq!(card_set.person_types.len(), standard.person_types.len());
+    }
+
+    #[test]
+    fn new_cards_are_based_on_standard_cards_and_added_in_order() {
+        let standard = CardSet::standard();
+        let card_set = parse(
+            r#"
+            [event."Carpet Bombing"]
+            based_on = "Strafe"
+            cost = 4
+
+            [person.Zealot]
+            count = 1
+            cost = 2
+            junk = "Injure"
+
+            [person.Medic]
+            based_on = "Looter"
+            "#,
+        )
+        .unwrap();
+        let carpet_bombing = card_set.event_types.last().unwrap();
+        let strafe = standard
+            .event_types
+            .iter()
+            .find(|e| e.name == "Strafe")
+            .unwrap();
+        assert_eq!(carpet_bombing.name, "Carpet Bombing");
+        assert_eq!(carpet_bombing.cost, 4);
+        assert_eq!(carpet_bombing.resolve_turns, strafe.resolve_turns);
+        assert_eq!(carpet_bombing.id, card_set.event_types.len() - 1);
+
+        let new_people = &card_set.person_types[standard.person_types.len()..];
+        assert_eq!(new_people.len(), 2);
+        assert_eq!((new_people[0].name, new_people[0].cost), ("Zealot", 2));
+        assert!(new_people[0].abilities.is_empty());
+        assert_eq!(new_people[1].name, "Medic");
+        assert_eq!(new_people[1].cost, person(&standard, "Looter").cost);
+
+        parse_error("[event.Flood]\ncost = 1\n", "must be based_on");
+        parse_error("[person.Zealot]\ncount = 1\n", "needs a cost");
+        parse_error(
+            "[person.Zealot]\nbased_on = \"Nobody\"\n",
+            "no standard person",
+        );
+        parse_error(
+            "[person.Looter]\nbased_on = \"Sniper\"\n",
+            "can't be based_on",
+        );
+    }
+
+    #[test]
+    fn removed_cards_are_left_out_and_the_rest_renumbered() {
+        let standard = CardSet::standard();
+        let card_set = parse("[camp.Outpost]\nremoved = true\n").unwrap();
+        assert_eq!(card_set.camp_types.len(), standard.camp_types.len() - 1);
+        assert!(card_set.camp_types.iter().all(|c| c.name != "Outpost"));
+        for (id, camp_type) in card_set.camp_types.iter().enumerate() {
+            assert_eq!(camp_type.id, id);
+        }
+
+        parse_error("[camp.Nowhere]\nremoved = true\n", "no standard card");
+        parse_error(
+            "[camp.Outpost]\nremoved = true\ninitial_cards = 2\n",
+            "can't have other properties",
+        );
+    }
+
+    #[test]
+    fn a_card_can_only_have_one_section() {
+        parse_error(
+            "[person.Looter]\ncount = 3\n\n[person.Looter]\ncost = 1\n",
+            "Looter",
+        );
+        parse_error("[person.Looter]\ncount = 3\ncount = 4\n", "count");
+    }
+
+    #[test]
+    fn unknown_kinds_keys_effects_and_traits_are_errors() {
+        parse_error("[spell.Fireball]\ncost = 1\n", "unknown card kind");
+        parse_error(
+            "[camp.Outpost]\ncount = 2\n",
+            "a camp can't have a \"count\"",
+        );
+        parse_error(
+            "[person.Looter]\ncots = 2\n",
+            "a person can't have a \"cots\"",
+        );
+        parse_error("[person.Looter]\ncost = \"two\"\n", "Looter");
+        parse_error(
+            "[person.Looter]\njunk = \"Explode\"\n",
+            "unknown effect \"Explode\"",
+        );
+        parse_error(
+            "[person.Looter]\nabilities = [{ cost = 1, effects = [\"Damage\", \"Fly\"] }]\n",
+            "unknown effect \"Fly\"",
+        );
+        parse_error(
+            "[person.Looter]\ntraits = [\"Flying\"]\n",
+            "unknown trait \"Flying\"",
+        );
+        parse_error(
+            "[person.Looter]\nabilities = [{ cost = 1, effects = [] }]\n",
+            "at least one effect",
+        );
+    }
+
+    #[test]
+    fn abilities_perform_their_effects_in_the_order_given() {
+        let card_set = parse(
+            r#"
+            [person.Looter]
+            abilities = [
+                { cost = 1, effects = ["Restore"] },
+                { cost = 2, effects = ["Draw", "Damage"] },
+            ]
+            "#,
+        )
+        .unwrap();
+        let abilities = &person(&card_set, "Looter").abilities;
+        assert_eq!(abilities.len(), 2);
+        assert_eq!(abilities[0].icon_effect(), Some(IconEffect::Restore));
+        assert_eq!(abilities[0].printed_cost(), 1);
+        // a sequence isn't a plain icon ability, so it's told apart by its description
+        assert_eq!(abilities[1].icon_effect(), None);
+        assert_eq!(abilities[1].printed_cost(), 2);
+        assert_eq!(
+            abilities[1].description(),
+            format!(
+                "{}, {}",
+                IconEffect::Draw.display_name(),
+                IconEffect::Damage.display_name()
+            ),
+        );
+    }
+
+    #[test]
+    fn on_junk_effects_follow_or_replace_the_junk_effect() {
+        let card_set: &'static CardSet = Box::leak(Box::new(
+            parse(
+                r#"
+                [person.Looter]
+                on_junk = { effects = ["Water", "Water"] }
+
+                [person.Vigilante]
+                on_junk = { effects = ["Water"], replaces_junk = true }
+                "#,
+            )
+            .unwrap(),
+        ));
+        let mut sandbox = Sandbox::new(
+            &card_set.camp_types,
+            &card_set.person_types,
+            &card_set.event_types,
+            GameRules::casual(false),
+            0,
+        );
+        for command in [
+            "hand p1 clear",
+            "hand p1 add Looter",
+            "hand p1 add Vigilante",
+            "turn p1",
+        ] {
+            sandbox.apply(command).unwrap();
+        }
+        let (mut game_state, choice) = sandbox.start().unwrap();
+        let junk_labels = labels(&game_state, &choice)
+            .into_iter()
+            .filter(|label| label.starts_with("Junk"))
+            .collect_vec();
+        assert_eq!(
+            junk_labels,
+            [
+                "Junk Looter (effect: Water, then Water, Water)",
+                "Junk Vigilante (effect: Water)",
+            ]
+        );
+        assert_eq!(game_state.cur_player_water, 3);
+
+        // Looter's junk effect gains 1 water, and then its on-junk effects gain 2 more
+        let choice = choose(&mut game_state, &choice, "Junk Looter").unwrap();
+        assert_eq!(game_state.cur_player_water, 6);
+
+        // Vigilante gains 1 water instead of raiding
+        let choice = choose(&mut game_state, &choice, "Junk Vigilante").unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(game_state.cur_player_water, 7);
+        assert!(game_state
+            .player(Player::Player1)
+            .events
+            .iter()
+            .all(Option::is_none));
+    }
+}
@@ -1,19 +1,25 @@
+use std::iter;
 use std::sync::Arc;
 
 use itertools::Itertools;
-use tui::text::Spans;
+use tui::text::{Span, Spans};
 
 use crate::cards::Cards;
 use crate::make_spans;
+use crate::metrics::{self, Counter};
 
+use super::abilities::{perform_ability, Ability};
+use super::camps::CampType;
+use super::events::EventType;
+use super::people::PersonType;
 use super::player_state::Person;
-use super::styles::StyledName;
+use super::styles::{StyledName, WATER};
+use super::{describe_ability, Action, GameResult, GameState, IconEffect};
 use super::{locations::*, PersonOrEventType};
-use super::{Action, GameResult, GameState, IconEffect};
 
 /// A choice between several options that must be made by a player, along with the logic for
-/// advancing the game state based on the choice.
-#[derive(Clone)]
+/// advancing the game state based on the choice. Its options and callbacks are shared between
+/// clones, so cloning it is cheap.
 #[must_use]
 pub enum Choice<'ctype> {
     Action(ActionChoice<'ctype>),
@@ -22,37 +28,139 @@ pub enum Choice<'ctype> {
     Restore(RestoreChoice<'ctype>),
     IconEffect(IconEffectChoice<'ctype>),
     RescuePerson(RescuePersonChoice<'ctype>), // only used for Rescue Team's ability
-    MoveEvents(MoveEventsChoice<'ctype>),     // only used for Doomsayer's on-enter-play effect
+    OptionalEffect(OptionalEffectChoice<'ctype>),
     DamageColumn(DamageColumnChoice<'ctype>), // only used for Magnus Karv's ability
     Discard(DiscardChoice<'ctype>),
+    DiscardDrawn(DiscardDrawnChoice<'ctype>),
+    RevealedJunk(RevealedJunkChoice<'ctype>), // only used for Scientist's ability
+    Mulligan(MulliganChoice<'ctype>),         // only used before the first turn
+    CampSelect(CampSelectChoice<'ctype>),     // only used before the first turn
+    AdvanceEvent(AdvanceEventChoice<'ctype>), // only used for Omen Clock's ability
+    MovePerson(MovePersonChoice<'ctype>),     // only used for Construction Yard's ability
+    MoveDestination(MoveDestinationChoice<'ctype>), // only used for Construction Yard's ability
+    CopyAbility(CopyAbilityChoice<'ctype>),   // only used for Mimic's trait
+    MultiSelect(MultiSelectChoice<'ctype>),
+}
+
+impl Clone for Choice<'_> {
+    fn clone(&self) -> Self {
+        metrics::increment(Counter::ChoiceClones);
+        match self {
+            Choice::Action(choice) => Choice::Action(choice.clone()),
+            Choice::PlayLoc(choice) => Choice::PlayLoc(choice.clone()),
+            Choice::Damage(choice) => Choice::Damage(choice.clone()),
+            Choice::Restore(choice) => Choice::Restore(choice.clone()),
+            Choice::IconEffect(choice) => Choice::IconEffect(choice.clone()),
+            Choice::RescuePerson(choice) => Choice::RescuePerson(choice.clone()),
+            Choice::OptionalEffect(choice) => Choice::OptionalEffect(choice.clone()),
+            Choice::DamageColumn(choice) => Choice::DamageColumn(choice.clone()),
+            Choice::Discard(choice) => Choice::Discard(choice.clone()),
+            Choice::DiscardDrawn(choice) => Choice::DiscardDrawn(choice.clone()),
+            Choice::RevealedJunk(choice) => Choice::RevealedJunk(choice.clone()),
+            Choice::Mulligan(choice) => Choice::Mulligan(choice.clone()),
+            Choice::CampSelect(choice) => Choice::CampSelect(choice.clone()),
+            Choice::AdvanceEvent(choice) => Choice::AdvanceEvent(choice.clone()),
+            Choice::MovePerson(choice) => Choice::MovePerson(choice.clone()),
+            Choice::MoveDestination(choice) => Choice::MoveDestination(choice.clone()),
+            Choice::CopyAbility(choice) => Choice::CopyAbility(choice.clone()),
+            Choice::MultiSelect(choice) => Choice::MultiSelect(choice.clone()),
+        }
+    }
 }
 
-impl<'v, 'g: 'v, 'ctype: 'g> Choice<'ctype> {
+impl<'g, 'ctype: 'g> Choice<'ctype> {
     /// Returns a choice for top-level turn Actions for the current player.
     pub fn new_actions(game_state: &mut GameState<'ctype>) -> Choice<'ctype> {
         let view = game_state.view_for_cur();
         let actions = view.my_state().actions(&view);
-        Choice::Action(ActionChoice { actions })
+        metrics::increment(Counter::Choices);
+        Choice::Action(ActionChoice {
+            actions: actions.into(),
+        })
     }
 
-    /// Returns the number of options available for this choice.
-    pub fn num_options(&self, game_state: &GameState<'ctype>) -> usize {
+    /// Returns the number of options available for this choice. The options are computed when
+    /// the choice is created, so this is cheap.
+    pub fn num_options(&self) -> usize {
         match self {
             Choice::Action(action_choice) => action_choice.actions().len(),
             Choice::PlayLoc(play_choice) => play_choice.locations().len(),
             Choice::Damage(damage_choice) => damage_choice.locations().len(),
             Choice::Restore(restore_choice) => restore_choice.locations().len(),
             Choice::IconEffect(icon_effect_choice) => icon_effect_choice.icon_effects().len() + 1,
-            Choice::RescuePerson(rescue_person_choice) => game_state
-                .player(rescue_person_choice.chooser())
-                .people()
-                .count(),
-            Choice::MoveEvents(_move_events_choice) => 2,
+            Choice::RescuePerson(rescue_person_choice) => rescue_person_choice.locations().len(),
+            Choice::OptionalEffect(_optional_effect_choice) => 2,
             Choice::DamageColumn(damage_column_choice) => damage_column_choice.columns().len(),
             Choice::Discard(discard_choice) => discard_choice.cards().len(),
+            Choice::DiscardDrawn(discard_drawn_choice) => discard_drawn_choice.cards().len(),
+            Choice::RevealedJunk(revealed_junk_choice) => revealed_junk_choice.usable().len() + 1,
+            Choice::Mulligan(_mulligan_choice) => 2,
+            Choice::CampSelect(camp_select_choice) => camp_select_choice.camp_sets().len(),
+            Choice::AdvanceEvent(advance_event_choice) => advance_event_choice.events().len(),
+            Choice::MovePerson(move_person_choice) => move_person_choice.locations().len(),
+            Choice::MoveDestination(move_destination_choice) => {
+                move_destination_choice.destinations().len()
+            }
+            Choice::CopyAbility(copy_ability_choice) => copy_ability_choice.abilities().len(),
+            Choice::MultiSelect(multi_select_choice) => {
+                multi_select_choice.options().len() + multi_select_choice.can_finish() as usize
+            }
+        }
+    }
+
+    /// Returns what this choice is part of resolving, if anything.
+    pub fn context(&self) -> Option<ChoiceContext<'ctype>> {
+        match self {
+            Choice::Action(_action_choice) => None,
+            Choice::PlayLoc(play_choice) => play_choice.context,
+            Choice::Damage(damage_choice) => damage_choice.context,
+            Choice::Restore(restore_choice) => restore_choice.context,
+            Choice::IconEffect(icon_effect_choice) => icon_effect_choice.context,
+            Choice::RescuePerson(rescue_person_choice) => rescue_person_choice.context,
+            Choice::OptionalEffect(optional_effect_choice) => optional_effect_choice.context,
+            Choice::DamageColumn(damage_column_choice) => damage_column_choice.context,
+            Choice::Discard(discard_choice) => discard_choice.context,
+            Choice::DiscardDrawn(discard_drawn_choice) => discard_drawn_choice.context,
+            Choice::RevealedJunk(revealed_junk_choice) => revealed_junk_choice.context,
+            Choice::Mulligan(mulligan_choice) => mulligan_choice.context,
+            Choice::CampSelect(camp_select_choice) => camp_select_choice.context,
+            Choice::AdvanceEvent(advance_event_choice) => advance_event_choice.context,
+            Choice::MovePerson(move_person_choice) => move_person_choice.context,
+            Choice::MoveDestination(move_destination_choice) => move_destination_choice.context,
+            Choice::CopyAbility(copy_ability_choice) => copy_ability_choice.context,
+            Choice::MultiSelect(multi_select_choice) => multi_select_choice.context,
         }
     }
 
+    /// Marks this choice as part of resolving the given context, unless it is already part of
+    /// resolving something more specific.
+    fn with_context(mut self, context: ChoiceContext<'ctype>) -> Self {
+        let choice_context = match &mut self {
+            Choice::Action(_action_choice) => return self,
+            Choice::PlayLoc(play_choice) => &mut play_choice.context,
+            Choice::Damage(damage_choice) => &mut damage_choice.context,
+            Choice::Restore(restore_choice) => &mut restore_choice.context,
+            Choice::IconEffect(icon_effect_choice) => &mut icon_effect_choice.context,
+            Choice::RescuePerson(rescue_person_choice) => &mut rescue_person_choice.context,
+            Choice::OptionalEffect(optional_effect_choice) => &mut optional_effect_choice.context,
+            Choice::DamageColumn(damage_column_choice) => &mut damage_column_choice.context,
+            Choice::Discard(discard_choice) => &mut discard_choice.context,
+            Choice::DiscardDrawn(discard_drawn_choice) => &mut discard_drawn_choice.context,
+            Choice::RevealedJunk(revealed_junk_choice) => &mut revealed_junk_choice.context,
+            Choice::Mulligan(mulligan_choice) => &mut mulligan_choice.context,
+            Choice::CampSelect(camp_select_choice) => &mut camp_select_choice.context,
+            Choice::AdvanceEvent(advance_event_choice) => &mut advance_event_choice.context,
+            Choice::MovePerson(move_person_choice) => &mut move_person_choice.context,
+            Choice::MoveDestination(move_destination_choice) => {
+                &mut move_destination_choice.context
+            }
+            Choice::CopyAbility(copy_ability_choice) => &mut copy_ability_choice.context,
+            Choice::MultiSelect(multi_select_choice) => &mut multi_select_choice.context,
+        };
+        choice_context.get_or_insert(context);
+        self
+    }
+
     /// Returns which player must make the choice.
     pub fn chooser(&self, game_state: &GameState<'ctype>) -> Player {
         match self {
@@ -62,9 +170,18 @@ impl<'v, 'g: 'v, 'ctype: 'g> Choice<'ctype> {
             Choice::Restore(restore_choice) => restore_choice.chooser(),
             Choice::IconEffect(icon_effect_choice) => icon_effect_choice.chooser(),
             Choice::RescuePerson(rescue_person_choice) => rescue_person_choice.chooser(),
-            Choice::MoveEvents(move_events_choice) => move_events_choice.chooser(),
+            Choice::OptionalEffect(optional_effect_choice) => optional_effect_choice.chooser(),
             Choice::DamageColumn(damage_column_choice) => damage_column_choice.chooser(),
             Choice::Discard(discard_choice) => discard_choice.chooser(),
+            Choice::DiscardDrawn(discard_drawn_choice) => discard_drawn_choice.chooser(),
+            Choice::RevealedJunk(revealed_junk_choice) => revealed_junk_choice.chooser(),
+            Choice::Mulligan(mulligan_choice) => mulligan_choice.chooser(),
+            Choice::CampSelect(camp_select_choice) => camp_select_choice.chooser(),
+            Choice::AdvanceEvent(advance_event_choice) => advance_event_choice.chooser(),
+            Choice::MovePerson(move_person_choice) => move_person_choice.chooser(),
+            Choice::MoveDestination(move_destination_choice) => move_destination_choice.chooser(),
+            Choice::CopyAbility(copy_ability_choice) => copy_ability_choice.chooser(),
+            Choice::MultiSelect(multi_select_choice) => multi_select_choice.chooser(),
         }
     }
 
@@ -99,17 +216,15 @@ impl<'v, 'g: 'v, 'ctype: 'g> Choice<'ctype> {
                 icon_effect_choice.choose(game_state, icon_effect)
             }
             Choice::RescuePerson(rescue_person_choice) => {
-                let (location, _) = game_state
-                    .player(rescue_person_choice.chooser())
-                    .nth_person(option);
+                let location = rescue_person_choice.locations()[option];
                 rescue_person_choice.choose(game_state, location)
             }
-            Choice::MoveEvents(move_events_choice) => move_events_choice.choose(
+            Choice::OptionalEffect(optional_effect_choice) => optional_effect_choice.choose(
                 game_state,
                 match option {
                     0 => false,
                     1 => true,
-                    _ => panic!("Invalid option for Choice::MoveEvents"),
+                    _ => panic!("Invalid option for Choice::OptionalEffect"),
                 },
             ),
             Choice::DamageColumn(damage_column_choice) => {
@@ -118,16 +233,144 @@ impl<'v, 'g: 'v, 'ctype: 'g> Choice<'ctype> {
             Choice::Discard(discard_choice) => {
                 discard_choice.choose(game_state, discard_choice.cards()[option])
             }
+            Choice::DiscardDrawn(discard_drawn_choice) => {
+                discard_drawn_choice.choose(game_state, discard_drawn_choice.cards()[option])
+            }
+            Choice::RevealedJunk(revealed_junk_choice) => {
+                let card = if option == 0 {
+                    None
+                } else {
+                    Some(revealed_junk_choice.usable()[option - 1])
+                };
+                revealed_junk_choice.choose(game_state, card)
+            }
+            Choice::Mulligan(mulligan_choice) => mulligan_choice.choose(
+                game_state,
+                match option {
+                    0 => false,
+                    1 => true,
+                    _ => panic!("Invalid option for Choice::Mulligan"),
+                },
+            ),
+            Choice::CampSelect(camp_select_choice) => {
+                camp_select_choice.choose(game_state, camp_select_choice.camp_sets()[option])
+            }
+            Choice::AdvanceEvent(advance_event_choice) => {
+                advance_event_choice.choose(game_state, advance_event_choice.events()[option])
+            }
+            Choice::MovePerson(move_person_choice) => {
+                move_person_choice.choose(game_state, move_person_choice.locations()[option])
+            }
+            Choice::MoveDestination(move_destination_choice) => move_destination_choice
+                .choose(game_state, move_destination_choice.destinations()[option]),
+            Choice::CopyAbility(copy_ability_choice) => {
+                copy_ability_choice.choose(game_state, copy_ability_choice.abilities()[option])
+            }
+            Choice::MultiSelect(multi_select_choice) => {
+                multi_select_choice.choose(game_state, multi_select_choice.option_selection(option))
+            }
         }
     }
 
-    /// Formats the option with the given index for human-readable display.
+    /// Returns the option with the given index as plain text (see `format_option`), for
+    /// display outside of the UI.
+    pub fn option_label(&self, option: usize, game_state: &'g GameState<'ctype>) -> String {
+        let spans = self.format_option(option, game_state);
+        spans.0.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    /// Formats the option with the given index for human-readable display, noting what the
+    /// choice is part of resolving (if anything).
     ///
     /// Panics if the index is is greater than equal to the number of options for this choice.
     pub fn format_option(
         &self,
         option: usize,
         game_state: &'g GameState<'ctype>,
+    ) -> Spans<'static> {
+        let mut spans = self.format_option_without_context(option, game_state);
+        if let Some(context) = self.context() {
+            spans.0.push(" (".into());
+            spans.0.extend(context.format().0);
+            spans.0.push(")".into());
+        }
+        spans
+    }
+
+    /// Returns the card on the board that the option with the given index targets, if it
+    /// targets a single card (e.g. a card to damage, or a person whose ability to use).
+    pub fn option_target(
+        &self,
+        option: usize,
+        game_state: &GameState<'ctype>,
+    ) -> Option<CardLocation> {
+        let chooser = self.chooser(game_state);
+        match self {
+            Choice::Action(action_choice) => match action_choice.actions()[option] {
+                Action::UsePersonAbility(_, loc) | Action::UseMimicAbility(loc) => {
+                    Some(loc.for_player(chooser))
+                }
+                Action::UseCampAbility(_, column) => {
+                    Some(CardLocation::new(column, CardRowIndex::camp(), chooser))
+                }
+                _ => None,
+            },
+            Choice::Damage(damage_choice) => Some(damage_choice.locations()[option]),
+            Choice::Restore(restore_choice) => {
+                Some(restore_choice.locations()[option].for_player(chooser))
+            }
+            Choice::RescuePerson(rescue_person_choice) => {
+                Some(rescue_person_choice.locations()[option].for_player(chooser))
+            }
+            Choice::MovePerson(move_person_choice) => Some(move_person_choice.locations()[option]),
+            Choice::CopyAbility(copy_ability_choice) => {
+                Some(copy_ability_choice.abilities()[option].0)
+            }
+            Choice::MultiSelect(multi_select_choice) => {
+                match multi_select_choice.option_selection(option) {
+                    Some(SelectTarget::Card(loc)) => Some(loc),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Formats an option that targets a card (see `option_target`) as the given verb followed by
+    /// the card, noting whose it is from the chooser's perspective (e.g. "Damage opponent's
+    /// Railgun at <column: 1, row: camp>").
+    fn format_target(
+        &self,
+        verb: &'static str,
+        option: usize,
+        game_state: &GameState<'ctype>,
+    ) -> Spans<'static> {
+        let target = self
+            .option_target(option, game_state)
+            .expect("the option doesn't target a card");
+        let owner = if target.player() == self.chooser(game_state) {
+            "your "
+        } else {
+            "opponent's "
+        };
+        make_spans!(
+            verb,
+            owner,
+            game_state
+                .player(target.player())
+                .card_name_at(target.player_loc()),
+            format!(" at {}", target.player_loc()),
+        )
+    }
+
+    /// Formats the option with the given index for human-readable display, without noting what
+    /// the choice is part of resolving.
+    ///
+    /// Panics if the index is is greater than equal to the number of options for this choice.
+    fn format_option_without_context(
+        &self,
+        option: usize,
+        game_state: &'g GameState<'ctype>,
     ) -> Spans<'static> {
         match self {
             Choice::Action(action_choice) => {
@@ -138,13 +381,13 @@ impl<'v, 'g: 'v, 'ctype: 'g> Choice<'ctype> {
                 play_choice.person().styled_name(),
                 format!(" at {}", play_choice.locations()[option]),
             ),
-            Choice::Damage(damage_choice) => Spans::from(format!(
-                "{} {}",
-                if damage_choice.destroy() { "Destroy" } else { "Damage" },
-                damage_choice.locations()[option],
-            )),
+            Choice::Damage(damage_choice) => {
+                let verb = if damage_choice.destroy() { "Destroy " } else { "Damage " };
+                self.format_target(verb, option, game_state)
+            }
             Choice::Restore(restore_choice) => {
-                Spans::from(format!("Restore {}", restore_choice.locations()[option]))
+                let verb = if restore_choice.ready() { "Restore and ready " } else { "Restore " };
+                self.format_target(verb, option, game_state)
             }
             Choice::IconEffect(icon_effect_choice) => Spans::from({
                 if option == 0 {
@@ -156,19 +399,21 @@ impl<'v, 'g: 'v, 'ctype: 'g> Choice<'ctype> {
                     )
                 }
             }),
-            Choice::RescuePerson(rescue_person_choice) => {
-                let (location, person) = game_state
-                    .player(rescue_person_choice.chooser())
-                    .nth_person(option);
-                make_spans!("Rescue ", person.styled_name(), format!(" at {}", location))
-            }
-            Choice::MoveEvents(_move_events_choice) => Spans::from(match option {
-                0 => "Don't move events back",
-                1 => "Move events back",
-                _ => panic!("Invalid option for Choice::MoveEvents"),
-            }),
+            Choice::RescuePerson(_) => self.format_target("Rescue ", option, game_state),
+            Choice::OptionalEffect(optional_effect_choice) => {
+                let verb = match option {
+                    0 => "Decline ",
+                    1 => "Resolve ",
+                    _ => panic!("Invalid option for Choice::OptionalEffect"),
+                };
+                make_spans!(
+                    verb,
+                    optional_effect_choice.source().clone(),
+                    "'s optional effect"
+                )
+            }
             Choice::DamageColumn(damage_column_choice) => Spans::from(format!(
-                "{}{} column {}",
+                "{}{} opponent's column {}",
                 if damage_column_choice.destroy() { "Destroy" } else { "Damage" },
                 if damage_column_choice.people_only() { " people in" } else { "" },
                 damage_column_choice.columns()[option].as_usize(),
@@ -176,10 +421,304 @@ impl<'v, 'g: 'v, 'ctype: 'g> Choice<'ctype> {
             Choice::Discard(discard_choice) => {
                 make_spans!("Discard ", discard_choice.cards()[option].styled_name())
             }
+            Choice::DiscardDrawn(discard_drawn_choice) => {
+                make_spans!(
+                    "Discard the drawn ",
+                    discard_drawn_choice.cards()[option].styled_name()
+                )
+            }
+            Choice::RevealedJunk(revealed_junk_choice) => {
+                if option == 0 {
+                    // list the cards whose junk effects can't be used
+                    let mut spans = Spans::from("Don't use a junk effect");
+                    let unusable = revealed_junk_choice
+                        .revealed()
+                        .iter()
+                        .filter(|card| !revealed_junk_choice.usable().contains(card))
+                        .collect_vec();
+                    for (i, card) in unusable.into_iter().enumerate() {
+                        spans
+                            .0
+                            .push(if i == 0 { " (can't use: " } else { ", " }.into());
+                        spans.0.push(card.styled_name());
+                        spans.0.push(format!(" {:?}", card.junk_effect()).into());
+                    }
+                    if spans.0.len() > 1 {
+                        spans.0.push(")".into());
+                    }
+                    spans
+                } else {
+                    let card = revealed_junk_choice.usable()[option - 1];
+                    make_spans!(
+                        "Use ",
+                        card.styled_name(),
+                        format!("'s junk effect: {:?}", card.junk_effect()),
+                    )
+                }
+            }
+            Choice::Mulligan(_mulligan_choice) => Spans::from(match option {
+                0 => "Keep opening hand",
+                1 => "Mulligan (shuffle hand into the deck and redraw)",
+                _ => panic!("Invalid option for Choice::Mulligan"),
+            }),
+            Choice::CampSelect(camp_select_choice) => {
+                let mut spans = Spans::from("Keep ");
+                for (i, camp_type) in camp_select_choice.camp_sets()[option].iter().enumerate() {
+                    if i > 0 {
+                        spans.0.push(", ".into());
+                    }
+                    spans.0.push(camp_type.styled_name());
+                }
+                spans
+            }
+            Choice::AdvanceEvent(advance_event_choice) => {
+                let (player, slot) = advance_event_choice.events()[option];
+                let event = game_state.player(player).events[slot]
+                    .expect("Tried to advance an event from an empty slot");
+                make_spans!(
+                    if slot == 0 { "Resolve " } else { "Advance " },
+                    if player == advance_event_choice.chooser() { "your " } else { "opponent's " },
+                    event.styled_name(),
+                    format!(" from event slot {}", slot + 1),
+                )
+            }
+            Choice::MovePerson(_) => self.format_target("Move ", option, game_state),
+            Choice::MoveDestination(move_destination_choice) => {
+                let person_loc = *move_destination_choice.person_loc();
+                make_spans!(
+                    "Move ",
+                    if person_loc.player() == move_destination_choice.chooser() {
+                        "your "
+                    } else {
+                        "opponent's "
+                    },
+                    game_state
+                        .player(person_loc.player())
+                        .card_name_at(person_loc.player_loc()),
+                    format!(" to {}", move_destination_choice.destinations()[option]),
+                )
+            }
+            Choice::CopyAbility(copy_ability_choice) => {
+                let (_, ability) = copy_ability_choice.abilities()[option];
+                let game_view = game_state.view_for(copy_ability_choice.chooser());
+                let mut spans = self.format_target("Copy the ability of ", option, game_state);
+                spans.0.extend(
+                    make_spans!(
+                        ": ",
+                        describe_ability(ability, &game_view),
+                        WATER_COST: ability.cost(&game_view),
+                    )
+                    .0,
+                );
+                spans
+            }
+            Choice::MultiSelect(multi_select_choice) => {
+                let chooser = multi_select_choice.chooser();
+                let verb = *multi_select_choice.verb();
+                match multi_select_choice.option_selection(option) {
+                    Some(target) => {
+                        let mut spans = Spans::from(format!("{} ", verb));
+                        spans.0.extend(target.format(chooser, game_state).0);
+                        let (min, max) = (multi_select_choice.min(), multi_select_choice.max());
+                        if min == max {
+                            // say how far along the player is, since they can't stop early
+                            let number = multi_select_choice.selected().len() + 1;
+                            spans.0.push(format!(" ({} of {})", number, max).into());
+                        }
+                        spans
+                    }
+                    None => {
+                        // list what has been selected
+                        let mut spans =
+                            Spans::from(format!("Done selecting ({}", verb.to_lowercase()));
+                        if multi_select_choice.selected().is_empty() {
+                            spans.0.push(" nothing".into());
+                        }
+                        for (i, target) in multi_select_choice.selected().iter().enumerate() {
+                            spans.0.push(if i == 0 { " " } else { ", " }.into());
+                            spans.0.extend(target.format(chooser, game_state).0);
+                        }
+                        spans.0.push(")".into());
+                        spans
+                    }
+                }
+            }
+        }
+    }
+
+    /// Formats the option with the given index for the game log, after it has been chosen.
+    /// This is the same as `format_option`, except that it also records any cards that the
+    /// choice revealed, and that the camps a player keeps aren't given away (since the other
+    /// player may not have chosen theirs yet; they're revealed on the board afterwards).
+    ///
+    /// Panics if the index is is greater than equal to the number of options for this choice.
+    pub fn format_chosen_option(
+        &self,
+        option: usize,
+        game_state: &'g GameState<'ctype>,
+    ) -> Spans<'static> {
+        if let Choice::CampSelect(_) = self {
+            return Spans::from("Chose which camps to keep");
+        }
+        let mut spans = self.format_option(option, game_state);
+        if let Choice::RevealedJunk(revealed_junk_choice) = self {
+            let mut reveal = vec!["Revealed ".into()];
+            for (i, card) in revealed_junk_choice.revealed().iter().enumerate() {
+                if i > 0 {
+                    reveal.push(", ".into());
+                }
+                reveal.push(card.styled_name());
+            }
+            reveal.push("; ".into());
+            spans.0.splice(0..0, reveal);
+        }
+        spans
+    }
+
+    /// Formats the canonical option of the given group for human-readable display, noting how
+    /// many other options are equivalent to it.
+    pub fn format_option_group(
+        &self,
+        group: OptionGroup,
+        game_state: &'g GameState<'ctype>,
+    ) -> Spans<'static> {
+        let mut spans = self.format_option(group.option, game_state);
+        if group.multiplicity > 1 {
+            spans
+                .0
+                .push(format!(" (+{} equivalent)", group.multiplicity - 1).into());
+        }
+        spans
+    }
+
+    /// Groups together the options of this choice that have identical effects, returning one
+    /// group per distinct effect, ordered by each group's canonical (first) option.
+    ///
+    /// Options are only grouped when they lead to identical game states and the rest of the
+    /// game is known to depend only on that state, so options with the same effect are
+    /// sometimes kept apart. This plays out every option on a copy of the game state, so it
+    /// is too slow to use in rollouts.
+    pub fn option_groups(&self, game_state: &GameState<'ctype>) -> Vec<OptionGroup> {
+        let mut groups: Vec<(OptionGroup, Option<OptionOutcome>)> = Vec::new();
+        for option in 0..self.num_options() {
+            let outcome = self.option_outcome(game_state, option);
+            let existing_group = groups
+                .iter_mut()
+                .find(|(_, group_outcome)| outcome.is_some() && group_outcome == &outcome);
+            match existing_group {
+                Some((group, _)) => group.multiplicity += 1,
+                None => groups.push((
+                    OptionGroup {
+                        option,
+                        multiplicity: 1,
+                    },
+                    outcome,
+                )),
+            }
+        }
+        groups.into_iter().map(|(group, _)| group).collect()
+    }
+
+    /// Plays out the given option on a copy of the game state and returns its outcome, or
+    /// `None` if the option's effects can't be fully determined from the resulting state.
+    fn option_outcome(
+        &self,
+        game_state: &GameState<'ctype>,
+        option: usize,
+    ) -> Option<OptionOutcome> {
+        // whether this choice always continues by passing the updated game state (and nothing
+        // else) to the same callback, whichever option is chosen
+        let continues_from_state = match self {
+            Choice::Restore(_)
+            | Choice::RescuePerson(_)
+            | Choice::DamageColumn(_)
+            | Choice::MoveDestination(_) => true,
+            Choice::PlayLoc(play_choice) => !matches!(
+                play_choice.person(),
+                Person::NonPunk { person_type, .. } if person_type.on_enter_play.is_some()
+            ),
+            _ => false,
+        };
+
+        let mut game_state = game_state.clone();
+        match self.choose(&mut game_state, option) {
+            Err(game_result) => Some(OptionOutcome::GameOver(game_result)),
+            // the actions available are determined by the game state alone
+            Ok(Choice::Action(_)) => Some(OptionOutcome::State(game_state.fingerprint())),
+            Ok(_) if continues_from_state => Some(OptionOutcome::State(game_state.fingerprint())),
+            Ok(_) => None,
+        }
+    }
+}
+
+/// Something that a choice is part of resolving, which ties the choice to the card that caused
+/// it when the two are shown apart (e.g. in the log).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChoiceContext<'ctype> {
+    /// The "when this card enters play" effect of the given person.
+    EnterPlay(&'ctype PersonType),
+
+    /// The resolution of the given event.
+    ResolveEvent(&'ctype EventType),
+}
+
+impl ChoiceContext<'_> {
+    /// Returns this context as plain text (e.g. "resolving Napalm"), without styling.
+    pub fn label(&self) -> String {
+        self.format()
+            .0
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    /// Formats this context for human-readable display.
+    pub fn format(&self) -> Spans<'static> {
+        match self {
+            ChoiceContext::EnterPlay(person_type) => make_spans!(
+                "resolving ",
+                person_type.styled_name(),
+                "'s enter-play effect",
+            ),
+            ChoiceContext::ResolveEvent(event_type) => {
+                make_spans!("resolving ", event_type.styled_name())
+            }
         }
     }
 }
 
+/// A set of options of a choice that have identical effects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptionGroup {
+    /// The first option in the group, which stands in for the whole group.
+    pub option: usize,
+
+    /// The number of options in the group.
+    pub multiplicity: usize,
+}
+
+impl OptionGroup {
+    /// Returns a separate group for each of `num_options` options.
+    pub fn singletons(num_options: usize) -> Vec<OptionGroup> {
+        (0..num_options)
+            .map(|option| OptionGroup {
+                option,
+                multiplicity: 1,
+            })
+            .collect()
+    }
+}
+
+/// The outcome of choosing an option, used to detect equivalent options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OptionOutcome {
+    /// The game ended with the given result.
+    GameOver(GameResult),
+
+    /// The game continued from a state with the given fingerprint.
+    State(u64),
+}
+
 type ThenCallback<'ctype, T> = Arc<
     dyn Fn(&mut GameState<'ctype>, T) -> Result<Choice<'ctype>, GameResult> + Sync + Send + 'ctype,
 >;
@@ -188,11 +727,49 @@ type ThenCallback<'ctype, T> = Arc<
 /// Can be converted into a full `Choice` by attaching a callback with `.then(...)`.
 #[must_use]
 pub struct ChoiceFuture<'g, 'ctype: 'g, T = ()> {
-    choice_builder:
-        Box<dyn FnOnce(ThenCallback<'ctype, T>) -> Result<Choice<'ctype>, GameResult> + 'g>,
+    state: FutureState<'g, 'ctype, T>,
+}
+
+/// The state of a `ChoiceFuture`. Futures that have already resolved (which most do) are
+/// handled without allocating any callbacks.
+enum FutureState<'g, 'ctype: 'g, T> {
+    /// The future has resolved with the given value.
+    Ready(&'g mut GameState<'ctype>, T),
+
+    /// The game ended with the given result.
+    GameOver(GameResult),
+
+    /// The future is waiting for a choice, which will be built by this function once the
+    /// callback for after the future resolves is known.
+    Pending(Box<dyn FnOnce(ThenCallback<'ctype, T>) -> Result<Choice<'ctype>, GameResult> + 'g>),
 }
 
 impl<'g, 'ctype: 'g, T: 'ctype> ChoiceFuture<'g, 'ctype, T> {
+    /// Returns a future that waits for a choice, which is built by the given function.
+    fn pending(
+        choice_builder: impl FnOnce(ThenCallback<'ctype, T>) -> Result<Choice<'ctype>, GameResult> + 'g,
+    ) -> Self {
+        ChoiceFuture {
+            state: FutureState::Pending(Box::new(choice_builder)),
+        }
+    }
+
+    /// Returns a future that has resolved with the given value, or ended the game.
+    fn from_result(game_state: &'g mut GameState<'ctype>, result: Result<T, GameResult>) -> Self {
+        ChoiceFuture {
+            state: match result {
+                Ok(value) => FutureState::Ready(game_state, value),
+                Err(game_result) => FutureState::GameOver(game_result),
+            },
+        }
+    }
+
+    /// Returns whether this future has already resolved (or ended the game), without waiting
+    /// for a choice.
+    fn is_ready(&self) -> bool {
+        !matches!(self.state, FutureState::Pending(_))
+    }
+
     /// Returns a `Choice` that encapsulates the given logic for advancing the game state after
     /// this future resolves.
     pub fn then(
@@ -202,7 +779,20 @@ impl<'g, 'ctype: 'g, T: 'ctype> ChoiceFuture<'g, 'ctype, T> {
             + Send
             + 'ctype,
     ) -> Result<Choice<'ctype>, GameResult> {
-        (self.choice_builder)(Arc::new(callback))
+        match self.state {
+            FutureState::Ready(game_state, value) => callback(game_state, value),
+            FutureState::GameOver(game_result) => Err(game_result),
+            FutureState::Pending(choice_builder) => choice_builder(Arc::new(callback)),
+        }
+    }
+
+    /// Like `then`, but takes a callback that is already shared.
+    fn then_shared(self, callback: ThenCallback<'ctype, T>) -> Result<Choice<'ctype>, GameResult> {
+        match self.state {
+            FutureState::Ready(game_state, value) => callback(game_state, value),
+            FutureState::GameOver(game_result) => Err(game_result),
+            FutureState::Pending(choice_builder) => choice_builder(callback),
+        }
     }
 
     /// Returns a new future that encapsulates the given logic for advancing the game state after
@@ -211,9 +801,16 @@ impl<'g, 'ctype: 'g, T: 'ctype> ChoiceFuture<'g, 'ctype, T> {
         self,
         callback: impl Fn(&mut GameState<'ctype>, T) -> Result<U, GameResult> + Sync + Send + 'ctype,
     ) -> ChoiceFuture<'g, 'ctype, U> {
-        ChoiceFuture {
-            choice_builder: Box::new(move |callback2| {
-                (self.choice_builder)(Arc::new(move |game_state, value| {
+        match self.state {
+            FutureState::Ready(game_state, value) => {
+                let result = callback(game_state, value);
+                ChoiceFuture::from_result(game_state, result)
+            }
+            FutureState::GameOver(game_result) => ChoiceFuture {
+                state: FutureState::GameOver(game_result),
+            },
+            FutureState::Pending(choice_builder) => ChoiceFuture::pending(move |callback2| {
+                choice_builder(Arc::new(move |game_state, value| {
                     let value2 = callback(game_state, value)?;
                     callback2(game_state, value2)
                 }))
@@ -233,21 +830,43 @@ impl<'g, 'ctype: 'g, T: 'ctype> ChoiceFuture<'g, 'ctype, T> {
             + Send
             + 'ctype,
     ) -> ChoiceFuture<'g, 'ctype, U> {
-        ChoiceFuture {
-            choice_builder: Box::new(move |callback2| {
-                (self.choice_builder)(Arc::new(move |game_state, value| {
+        match self.state {
+            FutureState::Ready(game_state, value) => match callback(game_state, value) {
+                Ok(future2) => future2,
+                Err(game_result) => ChoiceFuture {
+                    state: FutureState::GameOver(game_result),
+                },
+            },
+            FutureState::GameOver(game_result) => ChoiceFuture {
+                state: FutureState::GameOver(game_result),
+            },
+            FutureState::Pending(choice_builder) => ChoiceFuture::pending(move |callback2| {
+                choice_builder(Arc::new(move |game_state, value| {
                     let future2 = callback(game_state, value)?;
-                    (future2.choice_builder)(callback2.clone())
+                    future2.then_shared(callback2.clone())
                 }))
             }),
         }
     }
 
+    /// Marks the choice that this future waits for (if any) as part of resolving the given
+    /// context.
+    pub fn in_context(self, context: ChoiceContext<'ctype>) -> Self {
+        match self.state {
+            FutureState::Pending(choice_builder) => ChoiceFuture::pending(move |callback| {
+                choice_builder(callback).map(|choice| choice.with_context(context))
+            }),
+            state => ChoiceFuture { state },
+        }
+    }
+
     /// Converts this future into one that has no extra result value.
     pub fn ignore_result(self) -> ChoiceFuture<'g, 'ctype> {
-        ChoiceFuture {
-            choice_builder: Box::new(move |callback| {
-                (self.choice_builder)(Arc::new(move |game_state, _| callback(game_state, ())))
+        match self.state {
+            FutureState::Ready(game_state, _) => ChoiceFuture::immediate(game_state),
+            FutureState::GameOver(game_result) => ChoiceFuture::end_game(game_result),
+            FutureState::Pending(choice_builder) => ChoiceFuture::pending(move |callback| {
+                choice_builder(Arc::new(move |game_state, _| callback(game_state, ())))
             }),
         }
     }
@@ -257,21 +876,22 @@ impl<'g, 'ctype: 'g> ChoiceFuture<'g, 'ctype> {
     /// Returns a future that resolves immediately with no value using the given `GameState`.
     pub fn immediate(game_state: &'g mut GameState<'ctype>) -> ChoiceFuture<'g, 'ctype> {
         ChoiceFuture {
-            choice_builder: Box::new(move |callback| callback(game_state, ())),
+            state: FutureState::Ready(game_state, ()),
         }
     }
 
     /// Returns a future that ends the game immediately with the given `GameResult`.
     pub fn end_game(game_result: GameResult) -> ChoiceFuture<'g, 'ctype> {
         ChoiceFuture {
-            choice_builder: Box::new(move |_| Err(game_result)),
+            state: FutureState::GameOver(game_result),
         }
     }
 }
 
 #[derive(Clone)]
 pub struct ActionChoice<'ctype> {
-    actions: Vec<Action<'ctype>>,
+    /// The actions, shared so that cloning the choice doesn't copy them.
+    actions: Arc<[Action<'ctype>]>,
 }
 
 impl<'g, 'ctype: 'g> ActionChoice<'ctype> {
@@ -280,6 +900,21 @@ impl<'g, 'ctype: 'g> ActionChoice<'ctype> {
         &self.actions
     }
 
+    /// Returns the number of actions that can be taken besides ending the turn.
+    pub fn num_actions_besides_end_turn(&self) -> usize {
+        self.actions
+            .iter()
+            .filter(|action| !matches!(action, Action::EndTurn))
+            .count()
+    }
+
+    /// Returns whether ending the turn is the only action that can be taken. The available
+    /// actions are already limited to ones that can be afforded and would do something, so
+    /// there's nothing meaningful left to do in the turn if so.
+    pub fn only_end_turn_left(&self) -> bool {
+        matches!(self.actions[..], [Action::EndTurn])
+    }
+
     /// Chooses the given action, updating the game state and returning the next Choice.
     pub fn choose(
         &self,
@@ -297,6 +932,7 @@ macro_rules! choice_struct {
         pub struct $StructName:ident => $result_type:ty {
             $($(#[$field_meta:meta])* $field:ident: ($($field_type:tt)+),)*
         }
+        $(non_empty => $options_field:ident;)?
 
         $(#[$choose_meta:meta])*
         pub fn choose(&$self:ident, $game_state:ident, $action:ident: $action_type:ty $(,)?)
@@ -307,7 +943,10 @@ macro_rules! choice_struct {
             /// The player who must choose.
             chooser: Player,
 
-            $($(#[$field_meta])* $field: $($field_type)+,)*
+            $($(#[$field_meta])* $field: field_storage_type!($($field_type)+),)*
+
+            /// What the choice is part of resolving, if anything.
+            context: Option<ChoiceContext<'ctype>>,
 
             /// A callback for what to do after the player chooses and the game state is updated.
             then: Arc<dyn Fn(&mut GameState<'ctype>, $result_type) -> Result<Choice<'ctype>, GameResult> + Sync + Send + 'ctype>,
@@ -333,15 +972,26 @@ macro_rules! choice_struct {
                 chooser: Player,
                 $($field: $($field_type)+,)*
             ) -> ChoiceFuture<'g, 'ctype, $result_type> {
-                ChoiceFuture {
-                    choice_builder: Box::new(move |callback| {
-                        Ok(Choice::$VariantName($StructName {
-                            chooser,
-                            $($field,)*
-                            then: callback,
-                        }))
-                    }),
-                }
+                // a choice with no options would leave the chooser stuck
+                $(
+                    debug_assert!(
+                        !$options_field.is_empty(),
+                        "Tried to make {:?} choose from an empty list of {} (in a {})",
+                        chooser,
+                        stringify!($options_field),
+                        stringify!($StructName),
+                    );
+                )?
+
+                ChoiceFuture::pending(move |callback| {
+                    metrics::increment(Counter::Choices);
+                    Ok(Choice::$VariantName($StructName {
+                        chooser,
+                        $($field: $field.into(),)*
+                        context: None,
+                        then: callback,
+                    }))
+                })
             }
 
             $(#[$choose_meta])*
@@ -356,10 +1006,24 @@ macro_rules! choice_struct {
     };
 }
 
+/// The type that a choice's field is stored as: lists are shared, so that cloning a choice (e.g.
+/// for the UI's history) doesn't copy them.
+macro_rules! field_storage_type {
+    (Vec<$type:ty>) => {
+        Arc<[$type]>
+    };
+    ($type:ty) => {
+        $type
+    };
+}
+
 macro_rules! field_return_type {
     (bool) => {
         bool
     };
+    (usize) => {
+        usize
+    };
     (Vec<$type:ty>) => {
         &[$type]
     };
@@ -372,6 +1036,9 @@ macro_rules! field_return_expr {
     ($self:ident, $field:ident, bool) => {
         $self.$field
     };
+    ($self:ident, $field:ident, usize) => {
+        $self.$field
+    };
     ($self:ident, $field:ident, $type:ty) => {
         &$self.$field
     };
@@ -386,6 +1053,7 @@ choice_struct! {
         /// The locations where the card can be played.
         locations: (Vec<PlayLocation>),
     }
+    non_empty => locations;
 
     /// Plays the person at the given location,
     /// updating the game state and returning the next Choice.
@@ -395,18 +1063,23 @@ choice_struct! {
         // place the card onto the board
         let col = view.my_state_mut().column_mut(play_loc.column());
         let row_index = play_loc.row().as_usize();
-        if let Some(old_person) = col.person_slots[row_index].replace(self.person.clone()) {
-            // if there was a person already in the slot, move the old person to the other slot
-            let other_row_index = 1 - row_index;
-            let other_slot_old = col.person_slots[other_row_index].replace(old_person);
-            assert!(other_slot_old.is_none()); // the other slot should have been empty
-        }
+        col.place_person(self.person.clone(), play_loc.row());
 
         // activate any "when this card enters play" effect of the person
         if let Person::NonPunk { person_type, .. } = col.person_slots[row_index].as_ref().unwrap() {
+            let person_type = *person_type;
             if let Some(on_enter_play) = person_type.on_enter_play {
-                let future = on_enter_play(view, play_loc)?;
-                return (future.choice_builder)(self.then.clone());
+                let future = if person_type.enter_play_optional {
+                    let chooser = self.chooser;
+                    let source = person_type.styled_name();
+                    perform_optional_effect(view.game_state, chooser, source, move |game_state| {
+                        on_enter_play(game_state.view_for_mut(chooser), play_loc)
+                    })?
+                } else {
+                    on_enter_play(view, play_loc)?
+                };
+                let future = future.in_context(ChoiceContext::EnterPlay(person_type));
+                return future.then_shared(self.then.clone());
             }
         }
 
@@ -424,6 +1097,7 @@ choice_struct! {
         /// The locations of the cards that can be damaged.
         locations: (Vec<CardLocation>),
     }
+    non_empty => locations;
 
     /// Chooses the given card to damage, updating the game state and returning the next Choice.
     pub fn choose(&self, game_state, target_loc: CardLocation) {
@@ -439,16 +1113,31 @@ choice_struct! {
     /// asks the player to restore a card
     Restore:
     pub struct RestoreChoice => () {
+        /// Whether to also ready the card (only allowed when every location is a person).
+        ready: (bool),
         /// The locations of the cards that can be restored.
         locations: (Vec<PlayerCardLocation>),
     }
+    non_empty => locations;
 
     /// Chooses the given card to restore, updating the game state and returning the next Choice.
     pub fn choose(&self, game_state, target_loc: PlayerCardLocation) {
         // restore the card
-        game_state
-            .player_mut(self.chooser)
-            .restore_card_at(target_loc);
+        let player_state = game_state.player_mut(self.chooser);
+        player_state.restore_card_at(target_loc);
+
+        // ready it if requested
+        if self.ready {
+            let row = target_loc
+                .row()
+                .to_person_index()
+                .expect("Tried to restore and ready a camp");
+            player_state
+                .column_mut(target_loc.column())
+                .person_mut_slot(row)
+                .expect("Tried to ready an empty person slot")
+                .set_ready();
+        }
 
         // advance the game state until the next choice
         (self.then)(game_state, ())
@@ -469,7 +1158,7 @@ choice_struct! {
         if let Some(icon_effect) = icon_effect {
             // perform the icon effect
             let future = icon_effect.perform(game_state.view_for_mut(self.chooser))?;
-            (future.choice_builder)(self.then.clone())
+            future.then_shared(self.then.clone())
         } else {
             // no icon effect was chosen, so just advance the game state until the next choice
             (self.then)(game_state, ())
@@ -480,19 +1169,24 @@ choice_struct! {
 choice_struct! {
     /// asks the player to rescue one of their people
     RescuePerson:
-    pub struct RescuePersonChoice => () {}
+    pub struct RescuePersonChoice => () {
+        /// The locations of the people that can be rescued.
+        locations: (Vec<PlayLocation>),
+    }
+    non_empty => locations;
 
     /// Chooses the given person to rescue, updating the game state
     /// and returning the next Choice.
     pub fn choose(&self, game_state, person_loc: PlayLocation) {
-        // remove the person from the board
-        let person = game_state.player_mut(self.chooser).remove_person_at(person_loc);
+        // find the card to return to the player's hand (a punk's face-down card is revealed)
+        let card_type = game_state
+            .player(self.chooser)
+            .person_slot(person_loc)
+            .expect("Tried to rescue a person from an empty slot")
+            .card();
 
-        // add the card to the player's hand
-        let card_type = match person {
-            Person::Punk { .. } => game_state.draw_card()?,
-            Person::NonPunk { person_type, .. } => PersonOrEventType::Person(person_type),
-        };
+        // move the person from the board to the player's hand
+        game_state.player_mut(self.chooser).remove_person_at(person_loc);
         game_state.player_mut(self.chooser).hand.add_one(card_type);
 
         // advance the game state until the next choice
@@ -501,23 +1195,50 @@ choice_struct! {
 }
 
 choice_struct! {
-    /// asks the player whether to move their opponent's events back 1
-    MoveEvents:
-    pub struct MoveEventsChoice => () {}
+    /// asks the player whether to resolve an optional ("may") effect
+    OptionalEffect:
+    pub struct OptionalEffectChoice => bool {
+        /// The styled name of the card whose effect it is.
+        source: (Span<'static>),
+    }
 
-    /// Chooses whether to move the opponent's events back 1, updating the game state
-    /// and returning the next Choice.
-    pub fn choose(&self, game_state, move_events: bool) {
-        if move_events {
-            // move the events back 1
-            game_state
-                .player_mut(self.chooser.other())
-                .move_events_back();
-        }
+    /// Chooses whether to resolve the effect, returning the next Choice. The effect itself is
+    /// resolved by the callback (see `perform_optional_effect`).
+    pub fn choose(&self, game_state, resolve: bool) {
+        (self.then)(game_state, resolve)
+    }
+}
 
-        // advance the game state until the next choice
-        (self.then)(game_state, ())
+/// Resolves an optional ("may") effect of the given card (by its styled name) for the given
+/// player, after asking them whether to. The question is skipped if resolving the effect would
+/// immediately leave the game state unchanged (e.g. moving back events that are already at the
+/// back), since then there's nothing to decline.
+pub fn perform_optional_effect<'g, 'ctype: 'g>(
+    game_state: &'g mut GameState<'ctype>,
+    player: Player,
+    source: Span<'static>,
+    effect: impl for<'g2> Fn(&'g2 mut GameState<'ctype>) -> Result<ChoiceFuture<'g2, 'ctype>, GameResult>
+        + Sync
+        + Send
+        + 'ctype,
+) -> Result<ChoiceFuture<'g, 'ctype>, GameResult> {
+    let mut trial_state = game_state.clone();
+    let resolves_immediately = matches!(effect(&mut trial_state), Ok(future) if future.is_ready());
+    if resolves_immediately && trial_state.fingerprint() == game_state.fingerprint() {
+        return Ok(ChoiceFuture::immediate(game_state));
     }
+
+    Ok(
+        OptionalEffectChoice::future(player, source).then_future_chain(
+            move |game_state, resolve| {
+                if resolve {
+                    effect(game_state)
+                } else {
+                    Ok(ChoiceFuture::immediate(game_state))
+                }
+            },
+        ),
+    )
 }
 
 choice_struct! {
@@ -531,23 +1252,17 @@ choice_struct! {
         /// The columns that can be damaged.
         columns: (Vec<ColumnIndex>),
     }
+    non_empty => columns;
 
     /// Chooses the given column to damage, updating the game state and returning the next Choice.
     pub fn choose(&self, game_state, column: ColumnIndex) {
-        let column_ref = game_state.player(self.chooser.other()).column(column);
-        let target_locs = if self.people_only {
-            // target only the people (if any) in the column
-            column_ref
-                .enumerate_people()
-                .map(|(row, _)| CardLocation::new(column, row.into(), self.chooser.other()))
-                .collect_vec()
-        } else {
-            // target all cards in the column
-            column_ref
-                .card_rows()
-                .map(|row| CardLocation::new(column, row, self.chooser.other()))
-                .collect_vec()
-        };
+        // target the people in the column, plus the camp unless `people_only` is set
+        let target_locs = game_state
+            .player(self.chooser.other())
+            .column(column)
+            .column_damage_rows(self.people_only)
+            .map(|row| CardLocation::new(column, row, self.chooser.other()))
+            .collect_vec();
         game_state.damage_cards_at(target_locs, self.destroy)?;
 
         // advance the game state until the next choice
@@ -562,6 +1277,7 @@ choice_struct! {
         /// The card types from the player's hand that are allowed to be discarded.
         cards: (Vec<PersonOrEventType<'ctype>>),
     }
+    non_empty => cards;
 
     /// Chooses the given card to discard, updating the game state and returning the next Choice.
     pub fn choose(&self, game_state, card: PersonOrEventType<'ctype>) {
@@ -574,9 +1290,248 @@ choice_struct! {
     }
 }
 
+choice_struct! {
+    /// asks the player to discard one of the cards they just drew
+    DiscardDrawn:
+    pub struct DiscardDrawnChoice => PersonOrEventType<'ctype> {
+        /// All the drawn cards (which are now in the player's hand).
+        drawn: (Vec<PersonOrEventType<'ctype>>),
+        /// The unique drawn cards, which are the ones that can be discarded.
+        cards: (Vec<PersonOrEventType<'ctype>>),
+    }
+    non_empty => cards;
+
+    /// Chooses the given drawn card to discard, updating the game state and returning the
+    /// next Choice.
+    pub fn choose(&self, game_state, card: PersonOrEventType<'ctype>) {
+        // discard the card (the other drawn cards stay in the player's hand)
+        game_state.player_mut(self.chooser).hand.remove_one(card);
+        game_state.discard.push(card);
+
+        // advance the game state until the next choice
+        (self.then)(game_state, card)
+    }
+}
+
+choice_struct! {
+    /// asks the player whether to use the junk effect of one of the revealed cards
+    RevealedJunk:
+    pub struct RevealedJunkChoice => () {
+        /// All the revealed cards, in the order they were revealed.
+        revealed: (Vec<PersonOrEventType<'ctype>>),
+        /// The unique revealed cards whose junk effects can be used.
+        usable: (Vec<PersonOrEventType<'ctype>>),
+    }
+
+    /// Chooses the card whose junk effect to use (or None), updating the game state
+    /// and returning the next Choice.
+    pub fn choose(&self, game_state, card: Option<PersonOrEventType<'ctype>>) {
+        if let Some(card) = card {
+            // perform the card's junk effect
+            let view = game_state.view_for_mut(self.chooser);
+            let future = card.junk_effect().perform(view)?;
+            future.then_shared(self.then.clone())
+        } else {
+            // no junk effect was chosen, so just advance the game state until the next choice
+            (self.then)(game_state, ())
+        }
+    }
+}
+
+choice_struct! {
+    /// asks the player whether to mulligan their opening hand
+    Mulligan:
+    pub struct MulliganChoice => bool {}
+
+    /// Chooses whether to mulligan, updating the game state and returning the next Choice.
+    pub fn choose(&self, game_state, mulligan: bool) {
+        if mulligan {
+            // shuffle the hand back into the deck and draw a new one
+            game_state.mulligan(self.chooser);
+        }
+
+        // advance the game state until the next choice
+        (self.then)(game_state, mulligan)
+    }
+}
+
+choice_struct! {
+    /// asks the player which of the camps they were dealt to keep
+    CampSelect:
+    pub struct CampSelectChoice => [&'ctype CampType; 3] {
+        /// The sets of camps that can be kept, each in the order of the columns they go in.
+        camp_sets: (Vec<[&'ctype CampType; 3]>),
+    }
+    non_empty => camp_sets;
+
+    /// Chooses the given camps to keep, returning the next Choice. The camps are put in play by
+    /// the callback, so that neither player sees the other's camps before choosing their own.
+    pub fn choose(&self, game_state, camps: [&'ctype CampType; 3]) {
+        (self.then)(game_state, camps)
+    }
+}
+
+choice_struct! {
+    /// asks the player to advance an event in either player's event queue
+    AdvanceEvent:
+    pub struct AdvanceEventChoice => () {
+        /// The event slots (of either player) whose events can be advanced.
+        events: (Vec<(Player, usize)>),
+    }
+    non_empty => events;
+
+    /// Chooses the given event to advance (resolving it if it leaves the first slot), updating
+    /// the game state and returning the next Choice.
+    pub fn choose(&self, game_state, event_slot: (Player, usize)) {
+        let (player, slot) = event_slot;
+        let future = game_state.advance_event(player, slot)?;
+        future.then_shared(self.then.clone())
+    }
+}
+
+choice_struct! {
+    /// asks the player to choose a person (of either player) to move
+    MovePerson:
+    pub struct MovePersonChoice => CardLocation {
+        /// The locations of the people that can be moved.
+        locations: (Vec<CardLocation>),
+    }
+    non_empty => locations;
+
+    /// Chooses the given person to move, returning the next Choice (where to move it).
+    pub fn choose(&self, game_state, person_loc: CardLocation) {
+        (self.then)(game_state, person_loc)
+    }
+}
+
+choice_struct! {
+    /// asks the player to choose where to move a person
+    MoveDestination:
+    pub struct MoveDestinationChoice => () {
+        /// The location of the person being moved.
+        person_loc: (CardLocation),
+        /// The locations on the person's owner's board that it can be moved to.
+        destinations: (Vec<PlayLocation>),
+    }
+    non_empty => destinations;
+
+    /// Moves the person to the given location, updating the game state and returning the next
+    /// Choice.
+    pub fn choose(&self, game_state, destination: PlayLocation) {
+        // move the person (which doesn't count as playing it, so no effects are triggered)
+        let person_loc = self.person_loc;
+        game_state
+            .player_mut(person_loc.player())
+            .move_person(person_loc.play_loc(), destination);
+
+        // advance the game state until the next choice
+        (self.then)(game_state, ())
+    }
+}
+
+choice_struct! {
+    /// asks the player to choose an ability to copy from another person (for Mimic's trait)
+    CopyAbility:
+    pub struct CopyAbilityChoice => () {
+        /// The location of the person copying the ability.
+        copier_loc: (CardLocation),
+        /// The abilities that can be copied, each with the location of a person it is copied
+        /// from.
+        abilities: (Vec<(CardLocation, &'ctype dyn Ability)>),
+    }
+    non_empty => abilities;
+
+    /// Pays for the given ability and performs it as the copying person's own, updating the game
+    /// state and returning the next Choice.
+    pub fn choose(&self, game_state, copied: (CardLocation, &'ctype dyn Ability)) {
+        let (_, ability) = copied;
+        let view = game_state.view_for_mut(self.chooser);
+        view.game_state.spend_water(ability.cost(&view.as_non_mut()));
+        let future = perform_ability(ability, view, self.copier_loc)?;
+        future.then_shared(self.then.clone())
+    }
+}
+
+choice_struct! {
+    /// asks the player to select several targets, one at a time
+    MultiSelect:
+    pub struct MultiSelectChoice => Vec<SelectTarget<'ctype>> {
+        /// What will be done to the selected targets (e.g. "Restore").
+        verb: (&'static str),
+        /// The fewest targets that must be selected.
+        min: (usize),
+        /// The most targets that can be selected.
+        max: (usize),
+        /// The targets selected so far, in the order they were listed.
+        selected: (Vec<SelectTarget<'ctype>>),
+        /// The targets listed after the last selected one, which are the only ones that can
+        /// still be selected (a card in hand is listed once per copy).
+        remaining: (Vec<SelectTarget<'ctype>>),
+        /// The distinct targets that can be selected next, which are the options after the
+        /// option to finish selecting (if enough targets are selected to finish).
+        options: (Vec<SelectTarget<'ctype>>),
+    }
+    non_empty => options;
+
+    /// Selects the given target (or finishes selecting, if None), returning the next Choice.
+    /// The callback is given the selected targets once the selection is finished, which happens
+    /// as soon as no more targets can be selected.
+    pub fn choose(&self, game_state, target: Option<SelectTarget<'ctype>>) {
+        let target = match target {
+            Some(target) => target,
+            None => return (self.then)(game_state, self.selected.to_vec()),
+        };
+
+        // select the target, leaving only the ones listed after it to select from
+        let index = self
+            .remaining
+            .iter()
+            .position(|&remaining| remaining == target)
+            .expect("Tried to select a target that can't be selected");
+        let mut selected = self.selected.to_vec();
+        selected.push(target);
+        let remaining = &self.remaining[index + 1..];
+        if selected.len() == self.max || remaining.is_empty() {
+            return (self.then)(game_state, selected);
+        }
+
+        // ask again for the next target
+        let options = MultiSelectChoice::selectable(remaining, self.min.saturating_sub(selected.len()));
+        metrics::increment(Counter::Choices);
+        Ok(Choice::MultiSelect(MultiSelectChoice {
+            chooser: self.chooser,
+            verb: self.verb,
+            min: self.min,
+            max: self.max,
+            selected: selected.into(),
+            remaining: remaining.into(),
+            options: options.into(),
+            context: self.context,
+            then: self.then.clone(),
+        }))
+    }
+}
+
+impl DamageColumnChoice<'_> {
+    /// Returns the columns of the chooser's opponent that have at least one card that would be
+    /// hit by damaging the column (only counting people if `people_only` is set).
+    pub fn target_columns(
+        game_state: &GameState,
+        chooser: Player,
+        people_only: bool,
+    ) -> Vec<ColumnIndex> {
+        game_state
+            .player(chooser.other())
+            .enumerate_columns()
+            .filter(|(_, col)| col.column_damage_rows(people_only).next().is_some())
+            .map(|(col_index, _)| col_index)
+            .collect()
+    }
+}
+
 impl DiscardChoice<'_> {
-    /// Creates a new future that asks the player to discard `n` cards, one at a time,
-    /// before resolving.
+    /// Creates a new future that asks the player to discard `n` cards before resolving. More
+    /// than one card is selected with a `MultiSelectChoice`, and then they're all discarded.
     ///
     /// If `cards` is `Some(...)`, the player can only discard out of that subset of their hand.
     ///
@@ -592,26 +1547,267 @@ impl DiscardChoice<'_> {
         assert!(n > 0);
         assert!(cards.count() >= n); // assert that there are enough cards to discard
 
-        let future = DiscardChoice::future(chooser, cards.iter_unique().collect());
         if n == 1 {
-            future.ignore_result() // no further choices to make after this discard
-        } else {
-            // discard the remaining after this discard
-            future.then_future_chain(move |game_state, discarded_card| {
-                // make a copy of the original subset (if given), minus the card that was just discarded
-                let mut subset = subset.clone();
-                if let Some(subset) = &mut subset {
-                    subset.remove_one(discarded_card);
+            return DiscardChoice::future(chooser, cards.iter_unique().collect()).ignore_result();
+        }
+
+        // list each copy of a card separately, so that several copies can be discarded
+        let targets = cards
+            .iter()
+            .flat_map(|(card, count)| iter::repeat(SelectTarget::HandCard(card)).take(count))
+            .collect_vec();
+        MultiSelectChoice::select_future(chooser, "Discard", targets, n, n).then_future(
+            move |game_state, selected| {
+                for target in selected {
+                    let card = match target {
+                        SelectTarget::HandCard(card) => card,
+                        SelectTarget::Card(_) => panic!("Tried to discard a card on the board"),
+                    };
+                    game_state.player_mut(chooser).hand.remove_one(card);
+                    game_state.discard.push(card);
                 }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl DiscardDrawnChoice<'_> {
+    /// Creates a new future that asks the player to discard one of the cards they just drew
+    /// (and which are now in their hand) before resolving with the discarded card.
+    ///
+    /// Panics if no cards were drawn.
+    pub fn from_drawn<'g, 'ctype: 'g>(
+        chooser: Player,
+        drawn: &Cards<PersonOrEventType<'ctype>>,
+    ) -> ChoiceFuture<'g, 'ctype, PersonOrEventType<'ctype>> {
+        assert!(drawn.count() > 0);
+        let all_drawn = drawn
+            .iter()
+            .flat_map(|(card, count)| iter::repeat(card).take(count))
+            .collect();
+        DiscardDrawnChoice::future(chooser, all_drawn, drawn.iter_unique().collect())
+    }
+}
+
+impl<'g, 'ctype: 'g> MultiSelectChoice<'ctype> {
+    /// Creates a new future that asks the player to select between `min` and `max` of the given
+    /// targets before resolving with the selected ones. Targets are selected in the order
+    /// they're listed, so each set of targets can only be selected one way; once at least `min`
+    /// are selected, the first option is to finish selecting.
+    ///
+    /// Panics if `max` is 0, is less than `min`, or is greater than the number of targets.
+    pub fn select_future(
+        chooser: Player,
+        verb: &'static str,
+        targets: Vec<SelectTarget<'ctype>>,
+        min: usize,
+        max: usize,
+    ) -> ChoiceFuture<'g, 'ctype, Vec<SelectTarget<'ctype>>> {
+        assert!(0 < max && min <= max && max <= targets.len());
+        let options = MultiSelectChoice::selectable(&targets, min);
+        MultiSelectChoice::future(chooser, verb, min, max, Vec::new(), targets, options)
+    }
 
-                // "recurse" to discard n-1 cards from the remaining subset
-                Ok(DiscardChoice::discard_n_future(
-                    game_state,
-                    chooser,
-                    subset,
-                    n - 1,
-                ))
+    /// Returns the distinct targets out of `remaining` that can be selected when `num_needed`
+    /// more must be selected, which are the ones listed early enough to be followed by the rest.
+    fn selectable(
+        remaining: &[SelectTarget<'ctype>],
+        num_needed: usize,
+    ) -> Vec<SelectTarget<'ctype>> {
+        remaining
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| remaining.len() - index >= num_needed)
+            .map(|(_, &target)| target)
+            .unique()
+            .collect()
+    }
+
+    /// Returns whether enough targets are selected that the player can finish selecting.
+    pub fn can_finish(&self) -> bool {
+        self.selected.len() >= self.min
+    }
+
+    /// Returns the target that the option with the given index selects, or None if it's the
+    /// option to finish selecting.
+    pub fn option_selection(&self, option: usize) -> Option<SelectTarget<'ctype>> {
+        if self.can_finish() {
+            option.checked_sub(1).map(|i| self.options[i])
+        } else {
+            Some(self.options[option])
+        }
+    }
+}
+
+/// Something that can be selected in a `MultiSelectChoice`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SelectTarget<'ctype> {
+    /// A card on the board.
+    Card(CardLocation),
+
+    /// A card in the chooser's hand.
+    HandCard(PersonOrEventType<'ctype>),
+}
+
+impl SelectTarget<'_> {
+    /// Formats this target for human-readable display, noting whose card it is (from the given
+    /// chooser's perspective) if it's on the board.
+    fn format(&self, chooser: Player, game_state: &GameState) -> Spans<'static> {
+        match *self {
+            SelectTarget::Card(loc) => make_spans!(
+                if loc.player() == chooser { "your " } else { "opponent's " },
+                game_state
+                    .player(loc.player())
+                    .card_name_at(loc.player_loc()),
+                format!(" at {}", loc.player_loc()),
+            ),
+            SelectTarget::HandCard(card) => Spans::from(card.styled_name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+
+    use super::super::locations::Player::{Player1, Player2};
+    use super::super::observed_state::ObservedState;
+    use super::super::sandbox::testing::*;
+    use super::*;
+
+    /// The selections that the multi-select choices in a test have resolved with.
+    type Selections<'ctype> = Arc<Mutex<Vec<Vec<SelectTarget<'ctype>>>>>;
+
+    /// Returns the given multi-select future as a choice whose selection is recorded in
+    /// `selections` once it's finished.
+    fn record_selection<'ctype>(
+        future: ChoiceFuture<'_, 'ctype, Vec<SelectTarget<'ctype>>>,
+        selections: &Selections<'ctype>,
+    ) -> Choice<'ctype> {
+        let selections = selections.clone();
+        future
+            .then(move |game_state, selected| {
+                selections.lock().unwrap().push(selected);
+                Ok(Choice::new_actions(game_state))
             })
+            .unwrap()
+    }
+
+    /// Returns the opponent's (Player 2's) camps, as targets to select.
+    fn camp_targets() -> Vec<SelectTarget<'static>> {
+        (1..=3)
+            .map(|column| SelectTarget::Card(card_loc(Player2, column, 0)))
+            .collect()
+    }
+
+    /// Returns the targets that the choice's options select (None for the option to finish).
+    fn option_selections<'ctype>(choice: &Choice<'ctype>) -> Vec<Option<SelectTarget<'ctype>>> {
+        match choice {
+            Choice::MultiSelect(multi_select_choice) => (0..choice.num_options())
+                .map(|option| multi_select_choice.option_selection(option))
+                .collect(),
+            _ => panic!("expected a multi-select choice"),
         }
     }
+
+    #[test]
+    fn multi_select_offers_done_only_once_the_minimum_is_selected() {
+        let (mut game_state, _) = position(&["turn p1"]);
+        let selections = Selections::default();
+        let targets = camp_targets();
+        let future = MultiSelectChoice::select_future(Player1, "Damage", targets.clone(), 2, 3);
+        let choice = record_selection(future, &selections);
+
+        // the last camp can't be selected first, since 2 must be selected in the listed order
+        assert_eq!(
+            option_selections(&choice),
+            [Some(targets[0]), Some(targets[1])]
+        );
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 1, 0)).unwrap();
+        assert_eq!(
+            option_selections(&choice),
+            [Some(targets[1]), Some(targets[2])]
+        );
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 2, 0)).unwrap();
+        assert_eq!(option_selections(&choice), [None, Some(targets[2])]);
+
+        let choice = choose(&mut game_state, &choice, "Done selecting").unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(*selections.lock().unwrap(), [targets[..2].to_vec()]);
+    }
+
+    #[test]
+    fn multi_select_finishes_when_the_maximum_is_selected() {
+        let (mut game_state, _) = position(&["turn p1"]);
+        let selections = Selections::default();
+        let targets = camp_targets();
+        let future = MultiSelectChoice::select_future(Player1, "Damage", targets.clone(), 0, 2);
+        let choice = record_selection(future, &selections);
+
+        // with no minimum, the player can finish right away
+        assert_eq!(
+            option_selections(&choice),
+            [None, Some(targets[0]), Some(targets[1]), Some(targets[2])]
+        );
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 1, 0)).unwrap();
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 3, 0)).unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(*selections.lock().unwrap(), [vec![targets[0], targets[2]]]);
+    }
+
+    /// Makes every possible sequence of choices from the given choice until it's finished.
+    fn choose_every_path<'ctype>(game_state: &GameState<'ctype>, choice: &Choice<'ctype>) {
+        if matches!(choice, Choice::Action(_)) {
+            return;
+        }
+        for option in 0..choice.num_options() {
+            let mut game_state = game_state.clone();
+            let next_choice = choice.choose(&mut game_state, option).unwrap();
+            choose_every_path(&game_state, &next_choice);
+        }
+    }
+
+    #[test]
+    fn multi_select_reaches_each_set_of_targets_one_way() {
+        let (game_state, _) = position(&["turn p1", "person p2 1 1 punk"]);
+        let selections = Selections::default();
+        let mut targets = camp_targets();
+        targets.push(SelectTarget::Card(card_loc(Player2, 1, 1)));
+        let future = MultiSelectChoice::select_future(Player1, "Damage", targets, 1, 3);
+        let choice = record_selection(future, &selections);
+
+        // every set of 1 to 3 of the 4 targets, each selected exactly once (always in the
+        // listed order)
+        choose_every_path(&game_state, &choice);
+        let selections = selections.lock().unwrap();
+        let distinct = selections.iter().collect::<HashSet<_>>();
+        assert_eq!(selections.len(), 4 + 6 + 4);
+        assert_eq!(distinct.len(), selections.len());
+    }
+
+    #[test]
+    fn the_opponent_observes_only_how_many_targets_are_selected() {
+        let (game_state, _) = position(&["turn p1"]);
+        let selections = Selections::default();
+        let targets = camp_targets();
+        let choice_after = |selected: SelectTarget<'static>| {
+            let future = MultiSelectChoice::future(
+                Player1,
+                "Damage",
+                0,
+                3,
+                vec![selected],
+                vec![targets[2]],
+                vec![targets[2]],
+            );
+            record_selection(future, &selections)
+        };
+        let (first, second) = (choice_after(targets[0]), choice_after(targets[1]));
+
+        let observe = |choice, player| ObservedState::from_game_state(&game_state, choice, player);
+        assert!(observe(&first, Player1) != observe(&second, Player1));
+        assert!(observe(&first, Player2) == observe(&second, Player2));
+    }
 }
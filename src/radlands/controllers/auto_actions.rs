@@ -0,0 +1,223 @@
+use tui::widgets::ListItem;
+
+use crate::radlands::choices::*;
+use crate::radlands::end_turn_warnings::{EndTurnWarning, EndTurnWarnings};
+use crate::radlands::*;
+use crate::ui::{get_user_input, set_controller_stats};
+
+use super::human::read_end_game_request;
+use super::monte_carlo::StatsWidget;
+use super::EndGameRequest;
+
+/// What to do when the only meaningful action left in a turn is to end it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoEndTurn {
+    /// Let the player choose to end the turn, as usual.
+    Manual,
+    /// Ask the player to end the turn, offering to end such turns automatically from then on.
+    Prompt,
+    /// End the turn automatically.
+    Auto,
+}
+
+/// Settings for making trivial choices automatically on behalf of a human player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoActions {
+    /// What to do when ending the turn is the only meaningful action.
+    pub end_turn: AutoEndTurn,
+
+    /// Whether to automatically decline to use an icon effect when none of the offered effects
+    /// can be used (e.g. Mutant's restore when nothing is damaged).
+    pub skip_unusable_icon_effects: bool,
+
+    /// Which warnings the player is asked to confirm before ending the turn (while there's
+    /// still something else to do).
+    pub end_turn_warnings: EndTurnWarnings,
+}
+
+impl AutoActions {
+    /// Settings that never choose anything automatically.
+    pub const NONE: AutoActions = AutoActions {
+        end_turn: AutoEndTurn::Manual,
+        skip_unusable_icon_effects: false,
+        end_turn_warnings: EndTurnWarnings::NONE,
+    };
+}
+
+impl Default for AutoActions {
+    fn default() -> Self {
+        AutoActions {
+            end_turn: AutoEndTurn::Prompt,
+            skip_unusable_icon_effects: true,
+            end_turn_warnings: EndTurnWarnings::ALL,
+        }
+    }
+}
+
+/// A `PlayerController` that makes trivial choices according to its `AutoActions` settings, and
+/// otherwise defers to the wrapped controller (usually a `HumanController`), asking the player
+/// to confirm ending the turn if any of its end-turn warnings apply.
+pub struct AutoActionController<C> {
+    pub inner: C,
+    pub auto_actions: AutoActions,
+}
+
+impl<C> AutoActionController<C> {
+    /// Asks the player to end the turn, and returns the index of the EndTurn option. If
+    /// `can_end_game` is true, the player may ask to end the game instead.
+    fn prompt_end_turn(
+        &mut self,
+        player: Player,
+        end_turn_option: usize,
+        can_end_game: bool,
+    ) -> Result<usize, EndGameRequest> {
+        let lines = vec![
+            ListItem::new("There is nothing left to do this turn but end it."),
+            ListItem::new(format!(
+                "Enter {} to end the turn, or A to end it and automatically end such turns from \
+                 now on.",
+                end_turn_option + 1,
+            )),
+        ];
+        set_controller_stats(Some(Box::new(StatsWidget { lines })), player);
+
+        let result = loop {
+            let input = get_user_input();
+            let input = input.trim();
+            if input.eq_ignore_ascii_case("a") {
+                self.auto_actions.end_turn = AutoEndTurn::Auto;
+                break Ok(end_turn_option);
+            }
+            if input.parse() == Ok(end_turn_option + 1) {
+                break Ok(end_turn_option);
+            }
+            if can_end_game {
+                if let Some(request) = read_end_game_request(input, player) {
+                    break Err(request);
+                }
+            }
+        };
+
+        set_controller_stats(None, player);
+        result
+    }
+
+    /// Has the wrapped controller make the choice (or, if `can_end_game` is true, possibly ask to
+    /// end the game). If it chooses to end the turn while any of the end-turn warnings apply,
+    /// the player is asked to confirm, and otherwise chooses again.
+    fn inner_choose<'ctype>(
+        &mut self,
+        game_view: &GameView<'_, 'ctype>,
+        choice: &Choice<'ctype>,
+        can_end_game: bool,
+    ) -> Result<usize, EndGameRequest>
+    where
+        C: PlayerController<'ctype>,
+    {
+        loop {
+            let option = if can_end_game {
+                self.inner.choose_option_or_end_game(game_view, choice)?
+            } else {
+                self.inner.choose_option(game_view, choice)
+            };
+            let warnings = match choice {
+                Choice::Action(action_choice)
+                    if matches!(action_choice.actions()[option], Action::EndTurn) =>
+                {
+                    self.auto_actions
+                        .end_turn_warnings
+                        .check(game_view, action_choice)
+                }
+                _ => Vec::new(),
+            };
+            if warnings.is_empty() || confirm_end_turn(game_view.player, &warnings) {
+                return Ok(option);
+            }
+        }
+    }
+
+    /// Makes the choice if it's trivial, or returns None to leave it to the player. If the
+    /// player is asked to end the turn and `can_end_game` is true, they may ask to end the game
+    /// instead.
+    fn auto_choose<'ctype>(
+        &mut self,
+        game_view: &GameView<'_, 'ctype>,
+        choice: &Choice<'ctype>,
+        can_end_game: bool,
+    ) -> Option<Result<usize, EndGameRequest>> {
+        match choice {
+            Choice::Action(action_choice) if action_choice.only_end_turn_left() => {
+                match self.auto_actions.end_turn {
+                    AutoEndTurn::Manual => None,
+                    AutoEndTurn::Prompt => {
+                        Some(self.prompt_end_turn(game_view.player, 0, can_end_game))
+                    }
+                    AutoEndTurn::Auto => Some(Ok(0)),
+                }
+            }
+            Choice::IconEffect(icon_effect_choice)
+                if self.auto_actions.skip_unusable_icon_effects =>
+            {
+                let icon_effects = icon_effect_choice.icon_effects();
+                if !icon_effects
+                    .iter()
+                    .any(|effect| effect.can_perform(game_view))
+                {
+                    Some(Ok(0)) // don't use an icon effect
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Shows the warnings in the stats pane, and returns whether the player confirms that they want
+/// to end the turn anyway.
+fn confirm_end_turn(player: Player, warnings: &[EndTurnWarning]) -> bool {
+    let lines = warnings
+        .iter()
+        .map(|warning| ListItem::new(format!("Warning: {}", warning.describe())))
+        .chain([ListItem::new(
+            "Enter Y to end the turn anyway, or anything else to choose again.",
+        )])
+        .collect();
+    set_controller_stats(Some(Box::new(StatsWidget { lines })), player);
+    let confirmed = get_user_input().trim().eq_ignore_ascii_case("y");
+    set_controller_stats(None, player);
+    confirmed
+}
+
+impl<'ctype, C: PlayerController<'ctype>> PlayerController<'ctype> for AutoActionController<C> {
+    fn choose_option<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> usize {
+        match self
+            .auto_choose(game_view, choice, false)
+            .unwrap_or_else(|| self.inner_choose(game_view, choice, false))
+        {
+            Ok(option) => option,
+            Err(_) => unreachable!("the player can't end the game here"),
+        }
+    }
+
+    fn choose_option_or_end_game<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> Result<usize, EndGameRequest> {
+        self.auto_choose(game_view, choice, true)
+            .unwrap_or_else(|| self.inner_choose(game_view, choice, true))
+    }
+
+    fn accepts_tie<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> bool {
+        self.inner.accepts_tie(game_view, choice)
+    }
+}
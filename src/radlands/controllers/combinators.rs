@@ -0,0 +1,314 @@
+//! Controllers that wrap other controllers to change how they behave, e.g. for running
+//! experiments or guarding against buggy controllers.
+
+use std::io::Write;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::{self, Scope};
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::radlands::choices::*;
+use crate::radlands::*;
+
+use super::random::RandomController;
+use super::{show_caption, BestOptionSoFar, ControllerFailure};
+
+/// The name of the threads that `WithTimeLimit` runs controllers on.
+pub const TIME_LIMIT_THREAD_NAME: &str = "controller with time limit";
+
+/// A controller that writes a line to a log for every option that its inner controller chooses.
+pub struct WithLogging<C, W> {
+    inner: C,
+    name: String,
+    log: W,
+}
+
+impl<C, W: Write> WithLogging<C, W> {
+    /// Wraps the controller, logging its choices to `log` under the given name.
+    pub fn new(inner: C, name: impl Into<String>, log: W) -> Self {
+        WithLogging {
+            inner,
+            name: name.into(),
+            log,
+        }
+    }
+}
+
+impl<'ctype, C: PlayerController<'ctype>, W: Write> PlayerController<'ctype> for WithLogging<C, W> {
+    fn choose_option<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> usize {
+        let start_time = Instant::now();
+        let option = self.inner.choose_option(game_view, choice);
+
+        // logging is best-effort, so errors are ignored
+        let _ = writeln!(
+            self.log,
+            "{}: chose option {} of {} in {:.1?}: {}",
+            self.name,
+            option + 1,
+            choice.num_options(),
+            start_time.elapsed(),
+            choice.option_label(option, game_view.game_state),
+        );
+        option
+    }
+}
+
+/// A request for the controller thread of a `WithTimeLimit`: an ID (numbered separately for each
+/// kind of request), what to do, and the game state, choice, and player to do it for.
+type TimeLimitRequest<'ctype> = (u64, RequestKind, GameState<'ctype>, Choice<'ctype>, Player);
+
+/// What the controller thread of a `WithTimeLimit` is asked to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RequestKind {
+    /// Choose an option (see `PlayerController::choose_option`).
+    Choose,
+    /// Decide whether to accept a tie offered by the opponent (see
+    /// `PlayerController::accepts_tie`).
+    AcceptTie,
+}
+
+/// A controller that runs its inner controller on a separate thread, and gives up on a choice if
+/// the inner controller panics or doesn't choose within the time limit. When it gives up, it
+/// chooses a random option (or, if wrapped in a `WithFallback`, lets the fallback choose), except
+/// that if the inner controller ran out of time but has a best option so far (see
+/// `PlayerController::best_option_so_far`), that option is chosen. Running out of time is noted
+/// in the UI's log.
+///
+/// A choice that is given up on keeps running in the background, and is ignored when it
+/// finishes (the controller's thread belongs to a scope, which waits for it to finish). After
+/// the inner controller panics, every later choice fails immediately. A tie offer that isn't
+/// answered within the time limit is declined.
+pub struct WithTimeLimit<'ctype> {
+    time_limit: Duration,
+    requests: mpsc::Sender<TimeLimitRequest<'ctype>>,
+    replies: mpsc::Receiver<(u64, usize)>,
+    tie_replies: mpsc::Receiver<(u64, bool)>,
+    next_request_id: u64,
+    next_tie_request_id: u64,
+
+    /// The inner controller's best option so far, if it keeps track of one.
+    best_option_so_far: Option<BestOptionSoFar>,
+}
+
+impl<'ctype> WithTimeLimit<'ctype> {
+    /// Creates the controller returned by `make_inner` on a new thread in the given scope, and
+    /// gives it the given time limit for each choice. (The controller is created on its thread
+    /// so that it doesn't need to be `Send`.)
+    pub fn new<'scope, C: PlayerController<'ctype>>(
+        scope: &'scope Scope<'scope, '_>,
+        make_inner: impl FnOnce() -> C + Send + 'scope,
+        time_limit: Duration,
+    ) -> Self
+    where
+        'ctype: 'scope,
+    {
+        let (request_tx, request_rx) = mpsc::channel::<TimeLimitRequest>();
+        let (reply_tx, reply_rx) = mpsc::channel();
+        let (tie_reply_tx, tie_reply_rx) = mpsc::channel();
+        let (best_option_tx, best_option_rx) = mpsc::channel();
+        thread::Builder::new()
+            .name(TIME_LIMIT_THREAD_NAME.into())
+            .spawn_scoped(scope, move || {
+                let mut inner = make_inner();
+                let _ = best_option_tx.send(inner.best_option_so_far());
+                for (request_id, kind, game_state, choice, player) in request_rx {
+                    let game_view = game_state.view_for(player);
+                    let sent = match kind {
+                        RequestKind::Choose => {
+                            let option = inner.choose_option(&game_view, &choice);
+                            reply_tx.send((request_id, option)).is_ok()
+                        }
+                        RequestKind::AcceptTie => {
+                            let accepted = inner.accepts_tie(&game_view, &choice);
+                            tie_reply_tx.send((request_id, accepted)).is_ok()
+                        }
+                    };
+                    if !sent {
+                        break; // the WithTimeLimit was dropped
+                    }
+                }
+            })
+            .expect("Failed to spawn controller thread");
+
+        WithTimeLimit {
+            time_limit,
+            requests: request_tx,
+            replies: reply_rx,
+            tie_replies: tie_reply_rx,
+            next_request_id: 0,
+            next_tie_request_id: 0,
+            // if the controller panicked while being created, every choice will fail anyway
+            best_option_so_far: best_option_rx.recv().ok().flatten(),
+        }
+    }
+}
+
+impl<'ctype> PlayerController<'ctype> for WithTimeLimit<'ctype> {
+    fn choose_option<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> usize {
+        self.try_choose_option(game_view, choice)
+            .unwrap_or_else(|_| RandomController::unseeded().choose_option(game_view, choice))
+    }
+
+    fn try_choose_option<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> Result<usize, ControllerFailure> {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        let request = (
+            request_id,
+            RequestKind::Choose,
+            game_view.game_state.clone(),
+            choice.clone(),
+            game_view.player,
+        );
+        if self.requests.send(request).is_err() {
+            return Err(ControllerFailure::Panicked); // the thread is gone
+        }
+
+        let deadline = Instant::now() + self.time_limit;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.replies.recv_timeout(timeout) {
+                Ok((reply_id, option)) if reply_id == request_id => return Ok(option),
+                Ok(_) => {} // a late reply for a choice that was given up on
+                Err(RecvTimeoutError::Timeout) => {
+                    // the inner controller has started one choice for each request so far
+                    let best_option = self
+                        .best_option_so_far
+                        .as_ref()
+                        .and_then(|best| best.get(request_id + 1))
+                        .filter(|&option| option < choice.num_options());
+                    let outcome = match best_option {
+                        Some(_) => "its best option so far was chosen",
+                        None => "a fallback option was chosen",
+                    };
+                    show_caption(format!(
+                        "Player {}'s controller didn't choose within {:.1?}, so {outcome}",
+                        game_view.player.number(),
+                        self.time_limit
+                    ));
+                    return best_option.ok_or(ControllerFailure::TimedOut);
+                }
+                Err(RecvTimeoutError::Disconnected) => return Err(ControllerFailure::Panicked),
+            }
+        }
+    }
+
+    fn accepts_tie<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> bool {
+        let request_id = self.next_tie_request_id;
+        self.next_tie_request_id += 1;
+        let request = (
+            request_id,
+            RequestKind::AcceptTie,
+            game_view.game_state.clone(),
+            choice.clone(),
+            game_view.player,
+        );
+        if self.requests.send(request).is_err() {
+            return false; // the thread is gone
+        }
+
+        let deadline = Instant::now() + self.time_limit;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.tie_replies.recv_timeout(timeout) {
+                Ok((reply_id, accepted)) if reply_id == request_id => return accepted,
+                Ok(_) => {} // a late reply for an offer that was given up on
+                Err(_) => return false,
+            }
+        }
+    }
+}
+
+/// A controller that uses its primary controller, unless the primary fails to choose a valid
+/// option (see `PlayerController::try_choose_option`), in which case the fallback chooses.
+pub struct WithFallback<P, F> {
+    primary: P,
+    fallback: F,
+}
+
+impl<P, F> WithFallback<P, F> {
+    pub fn new(primary: P, fallback: F) -> Self {
+        WithFallback { primary, fallback }
+    }
+}
+
+impl<'ctype, P: PlayerController<'ctype>, F: PlayerController<'ctype>> PlayerController<'ctype>
+    for WithFallback<P, F>
+{
+    fn choose_option<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> usize {
+        match self.primary.try_choose_option(game_view, choice) {
+            Ok(option) if option < choice.num_options() => option,
+            _ => self.fallback.choose_option(game_view, choice),
+        }
+    }
+
+    fn accepts_tie<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> bool {
+        self.primary.accepts_tie(game_view, choice)
+    }
+}
+
+/// A controller that, for each choice, uses controller `a` with probability `p` and controller
+/// `b` otherwise.
+pub struct Mix<A, B> {
+    a: A,
+    b: B,
+    p: f64,
+    rng: StdRng,
+}
+
+impl<A, B> Mix<A, B> {
+    /// Mixes the controllers, picking between them with an RNG seeded with `seed`.
+    pub fn new(a: A, b: B, p: f64, seed: u64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&p),
+            "Mix probability must be in [0, 1]"
+        );
+        Mix {
+            a,
+            b,
+            p,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl<'ctype, A: PlayerController<'ctype>, B: PlayerController<'ctype>> PlayerController<'ctype>
+    for Mix<A, B>
+{
+    fn choose_option<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> usize {
+        if self.rng.gen_bool(self.p) {
+            self.a.choose_option(game_view, choice)
+        } else {
+            self.b.choose_option(game_view, choice)
+        }
+    }
+}
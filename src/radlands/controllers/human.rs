@@ -1,23 +1,130 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tui::widgets::ListItem;
+
 use crate::radlands::choices::*;
+use crate::radlands::mulligan::evaluate_opening_hand;
 use crate::radlands::*;
-use crate::ui::get_user_input;
+use crate::ui::{get_user_input, set_controller_stats};
+
+use super::monte_carlo::StatsWidget;
+use super::EndGameRequest;
+
+/// The number of rollouts per option used to give mulligan advice.
+const MULLIGAN_ADVICE_ROLLOUTS: u32 = 500;
 
 /// A `PlayerController` that allows manual, human input.
 pub struct HumanController;
 
-impl<'ctype> PlayerController<'ctype> for HumanController {
-    fn choose_option<'g>(
+impl HumanController {
+    /// Waits for the player to enter the number of an option or, if `can_end_game` is true, a
+    /// request to end the game (see `read_end_game_request`).
+    fn choose<'ctype>(
         &mut self,
-        game_view: &GameView<'g, 'ctype>,
+        game_view: &GameView<'_, 'ctype>,
         choice: &Choice<'ctype>,
-    ) -> usize {
+        can_end_game: bool,
+    ) -> Result<usize, EndGameRequest> {
+        if let Choice::Mulligan(_) = choice {
+            // show the mulligan advisor's recommendation in the stats pane
+            // (the advice doesn't affect the game, so it needn't be reproducible)
+            let advice = evaluate_opening_hand(
+                game_view.game_state,
+                choice,
+                MULLIGAN_ADVICE_ROLLOUTS,
+                &mut StdRng::from_entropy(),
+            );
+            let recommendation = if advice.keep { "keep" } else { "mulligan" };
+            let lines = [format!("Mulligan advisor recommends: {recommendation}")]
+                .into_iter()
+                .chain(
+                    advice
+                        .reasons
+                        .into_iter()
+                        .map(|reason| format!("  - {reason}")),
+                )
+                .map(ListItem::new)
+                .collect();
+            set_controller_stats(Some(Box::new(StatsWidget { lines })), game_view.player);
+        }
+
         loop {
             let input = get_user_input();
             if let Ok(action_number) = input.parse() {
-                if (1..=choice.num_options(game_view.game_state)).contains(&action_number) {
-                    return action_number - 1;
+                if (1..=choice.num_options()).contains(&action_number) {
+                    return Ok(action_number - 1);
+                }
+            } else if can_end_game {
+                if let Some(request) = read_end_game_request(&input, game_view.player) {
+                    return Err(request);
                 }
             }
         }
     }
 }
+
+/// Returns the request to end the game that the player entered instead of an option, if any:
+/// `resign` (which the player is asked to confirm) or `tie`.
+pub fn read_end_game_request(input: &str, player: Player) -> Option<EndGameRequest> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("tie") {
+        Some(EndGameRequest::OfferTie)
+    } else if input.eq_ignore_ascii_case("resign") {
+        let lines = vec![ListItem::new(
+            "Enter Y to confirm that you resign, or anything else to keep playing.",
+        )];
+        set_controller_stats(Some(Box::new(StatsWidget { lines })), player);
+        let confirmed = get_user_input().trim().eq_ignore_ascii_case("y");
+        set_controller_stats(None, player);
+        confirmed.then_some(EndGameRequest::Resign)
+    } else {
+        None
+    }
+}
+
+impl<'ctype> PlayerController<'ctype> for HumanController {
+    fn choose_option<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> usize {
+        match self.choose(game_view, choice, false) {
+            Ok(option) => option,
+            Err(_) => unreachable!("the player can't end the game here"),
+        }
+    }
+
+    fn choose_option_or_end_game<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> Result<usize, EndGameRequest> {
+        self.choose(game_view, choice, true)
+    }
+
+    fn accepts_tie<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        _choice: &Choice<'ctype>,
+    ) -> bool {
+        let lines = vec![ListItem::new(format!(
+            "Player {} offers a tie. Enter Y to accept it, or N to keep playing.",
+            game_view.player.other().number()
+        ))];
+        set_controller_stats(Some(Box::new(StatsWidget { lines })), game_view.player);
+
+        let accepted = loop {
+            let input = get_user_input();
+            let input = input.trim();
+            if input.eq_ignore_ascii_case("y") {
+                break true;
+            }
+            if input.eq_ignore_ascii_case("n") {
+                break false;
+            }
+        };
+
+        set_controller_stats(None, game_view.player);
+        accepted
+    }
+}
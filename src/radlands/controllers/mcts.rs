@@ -1,11 +1,16 @@
-use rand::thread_rng;
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::borrow::Cow;
+use std::cmp::Reverse;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
-use std::fmt;
-use std::time::{Duration, Instant};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Write};
+use std::time::Instant;
 use tui::widgets::ListItem;
 
+use crate::autosave::{self, SavedGame};
+use crate::golden::parse_fields;
 use crate::radlands::choices::*;
 use crate::radlands::controllers::monte_carlo::format_stats_prefix;
 use crate::radlands::observed_state::ObservedState;
@@ -13,9 +18,14 @@ use crate::radlands::*;
 use crate::ui;
 
 use super::monte_carlo::{
-    compute_rollout_score, format_option_stats, get_best_options, get_score, randomize_unobserved,
-    OptionStats, StatsWidget,
+    ai_accepts_tie, compute_world_rollout_score, format_option_stats, get_best_options, get_score,
+    randomize_unobserved, randomize_unobserved_with, visit_gap, OptionStats, StatsWidget,
 };
+use super::search_tree::{
+    dump_search_state, dump_tree, tree_dump_settings, SearchTreeNode, SearchTreeOption,
+    TreeDumpSettings,
+};
+use super::{BestOptionSoFar, SearchBudget};
 
 #[derive(Debug, Clone)]
 struct StateStats {
@@ -39,25 +49,102 @@ impl StateStats {
             last_visit_ply: current_ply,
         }
     }
+
+    /// Returns the option that the selection phase of the search picks at this state: the one
+    /// with the highest PUCT score (the last of them, if several are tied).
+    fn select_option(&self) -> usize {
+        let (option_index, _) = self
+            .options
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, option_stats)| option_stats.puct_score(self.num_rollouts))
+            .unwrap();
+        option_index
+    }
+
+    /// Returns these stats as the fields of a line of a search state dump, after the state's
+    /// key.
+    fn to_dump_fields(&self) -> String {
+        format!(
+            "rollouts={} last_visit={} options={}",
+            self.num_rollouts,
+            self.last_visit_ply,
+            self.options
+                .iter()
+                .map(|option| format!("{}:{}", option.num_rollouts, option.total_score))
+                .join(","),
+        )
+    }
+
+    /// Parses the stats from the fields written by `to_dump_fields`.
+    fn from_dump_fields(rollouts: &str, last_visit: &str, options: &str) -> Result<Self, String> {
+        let options = options
+            .split(',')
+            .map(|option| {
+                let (num_rollouts, total_score) = option
+                    .split_once(':')
+                    .ok_or_else(|| format!("invalid option stats: {option:?}"))?;
+                Ok(OptionStats {
+                    num_rollouts: num_rollouts
+                        .parse()
+                        .map_err(|_| format!("invalid option rollouts: {num_rollouts:?}"))?,
+                    total_score: total_score
+                        .parse()
+                        .map_err(|_| format!("invalid option score: {total_score:?}"))?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(StateStats {
+            options,
+            num_rollouts: rollouts
+                .parse()
+                .map_err(|_| format!("invalid rollouts: {rollouts:?}"))?,
+            last_visit_ply: last_visit
+                .parse()
+                .map_err(|_| format!("invalid last visit: {last_visit:?}"))?,
+        })
+    }
 }
 
+/// How many samples the MCTS AI takes between checks of whether it can stop early.
+const EARLY_STOP_CHECK_INTERVAL: usize = 16;
+
 pub struct MCTSController<'ctype, F> {
     pub player: Player,
-    pub choice_time_limit: Duration,
+    pub budget: SearchBudget,
+    /// Whether to stop before the budget is spent once the decision can't change (true by
+    /// default).
+    pub early_stop: bool,
     pub make_rollout_controller: F,
 
+    /// The RNG for the randomized worlds, the rollouts, and breaking ties between the best
+    /// options (separate from the game's RNG, so that a seeded AI is reproducible).
+    rng: StdRng,
     explored_states: HashMap<ObservedState<'ctype>, StateStats>,
     current_ply: u32,
+    best_option_so_far: BestOptionSoFar,
 }
 
-impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSController<'ctype, F> {
-    pub fn new(player: Player, choice_time_limit: Duration, make_rollout_controller: F) -> Self {
+impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player, &mut StdRng) -> C>
+    MCTSController<'ctype, F>
+{
+    /// Creates an MCTS AI for the given player, which searches within the given budget for each
+    /// choice. Its RNG is seeded with `seed`.
+    pub fn new(
+        player: Player,
+        budget: SearchBudget,
+        make_rollout_controller: F,
+        seed: u64,
+    ) -> Self {
         Self {
             player,
-            choice_time_limit,
+            budget,
+            early_stop: true,
             make_rollout_controller,
+            rng: StdRng::seed_from_u64(seed),
             explored_states: HashMap::new(),
             current_ply: 0,
+            best_option_so_far: BestOptionSoFar::default(),
         }
     }
 
@@ -69,8 +156,14 @@ impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSContro
         let game_state = game_view.game_state;
         let chooser = choice.chooser(game_state);
         let observed_state = ObservedState::from_game_state(game_state, choice, chooser);
+        self.option_stats_at(&observed_state)
+    }
+
+    /// Like `get_root_option_stats`, but for a root state that's already been observed (which is
+    /// much cheaper than observing it again).
+    fn option_stats_at(&self, root_state: &ObservedState<'ctype>) -> (u32, &[OptionStats]) {
         self.explored_states
-            .get(&observed_state)
+            .get(root_state)
             .map(|stats| (stats.num_rollouts, stats.options.as_slice()))
             .expect("root state not explored")
     }
@@ -79,15 +172,23 @@ impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSContro
         &self,
         game_view: &GameView<'g, 'ctype>,
         choice: &Choice<'ctype>,
-        num_samples: i32,
+        num_samples: usize,
         start_time: Instant,
+        footer: Option<String>,
     ) {
         let mut lines;
         let title;
         if ui::get_debug_counter() % 2 == 0 {
             title = "Options at current choice root:";
             let (rollouts, option_stats) = self.get_root_option_stats(game_view, choice);
-            lines = format_option_stats(option_stats, rollouts as usize, game_view, choice);
+            let option_groups = OptionGroup::singletons(option_stats.len());
+            lines = format_option_stats(
+                option_stats,
+                &option_groups,
+                rollouts as usize,
+                game_view,
+                choice,
+            );
         } else {
             title = "Most visited sequence:";
             lines = self.format_predicted_sequence(game_view, choice);
@@ -106,6 +207,9 @@ impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSContro
             "--------  ----------  -------   ------".into(),
         ];
         lines.splice(0..0, top_lines.into_iter().map(ListItem::new));
+        if let Some(footer) = footer {
+            lines.extend([ListItem::new(" "), ListItem::new(footer)]);
+        }
 
         ui::set_controller_stats(Some(Box::new(StatsWidget { lines })), game_view.player);
     }
@@ -115,7 +219,7 @@ impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSContro
         game_view: &GameView<'g, 'ctype>,
         choice: &Choice<'ctype>,
     ) -> Vec<ListItem<'static>> {
-        let mut game_state = randomize_unobserved(game_view.game_state);
+        let mut game_state = randomize_unobserved(game_view);
         let mut choice = Cow::Borrowed(choice);
 
         // collect most likely move sequence
@@ -123,7 +227,7 @@ impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSContro
         let mut root_count = None;
         loop {
             // immediately continue to the next move if there's only one option
-            let num_options = choice.num_options(&game_state);
+            let num_options = choice.num_options();
 
             let (option_index, stats) = if num_options == 1 {
                 (0, None)
@@ -182,6 +286,272 @@ impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSContro
         lines
     }
 
+    /// Returns the explored part of the search tree below the given choice, or None if it hasn't
+    /// been explored. Since the tree is keyed by what the choosing player can observe, it is
+    /// traversed through one randomization of the hidden information; choices with only one
+    /// option are skipped over, as in the search. The tree is limited to `max_depth` levels
+    /// below the root and `max_nodes` nodes, expanding more-visited options first.
+    pub fn search_tree(
+        &self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+        max_depth: usize,
+        max_nodes: usize,
+    ) -> Option<SearchTreeNode> {
+        let game_state = randomize_unobserved(game_view);
+        let mut num_nodes = 0;
+        self.search_tree_node(&game_state, choice, max_depth, max_nodes, &mut num_nodes)
+    }
+
+    fn search_tree_node(
+        &self,
+        game_state: &GameState<'ctype>,
+        choice: &Choice<'ctype>,
+        max_depth: usize,
+        max_nodes: usize,
+        num_nodes: &mut usize,
+    ) -> Option<SearchTreeNode> {
+        if *num_nodes >= max_nodes {
+            return None;
+        }
+        let chooser = choice.chooser(game_state);
+        let observed_state = ObservedState::from_game_state(game_state, choice, chooser);
+        let stats = self.explored_states.get(&observed_state)?;
+        *num_nodes += 1;
+
+        let mut options = stats
+            .options
+            .iter()
+            .enumerate()
+            .map(|(option_index, option_stats)| SearchTreeOption {
+                label: choice.option_label(option_index, game_state),
+                num_rollouts: option_stats.num_rollouts,
+                win_rate: *option_stats.win_rate(),
+                child: None,
+            })
+            .collect_vec();
+
+        if max_depth > 0 {
+            let visited_options = (0..options.len())
+                .filter(|&i| options[i].num_rollouts > 0)
+                .sorted_by_key(|&i| Reverse(options[i].num_rollouts))
+                .collect_vec();
+            for option_index in visited_options {
+                // get the next choice, skipping any that only have one option
+                let mut child_state = game_state.clone();
+                let mut next_choice = choice.choose(&mut child_state, option_index);
+                loop {
+                    match &next_choice {
+                        Ok(forced_choice) if forced_choice.num_options() == 1 => {
+                            let result = forced_choice.choose(&mut child_state, 0);
+                            next_choice = result;
+                        }
+                        _ => break,
+                    }
+                }
+
+                if let Ok(next_choice) = next_choice {
+                    options[option_index].child = self.search_tree_node(
+                        &child_state,
+                        &next_choice,
+                        max_depth - 1,
+                        max_nodes,
+                        num_nodes,
+                    );
+                }
+            }
+        }
+
+        Some(SearchTreeNode {
+            chooser,
+            num_rollouts: stats.num_rollouts,
+            options,
+        })
+    }
+
+    /// Dumps the search tree for the current decision, according to the given settings, along
+    /// with the search state if the game is autosaved (which lets the decision be replayed).
+    fn dump_search_tree(
+        &self,
+        settings: &TreeDumpSettings,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) {
+        let tree = self
+            .search_tree(game_view, choice, settings.max_depth, settings.max_nodes)
+            .expect("root state not explored");
+        let name = format!("mcts-p{}-{:04}", self.player.number(), self.current_ply);
+        dump_tree(settings, &name, &tree);
+        if let Some(game) = autosave::current_game() {
+            dump_search_state(settings, &name, &self.state_dump(&game));
+        }
+    }
+
+    /// Returns the search state as a search state dump (see `search_trace`) of the given game,
+    /// which must be the game as it was when the current decision was reached.
+    pub fn state_dump(&self, game: &SavedGame) -> String {
+        let mut dump = format!(
+            "game {game}\nplayer={}\nply={}\n",
+            self.player.number(),
+            self.current_ply
+        );
+        let explored_states = self
+            .explored_states
+            .iter()
+            .sorted_by_key(|(observed_state, _)| observed_state.key());
+        for (observed_state, stats) in explored_states {
+            writeln!(
+                dump,
+                "key={:016x} {}",
+                observed_state.key(),
+                stats.to_dump_fields()
+            )
+            .unwrap();
+        }
+        dump
+    }
+
+    /// Loads the search state from the lines of a search state dump after its game and player
+    /// lines, replacing the explored states.
+    pub fn load_state_dump<'a>(
+        &mut self,
+        mut lines: impl Iterator<Item = &'a str>,
+    ) -> Result<(), String> {
+        let [ply] = parse_fields(lines.next().unwrap_or_default(), ["ply"])?;
+        self.current_ply = ply.parse().map_err(|_| format!("invalid ply: {ply:?}"))?;
+
+        self.explored_states.clear();
+        for line in lines.filter(|line| !line.trim().is_empty()) {
+            let [key, rollouts, last_visit, options] =
+                parse_fields(line, ["key", "rollouts", "last_visit", "options"])?;
+            let key = u64::from_str_radix(key, 16).map_err(|_| format!("invalid key: {key:?}"))?;
+            let stats = StateStats::from_dump_fields(rollouts, last_visit, options)?;
+            self.explored_states
+                .insert(ObservedState::from_key(key), stats);
+        }
+        Ok(())
+    }
+
+    /// Runs one iteration of the search from the given choice, like `sample_move`, but
+    /// deterministically and with tracing. The hidden information is randomized with the given
+    /// RNG, which also seeds the rollout controllers (see `make_rollout_controller`).
+    /// At each explored state that the selection phase passes through, every option's PUCT
+    /// score and its parts are written to `trace`.
+    pub fn trace_sample(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+        rng: &mut StdRng,
+        trace: &mut String,
+    ) {
+        let mut game_state = randomize_unobserved_with(game_view, rng);
+        let mut choice = Cow::Borrowed(choice);
+
+        // select options down the tree until reaching an unexplored state or the end of the game
+        let mut path = Vec::new();
+        let rollout_score = loop {
+            let num_options = choice.num_options();
+            let option_index = if num_options == 1 {
+                0
+            } else {
+                let chooser = choice.chooser(&game_state);
+                let observed_state = ObservedState::from_game_state(&game_state, &choice, chooser);
+                if path.iter().any(|(state, _, _)| *state == observed_state) {
+                    // the moves since this state formed a cycle (see `sample_move`)
+                    let option_index = rng.gen_range(0..num_options);
+                    writeln!(
+                        trace,
+                        "{chooser:?} chooses at a state already on this path (state {:016x}); \
+                         rolling out from: {}",
+                        observed_state.key(),
+                        choice.option_label(option_index, &game_state),
+                    )
+                    .unwrap();
+                    break self.trace_rollout(&game_state, &choice, option_index, rng);
+                }
+                let option_index = match self.explored_states.get_mut(&observed_state) {
+                    Some(state_stats) => {
+                        state_stats.last_visit_ply = self.current_ply;
+                        let option_index = state_stats.select_option();
+                        writeln!(
+                            trace,
+                            "{chooser:?} chooses (state {:016x}, {} rollouts):",
+                            observed_state.key(),
+                            state_stats.num_rollouts
+                        )
+                        .unwrap();
+                        writeln!(trace, "     Visits   Win %   Explore      PUCT   Option")
+                            .unwrap();
+                        for (i, option_stats) in state_stats.options.iter().enumerate() {
+                            let win_rate = *option_stats.win_rate();
+                            let puct_score = *option_stats.puct_score(state_stats.num_rollouts);
+                            writeln!(
+                                trace,
+                                "  {} {:>7}  {:>5.1}%  {:>8.4}  {:>8.4}   {}",
+                                if i == option_index { '>' } else { ' ' },
+                                option_stats.num_rollouts,
+                                win_rate * 100.0,
+                                puct_score - win_rate,
+                                puct_score,
+                                choice.option_label(i, &game_state),
+                            )
+                            .unwrap();
+                        }
+                        option_index
+                    }
+                    None => {
+                        // expand the state, and start the rollout from a random option
+                        self.explored_states.insert(
+                            observed_state.clone(),
+                            StateStats::new(num_options, self.current_ply),
+                        );
+                        let option_index = rng.gen_range(0..num_options);
+                        writeln!(
+                            trace,
+                            "{chooser:?} chooses at a new state (state {:016x}); rolling out from: {}",
+                            observed_state.key(),
+                            choice.option_label(option_index, &game_state),
+                        )
+                        .unwrap();
+                        path.push((observed_state, chooser, option_index));
+                        break self.trace_rollout(&game_state, &choice, option_index, rng);
+                    }
+                };
+                path.push((observed_state, chooser, option_index));
+                option_index
+            };
+
+            match choice.choose(&mut game_state, option_index) {
+                Err(game_result) => break get_score(game_result, Player::Player1),
+                Ok(next_choice) => choice = Cow::Owned(next_choice),
+            }
+        };
+
+        writeln!(trace, "Rollout score for Player 1: {rollout_score} of 2").unwrap();
+        for (observed_state, chooser, option_index) in path {
+            self.record_rollout(&observed_state, chooser, option_index, rollout_score);
+        }
+    }
+
+    /// Plays out a rollout for `trace_sample` from the given option, with the same rollout
+    /// controllers as the search. Returns the score for Player 1.
+    fn trace_rollout(
+        &self,
+        game_state: &GameState<'ctype>,
+        choice: &Choice<'ctype>,
+        option_index: usize,
+        rng: &mut StdRng,
+    ) -> u32 {
+        compute_world_rollout_score(
+            Player::Player1,
+            game_state.clone(),
+            choice,
+            &self.make_rollout_controller,
+            option_index,
+            rng,
+        )
+    }
+
     fn prune_explored_states(&mut self) {
         const PAST_PLIES_TO_KEEP: u32 = 5;
         if self.current_ply > PAST_PLIES_TO_KEEP {
@@ -197,8 +567,10 @@ impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSContro
         game_view: &GameView<'g, 'ctype>,
         choice: &Choice<'ctype>,
     ) -> usize {
+        self.best_option_so_far.start_choice();
+
         // return immediately without searching if there's only one option
-        let num_options = choice.num_options(game_view.game_state);
+        let num_options = choice.num_options();
         if num_options == 1 {
             return 0;
         }
@@ -208,43 +580,78 @@ impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSContro
         self.current_ply += 1;
         self.prune_explored_states();
 
+        // the root state is observed once, rather than for every check of its stats
+        let root_state = ObservedState::from_game_state(
+            game_view.game_state,
+            choice,
+            choice.chooser(game_view.game_state),
+        );
+
+        let stats_interval = ui::stats_interval();
         let mut last_print_time = start_time;
         let mut num_samples = 0;
-        while start_time.elapsed() < self.choice_time_limit {
+        let mut early_stop = None;
+        while !self.budget.is_spent(start_time, num_samples) {
+            // stop once the most-visited option can't be overtaken (checked every few samples,
+            // since a few more samples can't change the decision)
+            if self.early_stop
+                && num_samples > 0
+                && num_samples % EARLY_STOP_CHECK_INTERVAL == 0
+                && self.budget.can_stop_early(
+                    start_time,
+                    num_samples,
+                    visit_gap(self.option_stats_at(&root_state).1),
+                )
+            {
+                early_stop = Some(self.budget.describe_early_stop(start_time, num_samples));
+                break;
+            }
+
             // sample a sequence of moves and update the tree
-            let mut game_state = randomize_unobserved(game_view.game_state);
-            self.sample_move(&mut game_state, choice);
+            let mut game_state = randomize_unobserved_with(game_view, &mut self.rng);
+            self.sample_move(&mut game_state, choice, &mut HashSet::new());
             num_samples += 1;
 
             // update the live stats display
             let now = Instant::now();
             let elapsed = now.duration_since(last_print_time);
-            if elapsed > Duration::from_millis(100) {
-                self.show_stats(game_view, choice, num_samples, start_time);
+            if elapsed > stats_interval {
+                self.show_stats(game_view, choice, num_samples, start_time, None);
                 last_print_time = now;
+
+                // in case the search is cut off before it ends
+                let root_options = self.option_stats_at(&root_state).1;
+                if let Some(&best_option) = get_best_options(root_options).first() {
+                    self.best_option_so_far.set(best_option);
+                }
             }
         }
-        self.show_stats(game_view, choice, num_samples, start_time);
+        self.show_stats(game_view, choice, num_samples, start_time, early_stop);
+        if let Some(settings) = tree_dump_settings() {
+            self.dump_search_tree(&settings, game_view, choice);
+        }
 
         // return a random best (maximum visit count) choice
-        *get_best_options(self.get_root_option_stats(game_view, choice).1)
-            .choose(&mut thread_rng())
+        *get_best_options(self.option_stats_at(&root_state).1)
+            .choose(&mut self.rng)
             .unwrap()
     }
 
     /// Samples a move that a player might make from a state, updating the search tree.
+    /// `path` holds the states that this sample has already passed through.
     /// Returns a tuple of (chosen option index, rollout score for Player 1).
     fn sample_move(
         &mut self,
         game_state: &mut GameState<'ctype>,
         choice: &Choice<'ctype>,
+        path: &mut HashSet<ObservedState<'ctype>>,
     ) -> (usize, u32) {
         // immediately continue to the next move if there's only one option
-        let num_options = choice.num_options(game_state);
+        let num_options = choice.num_options();
         if num_options == 1 {
             let score = match choice.choose(game_state, 0) {
                 Err(game_result) => get_score(game_result, Player::Player1),
-                Ok(next_choice) => self.sample_move(game_state, &next_choice).1,
+                Ok(next_choice) => self.sample_move(game_state, &next_choice, path).1,
             };
             return (0, score);
         }
@@ -255,6 +662,23 @@ impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSContro
         // get the observed state of the game (hash table key)
         let observed_state = ObservedState::from_game_state(game_state, choice, chooser);
 
+        // if this sample has already passed through this state, the moves since then formed a
+        // cycle that costs nothing (e.g. replaying Rescue Team for free into an Oasis column after
+        // it rescues itself), which the selection phase could follow forever; so roll out from
+        // here instead (leaving the stats to the earlier visit)
+        if !path.insert(observed_state.clone()) {
+            let first_move = self.rng.gen_range(0..num_options);
+            let final_score = compute_world_rollout_score(
+                Player::Player1,
+                game_state.clone(),
+                choice,
+                &self.make_rollout_controller,
+                first_move,
+                &mut self.rng,
+            );
+            return (first_move, final_score);
+        }
+
         // sample an option and the score for Player 1
         let (option_index, rollout_score) = match self.explored_states.entry(observed_state.clone())
         {
@@ -263,15 +687,17 @@ impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSContro
                 entry.insert(StateStats::new(num_options, self.current_ply));
 
                 // at leaf nodes, start by sampling a random option
-                let first_move = thread_rng().gen_range(0..num_options);
+                let first_move = self.rng.gen_range(0..num_options);
 
-                // perform a rollout from this state
-                let final_score = compute_rollout_score(
+                // perform a rollout from this state (which is already a randomized world,
+                // from the root chooser's view)
+                let final_score = compute_world_rollout_score(
                     Player::Player1,
-                    game_state,
+                    game_state.clone(),
                     choice,
                     &self.make_rollout_controller,
                     first_move,
+                    &mut self.rng,
                 );
 
                 (first_move, final_score)
@@ -282,19 +708,12 @@ impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSContro
                 state_stats.last_visit_ply = self.current_ply;
 
                 // choose an option based on the current stats
-                let (option_index, _) = state_stats
-                    .options
-                    .iter()
-                    .enumerate()
-                    .max_by_key(|(_, option_stats)| {
-                        option_stats.puct_score(state_stats.num_rollouts)
-                    })
-                    .unwrap();
+                let option_index = state_stats.select_option();
 
                 // get the next state and recurse (or return the result if the game ended)
                 let score = match choice.choose(game_state, option_index) {
                     Err(game_result) => get_score(game_result, Player::Player1),
-                    Ok(next_choice) => self.sample_move(game_state, &next_choice).1,
+                    Ok(next_choice) => self.sample_move(game_state, &next_choice, path).1,
                 };
 
                 (option_index, score)
@@ -302,7 +721,22 @@ impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSContro
         };
 
         // update the stats for this option
-        let state_stats = self.explored_states.get_mut(&observed_state).unwrap();
+        self.record_rollout(&observed_state, chooser, option_index, rollout_score);
+
+        // return the chosen option index and rollout score
+        (option_index, rollout_score)
+    }
+
+    /// Records a rollout that chose the given option at an explored state, with the given score
+    /// for Player 1.
+    fn record_rollout(
+        &mut self,
+        observed_state: &ObservedState<'ctype>,
+        chooser: Player,
+        option_index: usize,
+        rollout_score: u32,
+    ) {
+        let state_stats = self.explored_states.get_mut(observed_state).unwrap();
         state_stats.num_rollouts += 1;
         let option_stats = &mut state_stats.options[option_index];
         option_stats.num_rollouts += 1;
@@ -310,13 +744,10 @@ impl<'g, 'ctype: 'g, C: PlayerController<'ctype>, F: Fn(Player) -> C> MCTSContro
             Player::Player1 => rollout_score,
             Player::Player2 => 2 - rollout_score,
         };
-
-        // return the chosen option index and rollout score
-        (option_index, rollout_score)
     }
 }
 
-impl<'ctype, C: PlayerController<'ctype>, F: Fn(Player) -> C> PlayerController<'ctype>
+impl<'ctype, C: PlayerController<'ctype>, F: Fn(Player, &mut StdRng) -> C> PlayerController<'ctype>
     for MCTSController<'ctype, F>
 {
     fn choose_option<'g>(
@@ -326,6 +757,24 @@ impl<'ctype, C: PlayerController<'ctype>, F: Fn(Player) -> C> PlayerController<'
     ) -> usize {
         self.mcts_choose_impl(game_view, choice)
     }
+
+    fn best_option_so_far(&self) -> Option<BestOptionSoFar> {
+        Some(self.best_option_so_far.clone())
+    }
+
+    fn accepts_tie<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> bool {
+        ai_accepts_tie(
+            self.player,
+            game_view.game_state,
+            choice,
+            &self.make_rollout_controller,
+            &mut self.rng,
+        )
+    }
 }
 
 impl<F> fmt::Debug for MCTSController<'_, F> {
@@ -333,3 +782,72 @@ impl<F> fmt::Debug for MCTSController<'_, F> {
         write!(f, "MCTSController[{:?}]", self.player)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::locations::Player::Player1;
+    use super::super::super::rules::GameRules;
+    use super::super::super::sandbox::testing::*;
+    use super::super::random::RandomController;
+    use super::super::ControllerSpec;
+    use super::*;
+
+    /// Returns an MCTS AI for Player 1 with random rollouts, seeded with 0.
+    fn mcts_controller<'ctype>(
+        budget: SearchBudget,
+    ) -> MCTSController<'ctype, impl Fn(Player, &mut StdRng) -> RandomController> {
+        MCTSController::new(
+            Player1,
+            budget,
+            |_, rng: &mut StdRng| RandomController::from_rng(rng),
+            0,
+        )
+    }
+
+    /// A position where Rescue Team can rescue itself and be replayed for free into the empty
+    /// Oasis column, over and over.
+    const FREE_CYCLE: &[&str] = &[
+        "turn p1",
+        "water 0",
+        "camp p1 1 Oasis",
+        "person p1 1 1 Rescue Team",
+    ];
+
+    #[test]
+    fn a_sample_that_revisits_a_state_rolls_out_from_it() {
+        let (mut game_state, choice) = position(FREE_CYCLE);
+        let mut controller = mcts_controller(SearchBudget::Rollouts(1));
+
+        // as if the sample had already passed through this state
+        let observed_state = ObservedState::from_game_state(&game_state, &choice, Player1);
+        let mut path = HashSet::from([observed_state]);
+        let (option, _) = controller.sample_move(&mut game_state, &choice, &mut path);
+        assert!(option < choice.num_options());
+        assert!(controller.explored_states.is_empty());
+    }
+
+    #[test]
+    fn a_search_state_dump_reloads_to_the_same_search_state() {
+        let (game_state, choice) = position(&["turn p1", "water 3", "person p1 1 1 Looter"]);
+        let mut controller = mcts_controller(SearchBudget::Rollouts(200));
+        controller.choose_option(&game_state.view_for(Player1), &choice);
+        assert!(!controller.explored_states.is_empty());
+
+        let spec = ControllerSpec::Mcts {
+            budget: SearchBudget::Rollouts(200),
+            early_stop: true,
+        };
+        let game = SavedGame::new_game(GameRules::casual(false), spec, spec);
+        let dump = controller.state_dump(&game);
+
+        // skip the game and player lines, as `search_trace` does
+        let mut reloaded = mcts_controller(SearchBudget::Rollouts(200));
+        reloaded.load_state_dump(dump.lines().skip(2)).unwrap();
+        assert_eq!(reloaded.current_ply, controller.current_ply);
+        assert_eq!(
+            reloaded.explored_states.len(),
+            controller.explored_states.len()
+        );
+        assert_eq!(reloaded.state_dump(&game), dump);
+    }
+}
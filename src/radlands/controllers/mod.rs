@@ -1,10 +1,35 @@
+pub mod auto_actions;
+pub mod combinators;
 pub mod human;
 pub mod mcts;
 pub mod monte_carlo;
 pub mod random;
+pub mod remote;
+pub mod scripted;
+pub mod search_tree;
+pub mod template;
 
+use std::io;
+use std::net::SocketAddr;
+use std::panic::{self, AssertUnwindSafe};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread::Scope;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use tui::{buffer::Buffer, layout::Rect};
 
+use self::auto_actions::{AutoActionController, AutoActions, AutoEndTurn};
+use self::combinators::{Mix, WithFallback, WithLogging, WithTimeLimit};
+use self::human::HumanController;
+use self::mcts::MCTSController;
+use self::monte_carlo::{Determinization, MonteCarloController, WorldAggregation};
+use self::random::RandomController;
+use self::remote::RemoteHumanController;
+use self::template::TemplateController;
+use super::end_turn_warnings::EndTurnWarnings;
 use super::*;
 
 /// Trait for a player controller / agent.
@@ -16,8 +41,704 @@ pub trait PlayerController<'ctype> {
         game_view: &GameView<'g, 'ctype>,
         choice: &Choice<'ctype>,
     ) -> usize;
+
+    /// Like `choose_option`, but returns an error instead of panicking. Controllers that can
+    /// fail in other ways (e.g. by running out of time) report those failures here too.
+    fn try_choose_option<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> Result<usize, ControllerFailure> {
+        panic::catch_unwind(AssertUnwindSafe(|| self.choose_option(game_view, choice)))
+            .map_err(|_| ControllerFailure::Panicked)
+    }
+
+    /// Returns a handle to the option that this controller currently thinks is best, if it
+    /// keeps track of one while choosing. Controllers that search until a deadline should, so
+    /// that if one is cut off for taking too long (see `WithTimeLimit`), its best option so far
+    /// can be used instead of a random one.
+    fn best_option_so_far(&self) -> Option<BestOptionSoFar> {
+        None
+    }
+
+    /// Like `choose_option`, but the controller's player may ask to end the game early instead
+    /// of choosing. Only controllers for human players need to implement this; by default, the
+    /// controller just chooses.
+    fn choose_option_or_end_game<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> Result<usize, EndGameRequest> {
+        Ok(self.choose_option(game_view, choice))
+    }
+
+    /// Returns whether the controller's player accepts their opponent's offer to end the game
+    /// in a tie, at the given choice (which the opponent is making). Offers are declined by
+    /// default.
+    fn accepts_tie<'g>(
+        &mut self,
+        _game_view: &GameView<'g, 'ctype>,
+        _choice: &Choice<'ctype>,
+    ) -> bool {
+        false
+    }
+}
+
+impl<'ctype, C: PlayerController<'ctype> + ?Sized> PlayerController<'ctype> for Box<C> {
+    fn choose_option<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> usize {
+        (**self).choose_option(game_view, choice)
+    }
+
+    fn try_choose_option<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> Result<usize, ControllerFailure> {
+        (**self).try_choose_option(game_view, choice)
+    }
+
+    fn best_option_so_far(&self) -> Option<BestOptionSoFar> {
+        (**self).best_option_so_far()
+    }
+
+    fn choose_option_or_end_game<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> Result<usize, EndGameRequest> {
+        (**self).choose_option_or_end_game(game_view, choice)
+    }
+
+    fn accepts_tie<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> bool {
+        (**self).accepts_tie(game_view, choice)
+    }
+}
+
+/// A request by a player to end the game early, made instead of choosing an option (see
+/// `PlayerController::choose_option_or_end_game`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndGameRequest {
+    /// The player resigns, conceding the game.
+    Resign,
+    /// The player offers their opponent a tie, which the opponent may decline.
+    OfferTie,
+}
+
+/// The option that a controller currently thinks is best in the choice it's making, which it
+/// updates as it searches. Clones share the same value, so it can be read from another thread.
+#[derive(Debug, Clone, Default)]
+pub struct BestOptionSoFar(Arc<Mutex<(u64, Option<usize>)>>);
+
+impl BestOptionSoFar {
+    /// Starts a new choice, forgetting the best option in the last one. The controller must call
+    /// this at the start of every `choose_option`, even if the choice is forced.
+    pub fn start_choice(&self) {
+        let mut best = self.0.lock().unwrap();
+        *best = (best.0 + 1, None);
+    }
+
+    /// Records the best option so far in the current choice.
+    pub fn set(&self, option: usize) {
+        self.0.lock().unwrap().1 = Some(option);
+    }
+
+    /// Returns the best option so far in the given choice (numbered by how many choices the
+    /// controller has started, from 1), or None if the controller hasn't picked one yet or has
+    /// moved on to a later choice.
+    pub fn get(&self, choice_number: u64) -> Option<usize> {
+        match *self.0.lock().unwrap() {
+            (current_choice, best) if current_choice == choice_number => best,
+            _ => None,
+        }
+    }
+}
+
+/// A reason that a controller failed to choose an option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControllerFailure {
+    /// The controller panicked.
+    Panicked,
+    /// The controller didn't choose within its time limit.
+    TimedOut,
 }
 
 pub trait ControllerStats {
     fn render(&mut self, area: Rect, buf: &mut Buffer);
 }
+
+/// Receives captions from controllers (e.g. narrating a demo, or noting a timeout).
+pub type CaptionSink = Box<dyn Fn(String) + Send>;
+
+static CAPTION_SINK: Mutex<Option<CaptionSink>> = Mutex::new(None);
+
+/// Sets where controllers' captions are shown (e.g. in the UI's log), or stops showing them if
+/// None.
+pub fn set_caption_sink(sink: Option<CaptionSink>) {
+    *CAPTION_SINK.lock().unwrap() = sink;
+}
+
+/// Shows a caption from a controller, after the moves made so far. Does nothing if no caption
+/// sink is set (e.g. when AIs play each other in the arena).
+pub fn show_caption(caption: String) {
+    if let Some(sink) = CAPTION_SINK.lock().unwrap().as_ref() {
+        sink(caption);
+    }
+}
+
+/// How much an AI searches for each choice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchBudget {
+    /// Search until the given time has passed.
+    Time(Duration),
+
+    /// Search until the given number of rollouts have been played out. Unlike a time limit,
+    /// this doesn't depend on how fast the machine is, so an AI with a seeded RNG makes the same
+    /// choices every time.
+    Rollouts(usize),
+}
+
+impl SearchBudget {
+    /// Returns whether a search that started at `start_time` and has played out `num_rollouts`
+    /// rollouts has used up this budget.
+    pub fn is_spent(self, start_time: Instant, num_rollouts: usize) -> bool {
+        match self {
+            SearchBudget::Time(time_limit) => start_time.elapsed() >= time_limit,
+            SearchBudget::Rollouts(max_rollouts) => num_rollouts >= max_rollouts,
+        }
+    }
+
+    /// Returns how many more rollouts this budget allows a search that started at `start_time`
+    /// and has played out `num_rollouts` rollouts: exactly, for a rollout budget, or at twice the
+    /// rate so far, for a time limit (so that an estimate from a slow start errs on the side of
+    /// searching longer).
+    pub fn remaining_rollouts(self, start_time: Instant, num_rollouts: usize) -> usize {
+        match self {
+            SearchBudget::Time(time_limit) => {
+                let elapsed = start_time.elapsed();
+                let remaining = time_limit.saturating_sub(elapsed);
+                let rate = num_rollouts as f64 / elapsed.as_secs_f64().max(1e-6);
+                (2.0 * rate * remaining.as_secs_f64()).ceil() as usize
+            }
+            SearchBudget::Rollouts(max_rollouts) => max_rollouts.saturating_sub(num_rollouts),
+        }
+    }
+
+    /// Returns whether a search that chooses its most-visited option can stop early, because that
+    /// option leads the runner-up by `visit_gap` visits, more than the remaining rollouts could
+    /// make up. (Whether a controller stops early at all is part of its spec.)
+    pub fn can_stop_early(self, start_time: Instant, num_rollouts: usize, visit_gap: u32) -> bool {
+        visit_gap as usize > self.remaining_rollouts(start_time, num_rollouts)
+    }
+
+    /// Describes what stopping early saved a search that started at `start_time` and stopped
+    /// after `num_rollouts` rollouts, for the stats display.
+    pub fn describe_early_stop(self, start_time: Instant, num_rollouts: usize) -> String {
+        let saved = match self {
+            SearchBudget::Time(time_limit) => {
+                format!("{:.1?}", time_limit.saturating_sub(start_time.elapsed()))
+            }
+            SearchBudget::Rollouts(max_rollouts) => {
+                let skipped = max_rollouts.saturating_sub(num_rollouts);
+                let rate = num_rollouts as f64 / start_time.elapsed().as_secs_f64().max(1e-6);
+                format!(
+                    "{skipped} rollouts, about {:.1?}",
+                    Duration::from_secs_f64(skipped as f64 / rate)
+                )
+            }
+        };
+        format!("Stopped early, since the best option can't be overtaken (saved {saved})")
+    }
+}
+
+impl fmt::Display for SearchBudget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SearchBudget::Time(time_limit) => write!(f, "{}", time_limit.as_secs_f64()),
+            SearchBudget::Rollouts(num_rollouts) => write!(f, "{num_rollouts}r"),
+        }
+    }
+}
+
+impl FromStr for SearchBudget {
+    type Err = ();
+
+    /// Parses a number of seconds (e.g. `3` or `0.5`) or a number of rollouts (e.g. `2000r`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(num_rollouts) = s.strip_suffix('r') {
+            num_rollouts
+                .parse()
+                .ok()
+                .filter(|&num_rollouts| num_rollouts > 0)
+                .map(SearchBudget::Rollouts)
+                .ok_or(())
+        } else {
+            s.parse::<f64>()
+                .ok()
+                .filter(|&secs| secs > 0.0 && secs.is_finite())
+                .map(|secs| SearchBudget::Time(Duration::from_secs_f64(secs)))
+                .ok_or(())
+        }
+    }
+}
+
+/// A description of a player controller and its configuration, from which the controller can
+/// be created. Specs can be written to and parsed from strings (e.g. `mcts:3`, or `mcts:2000r`
+/// for a budget of rollouts instead of seconds), so that an autosaved game can be resumed with
+/// the same controllers. A searching AI's spec ends with `:no-early-stop` if it always uses its
+/// whole budget. The specs of AIs can be combined with `mix:<p>:<a>+<b>` and `log:<spec>` (see
+/// `combinators`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControllerSpec {
+    /// A human using the UI, with the given choices automated.
+    Human {
+        auto_actions: AutoActions,
+    },
+    Random,
+    /// The example controller in `template` (see `TemplateController`).
+    Template,
+    MonteCarlo {
+        budget: SearchBudget,
+        determinization: Determinization,
+        /// Whether to stop once the decision can't change (see `SearchBudget::can_stop_early`).
+        early_stop: bool,
+    },
+    Mcts {
+        budget: SearchBudget,
+        /// Whether to stop once the decision can't change (see `SearchBudget::can_stop_early`).
+        early_stop: bool,
+    },
+    /// A human playing over the network, who connects to the given address.
+    Remote {
+        addr: SocketAddr,
+    },
+    /// For each choice, uses controller `a` with probability `p` and controller `b` otherwise
+    /// (see `Mix`). The component specs are leaked when parsed, so that specs stay `Copy`.
+    Mix {
+        a: &'static ControllerSpec,
+        b: &'static ControllerSpec,
+        p: f64,
+    },
+    /// An AI whose choices are each printed to stderr (see `WithLogging`), e.g. to follow the
+    /// games of an arena run.
+    Logged {
+        inner: &'static ControllerSpec,
+    },
+}
+
+impl ControllerSpec {
+    /// Creates a controller for the given player according to this spec. Controllers that make
+    /// random choices have their own RNG, seeded with `seed`, so that the same seed (and the same
+    /// game seed) always produces the same choices, as long as the controller's search budget
+    /// isn't a time limit.
+    pub fn build<'ctype>(
+        self,
+        player: Player,
+        seed: u64,
+    ) -> Box<dyn PlayerController<'ctype> + 'ctype> {
+        match self {
+            ControllerSpec::Human { auto_actions } => {
+                if auto_actions == AutoActions::NONE {
+                    Box::new(HumanController)
+                } else {
+                    Box::new(AutoActionController {
+                        inner: HumanController,
+                        auto_actions,
+                    })
+                }
+            }
+            ControllerSpec::Random => Box::new(RandomController::new(seed)),
+            ControllerSpec::Template => Box::new(TemplateController::new(seed)),
+            ControllerSpec::MonteCarlo {
+                budget,
+                determinization,
+                early_stop,
+            } => Box::new(MonteCarloController {
+                player,
+                budget,
+                early_stop,
+                make_rollout_controller: random_rollouts,
+                determinization,
+                rng: StdRng::seed_from_u64(seed),
+            }),
+            ControllerSpec::Mcts { .. } => Box::new(self.build_mcts(player, seed).unwrap()),
+            ControllerSpec::Remote { addr } => Box::new(RemoteHumanController::new(addr)),
+            ControllerSpec::Mix { a, b, p } => Box::new(Mix::new(
+                a.build(player, seed),
+                b.build(player, seed.wrapping_add(1)),
+                p,
+                seed.wrapping_add(2),
+            )),
+            ControllerSpec::Logged { inner } => Box::new(WithLogging::new(
+                inner.build(player, seed),
+                format!("Player {} ({inner})", player.number()),
+                io::stderr(),
+            )),
+        }
+    }
+
+    /// Like `build`, but only for MCTS specs, returning the controller as its own type (e.g. so
+    /// that a search state can be loaded into it). Returns None if this isn't an MCTS spec.
+    pub fn build_mcts<'ctype>(
+        self,
+        player: Player,
+        seed: u64,
+    ) -> Option<MCTSController<'ctype, RolloutPolicy>> {
+        match self {
+            ControllerSpec::Mcts { budget, early_stop } => {
+                let mut controller: MCTSController<RolloutPolicy> =
+                    MCTSController::new(player, budget, random_rollouts, seed);
+                controller.early_stop = early_stop;
+                Some(controller)
+            }
+            _ => None,
+        }
+    }
+
+    /// Like `build`, but AI controllers are guarded so that if one panics or takes far longer
+    /// than its time limit, its best option so far (or else a random option) is chosen instead
+    /// of the game being lost. Guarded controllers run on threads in the given scope.
+    pub fn build_guarded<'scope, 'ctype: 'scope>(
+        self,
+        player: Player,
+        seed: u64,
+        scope: &'scope Scope<'scope, '_>,
+    ) -> Box<dyn PlayerController<'ctype> + 'ctype> {
+        let guard_time_limit = match self.guard_time_limit() {
+            Some(guard_time_limit) => guard_time_limit,
+            None => return self.build(player, seed),
+        };
+        Box::new(WithFallback::new(
+            WithTimeLimit::new(scope, move || self.build(player, seed), guard_time_limit),
+            RandomController::unseeded(),
+        ))
+    }
+
+    /// Returns how long a guarded controller built from this spec is given for each choice, or
+    /// None if it isn't guarded (because it doesn't search).
+    fn guard_time_limit(self) -> Option<Duration> {
+        match self {
+            ControllerSpec::MonteCarlo { budget, .. } | ControllerSpec::Mcts { budget, .. } => {
+                Some(match budget {
+                    SearchBudget::Time(time_limit) => time_limit * 2 + GUARD_GRACE_PERIOD,
+                    SearchBudget::Rollouts(_) => ROLLOUT_BUDGET_GUARD_TIME_LIMIT,
+                })
+            }
+            ControllerSpec::Mix { a, b, .. } => a.guard_time_limit().max(b.guard_time_limit()),
+            ControllerSpec::Logged { inner } => inner.guard_time_limit(),
+            _ => None,
+        }
+    }
+
+    /// Returns whether this spec is for an AI, rather than a human player.
+    pub fn is_ai(self) -> bool {
+        !matches!(
+            self,
+            ControllerSpec::Human { .. } | ControllerSpec::Remote { .. }
+        )
+    }
+}
+
+/// Creates the controller that plays one side of a searching AI's rollouts.
+pub type RolloutPolicy = fn(Player, &mut StdRng) -> RandomController;
+
+/// The rollout policy of the searching AIs: both sides choose uniformly at random.
+fn random_rollouts(_player: Player, rng: &mut StdRng) -> RandomController {
+    RandomController::from_rng(rng)
+}
+
+/// Extra time that a guarded AI controller is given beyond twice its own time limit.
+const GUARD_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// The time that a guarded AI controller with a rollout budget (rather than a time limit) is
+/// given for each choice.
+const ROLLOUT_BUDGET_GUARD_TIME_LIMIT: Duration = Duration::from_secs(300);
+
+impl fmt::Display for ControllerSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ControllerSpec::Human { auto_actions } => {
+                write!(f, "human")?;
+                if auto_actions != AutoActions::NONE {
+                    let end_turn = match auto_actions.end_turn {
+                        AutoEndTurn::Manual => "manual",
+                        AutoEndTurn::Prompt => "prompt",
+                        AutoEndTurn::Auto => "auto",
+                    };
+                    let icon_effects = match auto_actions.skip_unusable_icon_effects {
+                        true => "skip",
+                        false => "ask",
+                    };
+                    let warnings = auto_actions.end_turn_warnings;
+                    write!(f, ":{end_turn}:{icon_effects}:{warnings}")?;
+                }
+                Ok(())
+            }
+            ControllerSpec::Random => write!(f, "random"),
+            ControllerSpec::Template => write!(f, "template"),
+            ControllerSpec::MonteCarlo {
+                budget,
+                determinization,
+                early_stop,
+            } => {
+                write!(f, "monte-carlo:{budget}:")?;
+                match determinization {
+                    Determinization::Independent => write!(f, "independent"),
+                    Determinization::Paired => write!(f, "paired"),
+                    Determinization::Worlds {
+                        num_worlds,
+                        aggregation: WorldAggregation::Average,
+                    } => write!(f, "worlds-{num_worlds}-average"),
+                    Determinization::Worlds {
+                        num_worlds,
+                        aggregation: WorldAggregation::Vote,
+                    } => write!(f, "worlds-{num_worlds}-vote"),
+                }?;
+                write_early_stop(f, early_stop)
+            }
+            ControllerSpec::Mcts { budget, early_stop } => {
+                write!(f, "mcts:{budget}")?;
+                write_early_stop(f, early_stop)
+            }
+            ControllerSpec::Remote { addr } => write!(f, "remote:{addr}"),
+            ControllerSpec::Mix { a, b, p } => write!(f, "mix:{p}:{a}+{b}"),
+            ControllerSpec::Logged { inner } => write!(f, "log:{inner}"),
+        }
+    }
+}
+
+/// Writes the suffix of a searching AI's spec that says whether it stops early.
+fn write_early_stop(f: &mut fmt::Formatter, early_stop: bool) -> fmt::Result {
+    if early_stop {
+        Ok(())
+    } else {
+        write!(f, ":no-early-stop")
+    }
+}
+
+impl FromStr for ControllerSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid controller spec: {s:?}");
+        let parse_budget = |budget: &str| budget.parse::<SearchBudget>().map_err(|_| invalid());
+
+        if let Some(addr) = s.strip_prefix("remote:") {
+            // the address contains colons, so it can't be split like the other specs
+            let addr = addr.parse().map_err(|_| invalid())?;
+            return Ok(ControllerSpec::Remote { addr });
+        }
+
+        // only AIs can be combined, since a human can't be asked to make some choices but not
+        // others
+        let parse_ai = |spec: &str| -> Result<&'static ControllerSpec, String> {
+            let spec: ControllerSpec = spec.parse()?;
+            if spec.is_ai() {
+                Ok(Box::leak(Box::new(spec)))
+            } else {
+                Err(invalid())
+            }
+        };
+        if let Some(mix) = s.strip_prefix("mix:") {
+            // the first spec can't contain a '+', but the second can (e.g. another mix)
+            let (p, specs) = mix.split_once(':').ok_or_else(invalid)?;
+            let (a, b) = specs.split_once('+').ok_or_else(invalid)?;
+            let p = p
+                .parse()
+                .ok()
+                .filter(|p| (0.0..=1.0).contains(p))
+                .ok_or_else(invalid)?;
+            return Ok(ControllerSpec::Mix {
+                a: parse_ai(a)?,
+                b: parse_ai(b)?,
+                p,
+            });
+        }
+        if let Some(inner) = s.strip_prefix("log:") {
+            return Ok(ControllerSpec::Logged {
+                inner: parse_ai(inner)?,
+            });
+        }
+
+        // searching AIs stop early unless their spec says otherwise
+        let mut parts = s.split(':').collect_vec();
+        let early_stop = parts.last() != Some(&"no-early-stop");
+        if !early_stop && matches!(parts[..], ["mcts" | "monte-carlo", ..]) {
+            parts.pop();
+        }
+
+        match parts[..] {
+            ["human"] => Ok(ControllerSpec::Human {
+                auto_actions: AutoActions::NONE,
+            }),
+            ["human", end_turn, icon_effects, ref warnings @ ..] if warnings.len() <= 1 => {
+                Ok(ControllerSpec::Human {
+                    auto_actions: AutoActions {
+                        end_turn: match end_turn {
+                            "manual" => AutoEndTurn::Manual,
+                            "prompt" => AutoEndTurn::Prompt,
+                            "auto" => AutoEndTurn::Auto,
+                            _ => return Err(invalid()),
+                        },
+                        skip_unusable_icon_effects: match icon_effects {
+                            "skip" => true,
+                            "ask" => false,
+                            _ => return Err(invalid()),
+                        },
+                        end_turn_warnings: match warnings.first() {
+                            Some(warnings) => warnings.parse().map_err(|_| invalid())?,
+                            // specs written before there were end-turn warnings
+                            None => EndTurnWarnings::NONE,
+                        },
+                    },
+                })
+            }
+            ["random"] => Ok(ControllerSpec::Random),
+            ["template"] => Ok(ControllerSpec::Template),
+            ["mcts", budget] => Ok(ControllerSpec::Mcts {
+                budget: parse_budget(budget)?,
+                early_stop,
+            }),
+            ["monte-carlo", budget, determinization] => Ok(ControllerSpec::MonteCarlo {
+                budget: parse_budget(budget)?,
+                determinization: match determinization.split('-').collect_vec()[..] {
+                    ["independent"] => Determinization::Independent,
+                    ["paired"] => Determinization::Paired,
+                    ["worlds", num_worlds, aggregation] => Determinization::Worlds {
+                        num_worlds: num_worlds.parse().map_err(|_| invalid())?,
+                        aggregation: match aggregation {
+                            "average" => WorldAggregation::Average,
+                            "vote" => WorldAggregation::Vote,
+                            _ => return Err(invalid()),
+                        },
+                    },
+                    _ => return Err(invalid()),
+                },
+                early_stop,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::monte_carlo::{visit_gap, OptionStats};
+    use super::*;
+
+    /// Returns the time a search started, the given number of seconds ago.
+    fn started_secs_ago(secs: u64) -> Instant {
+        Instant::now() - Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn a_rollout_budget_has_exactly_its_unplayed_rollouts_remaining() {
+        let budget = SearchBudget::Rollouts(100);
+        let start_time = Instant::now();
+        assert_eq!(budget.remaining_rollouts(start_time, 0), 100);
+        assert_eq!(budget.remaining_rollouts(start_time, 30), 70);
+        assert_eq!(budget.remaining_rollouts(start_time, 100), 0);
+        assert_eq!(budget.remaining_rollouts(start_time, 120), 0);
+    }
+
+    #[test]
+    fn a_time_budget_estimates_its_remaining_rollouts_at_twice_the_rate_so_far() {
+        // 1000 rollouts in the first second leaves about 2 seconds, at up to 2000 per second
+        let budget = SearchBudget::Time(Duration::from_secs(3));
+        let remaining = budget.remaining_rollouts(started_secs_ago(1), 1000);
+        assert!((3900..=4000).contains(&remaining), "{remaining}");
+
+        // once the time is up, nothing remains
+        assert_eq!(budget.remaining_rollouts(started_secs_ago(5), 1000), 0);
+    }
+
+    #[test]
+    fn a_search_can_stop_early_once_the_remaining_rollouts_cant_close_the_gap() {
+        let budget = SearchBudget::Rollouts(100);
+        let start_time = Instant::now();
+        assert!(!budget.can_stop_early(start_time, 60, 40));
+        assert!(budget.can_stop_early(start_time, 60, 41));
+        assert!(!budget.can_stop_early(start_time, 0, 0));
+        assert!(budget.can_stop_early(start_time, 100, 1));
+    }
+
+    #[test]
+    fn the_visit_gap_is_between_the_two_most_visited_options() {
+        let options = |visits: &[u32]| {
+            visits
+                .iter()
+                .map(|&num_rollouts| OptionStats {
+                    num_rollouts,
+                    total_score: 0,
+                })
+                .collect_vec()
+        };
+        assert_eq!(visit_gap(&options(&[5, 9, 7])), 2);
+        assert_eq!(visit_gap(&options(&[6, 1, 6])), 0);
+        assert_eq!(visit_gap(&options(&[4])), 0);
+        assert_eq!(visit_gap(&options(&[])), 0);
+    }
+
+    #[test]
+    fn early_stopping_is_part_of_a_searching_ai_spec() {
+        for (spec, early_stop) in [
+            ("mcts:2000r", true),
+            ("mcts:3:no-early-stop", false),
+            ("monte-carlo:3:paired", true),
+            ("monte-carlo:500r:worlds-4-vote:no-early-stop", false),
+        ] {
+            let parsed: ControllerSpec = spec.parse().unwrap();
+            match parsed {
+                ControllerSpec::Mcts { early_stop: e, .. }
+                | ControllerSpec::MonteCarlo { early_stop: e, .. } => assert_eq!(e, early_stop),
+                _ => panic!("{spec} isn't a searching AI"),
+            }
+            assert_eq!(parsed.to_string(), spec);
+        }
+        assert!("random:no-early-stop".parse::<ControllerSpec>().is_err());
+    }
+
+    #[test]
+    fn ai_specs_can_be_mixed_and_logged() {
+        for spec in [
+            "mix:0.25:mcts:1:no-early-stop+random",
+            "mix:0.5:random+mix:1:template+mcts:200r",
+            "log:mcts:2000r",
+            "log:mix:0:random+template",
+        ] {
+            let parsed: ControllerSpec = spec.parse().unwrap();
+            assert_eq!(parsed.to_string(), spec);
+        }
+        match "mix:0.25:mcts:1+random".parse().unwrap() {
+            ControllerSpec::Mix { a, b, p } => {
+                assert!(matches!(a, ControllerSpec::Mcts { .. }));
+                assert_eq!(*b, ControllerSpec::Random);
+                assert_eq!(p, 0.25);
+            }
+            spec => panic!("{spec} isn't a mix"),
+        }
+
+        // the guard gives a mix as long as its slowest AI needs
+        let mix: ControllerSpec = "mix:0.5:random+mcts:3".parse().unwrap();
+        let mcts: ControllerSpec = "mcts:3".parse().unwrap();
+        assert_eq!(mix.guard_time_limit(), mcts.guard_time_limit());
+        assert_eq!(ControllerSpec::Random.guard_time_limit(), None);
+
+        for invalid in [
+            "mix:1.5:random+random",
+            "mix:0.5:random",
+            "mix:0.5:human+random",
+            "log:human",
+            "log:remote:127.0.0.1:8000",
+        ] {
+            assert!(invalid.parse::<ControllerSpec>().is_err(), "{invalid}");
+        }
+    }
+}
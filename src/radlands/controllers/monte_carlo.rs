@@ -1,28 +1,64 @@
 use ordered_float::NotNan;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng, SeedableRng};
 use std::fmt;
-use std::time::{Duration, Instant};
+use std::iter;
+use std::time::Instant;
 use tui::buffer::Buffer;
 use tui::layout::Rect;
 use tui::style::{Color, Modifier, Style};
 use tui::widgets::{List, ListItem, Widget};
 
+use crate::metrics::{self, Counter};
 use crate::play_to_end;
 use crate::radlands::choices::*;
 use crate::radlands::*;
-use crate::ui::set_controller_stats;
+use crate::ui::{set_controller_stats, stats_interval};
 
-use super::ControllerStats;
+use super::{ControllerStats, SearchBudget};
 
-pub fn randomize_unobserved<'ctype>(game_state: &GameState<'ctype>) -> GameState<'ctype> {
-    let mut rng = thread_rng();
-    let mut new_game_state = game_state.clone();
+/// Returns a copy of the game with everything that the view's player can't see randomized:
+/// the unaccounted cards (see `GameView::unaccounted_cards`) are shuffled and dealt back out to
+/// the opponent's hand, the punks, and the deck, the opponent's camps are re-dealt if the camp
+/// draft is still going on, and the outcomes of any future shuffles (and of which punk has which
+/// card) are reseeded.
+pub fn randomize_unobserved<'ctype>(game_view: &GameView<'_, 'ctype>) -> GameState<'ctype> {
+    randomize_unobserved_with(game_view, &mut thread_rng())
+}
+
+/// Like `randomize_unobserved`, but uses the given RNG, so that the same seed always produces
+/// the same randomization.
+pub fn randomize_unobserved_with<'ctype>(
+    game_view: &GameView<'_, 'ctype>,
+    rng: &mut impl Rng,
+) -> GameState<'ctype> {
+    let mut new_game_state = game_view.game_state.clone();
+
+    // deal the unaccounted cards back out in a random order (the opponent's hand first, then the
+    // punks' cards, taking from the top of the deck, which is the end of the list)
+    // TODO: keep the cards known to be in the opponent's hand (e.g. from Rescue Team)
+    let mut unaccounted_cards = game_view
+        .unaccounted_cards()
+        .iter()
+        .flat_map(|(card, count)| iter::repeat(card).take(count))
+        .collect_vec();
+    unaccounted_cards.shuffle(rng);
+    let opponent = game_view.player.other();
+    let hand_start = unaccounted_cards.len() - game_view.other_state().hand.count();
+    new_game_state.player_mut(opponent).hand = unaccounted_cards.drain(hand_start..).collect();
+    for card in new_game_state.punk_cards_mut() {
+        *card = unaccounted_cards
+            .pop()
+            .expect("There should be an unaccounted card for every punk");
+    }
+    new_game_state.deck = unaccounted_cards;
 
-    // shuffle the deck
-    new_game_state.deck.shuffle(&mut rng);
+    // so are the opponent's camps while the players are choosing which to keep
+    new_game_state.randomize_camp_draft(game_view.player, rng);
 
-    // TODO: shuffle all unobserved cards (deck, other player's hand, punks)
+    // the outcomes of any future shuffles (e.g. reshuffling the deck) are unobserved too
+    new_game_state.reseed_rng(rng);
 
     new_game_state
 }
@@ -41,29 +77,111 @@ pub fn get_score(game_result: GameResult, for_player: Player) -> u32 {
     }
 }
 
+/// Plays out a rollout after choosing the given option, in a world randomized from `for_player`'s
+/// view, and returns `for_player`'s score. The world is randomized with `rng`, which also seeds
+/// the rollout controllers (see `compute_world_rollout_score`).
 pub fn compute_rollout_score<'ctype, C: PlayerController<'ctype>>(
     for_player: Player,
     game_state: &GameState<'ctype>,
     choice: &Choice<'ctype>,
-    make_rollout_controller: &impl Fn(Player) -> C,
+    make_rollout_controller: &impl Fn(Player, &mut StdRng) -> C,
     option_index: usize,
+    rng: &mut StdRng,
 ) -> u32 {
-    let mut game_state = randomize_unobserved(game_state);
+    compute_world_rollout_score(
+        for_player,
+        randomize_unobserved_with(&game_state.view_for(for_player), rng),
+        choice,
+        make_rollout_controller,
+        option_index,
+        rng,
+    )
+}
 
+/// Like `compute_rollout_score`, but randomizes the unobserved state using the given seed.
+/// Rollouts of different options with the same seed see the same deck order and the same
+/// reshuffles, which reduces the noise when comparing the options.
+pub fn compute_seeded_rollout_score<'ctype, C: PlayerController<'ctype>>(
+    for_player: Player,
+    game_state: &GameState<'ctype>,
+    choice: &Choice<'ctype>,
+    make_rollout_controller: &impl Fn(Player, &mut StdRng) -> C,
+    option_index: usize,
+    seed: u64,
+    rng: &mut StdRng,
+) -> u32 {
+    compute_world_rollout_score(
+        for_player,
+        randomize_unobserved_with(
+            &game_state.view_for(for_player),
+            &mut StdRng::seed_from_u64(seed),
+        ),
+        choice,
+        make_rollout_controller,
+        option_index,
+        rng,
+    )
+}
+
+/// Like `compute_rollout_score`, but plays out the rollout in the given (already determinized)
+/// world instead of randomizing the unobserved state. The rollout controllers are created by
+/// `make_rollout_controller` from `rng`, so that an AI with a seeded RNG plays out the same
+/// rollouts every time.
+pub fn compute_world_rollout_score<'ctype, C: PlayerController<'ctype>>(
+    for_player: Player,
+    mut game_state: GameState<'ctype>,
+    choice: &Choice<'ctype>,
+    make_rollout_controller: &impl Fn(Player, &mut StdRng) -> C,
+    option_index: usize,
+    rng: &mut StdRng,
+) -> u32 {
+    metrics::increment(Counter::Rollouts);
     let game_result = match choice.choose(&mut game_state, option_index) {
         Err(game_result) => game_result,
         Ok(choice) => play_to_end(
             &mut game_state,
             choice,
-            &mut (make_rollout_controller)(Player::Player1),
-            &mut (make_rollout_controller)(Player::Player2),
+            &mut (make_rollout_controller)(Player::Player1, rng),
+            &mut (make_rollout_controller)(Player::Player2, rng),
+            None,
         ),
     };
 
     get_score(game_result, for_player)
 }
 
-#[derive(Debug, Clone)]
+/// The number of rollouts that an AI plays out to estimate its chances when it's offered a tie.
+const TIE_OFFER_ROLLOUTS: u32 = 1000;
+
+/// Returns whether an AI playing as `for_player` accepts its opponent's offer of a tie at the
+/// given choice. It estimates its expected score from the position with rollouts (from worlds
+/// randomized from its view with `rng`, starting with a random option of the choice), and
+/// accepts if it expects to do worse than a tie.
+pub fn ai_accepts_tie<'ctype, C: PlayerController<'ctype>>(
+    for_player: Player,
+    game_state: &GameState<'ctype>,
+    choice: &Choice<'ctype>,
+    make_rollout_controller: &impl Fn(Player, &mut StdRng) -> C,
+    rng: &mut StdRng,
+) -> bool {
+    let total_score: u32 = (0..TIE_OFFER_ROLLOUTS)
+        .map(|_| {
+            let option_index = rng.gen_range(0..choice.num_options());
+            compute_rollout_score(
+                for_player,
+                game_state,
+                choice,
+                make_rollout_controller,
+                option_index,
+                rng,
+            )
+        })
+        .sum();
+    // a tie scores 1 per rollout
+    total_score < TIE_OFFER_ROLLOUTS
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct OptionStats {
     pub num_rollouts: u32,
     pub total_score: u32,
@@ -94,8 +212,11 @@ impl OptionStats {
     }
 }
 
+/// Formats the stats for each group of options, where `option_stats_vec[i]` holds the stats for
+/// the option group `option_groups[i]`.
 pub fn format_option_stats<'g, 'ctype: 'g>(
     option_stats_vec: &[OptionStats],
+    option_groups: &[OptionGroup],
     parent_rollouts: usize,
     game_view: &GameView<'g, 'ctype>,
     choice: &Choice<'ctype>,
@@ -108,8 +229,8 @@ pub fn format_option_stats<'g, 'ctype: 'g>(
 
     option_stats_vec
         .iter()
-        .enumerate()
-        .map(|(option_index, option_stats)| {
+        .zip(option_groups)
+        .map(|(option_stats, &option_group)| {
             let stats = format_stats_prefix(
                 option_stats.num_rollouts,
                 (option_stats.num_rollouts as f64) / (parent_rollouts as f64),
@@ -122,7 +243,7 @@ pub fn format_option_stats<'g, 'ctype: 'g>(
             } else {
                 Style::default()
             };
-            let mut spans = choice.format_option(option_index, game_view.game_state);
+            let mut spans = choice.format_option_group(option_group, game_view.game_state);
             spans
                 .0
                 .splice(0..0, [Span::styled(stats, stats_style), "   ".into()]);
@@ -146,11 +267,18 @@ pub fn format_stats_prefix(visit_count: u32, visit_proportion: f64, win_rate: f6
 
 pub fn show_option_stats<'g, 'ctype: 'g>(
     option_stats_vec: &[OptionStats],
+    option_groups: &[OptionGroup],
     parent_rollouts: usize,
     game_view: &GameView<'g, 'ctype>,
     choice: &Choice<'ctype>,
 ) {
-    let lines = format_option_stats(option_stats_vec, parent_rollouts, game_view, choice);
+    let lines = format_option_stats(
+        option_stats_vec,
+        option_groups,
+        parent_rollouts,
+        game_view,
+        choice,
+    );
     set_controller_stats(Some(Box::new(StatsWidget { lines })), game_view.player);
 }
 
@@ -178,44 +306,154 @@ pub fn get_best_options(option_stats_vec: &[OptionStats]) -> Vec<usize> {
         .collect()
 }
 
+/// Returns how many more visits the most-visited option has than the runner-up (0 if there's
+/// only one option).
+pub fn visit_gap(option_stats_vec: &[OptionStats]) -> u32 {
+    let mut visit_counts = option_stats_vec
+        .iter()
+        .map(|option_stats| option_stats.num_rollouts)
+        .sorted_unstable_by(|a, b| b.cmp(a));
+    match (visit_counts.next(), visit_counts.next()) {
+        (Some(best), Some(runner_up)) => best - runner_up,
+        _ => 0,
+    }
+}
+
+/// How a `MonteCarloController` handles the information that is hidden from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Determinization {
+    /// Every rollout is played in a new, independent randomization of the unobserved state.
+    Independent,
+
+    /// Like `Independent`, but uses common random numbers: the n-th rollout of each option is
+    /// played in the same randomization (including any deck reshuffles) as the n-th rollout of
+    /// every other option.
+    Paired,
+
+    /// A fixed set of `num_worlds` randomizations (determinized worlds) is chosen for each
+    /// decision, and every option is evaluated in all of them. This keeps the hidden-information
+    /// variance the same across options, so that differences between them reflect move quality.
+    Worlds {
+        num_worlds: usize,
+        aggregation: WorldAggregation,
+    },
+}
+
+/// How the per-world results of `Determinization::Worlds` are combined into a decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldAggregation {
+    /// Choose the option with the best win rate averaged over all the worlds.
+    Average,
+
+    /// Let each world vote for its best option, and choose the option with the most votes.
+    /// Ties are broken by the average win rate.
+    Vote,
+}
+
 pub struct MonteCarloController<F> {
     pub player: Player,
-    pub choice_time_limit: Duration,
+    pub budget: SearchBudget,
+    /// Whether to stop before the budget is spent once the decision can't change.
+    pub early_stop: bool,
     pub make_rollout_controller: F,
+    pub determinization: Determinization,
+
+    /// The RNG for the randomized worlds, the rollouts, and breaking ties between the best
+    /// options (separate from the game's RNG, so that a seeded AI is reproducible).
+    pub rng: StdRng,
 }
 
-impl<'ctype, C: PlayerController<'ctype>, F: Fn(Player) -> C> MonteCarloController<F> {
+impl<'ctype, C: PlayerController<'ctype>, F: Fn(Player, &mut StdRng) -> C> MonteCarloController<F> {
     fn monte_carlo_choose_impl<'g>(
-        &self,
+        &mut self,
         game_view: &GameView<'g, 'ctype>,
         choice: &Choice<'ctype>,
     ) -> usize {
-        let num_options = choice.num_options(game_view.game_state);
+        let num_options = choice.num_options();
         if num_options == 1 {
             return 0;
         }
 
+        // only simulate one option out of each group of equivalent options
+        let option_groups = choice.option_groups(game_view.game_state);
+        if option_groups.len() == 1 {
+            return 0;
+        }
+
+        if let Determinization::Worlds {
+            num_worlds,
+            aggregation,
+        } = self.determinization
+        {
+            return self.worlds_choose_impl(
+                game_view,
+                choice,
+                &option_groups,
+                num_worlds,
+                aggregation,
+            );
+        }
+
         let start_time = Instant::now();
 
-        let mut option_stats_vec = (0..num_options)
-            .map(|option_index| OptionStats {
-                num_rollouts: 1,
-                total_score: compute_rollout_score(
+        // the seeds shared by the paired rollouts of each option, if enabled
+        let mut rollout_seeds = Vec::new();
+        let mut do_rollout = |group_index: usize, rollout_index: u32| {
+            let option_index = option_groups[group_index].option;
+            if self.determinization == Determinization::Paired {
+                let rollout_index = rollout_index as usize;
+                if rollout_index == rollout_seeds.len() {
+                    rollout_seeds.push(self.rng.gen());
+                }
+                compute_seeded_rollout_score(
                     self.player,
                     game_view.game_state,
                     choice,
                     &self.make_rollout_controller,
                     option_index,
-                ),
+                    rollout_seeds[rollout_index],
+                    &mut self.rng,
+                )
+            } else {
+                compute_rollout_score(
+                    self.player,
+                    game_view.game_state,
+                    choice,
+                    &self.make_rollout_controller,
+                    option_index,
+                    &mut self.rng,
+                )
+            }
+        };
+
+        let mut option_stats_vec = (0..option_groups.len())
+            .map(|group_index| OptionStats {
+                num_rollouts: 1,
+                total_score: do_rollout(group_index, 0),
             })
             .collect_vec();
 
+        let stats_interval = stats_interval();
         let mut last_print_time = start_time;
-        let mut rollout_num = num_options;
-        show_option_stats(&option_stats_vec, rollout_num, game_view, choice);
-        while start_time.elapsed() < self.choice_time_limit {
+        let mut rollout_num = option_groups.len();
+        show_option_stats(
+            &option_stats_vec,
+            &option_groups,
+            rollout_num,
+            game_view,
+            choice,
+        );
+        let mut early_stop = None;
+        while !self.budget.is_spent(start_time, rollout_num) {
+            // stop once the most-visited option can't be overtaken
+            let gap = visit_gap(&option_stats_vec);
+            if self.early_stop && self.budget.can_stop_early(start_time, rollout_num, gap) {
+                early_stop = Some(self.budget.describe_early_stop(start_time, rollout_num));
+                break;
+            }
+
             // choose a choice to simulate using UCB1
-            let (option_index, option_stats) = option_stats_vec
+            let (group_index, option_stats) = option_stats_vec
                 .iter_mut()
                 .enumerate()
                 .max_by_key(|(_, option_stats)| option_stats.ucb1_score(rollout_num))
@@ -223,33 +461,158 @@ impl<'ctype, C: PlayerController<'ctype>, F: Fn(Player) -> C> MonteCarloControll
 
             // perform a rollout for that choice
             rollout_num += 1;
+            option_stats.total_score += do_rollout(group_index, option_stats.num_rollouts);
             option_stats.num_rollouts += 1;
-            option_stats.total_score += compute_rollout_score(
-                self.player,
-                game_view.game_state,
-                choice,
-                &self.make_rollout_controller,
-                option_index,
-            );
 
             // update the live stats display
             let now = Instant::now();
             let elapsed = now.duration_since(last_print_time);
-            if elapsed > Duration::from_millis(100) {
-                show_option_stats(&option_stats_vec, rollout_num, game_view, choice);
+            if elapsed > stats_interval {
+                show_option_stats(
+                    &option_stats_vec,
+                    &option_groups,
+                    rollout_num,
+                    game_view,
+                    choice,
+                );
                 last_print_time = now;
             }
         }
-        show_option_stats(&option_stats_vec, rollout_num, game_view, choice);
+        let mut lines = format_option_stats(
+            &option_stats_vec,
+            &option_groups,
+            rollout_num,
+            game_view,
+            choice,
+        );
+        if let Some(early_stop) = early_stop {
+            lines.extend([ListItem::new(" "), ListItem::new(early_stop)]);
+        }
+        set_controller_stats(Some(Box::new(StatsWidget { lines })), game_view.player);
 
         // return a random best (maximum visit count) choice
-        *get_best_options(&option_stats_vec)
-            .choose(&mut thread_rng())
-            .unwrap()
+        let best_group_index = *get_best_options(&option_stats_vec)
+            .choose(&mut self.rng)
+            .unwrap();
+        option_groups[best_group_index].option
+    }
+
+    /// Chooses an option by evaluating every option in each of a fixed set of determinized
+    /// worlds, using UCB1 separately within each world, then aggregating across the worlds.
+    fn worlds_choose_impl<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+        option_groups: &[OptionGroup],
+        num_worlds: usize,
+        aggregation: WorldAggregation,
+    ) -> usize {
+        let num_options = option_groups.len();
+        let start_time = Instant::now();
+
+        let worlds = (0..num_worlds.max(1))
+            .map(|_| randomize_unobserved_with(game_view, &mut self.rng))
+            .collect_vec();
+        let mut world_stats = vec![vec![OptionStats::default(); num_options]; worlds.len()];
+
+        let stats_interval = stats_interval();
+        let mut last_print_time = start_time;
+        let mut rollout_num = 0;
+        let mut world_rollouts = 0;
+        loop {
+            // visit the worlds in turn so that each gets an equal share of the rollouts
+            for (world, option_stats_vec) in worlds.iter().zip(&mut world_stats) {
+                // try each option once, then choose an option to simulate using UCB1
+                let (group_index, option_stats) = option_stats_vec
+                    .iter_mut()
+                    .enumerate()
+                    .max_by_key(|(_, option_stats)| {
+                        if option_stats.num_rollouts == 0 {
+                            NotNan::new(f64::INFINITY).unwrap()
+                        } else {
+                            option_stats.ucb1_score(world_rollouts)
+                        }
+                    })
+                    .unwrap();
+
+                // perform a rollout for that choice in this world
+                rollout_num += 1;
+                option_stats.num_rollouts += 1;
+                option_stats.total_score += compute_world_rollout_score(
+                    self.player,
+                    world.clone(),
+                    choice,
+                    &self.make_rollout_controller,
+                    option_groups[group_index].option,
+                    &mut self.rng,
+                );
+            }
+            world_rollouts += 1;
+
+            // every option must be evaluated in every world before stopping
+            if world_rollouts >= num_options && self.budget.is_spent(start_time, rollout_num) {
+                break;
+            }
+
+            // update the live stats display
+            let now = Instant::now();
+            if now.duration_since(last_print_time) > stats_interval {
+                let total_stats = sum_world_stats(&world_stats);
+                show_option_stats(&total_stats, option_groups, rollout_num, game_view, choice);
+                last_print_time = now;
+            }
+        }
+        let total_stats = sum_world_stats(&world_stats);
+        show_option_stats(&total_stats, option_groups, rollout_num, game_view, choice);
+
+        // return a random best choice according to the aggregation method
+        let best_options = match aggregation {
+            WorldAggregation::Average => get_best_win_rate_options(&total_stats, &[]),
+            WorldAggregation::Vote => {
+                let mut votes = vec![0; num_options];
+                for option_stats_vec in &world_stats {
+                    for group_index in get_best_win_rate_options(option_stats_vec, &[]) {
+                        votes[group_index] += 1;
+                    }
+                }
+                get_best_win_rate_options(&total_stats, &votes)
+            }
+        };
+        option_groups[*best_options.choose(&mut self.rng).unwrap()].option
+    }
+}
+
+/// Combines the stats for each option across all the determinized worlds.
+fn sum_world_stats(world_stats: &[Vec<OptionStats>]) -> Vec<OptionStats> {
+    let mut total_stats = vec![OptionStats::default(); world_stats[0].len()];
+    for option_stats_vec in world_stats {
+        for (total, option_stats) in total_stats.iter_mut().zip(option_stats_vec) {
+            total.num_rollouts += option_stats.num_rollouts;
+            total.total_score += option_stats.total_score;
+        }
     }
+    total_stats
+}
+
+/// Returns the options with the most votes (if any are given) and, among those, the best win rate.
+fn get_best_win_rate_options(option_stats_vec: &[OptionStats], votes: &[u32]) -> Vec<usize> {
+    let key = |option_index: usize| {
+        (
+            votes.get(option_index).copied().unwrap_or(0),
+            option_stats_vec[option_index].win_rate(),
+        )
+    };
+    let best_key = (0..option_stats_vec.len())
+        .map(key)
+        .max()
+        .expect("option_stats_vec is empty");
+
+    (0..option_stats_vec.len())
+        .filter(|&option_index| key(option_index) == best_key)
+        .collect()
 }
 
-impl<'ctype, C: PlayerController<'ctype>, F: Fn(Player) -> C> PlayerController<'ctype>
+impl<'ctype, C: PlayerController<'ctype>, F: Fn(Player, &mut StdRng) -> C> PlayerController<'ctype>
     for MonteCarloController<F>
 {
     fn choose_option<'g>(
@@ -259,6 +622,20 @@ impl<'ctype, C: PlayerController<'ctype>, F: Fn(Player) -> C> PlayerController<'
     ) -> usize {
         self.monte_carlo_choose_impl(game_view, choice)
     }
+
+    fn accepts_tie<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> bool {
+        ai_accepts_tie(
+            self.player,
+            game_view.game_state,
+            choice,
+            &self.make_rollout_controller,
+            &mut self.rng,
+        )
+    }
 }
 
 impl<F> fmt::Debug for MonteCarloController<F> {
@@ -1,16 +1,44 @@
-use rand::thread_rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 use crate::radlands::choices::*;
 use crate::radlands::*;
 
-pub struct RandomController;
+/// A `PlayerController` that chooses uniformly at random, with its own RNG (separate from the
+/// game's), so that its choices are reproducible from its seed.
+pub struct RandomController {
+    rng: StdRng,
+}
+
+impl RandomController {
+    /// Creates a random controller whose choices are determined by the given seed.
+    pub fn new(seed: u64) -> Self {
+        RandomController {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Creates a random controller seeded from the given RNG, e.g. for a rollout of an AI whose
+    /// own RNG is seeded.
+    pub fn from_rng(rng: &mut impl Rng) -> Self {
+        Self::new(rng.gen())
+    }
+
+    /// Creates a random controller seeded from the system's entropy, for when its choices don't
+    /// need to be reproducible (e.g. as a fallback for a controller that failed).
+    pub fn unseeded() -> Self {
+        RandomController {
+            rng: StdRng::from_entropy(),
+        }
+    }
+}
 
 impl<'ctype> PlayerController<'ctype> for RandomController {
     fn choose_option<'g>(
         &mut self,
-        game_view: &GameView<'g, 'ctype>,
+        _game_view: &GameView<'g, 'ctype>,
         choice: &Choice<'ctype>,
     ) -> usize {
-        thread_rng().gen_range(0..choice.num_options(game_view.game_state))
+        self.rng.gen_range(0..choice.num_options())
     }
 }
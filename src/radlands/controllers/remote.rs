@@ -0,0 +1,125 @@
+//! A controller for a human playing remotely, using the client started with `--connect`.
+//!
+//! The engine stays the authority on the game: the client only sees what the server sends it
+//! and only sends back option numbers, which the server validates. The protocol is line-based
+//! text over TCP. The server sends these messages for each choice the remote player makes:
+//!
+//! - `VIEW <text>`: one line of the rendered game view, as seen by the remote player
+//! - `OPTION <number> <text>`: one of the options to choose from
+//! - `CHOOSE <count>`: asks the client to reply with the number of an option (1 to `count`)
+//!
+//! If the client replies with an invalid number, the server sends `CHOOSE` again. If the
+//! connection is lost, the server waits for the client to reconnect.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+
+use tui::widgets::ListItem;
+
+use crate::radlands::*;
+use crate::ui::{render_game_view_text, set_controller_stats, spans_to_string};
+
+use super::monte_carlo::StatsWidget;
+
+/// The width (in columns) of the game view sent to the client.
+const VIEW_WIDTH: u16 = 100;
+
+/// A `PlayerController` for a human who connects over the network.
+pub struct RemoteHumanController {
+    /// The address to listen for the client on.
+    addr: SocketAddr,
+
+    listener: Option<TcpListener>,
+    connection: Option<(BufReader<TcpStream>, TcpStream)>,
+}
+
+impl RemoteHumanController {
+    /// Creates a controller that will wait for a client on the given address when it first
+    /// needs to make a choice.
+    pub fn new(addr: SocketAddr) -> Self {
+        RemoteHumanController {
+            addr,
+            listener: None,
+            connection: None,
+        }
+    }
+
+    /// Returns the connection to the client, waiting for a client to connect if necessary.
+    fn connection(&mut self, player: Player) -> &mut (BufReader<TcpStream>, TcpStream) {
+        if self.connection.is_none() {
+            let lines = vec![ListItem::new(format!(
+                "Waiting for the remote player to connect on {}...",
+                self.addr
+            ))];
+            set_controller_stats(Some(Box::new(StatsWidget { lines })), player);
+
+            let addr = self.addr;
+            let listener = self.listener.get_or_insert_with(|| {
+                TcpListener::bind(addr).expect("Failed to listen for the remote player")
+            });
+            let (stream, _) = listener
+                .accept()
+                .expect("Failed to accept the remote player's connection");
+            let reader = BufReader::new(stream.try_clone().expect("Failed to clone TcpStream"));
+            self.connection = Some((reader, stream));
+
+            set_controller_stats(None, player);
+        }
+        self.connection.as_mut().unwrap()
+    }
+
+    /// Sends the choice to the client and returns the option it chose, or an error if the
+    /// connection was lost.
+    fn try_choose_option<'ctype>(
+        &mut self,
+        game_view: &GameView<'_, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> io::Result<usize> {
+        let game_state = game_view.game_state;
+        let num_options = choice.num_options();
+
+        let mut message = String::new();
+        let view = render_game_view_text(game_state, choice, game_view.player, VIEW_WIDTH);
+        for line in view {
+            message += &format!("VIEW {line}\n");
+        }
+        for group in choice.option_groups(game_state) {
+            let text = spans_to_string(&choice.format_option_group(group, game_state));
+            message += &format!("OPTION {} {text}\n", group.option + 1);
+        }
+
+        let (reader, stream) = self.connection(game_view.player);
+        stream.write_all(message.as_bytes())?;
+        loop {
+            writeln!(stream, "CHOOSE {num_options}")?;
+
+            let mut reply = String::new();
+            if reader.read_line(&mut reply)? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            if let Ok(option_number) = reply.trim().parse::<usize>() {
+                if (1..=num_options).contains(&option_number) {
+                    return Ok(option_number - 1);
+                }
+            }
+        }
+    }
+}
+
+impl<'ctype> PlayerController<'ctype> for RemoteHumanController {
+    fn choose_option<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> usize {
+        loop {
+            match self.try_choose_option(game_view, choice) {
+                Ok(option) => return option,
+                Err(_) => {
+                    // the connection was lost; wait for the client to reconnect
+                    self.connection = None;
+                }
+            }
+        }
+    }
+}
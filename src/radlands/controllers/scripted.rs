@@ -0,0 +1,94 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::radlands::choices::*;
+use crate::radlands::*;
+
+use super::show_caption;
+
+/// A step of a script for `ScriptedController`s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptStep {
+    /// Show a caption in the UI's log, before the next move.
+    Caption(String),
+    /// Choose the option with the given index.
+    Move(usize),
+}
+
+/// A controller that plays a prerecorded script of moves (such as for a demo), pausing before
+/// each move so that it can be followed. Both players' controllers share one script, which
+/// lists every move of the game in order.
+pub struct ScriptedController {
+    script: Arc<Mutex<VecDeque<ScriptStep>>>,
+
+    /// How long to pause before each move that wasn't forced.
+    pace: Duration,
+}
+
+impl ScriptedController {
+    /// Creates a controller that plays from the given shared script.
+    pub fn new(script: Arc<Mutex<VecDeque<ScriptStep>>>, pace: Duration) -> Self {
+        ScriptedController { script, pace }
+    }
+}
+
+impl<'ctype> PlayerController<'ctype> for ScriptedController {
+    fn choose_option<'g>(
+        &mut self,
+        _game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> usize {
+        // show any captions before the move, giving them time to be read
+        let mut pause = Duration::ZERO;
+        let option = loop {
+            let step = self.script.lock().unwrap().pop_front();
+            match step {
+                Some(ScriptStep::Caption(caption)) => {
+                    show_caption(caption);
+                    pause = self.pace * 2;
+                }
+                Some(ScriptStep::Move(option)) => break option,
+                None => panic!("The script ran out of moves before the game ended"),
+            }
+        };
+        assert!(
+            option < choice.num_options(),
+            "The script chose option {option}, but there are only {} options",
+            choice.num_options()
+        );
+
+        if choice.num_options() > 1 {
+            pause = pause.max(self.pace);
+        }
+        thread::sleep(pause);
+        option
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::super::{set_caption_sink, PlayerController};
+    use super::*;
+    use crate::radlands::sandbox::testing::*;
+
+    #[test]
+    fn captions_go_to_the_caption_sink_before_the_move() {
+        let (caption_tx, caption_rx) = mpsc::channel();
+        set_caption_sink(Some(Box::new(move |caption| {
+            caption_tx.send(caption).unwrap();
+        })));
+
+        let (game_state, choice) = position(&["turn p1"]);
+        let script = VecDeque::from([ScriptStep::Caption("Hello".into()), ScriptStep::Move(0)]);
+        let mut controller = ScriptedController::new(Arc::new(Mutex::new(script)), Duration::ZERO);
+        let option = controller.choose_option(&game_state.view_for(Player::Player1), &choice);
+        set_caption_sink(None);
+
+        assert_eq!(option, 0);
+        assert!(caption_rx.try_iter().any(|caption| caption == "Hello"));
+    }
+}
@@ -0,0 +1,174 @@
+//! Exporting the AI's search tree, for debugging its search offline (e.g. with Graphviz).
+
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::json::json_string;
+use crate::radlands::locations::Player;
+use crate::ui;
+
+/// A node of an exported search tree: a state where a player chooses between several options.
+#[derive(Debug, Clone)]
+pub struct SearchTreeNode {
+    /// The player who chooses at this node.
+    pub chooser: Player,
+
+    /// The number of rollouts that passed through this node.
+    pub num_rollouts: u32,
+
+    /// The options at this node.
+    pub options: Vec<SearchTreeOption>,
+}
+
+/// An option at a node of an exported search tree.
+#[derive(Debug, Clone)]
+pub struct SearchTreeOption {
+    /// The option as formatted for display.
+    pub label: String,
+
+    /// The number of rollouts that chose this option.
+    pub num_rollouts: u32,
+
+    /// The chooser's win rate over those rollouts.
+    pub win_rate: f64,
+
+    /// The node reached by choosing this option, if it has been explored (and is within the
+    /// export's limits).
+    pub child: Option<SearchTreeNode>,
+}
+
+impl SearchTreeNode {
+    /// Returns this tree as JSON.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json);
+        json.push('\n');
+        json
+    }
+
+    fn write_json(&self, json: &mut String) {
+        write!(
+            json,
+            r#"{{"chooser":{},"rollouts":{},"options":["#,
+            self.chooser.number(),
+            self.num_rollouts,
+        )
+        .unwrap();
+        for (i, option) in self.options.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            write!(
+                json,
+                r#"{{"label":{},"rollouts":{},"win_rate":{:.4},"child":"#,
+                json_string(&option.label),
+                option.num_rollouts,
+                option.win_rate,
+            )
+            .unwrap();
+            match &option.child {
+                Some(child) => child.write_json(json),
+                None => json.push_str("null"),
+            }
+            json.push('}');
+        }
+        json.push_str("]}");
+    }
+
+    /// Returns this tree as a Graphviz graph. Each node is labeled with its chooser and
+    /// rollout count, and each edge with its option's label, rollout count, and win rate.
+    /// Options that haven't been explored further lead to small unlabeled nodes.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph search_tree {\n    node [shape=box];\n");
+        let mut num_nodes = 0;
+        self.write_dot(&mut dot, &mut num_nodes);
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Writes this node and its subtree, returning the ID of this node.
+    fn write_dot(&self, dot: &mut String, num_nodes: &mut usize) -> usize {
+        let id = *num_nodes;
+        *num_nodes += 1;
+        writeln!(
+            dot,
+            "    n{id} [label=\"Player {}\\n{} rollouts\"];",
+            self.chooser.number(),
+            self.num_rollouts,
+        )
+        .unwrap();
+
+        for option in &self.options {
+            let child_id = match &option.child {
+                Some(child) => child.write_dot(dot, num_nodes),
+                None => {
+                    let leaf_id = *num_nodes;
+                    *num_nodes += 1;
+                    writeln!(dot, "    n{leaf_id} [shape=point];").unwrap();
+                    leaf_id
+                }
+            };
+            writeln!(
+                dot,
+                "    n{id} -> n{child_id} [label=\"{}\\n{} rollouts, {:.1}% wins\"];",
+                dot_escape(&option.label),
+                option.num_rollouts,
+                option.win_rate * 100.0,
+            )
+            .unwrap();
+        }
+        id
+    }
+}
+
+/// Escapes the string for use in a quoted Graphviz label.
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Settings for dumping the search tree after each of the AI's decisions.
+#[derive(Debug, Clone)]
+pub struct TreeDumpSettings {
+    /// The directory to write the files to.
+    pub dir: PathBuf,
+
+    /// The maximum depth of the dumped tree (the root has depth 0).
+    pub max_depth: usize,
+
+    /// The maximum number of nodes in the dumped tree. More-visited options are expanded first.
+    pub max_nodes: usize,
+}
+
+static TREE_DUMP: Mutex<Option<TreeDumpSettings>> = Mutex::new(None);
+
+/// Makes tree-searching controllers dump their search tree after each decision.
+pub fn set_tree_dump(settings: TreeDumpSettings) {
+    *TREE_DUMP.lock().unwrap() = Some(settings);
+}
+
+/// Returns the settings for dumping search trees, if they should be dumped. Analyses requested
+/// in the UI aren't part of the game, so their trees aren't dumped.
+pub fn tree_dump_settings() -> Option<TreeDumpSettings> {
+    if ui::is_analysis_thread() {
+        return None;
+    }
+    TREE_DUMP.lock().unwrap().clone()
+}
+
+/// Writes the tree for a decision to `<name>.json` and `<name>.dot` in the dump directory.
+pub fn dump_tree(settings: &TreeDumpSettings, name: &str, tree: &SearchTreeNode) {
+    let path = settings.dir.join(name);
+    fs::write(path.with_extension("json"), tree.to_json())
+        .expect("Failed to write the search tree (JSON)");
+    fs::write(path.with_extension("dot"), tree.to_dot())
+        .expect("Failed to write the search tree (Graphviz)");
+}
+
+/// Writes the search state for a decision (see `search_trace`) to `<name>.state` in the dump
+/// directory.
+pub fn dump_search_state(settings: &TreeDumpSettings, name: &str, dump: &str) {
+    let path = settings.dir.join(name).with_extension("state");
+    fs::write(path, dump).expect("Failed to write the search state");
+}
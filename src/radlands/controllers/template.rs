@@ -0,0 +1,110 @@
+//! A minimal example of a player controller, as a starting point for writing new ones. It only
+//! uses what `crate::prelude` exports, so it also checks that the prelude is enough to write a
+//! controller with. It can be played against with the `template` controller spec.
+
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::prelude::*;
+
+/// An example controller that plays aggressively: it chooses an option that damages one of the
+/// opponent's cards whenever there is one, and otherwise chooses randomly.
+pub struct TemplateController {
+    // A controller that makes random choices should have its own RNG, rather than using
+    // `thread_rng`, so that its choices can be reproduced from a seed (see `ControllerSpec`).
+    rng: StdRng,
+}
+
+impl TemplateController {
+    pub fn new(seed: u64) -> Self {
+        TemplateController {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl<'ctype> PlayerController<'ctype> for TemplateController {
+    fn choose_option<'g>(
+        &mut self,
+        game_view: &GameView<'g, 'ctype>,
+        choice: &Choice<'ctype>,
+    ) -> usize {
+        // Many choices are forced (e.g. when only one card can be damaged), so there's nothing
+        // to decide. Checking this first also keeps expensive controllers from wasting time.
+        let num_options = choice.num_options();
+        if num_options == 1 {
+            return 0;
+        }
+
+        // Options are identified by their index. `option_target` tells which card an option
+        // targets, if it targets a single card; comparing its owner to the view's player tells
+        // whether it's one of the opponent's cards. (Using an ability targets the controller's
+        // own card, so only damaging is picked out here.)
+        let opponent = game_view.player().other();
+        let attacks = (0..num_options)
+            .filter(|&option| {
+                matches!(
+                    game_view.option_target(choice, option),
+                    Some(target) if target.player() == opponent
+                )
+            })
+            .collect_vec();
+
+        // The chosen index must be less than `num_options`, or the game will panic.
+        match attacks.choose(&mut self.rng) {
+            Some(&option) => option,
+            None => (0..num_options)
+                .collect_vec()
+                .choose(&mut self.rng)
+                .copied()
+                .unwrap(),
+        }
+    }
+
+    // `try_choose_option` and `best_option_so_far` have default implementations. A controller
+    // that searches until a deadline should provide `best_option_so_far`, so that a guarded
+    // controller that runs out of time can still play its best option (see `WithTimeLimit`).
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play_to_end;
+    use crate::radlands::controllers::random::RandomController;
+    use crate::radlands::locations::Player;
+    use crate::radlands::rules::GameRules;
+    use crate::radlands::sandbox::testing::*;
+    use crate::radlands::GameState;
+
+    #[test]
+    fn plays_a_whole_game() {
+        let card_set = card_set();
+        let (mut game_state, choice) = GameState::new_seeded(
+            &card_set.camp_types,
+            &card_set.person_types,
+            &card_set.event_types,
+            GameRules::default(),
+            0,
+        );
+        let mut template = TemplateController::new(0);
+        let mut random = RandomController::new(0);
+        play_to_end(&mut game_state, choice, &mut template, &mut random, None);
+    }
+
+    #[test]
+    fn damages_an_opponents_card_when_it_can() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "person p1 1 1 Looter",
+            "person p2 2 1 Looter",
+        ]);
+        let choice = choose(&mut game_state, &choice, "Use Looter's ability").unwrap();
+        let game_view = game_state.view_for(Player::Player1);
+        let option = TemplateController::new(0).choose_option(&game_view, &choice);
+        let target = game_view.option_target(&choice, option).unwrap();
+        assert_eq!(target.player(), Player::Player2);
+    }
+}
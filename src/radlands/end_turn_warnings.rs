@@ -0,0 +1,273 @@
+//! Advisory warnings for a human player who is about to end their turn, based on a cheap
+//! analysis of the current state: ending the turn with water that could still be spent, with a
+//! damaged camp and an unused way to restore it, or with the opponent able to destroy every
+//! remaining camp on their next turn.
+//!
+//! The lethal check only looks one turn ahead, and only at what the player can see: the plain
+//! damage abilities (the damage icon) of the opponent's ready people (including abilities given
+//! by other cards, such as Argo Yesky's) and camps, paid for with a turn's water. The opponent's
+//! hand, events, and other abilities aren't considered, so it can miss a threat, but when it
+//! warns, the threat is on the board.
+
+use std::fmt;
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use super::abilities::Ability;
+use super::choices::ActionChoice;
+use super::locations::Player;
+use super::player_state::{CampStatus, NonPunkStatus, Person, PlayerState};
+use super::{Action, GameView, IconEffect, WATER_PER_TURN};
+
+/// A reason to reconsider ending the turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndTurnWarning {
+    /// The given amount of water would be left unspent (after taking Water Silo), although
+    /// there are actions that it could pay for.
+    UnspentWater(u32),
+
+    /// A camp is damaged, and an action that restores is available.
+    UnusedRestore,
+
+    /// The opponent's ready damage abilities could destroy all of the player's remaining camps
+    /// on their next turn.
+    OpponentLethal,
+}
+
+impl EndTurnWarning {
+    /// Returns a description of the warning for display.
+    pub fn describe(self) -> String {
+        match self {
+            EndTurnWarning::UnspentWater(water) => {
+                format!("You have {water} unspent water, and there are actions it could pay for.")
+            }
+            EndTurnWarning::UnusedRestore => {
+                "One of your camps is damaged, and you could still restore it.".to_string()
+            }
+            EndTurnWarning::OpponentLethal => {
+                "The damage abilities on your opponent's board could destroy all of your camps \
+                 next turn."
+                    .to_string()
+            }
+        }
+    }
+}
+
+/// Which warnings to check for before a human player ends their turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndTurnWarnings {
+    pub unspent_water: bool,
+    pub unused_restore: bool,
+    pub opponent_lethal: bool,
+}
+
+impl EndTurnWarnings {
+    /// No warnings.
+    pub const NONE: EndTurnWarnings = EndTurnWarnings {
+        unspent_water: false,
+        unused_restore: false,
+        opponent_lethal: false,
+    };
+
+    /// Every warning.
+    pub const ALL: EndTurnWarnings = EndTurnWarnings {
+        unspent_water: true,
+        unused_restore: true,
+        opponent_lethal: true,
+    };
+
+    /// Returns the enabled warnings that apply if the player choosing among the given actions
+    /// ends their turn now.
+    pub fn check(self, game_view: &GameView, action_choice: &ActionChoice) -> Vec<EndTurnWarning> {
+        let mut warnings = Vec::new();
+        if self.unspent_water {
+            if let Some(water) = unspent_water(game_view, action_choice) {
+                warnings.push(EndTurnWarning::UnspentWater(water));
+            }
+        }
+        if self.unused_restore && has_unused_restore(game_view, action_choice) {
+            warnings.push(EndTurnWarning::UnusedRestore);
+        }
+        if self.opponent_lethal && opponent_has_lethal(game_view) {
+            warnings.push(EndTurnWarning::OpponentLethal);
+        }
+        warnings
+    }
+
+    /// Returns the names of the enabled warnings, as written in specs and on the command line.
+    fn names(self) -> impl Iterator<Item = &'static str> {
+        [
+            (self.unspent_water, "water"),
+            (self.unused_restore, "restore"),
+            (self.opponent_lethal, "lethal"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, name)| enabled.then_some(name))
+    }
+}
+
+impl fmt::Display for EndTurnWarnings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if *self == EndTurnWarnings::NONE {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", self.names().join(","))
+        }
+    }
+}
+
+impl FromStr for EndTurnWarnings {
+    type Err = String;
+
+    /// Parses `none`, or a comma-separated list of `water`, `restore`, and `lethal`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut warnings = EndTurnWarnings::NONE;
+        if s == "none" {
+            return Ok(warnings);
+        }
+        for name in s.split(',') {
+            match name {
+                "water" => warnings.unspent_water = true,
+                "restore" => warnings.unused_restore = true,
+                "lethal" => warnings.opponent_lethal = true,
+                _ => return Err(format!("invalid end-turn warning: {name:?}")),
+            }
+        }
+        Ok(warnings)
+    }
+}
+
+/// Returns the water that would be left after ending the turn (which takes Water Silo for 1
+/// water, if it's on the board), if there's any and some available action costs water.
+fn unspent_water(game_view: &GameView, action_choice: &ActionChoice) -> Option<u32> {
+    let water = game_view.game_state.cur_player_water;
+    let silo_cost = if game_view.my_state().has_water_silo { 0 } else { 1 };
+    let unspent = water.saturating_sub(silo_cost);
+    let can_spend = action_choice
+        .actions()
+        .iter()
+        .any(|action| action.cost(game_view) > 0);
+    (unspent > 0 && can_spend).then_some(unspent)
+}
+
+/// Returns whether one of the player's camps is damaged and an available action (a restore
+/// ability or junk effect) could restore it.
+fn has_unused_restore(game_view: &GameView, action_choice: &ActionChoice) -> bool {
+    let has_damaged_camp = game_view
+        .my_state()
+        .columns
+        .iter()
+        .any(|column| column.camp.is_restorable());
+    has_damaged_camp
+        && action_choice.actions().iter().any(|action| match action {
+            Action::UsePersonAbility(ability, _) | Action::UseCampAbility(ability, _) => {
+                ability.icon_effect() == Some(IconEffect::Restore)
+            }
+            Action::UseMimicAbility(loc) => game_view
+                .my_state()
+                .copyable_abilities(game_view, *loc)
+                .iter()
+                .any(|(_, ability)| ability.icon_effect() == Some(IconEffect::Restore)),
+            Action::JunkCard(card) => card.junk_effect() == IconEffect::Restore,
+            _ => false,
+        })
+}
+
+/// Returns whether the opponent's ready damage abilities, paid for with their next turn's
+/// water, deal at least as much damage as it takes to destroy all of the player's camps (and
+/// the people protecting them).
+fn opponent_has_lethal(game_view: &GameView) -> bool {
+    let opponent = game_view.player.other();
+    let damage_needed = damage_to_destroy_camps(game_view.my_state());
+    damage_needed > 0 && opponent_damage(game_view, opponent) >= damage_needed
+}
+
+/// Returns the number of single damages it takes to destroy all of the player's camps,
+/// including destroying the people in front of them (punks and injured people take one damage,
+/// and other people two).
+fn damage_to_destroy_camps(player_state: &PlayerState) -> u32 {
+    player_state
+        .columns
+        .iter()
+        .map(|column| {
+            let camp_damage = match column.camp.status {
+                CampStatus::Undamaged => 2,
+                CampStatus::Damaged => 1,
+                CampStatus::Destroyed => return 0,
+            };
+            let people_damage: u32 = column
+                .people()
+                .map(|person| match person {
+                    Person::Punk { .. } => 1,
+                    Person::NonPunk { .. } if person.is_injured() => 1,
+                    Person::NonPunk { .. } => 2,
+                })
+                .sum();
+            camp_damage + people_damage
+        })
+        .sum()
+}
+
+/// Returns how many damage abilities (the plain damage icon) of the given player's ready people
+/// and camps they can pay for on their next turn, using the cheapest ones first. A person's
+/// abilities include those given to them by other cards (e.g. Argo Yesky's, which punks get too).
+fn opponent_damage(game_view: &GameView, opponent: Player) -> u32 {
+    let opponent_state = game_view.game_state.player(opponent);
+    let opponent_view = game_view.game_state.view_for(opponent);
+    let ready_people_abilities = opponent_state
+        .enumerate_people()
+        .filter_map(|(loc, person)| {
+            let own_abilities: Vec<&dyn Ability> = match person {
+                Person::Punk { is_ready: true, .. } => Vec::new(),
+                Person::NonPunk {
+                    person_type,
+                    status: NonPunkStatus::Ready,
+                    ..
+                } => person_type.abilities.iter().map(|a| a.as_ref()).collect(),
+                _ => return None,
+            };
+            Some(
+                own_abilities
+                    .into_iter()
+                    .chain(opponent_state.granted_abilities(loc))
+                    .collect_vec(),
+            )
+        });
+    let ready_camp_abilities = opponent_state
+        .columns
+        .iter()
+        .filter(|column| column.camp.is_ready())
+        .map(|column| {
+            column
+                .camp
+                .camp_type
+                .abilities
+                .iter()
+                .map(|a| a.as_ref())
+                .collect_vec()
+        });
+
+    // each card can use one ability per turn, so take its cheapest damage ability
+    let costs = ready_people_abilities
+        .chain(ready_camp_abilities)
+        .filter_map(|abilities| {
+            abilities
+                .into_iter()
+                .filter(|ability| ability.icon_effect() == Some(IconEffect::Damage))
+                .map(|ability| ability.cost(&opponent_view))
+                .min()
+        })
+        .sorted();
+
+    let mut water = WATER_PER_TURN + u32::from(opponent_state.has_water_silo);
+    let mut damage = 0;
+    for cost in costs {
+        if cost > water {
+            break;
+        }
+        water -= cost;
+        damage += 1;
+    }
+    damage
+}
@@ -5,38 +5,54 @@ use by_address::ByAddress;
 use itertools::Itertools;
 use tui::text::Span;
 
+use crate::locale::{translate, Section};
+
 use super::choices::*;
-use super::locations::Player;
+use super::player_state::PersonInteraction;
 use super::styles::*;
-use super::{GameResult, GameViewMut, IconEffect};
+use super::{GameResult, GameViewMut, IconEffect, OnJunk};
 
 /// A type of event card.
 pub struct EventType {
-    /// The event's name.
+    /// The event's name in English, which never changes (see `display_name`).
     pub name: &'static str,
 
+    /// The index of this event type in the list of event types, which identifies it.
+    pub id: usize,
+
     /// How many of this event type are in the deck.
     pub num_in_deck: u32,
 
     /// The event's junk effect.
     pub junk_effect: IconEffect,
 
+    /// The event's own behavior when junked, if any.
+    pub on_junk: Option<OnJunk>,
+
     /// The water cost to play this event.
     pub cost: u32,
 
     /// The number of turns this event resolves in. (Zero is immediate.)
     pub resolve_turns: u8,
 
+    /// The text of the event's effect when it resolves (in English).
+    pub description: &'static str,
+
+    /// Whether the event's effect is optional ("may"), so that its owner is asked whether to
+    /// resolve it (see `perform_optional_effect`). Either way, the event leaves play.
+    pub optional: bool,
+
     /// The handler function containing the logic to resolve this event.
     /// Takes a view from the perspective of this event's owner.
     pub on_resolve:
         for<'g, 'ctype> fn(GameViewMut<'g, 'ctype>) -> Result<ChoiceFuture<'g, 'ctype>, GameResult>,
 }
 
-// hash references by address
+// hash references by ID, which is cheap, stable across runs of the program, and (within a list
+// of card types) agrees with comparing by address
 impl Hash for &EventType {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        ByAddress(*self).hash(state);
+        self.id.hash(state);
     }
 }
 
@@ -54,41 +70,73 @@ impl fmt::Debug for EventType {
     }
 }
 
+impl EventType {
+    /// Returns the event's name in the loaded locale.
+    pub fn display_name(&self) -> &'static str {
+        translate(Section::Event, self.name)
+    }
+
+    /// Resolves this event's effect for its owner (after it has left its slot), asking them
+    /// first if the effect is optional.
+    pub fn resolve<'g, 'ctype: 'g>(
+        &'ctype self,
+        game_view: GameViewMut<'g, 'ctype>,
+    ) -> Result<ChoiceFuture<'g, 'ctype>, GameResult> {
+        game_view.game_state.turn_state.events_resolved += 1;
+        let future = if self.optional {
+            let player = game_view.player;
+            let on_resolve = self.on_resolve;
+            let source = self.styled_name();
+            perform_optional_effect(game_view.game_state, player, source, move |game_state| {
+                on_resolve(game_state.view_for_mut(player))
+            })?
+        } else {
+            (self.on_resolve)(game_view)?
+        };
+        Ok(future.in_context(ChoiceContext::ResolveEvent(self)))
+    }
+}
+
 impl StyledName for EventType {
     /// Returns this event's name, styled for display.
     fn styled_name(&self) -> Span<'static> {
-        Span::styled(self.name, *EVENT)
+        Span::styled(self.display_name(), *EVENT)
     }
 }
 
 pub fn get_event_types() -> Vec<EventType> {
-    vec![
+    let mut event_types = vec![
         EventType {
+            id: 0, // assigned below
             name: "Strafe",
             num_in_deck: 2,
             junk_effect: IconEffect::Draw,
+            on_junk: None,
             cost: 2,
             resolve_turns: 0,
-            // Injure all unprotected enemies
+            description: "Injure all unprotected enemies",
+            optional: false,
             on_resolve: |mut game_view| {
                 game_view.injure_all_unprotected_enemies();
                 Ok(ChoiceFuture::immediate(game_view.game_state))
             },
         },
         EventType {
+            id: 0, // assigned below
             name: "Napalm",
             num_in_deck: 2,
             junk_effect: IconEffect::Restore,
+            on_junk: None,
             cost: 2,
             resolve_turns: 1,
-            // Destroy all enemies in one column
+            description: "Destroy all enemies in one column",
+            optional: false,
             on_resolve: |game_view| {
-                let cols_with_people = game_view
-                    .other_state()
-                    .enumerate_columns()
-                    .filter(|(_, col)| col.people().next().is_some())
-                    .map(|(col_idx, _)| col_idx)
-                    .collect_vec();
+                let cols_with_people = DamageColumnChoice::target_columns(
+                    game_view.game_state,
+                    game_view.player,
+                    true,
+                );
                 let future = if cols_with_people.is_empty() {
                     // no opponent columns have people, so resolving this event is a no-op
                     ChoiceFuture::immediate(game_view.game_state)
@@ -99,31 +147,35 @@ pub fn get_event_types() -> Vec<EventType> {
             },
         },
         EventType {
+            id: 0, // assigned below
             name: "Bombardment",
             num_in_deck: 2,
             junk_effect: IconEffect::Restore,
+            on_junk: None,
             cost: 4,
             resolve_turns: 3,
-            // Damage all the opponent's camps. Then, draw for each destroyed camp they have
+            description:
+                "Damage all the opponent's camps. Then, draw for each destroyed camp they have",
+            optional: false,
             on_resolve: |mut game_view| {
-                // damage the camps and count the number of destroyed camps
-                let mut num_destroyed_camps = 0;
-                for column in &mut game_view.other_state_mut().columns {
-                    column.camp.damage(false);
-                    if column.camp.is_destroyed() {
-                        num_destroyed_camps += 1;
-                    }
-                }
-
-                // check win condition
-                if num_destroyed_camps == 3 {
-                    return Err(match game_view.player {
-                        Player::Player1 => GameResult::P1Wins,
-                        Player::Player2 => GameResult::P2Wins,
-                    });
-                }
+                // damage the camps that aren't already destroyed (which ends the game if it
+                // destroys the last one)
+                let opponent = game_view.player.other();
+                let camp_locs = game_view
+                    .other_state()
+                    .enumerate_camps()
+                    .filter(|(_, camp)| !camp.is_destroyed())
+                    .map(|(loc, _)| loc.for_player(opponent))
+                    .collect_vec();
+                game_view.game_state.damage_cards_at(camp_locs, false)?;
 
-                // draw cards
+                // draw a card for each of their destroyed camps
+                let num_destroyed_camps = game_view
+                    .other_state()
+                    .columns
+                    .iter()
+                    .filter(|column| column.camp.is_destroyed())
+                    .count();
                 for _ in 0..num_destroyed_camps {
                     game_view.draw_card_into_hand()?;
                 }
@@ -131,5 +183,211 @@ pub fn get_event_types() -> Vec<EventType> {
                 Ok(ChoiceFuture::immediate(game_view.game_state))
             },
         },
-    ]
+        EventType {
+            id: 0, // assigned below
+            name: "Banish",
+            num_in_deck: 2,
+            junk_effect: IconEffect::Raid,
+            on_junk: None,
+            cost: 1,
+            resolve_turns: 1,
+            description: "Destroy any enemy (even a protected one)",
+            optional: false,
+            on_resolve: |game_view| {
+                let future = if game_view
+                    .other_state()
+                    .has_person_counting_for(PersonInteraction::Destroy)
+                {
+                    game_view.destroy_any_enemy_person().ignore_result()
+                } else {
+                    // the opponent has no people, so resolving this event is a no-op
+                    ChoiceFuture::immediate(game_view.game_state)
+                };
+                Ok(future)
+            },
+        },
+        EventType {
+            id: 0, // assigned below
+            name: "Uprising",
+            num_in_deck: 2,
+            junk_effect: IconEffect::Injure,
+            on_junk: None,
+            cost: 1,
+            resolve_turns: 2,
+            description: "Gain 3 punks (as many as there is room for)",
+            optional: false,
+            on_resolve: |game_view| game_view.gain_punks(3),
+        },
+        EventType {
+            id: 0, // assigned below
+            name: "Interrogate",
+            num_in_deck: 2,
+            junk_effect: IconEffect::Water,
+            on_junk: None,
+            cost: 1,
+            resolve_turns: 0,
+            description: "Draw 4, then discard 3 of them",
+            optional: false,
+            on_resolve: |mut game_view| {
+                // only the drawn cards can be discarded
+                let drawn = game_view.draw_cards_into_hand(4)?;
+                Ok(DiscardChoice::discard_n_future(
+                    game_view.game_state,
+                    game_view.player,
+                    Some(drawn),
+                    3,
+                ))
+            },
+        },
+        EventType {
+            id: 0, // assigned below
+            name: "Truce",
+            num_in_deck: 2,
+            junk_effect: IconEffect::Injure,
+            on_junk: None,
+            cost: 2,
+            resolve_turns: 0,
+            description: "Return every person to their owner's hand (punks are destroyed)",
+            optional: false,
+            on_resolve: |game_view| {
+                game_view.game_state.return_all_people_to_hands();
+                Ok(ChoiceFuture::immediate(game_view.game_state))
+            },
+        },
+    ];
+    for (id, event_type) in event_types.iter_mut().enumerate() {
+        event_type.id = id;
+    }
+    event_types
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::locations::Player::{Player1, Player2};
+    use super::super::player_state::CampStatus;
+    use super::super::sandbox::testing::*;
+    use super::super::GameState;
+    use super::*;
+
+    #[test]
+    fn napalm_destroys_only_the_people_in_a_column() {
+        let (mut game_state, choice) = position(&[
+            "turn p2",
+            "event p1 1 Napalm",
+            "person p2 1 1 punk",
+            "person p2 1 2 Looter",
+            "person p2 3 1 Muse",
+        ]);
+        let discard_count = game_state.discard.len();
+
+        // Napalm resolves at the start of its owner's turn, and only offers columns with people
+        let choice = choose(&mut game_state, &choice, "End turn").unwrap();
+        assert_eq!(choice.chooser(&game_state), Player1);
+        assert_eq!(
+            labels(&game_state, &choice),
+            [
+                "Destroy people in opponent's column 0 (resolving Napalm)",
+                "Destroy people in opponent's column 2 (resolving Napalm)",
+            ],
+        );
+
+        // both people are destroyed (including the protected punk), but not the camp
+        let choice = choose(
+            &mut game_state,
+            &choice,
+            "Destroy people in opponent's column 0",
+        );
+        assert!(choice.is_ok());
+        let column = &game_state.player(Player2).columns[0];
+        assert!(column.person_slots.iter().all(|slot| slot.is_none()));
+        assert_eq!(column.camp.status, CampStatus::Undamaged);
+        assert!(game_state.player(Player2).columns[2].person_slots[0].is_some());
+        // (plus Napalm itself)
+        assert_eq!(game_state.discard.len(), discard_count + 3);
+    }
+
+    #[test]
+    fn napalm_does_nothing_without_enemy_people() {
+        let (mut game_state, choice) = position(&["turn p2", "event p1 1 Napalm"]);
+        let camps_before = game_state.player(Player2).columns.clone();
+
+        // with no column to choose, Napalm just leaves play
+        let choice = choose(&mut game_state, &choice, "End turn").unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(game_state.player(Player2).columns, camps_before);
+        assert_eq!(game_state.player(Player1).events, [None, None, None]);
+    }
+
+    #[test]
+    fn bombardment_destroying_a_reactor_fires_its_trigger() {
+        let (mut game_state, choice) = position(&[
+            "turn p2",
+            "event p1 1 Bombardment",
+            "camp p2 1 Reactor",
+            "status p2 1 damaged",
+            "person p1 1 1 Looter",
+            "person p2 2 1 Muse",
+        ]);
+
+        // Bombardment resolves at the start of its owner's turn, destroying the damaged Reactor
+        let choice = choose(&mut game_state, &choice, "End turn").unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        let camp_statuses = game_state
+            .player(Player2)
+            .columns
+            .iter()
+            .map(|column| column.camp.status)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            camp_statuses,
+            [
+                CampStatus::Destroyed,
+                CampStatus::Damaged,
+                CampStatus::Damaged
+            ]
+        );
+        assert!(game_state.player(Player1).people().next().is_none());
+        assert!(game_state.player(Player2).people().next().is_none());
+    }
+
+    #[test]
+    fn truce_returns_people_to_their_owners_hands_and_destroys_punks() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "hand p1 clear",
+            "hand p1 add Truce",
+            "hand p2 clear",
+            "person p1 1 1 Looter",
+            "status p1 1 1 injured",
+            "person p1 2 1 punk",
+            "person p2 1 1 Muse",
+            "person p2 1 2 punk",
+        ]);
+        let discard_len = game_state.discard.len();
+        let hand = |game_state: &GameState, player| {
+            game_state
+                .player(player)
+                .hand
+                .iter()
+                .map(|(card, count)| (card.name(), count))
+                .collect::<Vec<_>>()
+        };
+
+        let choice = choose(&mut game_state, &choice, "Play Truce").unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert!(game_state.player(Player1).people().next().is_none());
+        assert!(game_state.player(Player2).people().next().is_none());
+        assert_eq!(hand(&game_state, Player1), [("Looter", 1)]);
+        assert_eq!(hand(&game_state, Player2), [("Muse", 1)]);
+        // (the punks, plus Truce itself)
+        assert_eq!(game_state.discard.len(), discard_len + 3);
+
+        // the injury was lost along with the person
+        let choice = choose(&mut game_state, &choice, "Play Looter").unwrap();
+        let choice = choice.choose(&mut game_state, 0).unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        let looter = game_state.player(Player1).people().next().unwrap();
+        assert!(!looter.is_injured());
+    }
 }
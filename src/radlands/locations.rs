@@ -200,6 +200,16 @@ impl CardLocation {
     pub const fn player_loc(self) -> PlayerCardLocation {
         PlayerCardLocation::new(self.column, self.row)
     }
+
+    /// Converts the location of a person to a PlayLocation by removing the player field.
+    /// Panics if the location is a camp.
+    pub fn play_loc(self) -> PlayLocation {
+        let row = self
+            .row
+            .to_person_index()
+            .expect("Tried to get the play location of a camp");
+        PlayLocation::new(self.column, row)
+    }
 }
 
 impl fmt::Display for CardLocation {
@@ -1,35 +1,88 @@
 pub mod abilities;
 pub mod camps;
+pub mod card_set;
 pub mod choices;
 pub mod controllers;
+pub mod end_turn_warnings;
 pub mod events;
 pub mod locations;
+pub mod mulligan;
 pub mod observed_state;
 pub mod people;
 pub mod player_state;
+pub mod rules;
+pub mod sandbox;
+pub mod state_delta;
 pub mod styles;
+pub mod turn_state;
+pub mod turn_status;
+pub mod wire;
 
 use by_address::ByAddress;
 use itertools::Itertools;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::{thread_rng, Rng};
+use rand::{thread_rng, Rng, SeedableRng};
 use std::cmp::Ordering;
+use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::mem;
+use std::ptr;
 use tui::text::{Span, Spans};
 
-use crate::cards::Cards;
+use crate::cards::{Cards, DiscardPile};
+use crate::locale::{translate, Section};
 use crate::make_spans;
-
-use self::abilities::Ability;
-use self::camps::CampType;
-use self::choices::{Choice, ChoiceFuture, DamageChoice, PlayChoice, RestoreChoice};
+use crate::metrics::{self, Counter};
+use crate::stable_hash::StableHasher;
+
+use self::abilities::{perform_ability, Ability};
+use self::camps::{CampTrigger, CampType, SpecialCampType};
+use self::choices::{
+    CampSelectChoice, Choice, ChoiceFuture, CopyAbilityChoice, DamageChoice, MoveDestinationChoice,
+    MovePersonChoice, MulliganChoice, MultiSelectChoice, OptionGroup, PlayChoice, RestoreChoice,
+    SelectTarget,
+};
 use self::controllers::PlayerController;
 use self::events::EventType;
 use self::locations::*;
-use self::people::{PersonType, SpecialType};
+use self::people::{PassiveEffect, PersonType};
 use self::player_state::*;
+use self::rules::GameRules;
+use self::state_delta::StateDelta;
 use self::styles::*;
+use self::turn_state::TurnState;
+
+/// The amount of water that a player has at the start of each of their turns.
+pub const WATER_PER_TURN: u32 = 3;
+
+/// The number of camps that each player is dealt at the start of a game, to keep 3 of.
+pub const CAMPS_DEALT: usize = 6;
+
+/// The state of the camp draft at the start of a game, while the players choose which 3 of the
+/// camps they were dealt to keep (see `GameState::camp_draft`). Its lists are indexed as in
+/// `[player1, player2]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CampDraft<'ctype> {
+    /// The camps dealt to each player.
+    dealt: [Vec<&'ctype CampType>; 2],
+
+    /// The camps that weren't dealt to either player.
+    undealt: Vec<&'ctype CampType>,
+
+    /// The camps that each player has chosen to keep so far.
+    kept: [Option<[&'ctype CampType; 3]>; 2],
+}
+
+impl CampDraft<'_> {
+    /// Returns the index of the given player's entries in the draft's lists.
+    fn index(player: Player) -> usize {
+        match player {
+            Player::Player1 => 0,
+            Player::Player2 => 1,
+        }
+    }
+}
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum GameResult {
@@ -38,13 +91,58 @@ pub enum GameResult {
     Tie,
 }
 
-#[derive(Clone)]
+/// How a game was ended early by its players, rather than by the rules.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EarlyEnding {
+    /// The given player resigned, so the other player wins.
+    Resigned(Player),
+
+    /// The given player offered a tie, and the other player accepted.
+    TieAgreed { offered_by: Player },
+}
+
+impl EarlyEnding {
+    /// Returns the result of the game that ended this way.
+    pub fn result(self) -> GameResult {
+        match self {
+            EarlyEnding::Resigned(Player::Player1) => GameResult::P2Wins,
+            EarlyEnding::Resigned(Player::Player2) => GameResult::P1Wins,
+            EarlyEnding::TieAgreed { .. } => GameResult::Tie,
+        }
+    }
+
+    /// Describes how the game ended, e.g. "Player 1 resigned".
+    pub fn describe(self) -> String {
+        match self {
+            EarlyEnding::Resigned(player) => format!("Player {} resigned", player.number()),
+            EarlyEnding::TieAgreed { offered_by } => format!(
+                "Player {} offered a tie, and Player {} accepted",
+                offered_by.number(),
+                offered_by.other().number()
+            ),
+        }
+    }
+}
+
+/// The complete state of a game, including information hidden from the players (such as the
+/// order of the deck).
+///
+/// Two game states are equal only if everything about them is the same, including the state of
+/// the RNG, except for which punk has which face-down card (see `FaceDown`). The `Hash`
+/// implementation covers everything except the RNG, the rules (which are fixed for the whole
+/// game), and the turn number (which doesn't affect play), and (since card types are hashed by
+/// ID) is stable across runs of the program. This is distinct from
+/// `ObservedState`, which only covers what a single player can observe.
+#[derive(Debug, PartialEq, Eq)]
 pub struct GameState<'ctype> {
     player1: PlayerState<'ctype>,
     player2: PlayerState<'ctype>,
 
     deck: Vec<PersonOrEventType<'ctype>>,
-    discard: Vec<PersonOrEventType<'ctype>>,
+    discard: DiscardPile<PersonOrEventType<'ctype>>,
+
+    /// The number of the current turn, starting from 1 for the first player's first turn.
+    pub turn_number: u32,
 
     /// The identity of the player whose turn it currently is.
     pub cur_player: Player,
@@ -52,24 +150,158 @@ pub struct GameState<'ctype> {
     /// The amount of water that the current player has available for use.
     pub cur_player_water: u32,
 
-    /// Whether the current player has used the generic "pay 2 water to draw a card"
-    /// ability this turn.
-    has_paid_to_draw: bool,
-
-    /// Whether the current player has played an event this turn.
-    has_played_event: bool,
+    /// What has happened so far in the current turn.
+    turn_state: TurnState,
 
     /// Whether the the deck has been reshuffled from the discard pile in this game.
     has_reshuffled_deck: bool,
+
+    /// The source of all randomness in the game's rules (shuffling, dealing, etc.).
+    rng: StdRng,
+
+    /// The variable parts of the rules that the game is played with.
+    rules: GameRules,
+
+    /// The camps being chosen from, until both players have chosen which to keep. Meanwhile,
+    /// both boards hold the same placeholder camps, which give nothing away.
+    camp_draft: Option<CampDraft<'ctype>>,
+}
+
+impl Clone for GameState<'_> {
+    fn clone(&self) -> Self {
+        metrics::increment(Counter::GameStateClones);
+        GameState {
+            player1: self.player1.clone(),
+            player2: self.player2.clone(),
+            deck: self.deck.clone(),
+            discard: self.discard.clone(),
+            turn_number: self.turn_number,
+            cur_player: self.cur_player,
+            cur_player_water: self.cur_player_water,
+            turn_state: self.turn_state,
+            has_reshuffled_deck: self.has_reshuffled_deck,
+            rng: self.rng.clone(),
+            rules: self.rules,
+            camp_draft: self.camp_draft.clone(),
+        }
+    }
+}
+
+impl Hash for GameState<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // the RNG can't be hashed, and the turn number is left out so that otherwise identical
+        // states reached on different turns are treated as the same; everything else is included
+        let GameState {
+            player1,
+            player2,
+            deck,
+            discard,
+            turn_number: _,
+            cur_player,
+            cur_player_water,
+            turn_state,
+            has_reshuffled_deck,
+            rng: _,
+            rules: _,
+            camp_draft,
+        } = self;
+        player1.hash(state);
+        player2.hash(state);
+        deck.hash(state);
+        discard.hash(state);
+        cur_player.hash(state);
+        cur_player_water.hash(state);
+        turn_state.hash(state);
+        has_reshuffled_deck.hash(state);
+        if let Some(camp_draft) = camp_draft {
+            // (hashed only while there is one, so that states after the draft hash as before)
+            camp_draft.hash(state);
+        }
+    }
 }
 
 impl<'g, 'ctype: 'g> GameState<'ctype> {
-    /// Creates a game state and initial Choice for a random new game.
+    /// Creates a game state and initial Choice for a random new game, played with the given
+    /// rules. If the rules allow mulligans, each player (starting with the first player) is
+    /// asked whether to keep their opening hand before the first turn begins.
     pub fn new(
         camp_types: &'ctype [CampType],
         person_types: &'ctype [PersonType],
         event_types: &'ctype [EventType],
+        rules: GameRules,
+    ) -> (Self, Choice<'ctype>) {
+        let seed = thread_rng().gen();
+        Self::new_seeded(camp_types, person_types, event_types, rules, seed)
+    }
+
+    /// Creates a game state and initial Choice for a new game, using the given seed for all
+    /// of the game's randomness. Playing the same options from two games created with the
+    /// same seed (and card types) always produces identical games.
+    pub fn new_seeded(
+        camp_types: &'ctype [CampType],
+        person_types: &'ctype [PersonType],
+        event_types: &'ctype [EventType],
+        rules: GameRules,
+        seed: u64,
     ) -> (Self, Choice<'ctype>) {
+        Self::set_up(
+            camp_types,
+            person_types,
+            event_types,
+            rules,
+            StdRng::seed_from_u64(seed),
+        )
+        .start()
+    }
+
+    /// Creates a game state and initial Choice for a new game in a mirrored pair. The setup
+    /// (deck order, camps, hands, and first player) is determined by `setup_seed`, and the rest
+    /// of the game's randomness by `play_seed`. If `mirrored` is true, the players' setups are
+    /// swapped, so that each player starts with exactly what the other player started with in
+    /// the unmirrored game.
+    pub fn new_mirrorable(
+        camp_types: &'ctype [CampType],
+        person_types: &'ctype [PersonType],
+        event_types: &'ctype [EventType],
+        rules: GameRules,
+        setup_seed: u64,
+        play_seed: u64,
+        mirrored: bool,
+    ) -> (Self, Choice<'ctype>) {
+        let mut game_state = Self::set_up(
+            camp_types,
+            person_types,
+            event_types,
+            rules,
+            StdRng::seed_from_u64(setup_seed),
+        );
+        if mirrored {
+            mem::swap(&mut game_state.player1, &mut game_state.player2);
+            game_state.cur_player = game_state.cur_player.other();
+            if let Some(camp_draft) = &mut game_state.camp_draft {
+                camp_draft.dealt.swap(0, 1);
+            }
+        }
+        game_state.rng = StdRng::seed_from_u64(play_seed);
+        game_state.start()
+    }
+
+    /// Shuffles the deck, deals the camps and hands, and picks the first player, using the
+    /// given RNG. The RNG is kept as the game's source of randomness.
+    ///
+    /// Each player is dealt `CAMPS_DEALT` camps, or half of the camp types if there aren't
+    /// enough for that. If they have more than 3 to choose from, the camps dealt are kept in a
+    /// camp draft, and the hands are left to be dealt once the players have chosen which to keep
+    /// (see `start`); until then, both boards hold the first 3 camp types as placeholders.
+    fn set_up(
+        camp_types: &'ctype [CampType],
+        person_types: &'ctype [PersonType],
+        event_types: &'ctype [EventType],
+        rules: GameRules,
+        mut rng: StdRng,
+    ) -> Self {
+        metrics::increment(Counter::Games);
+
         // populate the deck and shuffle it
         let mut deck = Vec::new();
         for person_type in person_types {
@@ -82,36 +314,295 @@ impl<'g, 'ctype: 'g> GameState<'ctype> {
                 deck.push(PersonOrEventType::Event(event_type));
             }
         }
-        deck.shuffle(&mut thread_rng());
+        deck.shuffle(&mut rng);
 
-        // pick 3 camps for each player at random
-        let chosen_camps = camp_types
-            .choose_multiple(&mut thread_rng(), 6)
+        // deal the camps for each player at random
+        let num_dealt = CAMPS_DEALT.min(camp_types.len() / 2);
+        assert!(num_dealt >= 3, "There aren't enough camp types for a game");
+        let dealt_camps = camp_types
+            .choose_multiple(&mut rng, 2 * num_dealt)
             .collect_vec();
-        let p1_camps = &chosen_camps[..3];
-        let p2_camps = &chosen_camps[3..];
+        let (p1_dealt, p2_dealt) = dealt_camps.split_at(num_dealt);
+        let (player1, player2, camp_draft) = if num_dealt > 3 {
+            // the camps dealt stay hidden until both players have chosen which to keep
+            let placeholders = [&camp_types[0], &camp_types[1], &camp_types[2]];
+            let undealt = camp_types
+                .iter()
+                .filter(|&camp_type| !dealt_camps.iter().any(|&dealt| ptr::eq(dealt, camp_type)))
+                .collect_vec();
+            let camp_draft = CampDraft {
+                dealt: [p1_dealt.to_vec(), p2_dealt.to_vec()],
+                undealt,
+                kept: [None, None],
+            };
+            (
+                PlayerState::new(placeholders),
+                PlayerState::new(placeholders),
+                Some(camp_draft),
+            )
+        } else {
+            // there's nothing to choose, so the hands can be dealt right away
+            let mut player1 = PlayerState::new([p1_dealt[0], p1_dealt[1], p1_dealt[2]]);
+            let mut player2 = PlayerState::new([p2_dealt[0], p2_dealt[1], p2_dealt[2]]);
+            player1.draw_opening_hand(&mut deck);
+            player2.draw_opening_hand(&mut deck);
+            (player1, player2, None)
+        };
 
-        let mut game_state = GameState {
-            player1: PlayerState::new(p1_camps, &mut deck),
-            player2: PlayerState::new(p2_camps, &mut deck),
+        GameState {
+            player1,
+            player2,
             deck,
-            discard: Vec::new(),
-            cur_player: thread_rng().gen(), // randomly pick which player goes first
-            cur_player_water: 1,            // the first player gets 1 water for the first turn
-            has_paid_to_draw: false,
-            has_played_event: false,
+            discard: DiscardPile::new(),
+            turn_number: 1,
+            cur_player: rng.gen(), // randomly pick which player goes first
+            cur_player_water: 1,   // the first player gets 1 water for the first turn
+            turn_state: TurnState::default(),
             has_reshuffled_deck: false,
+            rng,
+            rules,
+            camp_draft,
+        }
+    }
+
+    /// Starts a game that has just been set up, returning the initial Choice. If camps were
+    /// dealt to choose from, the players first choose which to keep (see `camp_draft`).
+    fn start(mut self) -> (Self, Choice<'ctype>) {
+        let first_player = self.cur_player;
+        let allows_mulligans = self.rules.allows_mulligans();
+        let camp_draft = if self.camp_draft.is_some() {
+            Self::camp_draft(first_player, self.camp_sets_for(first_player))
+        } else {
+            ChoiceFuture::immediate(&mut self)
         };
+        let choice = camp_draft
+            .then_future_chain(move |game_state, ()| {
+                if allows_mulligans {
+                    // ask each player whether to mulligan
+                    Ok(MulliganChoice::future(first_player)
+                        .then_future_chain(move |_, _| {
+                            Ok(MulliganChoice::future(first_player.other()))
+                        })
+                        .ignore_result())
+                } else {
+                    Ok(ChoiceFuture::immediate(game_state))
+                }
+            })
+            .then(|game_state, ()| game_state.start_first_turn())
+            .expect("Setting up the game should not immediately end it");
+        (self, choice)
+    }
 
-        // have the current player draw a card for the start of their turn
-        game_state
-            .view_for_cur_mut()
-            .draw_card_into_hand()
-            .expect("The first draw of the game should always succeed");
+    /// Returns a future that has each player choose which 3 of the camps they were dealt to
+    /// keep (the first player first), then deals the opening hands. The camps chosen are only
+    /// put in play once both players have chosen, so that neither choice is made knowing the
+    /// other. The first player chooses from `first_camp_sets`.
+    ///
+    /// The deals and the choices are kept in the game state's camp draft rather than in the
+    /// choices' callbacks, so that they can be randomized like the rest of the hidden state
+    /// (see `randomize_camp_draft`).
+    fn camp_draft(
+        first_player: Player,
+        first_camp_sets: Vec<[&'ctype CampType; 3]>,
+    ) -> ChoiceFuture<'g, 'ctype> {
+        Self::keep_camps(first_player, first_camp_sets).then_future_chain(move |game_state, ()| {
+            let second_player = first_player.other();
+            Ok(
+                Self::keep_camps(second_player, game_state.camp_sets_for(second_player))
+                    .then_future(move |game_state, ()| {
+                        let camp_draft = game_state
+                            .camp_draft
+                            .take()
+                            .expect("The camp draft should still be going on");
+                        for player in [Player::Player1, Player::Player2] {
+                            let kept = camp_draft.kept[CampDraft::index(player)]
+                                .expect("Both players should have chosen their camps");
+                            *game_state.player_mut(player) = PlayerState::new(kept);
+                        }
+                        game_state.deal_opening_hands(first_player);
+                        Ok(())
+                    }),
+            )
+        })
+    }
+
+    /// Returns a future that has the given player choose one of the given sets of camps to keep,
+    /// recording it in the camp draft.
+    fn keep_camps(
+        player: Player,
+        camp_sets: Vec<[&'ctype CampType; 3]>,
+    ) -> ChoiceFuture<'g, 'ctype> {
+        CampSelectChoice::future(player, camp_sets).then_future(move |game_state, camps| {
+            let camp_draft = game_state
+                .camp_draft
+                .as_mut()
+                .expect("The camp draft should still be going on");
+            camp_draft.kept[CampDraft::index(player)] = Some(camps);
+            Ok(())
+        })
+    }
 
-        // return the game state and initial Choice of actions
-        let choice = Choice::new_actions(&mut game_state);
-        (game_state, choice)
+    /// Returns the sets of 3 camps that the given player can keep from the camps they were
+    /// dealt, each in the order they were dealt.
+    fn camp_sets_for(&self, player: Player) -> Vec<[&'ctype CampType; 3]> {
+        let camp_draft = self
+            .camp_draft
+            .as_ref()
+            .expect("The camp draft should still be going on");
+        camp_draft.dealt[CampDraft::index(player)]
+            .iter()
+            .copied()
+            .combinations(3)
+            .map(|camps| [camps[0], camps[1], camps[2]])
+            .collect_vec()
+    }
+
+    /// Re-deals the camps that the given player can't see during the camp draft: the camps
+    /// dealt to their opponent (from the camps not dealt to the player), and the camps their
+    /// opponent has chosen to keep, if they have chosen.
+    fn randomize_camp_draft(&mut self, player: Player, rng: &mut impl Rng) {
+        let camp_draft = match &mut self.camp_draft {
+            Some(camp_draft) => camp_draft,
+            None => return,
+        };
+        let opponent = CampDraft::index(player.other());
+        let num_dealt = camp_draft.dealt[opponent].len();
+        let mut unseen = mem::take(&mut camp_draft.dealt[opponent]);
+        unseen.append(&mut camp_draft.undealt);
+        unseen.shuffle(rng);
+        camp_draft.undealt = unseen.split_off(num_dealt);
+        camp_draft.dealt[opponent] = unseen;
+        if camp_draft.kept[opponent].is_some() {
+            let dealt = &camp_draft.dealt[opponent];
+            let mut kept = rand::seq::index::sample(rng, dealt.len(), 3).into_vec();
+            kept.sort_unstable();
+            camp_draft.kept[opponent] = Some([dealt[kept[0]], dealt[kept[1]], dealt[kept[2]]]);
+        }
+    }
+
+    /// Has each player draw their opening hand, starting with the given player.
+    fn deal_opening_hands(&mut self, first_player: Player) {
+        for player in [first_player, first_player.other()] {
+            let player_state = match player {
+                Player::Player1 => &mut self.player1,
+                Player::Player2 => &mut self.player2,
+            };
+            player_state.draw_opening_hand(&mut self.deck);
+        }
+    }
+
+    /// Has the first player draw a card for the start of their turn.
+    /// Returns the initial Choice of actions.
+    fn start_first_turn(&mut self) -> Result<Choice<'ctype>, GameResult> {
+        self.view_for_cur_mut().draw_card_into_hand()?;
+        Ok(Choice::new_actions(self))
+    }
+
+    /// Shuffles the given player's hand back into the deck, then has them draw a new hand of
+    /// the same size.
+    pub fn mulligan(&mut self, player: Player) {
+        // return the hand to the deck and shuffle it
+        let hand = mem::take(&mut self.player_mut(player).hand);
+        for (card, count) in hand.iter() {
+            self.deck.extend(std::iter::repeat(card).take(count));
+        }
+        self.deck.shuffle(&mut self.rng);
+
+        // draw the new hand from the top of the deck
+        let deck_cut_index = self.deck.len() - hand.count();
+        self.player_mut(player).hand = Cards::from_iter(self.deck.drain(deck_cut_index..));
+    }
+
+    /// Replaces the source of randomness for the rest of the game with a new, randomly-seeded
+    /// one, so that future shuffles are not predictable from the current state.
+    pub fn reseed_rng(&mut self, rng: &mut impl Rng) {
+        self.rng = StdRng::from_rng(rng).expect("Failed to seed the game's RNG");
+    }
+
+    /// Returns a hash of the complete game state (including the order of the deck) that is
+    /// stable across runs and builds of the program (see `StableHasher`).
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns a number that changes whenever the game uses its RNG (the next value it would
+    /// give, drawn from a copy of it), so that a trace of the game shows where its randomness
+    /// was used. Like `fingerprint`, it is stable across runs of the program.
+    pub fn rng_fingerprint(&self) -> u64 {
+        self.rng.clone().gen()
+    }
+
+    /// Returns the cards in the deck, in order from the bottom to the top (the next card drawn).
+    pub fn deck(&self) -> &[PersonOrEventType<'ctype>] {
+        &self.deck
+    }
+
+    /// Returns the discard pile.
+    pub fn discard(&self) -> &DiscardPile<PersonOrEventType<'ctype>> {
+        &self.discard
+    }
+
+    /// Returns whether the players are still choosing which of the camps they were dealt to keep.
+    /// Until they both have, the camps on the boards are only placeholders.
+    pub fn is_choosing_camps(&self) -> bool {
+        self.camp_draft.is_some()
+    }
+
+    /// Returns what has happened so far in the current turn.
+    pub fn turn_state(&self) -> &TurnState {
+        &self.turn_state
+    }
+
+    /// Returns every card from the deck that is anywhere in the game: in the deck, the discard
+    /// pile, a hand, an event queue, or on the board (face up, or face down as a punk). Cards
+    /// are only ever moved, so this is the same at every action choice as at the start of the
+    /// game. (During an effect, a card can be briefly out of all of these places, e.g. a person
+    /// waiting to be put on the board.)
+    pub fn all_cards(&self) -> Cards<PersonOrEventType<'ctype>> {
+        let mut cards = Cards::from_iter(self.deck.iter().chain(self.discard.as_slice()));
+        for player_state in [&self.player1, &self.player2] {
+            for (card, count) in player_state.hand.iter() {
+                cards.add(card, count);
+            }
+            for person in player_state
+                .columns
+                .iter()
+                .flat_map(|column| column.people())
+            {
+                cards.add_one(person.card());
+            }
+            for &event in player_state.events.iter().flatten() {
+                if event != &RAIDERS_EVENT {
+                    cards.add_one(PersonOrEventType::Event(event));
+                }
+            }
+        }
+        cards
+    }
+
+    /// Returns the face-down cards of the punks on both players' boards, as mutable.
+    fn punk_cards_mut(&mut self) -> impl Iterator<Item = &mut PersonOrEventType<'ctype>> {
+        [&mut self.player1, &mut self.player2]
+            .into_iter()
+            .flat_map(|player_state| player_state.columns.iter_mut())
+            .flat_map(|column| column.person_slots.iter_mut().flatten())
+            .filter_map(|person| match person {
+                Person::Punk { card, .. } => Some(&mut card.0),
+                Person::NonPunk { .. } => None,
+            })
+    }
+
+    /// Returns the rules that the game is played with.
+    pub fn rules(&self) -> GameRules {
+        self.rules
+    }
+
+    /// Returns what changed from this state to the given one: the cards damaged, destroyed, or
+    /// added, the events moved, and the changes to the water, hands, deck, and turn.
+    pub fn diff(&self, other: &GameState<'ctype>) -> StateDelta<'ctype> {
+        StateDelta::between(self, other)
     }
 
     pub fn player(&'g self, which: Player) -> &'g PlayerState<'ctype> {
@@ -175,7 +666,7 @@ impl<'g, 'ctype: 'g> GameState<'ctype> {
             }
 
             // resolve the event
-            (event.on_resolve)(self.view_for_cur_mut())
+            event.resolve(self.view_for_cur_mut())
         } else {
             Ok(ChoiceFuture::immediate(self))
         }
@@ -194,21 +685,18 @@ impl<'g, 'ctype: 'g> GameState<'ctype> {
 
         // switch whose turn it is
         self.cur_player = self.cur_player.other();
+        self.turn_number += 1;
+
+        // reset the turn state before resolving events, so ones that resolve at the start of the
+        // turn count for it
+        self.turn_state = TurnState::default();
 
         // resolve/advance events
         self.advance_cur_events()?.then(move |game_state, _| {
             let mut view = game_state.view_for_cur_mut();
 
             // replenish water
-            view.game_state.cur_player_water = 3;
-            if view.my_state().has_water_silo {
-                view.game_state.cur_player_water += 1;
-                view.my_state_mut().has_water_silo = false;
-            }
-
-            // reset other turn state
-            view.game_state.has_paid_to_draw = false;
-            view.game_state.has_played_event = false;
+            view.game_state.cur_player_water = WATER_PER_TURN;
 
             // draw a card
             view.draw_card_into_hand()?;
@@ -221,6 +709,8 @@ impl<'g, 'ctype: 'g> GameState<'ctype> {
     /// Damages or destroys the card at the given location.
     /// If `destroy` is true, the card is always destroyed; otherwise, it is damaged.
     /// If `shift` is true and the card is destroyed, any person in front of it is shifted back.
+    /// If the card is a camp with a trigger for this (see `CampTrigger`), the trigger is resolved
+    /// afterwards.
     ///
     /// If multiple cards need to be damaged/destroyed at the same time, `damage_cards_at` must be
     /// used instead.
@@ -232,6 +722,21 @@ impl<'g, 'ctype: 'g> GameState<'ctype> {
         destroy: bool,
         shift: bool,
     ) -> Result<(), GameResult> {
+        let mut triggered = Vec::new();
+        self.damage_card_without_triggers(loc, destroy, shift, &mut triggered)?;
+        self.resolve_camp_triggers(triggered)
+    }
+
+    /// Like `damage_card_at`, but adds the trigger of a damaged or destroyed camp (if any) to
+    /// `triggered` instead of resolving it.
+    fn damage_card_without_triggers(
+        &mut self,
+        loc: CardLocation,
+        destroy: bool,
+        shift: bool,
+        triggered: &mut Vec<(CampTrigger, CardLocation)>,
+    ) -> Result<(), GameResult> {
+        metrics::increment(Counter::Damages);
         let player_state = match loc.player() {
             Player::Player1 => &mut self.player1,
             Player::Player2 => &mut self.player2,
@@ -246,8 +751,10 @@ impl<'g, 'ctype: 'g> GameState<'ctype> {
                     .as_mut()
                     .expect("Tried to damage or destroy an empty person slot");
                 let was_destroyed = match person {
-                    Person::Punk { .. } => {
-                        // destroy the punk
+                    Person::Punk { card, .. } => {
+                        // destroy the punk; its card is discarded face up, like any other
+                        // destroyed person
+                        self.discard.push(card.0);
                         *slot = None;
                         true
                     }
@@ -271,14 +778,26 @@ impl<'g, 'ctype: 'g> GameState<'ctype> {
                 };
 
                 // if we're supposed to shift, and if the target person was destroyed and behind
-                // another person, shift the other person back
+                // another person, shift the other person back (this is the official rule, not a
+                // convenience, so it applies under either rules fidelity)
                 if shift && was_destroyed && person_row_index == 0.into() {
                     column.person_slots[0] = column.person_slots[1].take();
                 }
             }
             Err(()) => {
-                // damage/destroy the camp in the given column and check for win condition
+                // damage/destroy the camp in the given column, noting its trigger (if any)
+                let status_before = player_state.column(loc.column()).camp.status;
                 let no_camps_left = player_state.damage_camp_at(loc.column(), destroy);
+                let camp = &player_state.column(loc.column()).camp;
+                let trigger = match (status_before, camp.status) {
+                    (CampStatus::Destroyed, _) => None,
+                    (_, CampStatus::Destroyed) => camp.camp_type.on_destroyed,
+                    (_, CampStatus::Damaged) => camp.camp_type.on_damaged,
+                    (_, CampStatus::Undamaged) => None,
+                };
+                triggered.extend(trigger.map(|trigger| (trigger, loc)));
+
+                // check for win condition
                 if no_camps_left {
                     return Err(match loc.player() {
                         Player::Player1 => GameResult::P2Wins,
@@ -296,7 +815,8 @@ impl<'g, 'ctype: 'g> GameState<'ctype> {
     ///
     /// This function should always be used instead of calling `damage_card_at` multiple times,
     /// because it correctly handles cases where one card being destroyed causes another card to
-    /// be shifted back.
+    /// be shifted back, or a camp's trigger (see `CampTrigger`) damages other cards: triggers are
+    /// only resolved once every card has been damaged and the people have been shifted back.
     ///
     /// Assumes that all locations are unique.
     /// Panics if any location has no card there.
@@ -305,9 +825,10 @@ impl<'g, 'ctype: 'g> GameState<'ctype> {
         locations: impl IntoIterator<Item = CardLocation>,
         destroy: bool,
     ) -> Result<(), GameResult> {
-        // damage/destroy all the cards without shifting any cards
+        // damage/destroy all the cards without shifting any cards or resolving any triggers
+        let mut triggered = Vec::new();
         for loc in locations {
-            self.damage_card_at(loc, destroy, false)?;
+            self.damage_card_without_triggers(loc, destroy, false, &mut triggered)?;
         }
 
         // shift any cards back as necessary
@@ -319,10 +840,45 @@ impl<'g, 'ctype: 'g> GameState<'ctype> {
             }
         }
 
+        self.resolve_camp_triggers(triggered)
+    }
+
+    /// Returns every person in play (of both players) to its owner's hand, except punks, which
+    /// are destroyed instead (their cards are discarded face up). Any damage and readiness are
+    /// lost along with the people.
+    pub fn return_all_people_to_hands(&mut self) {
+        for player_state in [&mut self.player1, &mut self.player2] {
+            let hand = &mut player_state.hand;
+            for column in &mut player_state.columns {
+                for slot in &mut column.person_slots {
+                    match slot.take() {
+                        Some(Person::Punk { card, .. }) => self.discard.push(card.0),
+                        Some(Person::NonPunk { person_type, .. }) => {
+                            hand.add_one(PersonOrEventType::Person(person_type));
+                        }
+                        None => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Resolves the triggers of camps that were damaged or destroyed, in the order they were
+    /// triggered. Any cards that a trigger damages are a new batch of damage, so their own
+    /// triggers resolve before the next trigger in this list.
+    fn resolve_camp_triggers(
+        &mut self,
+        triggered: Vec<(CampTrigger, CardLocation)>,
+    ) -> Result<(), GameResult> {
+        for (trigger, camp_loc) in triggered {
+            (trigger.handler)(self, camp_loc)?;
+        }
         Ok(())
     }
 
-    /// Draws a card from the deck.
+    /// Draws a card from the deck, reshuffling the discard pile into the deck first if the deck
+    /// is empty. Returns an error if the game ends: in a tie if the deck runs out a second time,
+    /// or with a win for a player with an Obelisk if this draws the deck's last card.
     pub fn draw_card(&'g mut self) -> Result<PersonOrEventType<'ctype>, GameResult> {
         if self.deck.is_empty() {
             if self.discard.is_empty() {
@@ -330,7 +886,8 @@ impl<'g, 'ctype: 'g> GameState<'ctype> {
                 // Theoretically, this could legitimately happen if one or more players
                 // hoard a huge amount of cards in their hand. The following behavior
                 // is a bit of a hack to stop the game, since it couldn't meaningfully
-                // continue in such a case.
+                // continue in such a case. The official rules don't cover this, so it
+                // applies under either rules fidelity.
                 eprint!("\x1b[91m");
                 eprint!("Tried to draw, but both deck and discard are empty! ");
                 eprint!("Ending game with a tie.");
@@ -343,12 +900,28 @@ impl<'g, 'ctype: 'g> GameState<'ctype> {
                 return Err(GameResult::Tie);
             } else {
                 // reshuffle the discard pile into the deck
-                mem::swap(&mut self.deck, &mut self.discard);
-                self.deck.shuffle(&mut thread_rng());
+                self.deck = self.discard.take_all();
+                self.deck.shuffle(&mut self.rng);
                 self.has_reshuffled_deck = true;
+                metrics::increment(Counter::Reshuffles);
+            }
+        }
+        let card = self.deck.pop().unwrap();
+
+        // a player with an Obelisk wins when the last card of the deck is drawn
+        if self.deck.is_empty() {
+            let has_obelisk = |player| {
+                self.player(player)
+                    .has_special_camp(SpecialCampType::Obelisk)
+            };
+            match (has_obelisk(Player::Player1), has_obelisk(Player::Player2)) {
+                (true, true) => return Err(GameResult::Tie),
+                (true, false) => return Err(GameResult::P1Wins),
+                (false, true) => return Err(GameResult::P2Wins),
+                (false, false) => {}
             }
         }
-        Ok(self.deck.pop().unwrap())
+        Ok(card)
     }
 
     /// Subtracts the given amount of water from the current player's pool.
@@ -368,36 +941,67 @@ impl<'g, 'ctype: 'g> GameState<'ctype> {
         self.cur_player_water += 1;
     }
 
-    /// Plays or advances a player's Raiders event.
-    pub fn raid(&'g mut self, player: Player) -> ChoiceFuture<'g, 'ctype> {
-        // search for the Raiders event in the event queue
-        let my_state = self.player_mut(player);
-        for i in 0..my_state.events.len() {
-            if let Some(event) = my_state.events[i] {
-                if event == &RAIDERS_EVENT {
-                    // found the raiders event
-                    if i == 0 {
-                        // it's the first event, so remove and resolve it
-                        my_state.events[0] = None;
-                        return (event.on_resolve)(self.view_for_mut(player))
-                            .expect("Resolving Raiders shouldn't *immediately* end the game");
-                    } else {
-                        // it's not the first event, so advance it if possible
-                        let events = &mut my_state.events;
-                        if events[i - 1].is_none() {
-                            events[i - 1] = events[i].take();
-                        }
-                        return ChoiceFuture::immediate(self); // no choice to make
-                    }
-                }
-            }
+    /// Moves the event in the given slot of a player's event queue forward one slot, or removes
+    /// and resolves it (for its owner) if it's in the first slot. Returns a future that may
+    /// represent choices from the event resolution.
+    ///
+    /// Panics if the slot is empty, or if the slot in front of it isn't free.
+    pub fn advance_event(
+        &'g mut self,
+        player: Player,
+        slot: usize,
+    ) -> Result<ChoiceFuture<'g, 'ctype>, GameResult> {
+        let events = &mut self.player_mut(player).events;
+        let event = events[slot].expect("Tried to advance an event from an empty slot");
+        if slot > 0 {
+            // move it forward into the free slot in front of it
+            assert!(
+                events[slot - 1].is_none(),
+                "Tried to advance {}, but the slot in front of it isn't free",
+                event.name,
+            );
+            events[slot - 1] = events[slot].take();
+            return Ok(ChoiceFuture::immediate(self)); // no choice to make
         }
 
-        // if we get here, the raiders event was not found in the event queue;
-        // add it to the queue
-        self.view_for_mut(player)
-            .play_event(&RAIDERS_EVENT)
-            .expect("Playing Raiders shouldn't *immediately* end the game")
+        // it's the first event, so remove it, discard it if it's not Raiders, and resolve it
+        events[0] = None;
+        if event != &RAIDERS_EVENT {
+            self.discard.push(PersonOrEventType::Event(event));
+        }
+        event.resolve(self.view_for_mut(player))
+    }
+
+    /// Plays, advances, or resolves a player's Raiders event.
+    /// The future resolves with what happened to the event.
+    pub fn raid(&'g mut self, player: Player) -> ChoiceFuture<'g, 'ctype, RaidOutcome> {
+        let outcome = self.view_for(player).raid_outcome();
+        let future = match outcome {
+            RaidOutcome::Resolved if self.player(player).events[0] == Some(&RAIDERS_EVENT) => {
+                // it's the first event, so remove and resolve it
+                self.advance_event(player, 0)
+                    .expect("Resolving Raiders shouldn't *immediately* end the game")
+            }
+            RaidOutcome::Advanced => {
+                // move it forward into the free slot in front of it
+                let slot = self
+                    .player(player)
+                    .events
+                    .iter()
+                    .position(|event| event == &Some(&RAIDERS_EVENT))
+                    .expect("Tried to advance Raiders, but it wasn't in the event queue");
+                self.advance_event(player, slot)
+                    .expect("Advancing an event can't end the game")
+            }
+            RaidOutcome::Played | RaidOutcome::Resolved => {
+                // it's not in the event queue, so play it (which may resolve it immediately)
+                self.view_for_mut(player)
+                    .play_event(&RAIDERS_EVENT)
+                    .expect("Playing Raiders shouldn't *immediately* end the game")
+            }
+            RaidOutcome::Blocked => ChoiceFuture::immediate(self), // nothing happens
+        };
+        future.then_future(move |_, _| Ok(outcome))
     }
 }
 
@@ -420,7 +1024,29 @@ pub struct GameViewMut<'g, 'ctype: 'g> {
     player: Player,
 }
 
-impl<'v, 'g: 'v, 'ctype: 'g> From<GameViewMut<'g, 'ctype>> for GameView<'g, 'ctype> {
+impl<'g, 'ctype: 'g> GameView<'g, 'ctype> {
+    /// Returns the label of the given option of a choice in this game (see
+    /// `Choice::option_label`). Together with `option_target` and `option_groups`, this lets
+    /// code outside this module describe a choice without access to the whole game state, which
+    /// holds the opponent's hand and the order of the deck.
+    pub fn option_label(&self, choice: &Choice<'ctype>, option: usize) -> String {
+        choice.option_label(option, self.game_state)
+    }
+
+    /// Returns the card that the given option of a choice in this game targets, if it targets
+    /// a single card (see `Choice::option_target`).
+    pub fn option_target(&self, choice: &Choice<'ctype>, option: usize) -> Option<CardLocation> {
+        choice.option_target(option, self.game_state)
+    }
+
+    /// Returns the options of a choice in this game, grouped as the UI shows them (see
+    /// `Choice::option_groups`).
+    pub fn option_groups(&self, choice: &Choice<'ctype>) -> Vec<OptionGroup> {
+        choice.option_groups(self.game_state)
+    }
+}
+
+impl<'g, 'ctype: 'g> From<GameViewMut<'g, 'ctype>> for GameView<'g, 'ctype> {
     fn from(game_view_mut: GameViewMut<'g, 'ctype>) -> Self {
         Self {
             game_state: game_view_mut.game_state,
@@ -433,6 +1059,11 @@ impl<'v, 'g: 'v, 'ctype: 'g> From<GameViewMut<'g, 'ctype>> for GameView<'g, 'cty
 macro_rules! impl_game_view_common {
     ($ViewType:ident) => {
         impl<'v, 'g: 'v, 'ctype: 'g> $ViewType<'g, 'ctype> {
+            /// Returns the player whose view this is.
+            pub fn player(&self) -> Player {
+                self.player
+            }
+
             pub fn my_state(&self) -> &PlayerState<'ctype> {
                 self.game_state.player(self.player)
             }
@@ -441,6 +1072,28 @@ macro_rules! impl_game_view_common {
                 self.game_state.player(self.player.other())
             }
 
+            /// Returns the cards whose identities this player can't see: the deck, the
+            /// opponent's hand, and the punks' face-down cards, combined. Every other card (face
+            /// up in play, in the discard pile, or in this player's hand) is visible to this
+            /// player, so these counts can be deduced from what they can see, and don't reveal
+            /// which cards are in which pile.
+            pub fn unaccounted_cards(&self) -> Cards<PersonOrEventType<'ctype>> {
+                let mut cards = self.other_state().hand.clone();
+                for &card in &self.game_state.deck {
+                    cards.add_one(card);
+                }
+                for person in [self.my_state(), self.other_state()]
+                    .into_iter()
+                    .flat_map(|player_state| player_state.columns.iter())
+                    .flat_map(|column| column.people())
+                {
+                    if let Person::Punk { card, .. } = person {
+                        cards.add_one(card.0);
+                    }
+                }
+                cards
+            }
+
             /// Has this player damage an unprotected opponent card.
             /// Returns the location of the card that was damaged.
             pub fn damage_enemy(&self) -> ChoiceFuture<'g, 'ctype, CardLocation> {
@@ -485,19 +1138,33 @@ macro_rules! impl_game_view_common {
             /// Has this player injure an unprotected opponent person.
             /// Assumes that the opponent has at least one person.
             pub fn injure_enemy(&self) -> ChoiceFuture<'g, 'ctype, CardLocation> {
-                self.choose_and_damage_card(self.unprotected_enemies_vec())
+                let target_locs = self.unprotected_enemies_vec(PersonInteraction::Injure);
+                self.choose_and_damage_card(target_locs)
             }
 
             /// Has this player destroy an unprotected opponent person.
             /// Assumes that the opponent has at least one person.
             pub fn destroy_enemy(&self) -> ChoiceFuture<'g, 'ctype, CardLocation> {
-                self.choose_and_destroy_card(self.unprotected_enemies_vec())
+                let target_locs = self.unprotected_enemies_vec(PersonInteraction::Destroy);
+                self.choose_and_destroy_card(target_locs)
+            }
+
+            /// Has this player destroy any opponent person, whether or not it is protected.
+            /// Assumes that the opponent has at least one person.
+            pub fn destroy_any_enemy_person(&self) -> ChoiceFuture<'g, 'ctype, CardLocation> {
+                let target_locs = self
+                    .other_state()
+                    .people_for(PersonInteraction::Destroy)
+                    .map(|loc| loc.for_player(self.player.other()))
+                    .collect_vec();
+                self.choose_and_destroy_card(target_locs)
             }
 
-            /// Returns a Vec of the locations of all unprotected opponent people.
-            fn unprotected_enemies_vec(&self) -> Vec<CardLocation> {
+            /// Returns a Vec of the locations of all unprotected opponent people that count for
+            /// the given interaction.
+            fn unprotected_enemies_vec(&self, interaction: PersonInteraction) -> Vec<CardLocation> {
                 self.other_state()
-                    .unprotected_person_locs()
+                    .unprotected_people_for(interaction)
                     .map(|loc| loc.for_player(self.player.other()))
                     .collect()
             }
@@ -517,7 +1184,7 @@ macro_rules! impl_game_view_common {
                 // get all possible targets
                 let target_locs = self
                     .my_state()
-                    .person_locs()
+                    .people_for(PersonInteraction::Destroy)
                     .map(|loc| loc.for_player(self.player))
                     .collect_vec();
 
@@ -550,28 +1217,76 @@ macro_rules! impl_game_view_common {
             /// Returns whether this player can use the raid effect to play or advance
             /// their Raiders event.
             pub fn can_raid(&self) -> bool {
+                self.raid_outcome() != RaidOutcome::Blocked
+            }
+
+            /// Returns what using the raid effect would do to this player's Raiders event.
+            pub fn raid_outcome(&self) -> RaidOutcome {
                 // search for the Raiders event in the event queue
                 let events = self.my_state().events;
                 for i in 0..events.len() {
                     if matches!(events[i], Some(event) if event == &RAIDERS_EVENT) {
                         // found the raiders event
-                        if i == 0 {
+                        return if i == 0 {
                             // it's the first event, so the raid effect would resolve it
-                            return true;
+                            RaidOutcome::Resolved
+                        } else if events[i - 1].is_none() {
+                            // it's not the first event, and there's no event directly in
+                            // front of it, so the raid effect would advance it
+                            RaidOutcome::Advanced
                         } else {
-                            // it's not the first event; the raid effect can only advance it if
-                            // there is not an event directly in front of it
-                            return events[i - 1].is_none();
-                        }
+                            RaidOutcome::Blocked
+                        };
                     }
                 }
 
                 // if we get here, the raiders event was not found in the event queue;
                 // the raid effect can only be used if there is a free event slot for it
-                self.can_play_event(RAIDERS_EVENT.resolve_turns)
+                if !self.can_play_event(RAIDERS_EVENT.resolve_turns) {
+                    RaidOutcome::Blocked
+                } else if self.effective_resolve_turns(RAIDERS_EVENT.resolve_turns) == 0 {
+                    RaidOutcome::Resolved
+                } else {
+                    RaidOutcome::Played
+                }
             }
 
-            /// Returns whether this player can play an event that resolves in the given number of turns.
+            /// Returns the event slots of both players (this player's first) whose events can be
+            /// advanced (see `GameState::advance_event`): those in the first slot, or with a
+            /// free slot directly in front of them.
+            pub fn advanceable_events(&self) -> Vec<(Player, usize)> {
+                [self.player, self.player.other()]
+                    .into_iter()
+                    .flat_map(|player| {
+                        let events = self.game_state.player(player).events;
+                        (0..events.len())
+                            .filter(move |&i| {
+                                events[i].is_some() && (i == 0 || events[i - 1].is_none())
+                            })
+                            .map(move |i| (player, i))
+                    })
+                    .collect()
+            }
+
+            /// Returns the locations of the people of both players (this player's first) that
+            /// can be moved to another slot on their owner's board (see
+            /// `PlayerState::move_destinations`).
+            pub fn movable_person_locs(&self) -> Vec<CardLocation> {
+                [self.player, self.player.other()]
+                    .into_iter()
+                    .flat_map(|player| {
+                        let player_state = self.game_state.player(player);
+                        player_state
+                            .person_locs()
+                            .filter(move |&loc| !player_state.move_destinations(loc).is_empty())
+                            .map(move |loc| loc.for_player(player))
+                    })
+                    .collect()
+            }
+
+            /// Returns whether this player can play an event that normally resolves in the given
+            /// number of turns. This accounts for effects that change the resolve timer (see
+            /// `effective_resolve_turns`), and is the only check that playing events should use.
             pub fn can_play_event(&self, resolve_turns: u8) -> bool {
                 let resolve_turns = self.effective_resolve_turns(resolve_turns);
                 if resolve_turns == 0 {
@@ -587,13 +1302,57 @@ macro_rules! impl_game_view_common {
                 }
             }
 
+            /// Returns the water cost for this player to play the given person into the given
+            /// column, after applying any cost modifiers.
+            pub fn person_play_cost(&self, person_type: &PersonType, column: ColumnIndex) -> u32 {
+                let col = self.my_state().column(column);
+                if person_type.has_passive_effect(PassiveEffect::FreeInDestroyedColumn)
+                    && col.camp.is_destroyed()
+                {
+                    // a trait like Holdout's: it costs 0 to play in the column of a destroyed camp
+                    0
+                } else if col.camp.has_active_special_type(SpecialCampType::Oasis)
+                    && col.people().next().is_none()
+                {
+                    // Oasis's trait: people cost 1 less to play into its column while it's empty
+                    person_type.cost.saturating_sub(1)
+                } else {
+                    person_type.cost
+                }
+            }
+
+            /// Returns the columns with an empty person slot where this player can play the given
+            /// person for exactly the given water cost (see `person_play_cost`).
+            pub fn person_play_columns(
+                &self,
+                person_type: &PersonType,
+                cost: u32,
+            ) -> Vec<ColumnIndex> {
+                self.my_state()
+                    .enumerate_columns()
+                    .filter(|(_, col)| col.has_empty_person_slot())
+                    .map(|(col_index, _)| col_index)
+                    .filter(|&col_index| self.person_play_cost(person_type, col_index) == cost)
+                    .collect()
+            }
+
+            /// Returns the person whose trait makes an event played now resolve immediately (a
+            /// trait like Zeto Khan's: the first event played each turn resolves in 0 turns), if
+            /// there is one.
+            pub fn instant_event_source(&self) -> Option<&'ctype PersonType> {
+                if self.game_state.turn_state.has_played_event {
+                    None
+                } else {
+                    self.my_state()
+                        .active_passive_effect_source(PassiveEffect::InstantFirstEvent)
+                }
+            }
+
             /// Given the "normal" resolve timer for an event, returns the *actual* resolve timer
             /// for the event if played now, taking into account other card effects.
             pub fn effective_resolve_turns(&self, resolve_turns: u8) -> u8 {
-                if !self.game_state.has_played_event
-                    && self.my_state().has_special_person(SpecialType::ZetoKhan)
-                {
-                    0  // Zeto Khan's trait: the first event played this turn resolves in 0
+                if self.instant_event_source().is_some() {
+                    0
                 } else {
                     resolve_turns
                 }
@@ -632,10 +1391,48 @@ impl<'v, 'g: 'v, 'ctype: 'g> GameViewMut<'g, 'ctype> {
         ChoiceFuture::immediate(self.game_state)
     }
 
+    /// Damages this player's own card at the given location (e.g. as the cost of its ability),
+    /// then continues with the rest of the ability.
+    /// The rest of the ability still happens if the card is destroyed, unless destroying it
+    /// ends the game.
+    pub fn damage_self_then(
+        self,
+        card_loc: CardLocation,
+        then: impl FnOnce(Self) -> Result<ChoiceFuture<'g, 'ctype>, GameResult>,
+    ) -> Result<ChoiceFuture<'g, 'ctype>, GameResult> {
+        debug_assert_eq!(
+            card_loc.player(),
+            self.player,
+            "Tried to damage another player's card"
+        );
+        self.game_state.damage_card_at(card_loc, false, true)?;
+        then(self)
+    }
+
+    /// Has the opponent choose one of their (non-destroyed) camps to damage, or to destroy if
+    /// `destroy` is set (e.g. for Raiders).
+    pub fn opponent_damages_own_camp(self, destroy: bool) -> ChoiceFuture<'g, 'ctype> {
+        let target_locs = self
+            .other_state()
+            .enumerate_camps()
+            .filter(|(_, camp)| !camp.is_destroyed())
+            .map(|(location, _)| location.for_player(self.player.other()))
+            .collect_vec();
+
+        // (if they have none, the game should already be over, so there's nothing to do)
+        if target_locs.is_empty() {
+            return self.immediate_future();
+        }
+        DamageChoice::future(self.player.other(), destroy, target_locs).ignore_result()
+    }
+
     /// Injures all unprotected opponent people.
     pub fn injure_all_unprotected_enemies(&mut self) {
         self.game_state
-            .damage_cards_at(self.unprotected_enemies_vec(), false)
+            .damage_cards_at(
+                self.unprotected_enemies_vec(PersonInteraction::Injure),
+                false,
+            )
             .expect("injure_all_unprotected_enemies should not end the game");
     }
 
@@ -644,7 +1441,7 @@ impl<'v, 'g: 'v, 'ctype: 'g> GameViewMut<'g, 'ctype> {
         let injured_enemy_locs = self
             .other_state()
             .enumerate_people()
-            .filter(|(_, person)| person.is_injured())
+            .filter(|(_, person)| person.counts_as_person_for(PersonInteraction::Damaged))
             .map(|(loc, _)| loc.for_player(self.player.other()))
             .collect_vec();
         self.game_state
@@ -662,7 +1459,80 @@ impl<'v, 'g: 'v, 'ctype: 'g> GameViewMut<'g, 'ctype> {
         }
 
         // ask the player which one to restore
-        RestoreChoice::future(self.player, target_locs)
+        RestoreChoice::future(self.player, false, target_locs)
+    }
+
+    /// Has this player select any number of their own damaged cards, then restores them.
+    pub fn restore_any_number(self) -> ChoiceFuture<'g, 'ctype> {
+        // get all possible targets
+        let player = self.player;
+        let targets = self
+            .my_state()
+            .restorable_card_locs()
+            .map(|loc| SelectTarget::Card(loc.for_player(player)))
+            .collect_vec();
+        if targets.is_empty() {
+            return self.immediate_future();
+        }
+
+        // ask the player which ones to restore
+        let num_targets = targets.len();
+        MultiSelectChoice::select_future(player, "Restore", targets, 0, num_targets).then_future(
+            move |game_state, selected| {
+                for target in selected {
+                    let loc = match target {
+                        SelectTarget::Card(loc) => loc,
+                        SelectTarget::HandCard(_) => panic!("Tried to restore a card in hand"),
+                    };
+                    game_state
+                        .player_mut(player)
+                        .restore_card_at(loc.player_loc());
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Has this player move any person (of either player) to another slot on its owner's board,
+    /// or does nothing if no person can be moved.
+    pub fn move_any_person(self) -> ChoiceFuture<'g, 'ctype> {
+        // get all possible people to move
+        let person_locs = self.movable_person_locs();
+        if person_locs.is_empty() {
+            return self.immediate_future();
+        }
+
+        // ask the player which person to move, then where to move it
+        let player = self.player;
+        MovePersonChoice::future(player, person_locs).then_future_chain(
+            move |game_state, person_loc| {
+                let destinations = game_state
+                    .player(person_loc.player())
+                    .move_destinations(person_loc.play_loc());
+                Ok(MoveDestinationChoice::future(
+                    player,
+                    person_loc,
+                    destinations,
+                ))
+            },
+        )
+    }
+
+    /// Has this player restore one of their own injured people and ready it,
+    /// or does nothing if the player does not have at least one restorable person.
+    pub fn restore_and_ready_person(self) -> ChoiceFuture<'g, 'ctype> {
+        // get all possible targets
+        let target_locs = self
+            .my_state()
+            .restorable_card_locs()
+            .filter(|loc| !loc.row().is_camp())
+            .collect_vec();
+        if target_locs.is_empty() {
+            return self.immediate_future();
+        }
+
+        // ask the player which one to restore and ready
+        RestoreChoice::future(self.player, true, target_locs)
     }
 
     /// Draws a card from the deck and puts it in this player's hand.
@@ -684,15 +1554,26 @@ impl<'v, 'g: 'v, 'ctype: 'g> GameViewMut<'g, 'ctype> {
 
     /// Plays an event into this player's event queue (or resolves it immediately
     /// if it's a 0-turn event).
-    /// Panics if there is not a free slot for the event.
+    /// Panics if there is not a free slot for the event (i.e. if `can_play_event` is false).
     fn play_event(
         mut self,
         event: &'ctype EventType,
     ) -> Result<ChoiceFuture<'g, 'ctype>, GameResult> {
+        debug_assert!(
+            self.can_play_event(event.resolve_turns),
+            "Tried to play {}, but it can't be played",
+            event.name,
+        );
         let resolve_turns = self.effective_resolve_turns(event.resolve_turns);
-        self.game_state.has_played_event = true;
+        self.game_state.turn_state.has_played_event = true;
         if resolve_turns == 0 {
-            (event.on_resolve)(self)
+            // discard it if it's not Raiders (as when an event resolves from the queue)
+            if event != &RAIDERS_EVENT {
+                self.game_state
+                    .discard
+                    .push(PersonOrEventType::Event(event));
+            }
+            event.resolve(self)
         } else {
             let slot_index = (resolve_turns - 1) as usize;
             let free_slot = self.my_state_mut().events[slot_index..]
@@ -705,33 +1586,65 @@ impl<'v, 'g: 'v, 'ctype: 'g> GameViewMut<'g, 'ctype> {
         }
     }
 
-    /// Has this player add a punk to their board.
-    /// Does nothing if the player's board is full.
-    pub fn gain_punk(self) -> ChoiceFuture<'g, 'ctype> {
-        if self.my_state().has_empty_person_slot() {
-            let punk = Person::new_punk(&self.as_non_mut());
-            self.play_person(punk, None)
-        } else {
-            self.immediate_future()
+    /// Has this player add a punk to their board: the top card of the deck, face down.
+    /// Does nothing (without drawing) if the player's board is full. Returns an error if drawing
+    /// the punk's card ends the game.
+    pub fn gain_punk(self) -> Result<ChoiceFuture<'g, 'ctype>, GameResult> {
+        if !self.my_state().has_empty_person_slot() {
+            return Ok(self.immediate_future());
+        }
+        let card = self.game_state.draw_card()?;
+        let punk = Person::new_punk(card, &self.as_non_mut());
+        Ok(self.play_person(punk, None))
+    }
+
+    /// Has this player gain the given number of punks one at a time, choosing a location for
+    /// each. Stops gaining once the player's board is full. Returns an error if drawing a punk's
+    /// card ends the game.
+    pub fn gain_punks(self, num_punks: u32) -> Result<ChoiceFuture<'g, 'ctype>, GameResult> {
+        if num_punks == 0 {
+            return Ok(self.immediate_future());
+        }
+        let player = self.player;
+        Ok(self.gain_punk()?.then_future_chain(move |game_state, ()| {
+            game_state.view_for_mut(player).gain_punks(num_punks - 1)
+        }))
+    }
+
+    /// Marks that this player has used the ability of the person at the given location (before
+    /// performing it), making the person no longer ready unless a trait like Vera Vosh's is
+    /// active and it's the first time using this person this turn.
+    fn use_person(&mut self, location: PlayLocation) {
+        self.game_state.turn_state.abilities_used += 1;
+        let first_use_stays_ready = self
+            .my_state()
+            .has_active_passive_effect(PassiveEffect::FirstUseStaysReady);
+        let person = self
+            .my_state_mut()
+            .person_mut_slot(location)
+            .expect("Tried to use a person ability, but there was no person in the slot");
+        person.increment_times_used();
+        if !(first_use_stays_ready && person.times_used() == 1) {
+            person.set_not_ready();
         }
     }
 
     /// Asks this player's controller to choose a location, then plays the given person
     /// onto that location.
-    /// If `camp_destroyed` is `Some`, then the possible play locations are restricted to
-    /// columns where `column.camp.is_destroyed() == camp_destroyed`.
-    /// Assumes that there is at least one valid play location.
-    fn play_person(
-        &'v self,
-        person: Person<'ctype>,
-        camp_destroyed: Option<bool>,
-    ) -> ChoiceFuture<'g, 'ctype> {
+    /// If `cost` is `Some`, then the possible play locations are restricted to columns where
+    /// playing the person costs exactly that much (see `person_play_cost`).
+    /// If there is no valid play location (e.g. the board filled up while an effect was
+    /// resolving), the person fizzles, and its card is discarded. (A punk is never gained without
+    /// a location, since `gain_punk` checks for one before drawing its card.)
+    fn play_person(self, person: Person<'ctype>, cost: Option<u32>) -> ChoiceFuture<'g, 'ctype> {
         // determine possible locations to place the card
         let mut play_locs = Vec::new();
         for (col_index, col) in self.my_state().enumerate_columns() {
-            if matches!(camp_destroyed, Some(destroyed) if col.camp.is_destroyed() != destroyed) {
-                // this column doesn't match the `camp_destroyed` requirement; skip it
-                continue;
+            if let (Some(cost), Person::NonPunk { person_type, .. }) = (cost, &person) {
+                if self.person_play_cost(person_type, col_index) != cost {
+                    // playing the person here would cost a different amount; skip it
+                    continue;
+                }
             }
 
             match col.people().count() {
@@ -750,6 +1663,11 @@ impl<'v, 'g: 'v, 'ctype: 'g> GameViewMut<'g, 'ctype> {
             }
         }
 
+        if play_locs.is_empty() {
+            self.game_state.discard.push(person.card());
+            return self.immediate_future();
+        }
+
         // ask the player which location to play the card into
         PlayChoice::future(self.player, person, play_locs)
     }
@@ -758,13 +1676,11 @@ impl<'v, 'g: 'v, 'ctype: 'g> GameViewMut<'g, 'ctype> {
 /// An action that can be performed by a player during their turn.
 #[derive(Clone)]
 pub enum Action<'ctype> {
-    /// Play a person card from the hand onto the board.
-    /// If the card is "Holdout", then this action only allows playing into a column
-    /// whose camp is not destroyed.
-    PlayPerson(&'ctype PersonType),
-
-    /// Play a "Holdout" person into a column with a destroyed camp, for free.
-    PlayHoldout(&'ctype PersonType),
+    /// Play a person card from the hand onto the board for the given water cost.
+    /// A person's cost can depend on the column it's played into (e.g. "Holdout", or a column
+    /// with "Oasis"), so this action only allows playing into columns where it costs exactly
+    /// that much (see `GameView::person_play_columns`).
+    PlayPerson(&'ctype PersonType, u32),
 
     /// Play an event card from the hand onto the event queue.
     PlayEvent(&'ctype EventType),
@@ -775,9 +1691,17 @@ pub enum Action<'ctype> {
     /// Junk a card from the hand to use its junk effect.
     JunkCard(PersonOrEventType<'ctype>),
 
+    /// Junk Water Silo from the hand to gain 1 water. It returns to the player's board, so it
+    /// can be taken again at the end of a turn.
+    JunkWaterSilo,
+
     /// Use an ability of a ready person.
     UsePersonAbility(&'ctype dyn Ability, PlayLocation),
 
+    /// Use the ability of a ready Mimic, which copies the ability of another person (chosen
+    /// afterwards, see `CopyAbilityChoice`).
+    UseMimicAbility(PlayLocation),
+
     /// Use an ability of a ready camp.
     UseCampAbility(&'ctype dyn Ability, ColumnIndex),
 
@@ -793,40 +1717,19 @@ impl<'v, 'g: 'v, 'ctype: 'g> Action<'ctype> {
         mut game_view: GameViewMut<'g, 'ctype>,
     ) -> Result<Choice<'ctype>, GameResult> {
         match *self {
-            Action::PlayPerson(person_type) => {
+            Action::PlayPerson(person_type, cost) => {
                 // pay the person's cost and remove it from the player's hand
-                game_view.game_state.spend_water(person_type.cost);
+                game_view.game_state.spend_water(cost);
                 game_view
                     .my_state_mut()
                     .hand
                     .remove_one(PersonOrEventType::Person(person_type));
+                game_view.game_state.turn_state.people_played += 1;
 
-                // play the person onto the board
-                let destroyed_restriction = if person_type.special_type == SpecialType::Holdout {
-                    // Only allow a `PlayPerson` action to play Holdout into columns with
-                    // non-destroyed camps. Playing it for free into a column with a
-                    // destroyed camp is handled by the `PlayHoldout` action variant.
-                    Some(false)
-                } else {
-                    // No such restriction for other people.
-                    None
-                };
+                // play the person onto the board, in a column allowed by this action
                 let person = Person::new_non_punk(person_type, &game_view.as_non_mut());
                 game_view
-                    .play_person(person, destroyed_restriction)
-                    .then(|game_state, _| Ok(Choice::new_actions(game_state)))
-            }
-            Action::PlayHoldout(person_type) => {
-                // remove the person from the player's hand
-                game_view
-                    .my_state_mut()
-                    .hand
-                    .remove_one(PersonOrEventType::Person(person_type));
-
-                // play the person into a column with a destroyed camp
-                let person = Person::new_non_punk(person_type, &game_view.as_non_mut());
-                game_view
-                    .play_person(person, Some(true))
+                    .play_person(person, Some(cost))
                     .then(|game_state, _| Ok(Choice::new_actions(game_state)))
             }
             Action::PlayEvent(event_type) => {
@@ -843,9 +1746,11 @@ impl<'v, 'g: 'v, 'ctype: 'g> Action<'ctype> {
                     .then(|game_state, _| Ok(Choice::new_actions(game_state)))
             }
             Action::DrawCard => {
+                // pay for the draw before drawing, so the state is complete if the draw ends the
+                // game
                 game_view.game_state.spend_water(2);
+                game_view.game_state.turn_state.has_paid_to_draw = true;
                 game_view.draw_card_into_hand()?;
-                game_view.game_state.has_paid_to_draw = true;
                 Ok(Choice::new_actions(game_view.game_state))
             }
             Action::JunkCard(card) => {
@@ -853,35 +1758,38 @@ impl<'v, 'g: 'v, 'ctype: 'g> Action<'ctype> {
                 game_view.my_state_mut().hand.remove_one(card);
                 game_view.game_state.discard.push(card);
 
-                // perform the card's junk effect
-                card.junk_effect()
-                    .perform(game_view)?
+                // perform the card's junk effect (and its own on-junk behavior, if any)
+                card.perform_junk(game_view)?
                     .then(|game_state, _| Ok(Choice::new_actions(game_state)))
             }
+            Action::JunkWaterSilo => {
+                game_view.my_state_mut().has_water_silo = false;
+                game_view.game_state.cur_player_water += 1;
+                Ok(Choice::new_actions(game_view.game_state))
+            }
             Action::UsePersonAbility(ability, location) => {
                 // pay the ability's cost
                 game_view
                     .game_state
                     .spend_water(ability.cost(&game_view.as_non_mut()));
+                game_view.use_person(location);
 
-                // mark the person as no longer ready (unless Vera Vosh's trait is active and it's
-                // the first time using this person this turn)
-                let is_vera_vosh_trait_active = game_view
+                // perform the ability
+                let card_loc = location.for_player(game_view.player);
+                perform_ability(ability, game_view, card_loc)?
+                    .then(|game_state, _| Ok(Choice::new_actions(game_state)))
+            }
+            Action::UseMimicAbility(location) => {
+                // find the abilities to choose from before the Mimic is used (its cost is paid
+                // once the ability is chosen)
+                let abilities = game_view
                     .my_state()
-                    .has_special_person(SpecialType::VeraVosh);
-                let person = game_view
-                    .my_state_mut()
-                    .person_mut_slot(location)
-                    .expect("Tried to use a person ability, but there was no person in the slot");
-                person.increment_times_used();
-                if !(is_vera_vosh_trait_active && person.times_used() == 1) {
-                    person.set_not_ready();
-                }
+                    .copyable_abilities(&game_view.as_non_mut(), location);
+                game_view.use_person(location);
 
-                // perform the ability
+                // ask the player which ability to copy, and perform it
                 let card_loc = location.for_player(game_view.player);
-                ability
-                    .perform(game_view, card_loc)?
+                CopyAbilityChoice::future(game_view.player, card_loc, abilities)
                     .then(|game_state, _| Ok(Choice::new_actions(game_state)))
             }
             Action::UseCampAbility(ability, column_index) => {
@@ -889,60 +1797,94 @@ impl<'v, 'g: 'v, 'ctype: 'g> Action<'ctype> {
                 game_view
                     .game_state
                     .spend_water(ability.cost(&game_view.as_non_mut()));
+                game_view.game_state.turn_state.abilities_used += 1;
 
-                // mark the camp as no longer ready (unless Vera Vosh's trait is active and it's
-                // the first time using this camp this turn)
-                let is_vera_vosh_trait_active = game_view
+                // mark the camp as no longer ready (unless a trait like Vera Vosh's is active and
+                // it's the first time using this camp this turn)
+                let first_use_stays_ready = game_view
                     .my_state()
-                    .has_special_person(SpecialType::VeraVosh);
+                    .has_active_passive_effect(PassiveEffect::FirstUseStaysReady);
                 let camp = &mut game_view.my_state_mut().column_mut(column_index).camp;
                 camp.increment_times_used();
-                if !(is_vera_vosh_trait_active && camp.times_used() == 1) {
+                if !(first_use_stays_ready && camp.times_used() == 1) {
                     camp.set_not_ready();
                 }
 
                 // perform the ability
                 let card_loc =
                     CardLocation::new(column_index, CardRowIndex::camp(), game_view.player);
-                ability
-                    .perform(game_view, card_loc)?
+                perform_ability(ability, game_view, card_loc)?
                     .then(|game_state, _| Ok(Choice::new_actions(game_state)))
             }
             Action::EndTurn => {
-                // take Water Silo if possible, then end the turn
-                game_view.my_state_mut().has_water_silo =
-                    game_view.game_state.cur_player_water >= 1;
+                // take Water Silo if possible (i.e. if it's on the board and there is water to
+                // pay for it), then end the turn
+                if game_view.game_state.cur_player_water >= 1 {
+                    game_view.my_state_mut().has_water_silo = true;
+                }
                 game_view.game_state.end_turn()
             }
         }
     }
 
+    /// Returns the water cost of the action. Using a Mimic costs as much as the ability it
+    /// copies, which is only chosen afterwards, so this is the most that it could cost.
+    pub fn cost(&self, game_view: &'v GameView<'g, 'ctype>) -> u32 {
+        match *self {
+            Action::PlayPerson(_, cost) => cost,
+            Action::PlayEvent(card) => card.cost,
+            Action::DrawCard => 2,
+            Action::UsePersonAbility(ability, _) | Action::UseCampAbility(ability, _) => {
+                ability.cost(game_view)
+            }
+            Action::UseMimicAbility(location) => game_view
+                .my_state()
+                .copyable_abilities(game_view, location)
+                .iter()
+                .map(|(_, ability)| ability.cost(game_view))
+                .max()
+                .unwrap_or(0),
+            Action::JunkCard(_) | Action::JunkWaterSilo | Action::EndTurn => 0,
+        }
+    }
+
     /// Formats the action for display.
     pub fn format(&self, game_view: &'v GameView<'g, 'ctype>) -> Spans<'static> {
         match *self {
-            Action::PlayPerson(card) => make_spans!(
+            Action::PlayPerson(card, cost) => make_spans!(
                 "Play ",
                 card.styled_name(),
-                if card.special_type == SpecialType::Holdout {
-                    " in column without destroyed camp"
-                } else {
-                    ""
+                {
+                    // only name the columns if the cost differs in some other column
+                    let columns = game_view.person_play_columns(card, cost);
+                    let num_open_columns = game_view
+                        .my_state()
+                        .enumerate_columns()
+                        .filter(|(_, col)| col.has_empty_person_slot())
+                        .count();
+                    if columns.len() < num_open_columns {
+                        format!(
+                            " in column {}",
+                            columns.iter().map(|col| col.as_usize()).join(" or ")
+                        )
+                    } else {
+                        String::new()
+                    }
                 },
-                WATER_COST: card.cost,
+                WATER_COST: cost,
                 if card.on_enter_play.is_some() { " <has on-enter-play effect>" } else { "" },
                 if card.enters_play_ready { " <enters play ready>" } else { "" },
             ),
-            Action::PlayHoldout(card) => make_spans!(
-                "Play ",
-                card.styled_name(),
-                " in column with destroyed camp",
-                WATER_COST: 0,
-            ),
             Action::PlayEvent(card) => make_spans!(
                 "Play ",
                 card.styled_name(),
                 " (resolves ",
                 match game_view.effective_resolve_turns(card.resolve_turns) {
+                    0 if card.resolve_turns > 0 => {
+                        // name the card whose trait makes the event resolve immediately
+                        let source = game_view.instant_event_source().unwrap();
+                        format!("immediately due to {}", source.name)
+                    }
                     0 => "immediately".into(),
                     1 => "in 1 turn".into(),
                     resolve_turns => format!("in {resolve_turns} turns"),
@@ -957,20 +1899,34 @@ impl<'v, 'g: 'v, 'ctype: 'g> Action<'ctype> {
             Action::JunkCard(card) => make_spans!(
                 "Junk ",
                 card.styled_name(),
-                format!(" (effect: {:?})", card.junk_effect()),
+                format!(" (effect: {})", card.describe_junk(game_view)),
+            ),
+            Action::JunkWaterSilo => make_spans!(
+                "Junk ",
+                Span::styled("Water Silo", *WATER),
+                " (effect: gain 1 water)",
             ),
             Action::UsePersonAbility(ability, location) => make_spans!(
                 "Use ",
                 game_view.my_state().person_slot(location).unwrap().styled_name(),
                 "'s ability: ",
-                ability.description(),
+                describe_ability(ability, game_view),
                 WATER_COST: ability.cost(game_view),
             ),
+            Action::UseMimicAbility(location) => make_spans!(
+                "Use ",
+                game_view
+                    .my_state()
+                    .person_slot(location)
+                    .unwrap()
+                    .styled_name(),
+                "'s ability: copy another person's ability",
+            ),
             Action::UseCampAbility(ability, column_index) => make_spans!(
                 "Use ",
                 game_view.my_state().column(column_index).camp.styled_name(),
                 "'s ability: ",
-                ability.description(),
+                describe_ability(ability, game_view),
                 WATER_COST: ability.cost(game_view),
             ),
             Action::EndTurn => make_spans!(
@@ -990,6 +1946,22 @@ pub enum PersonOrEventType<'ctype> {
 }
 
 impl PersonOrEventType<'_> {
+    /// Returns the card's name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PersonOrEventType::Person(person_type) => person_type.name,
+            PersonOrEventType::Event(event_type) => event_type.name,
+        }
+    }
+
+    /// Returns the card's name in the loaded locale.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            PersonOrEventType::Person(person_type) => person_type.display_name(),
+            PersonOrEventType::Event(event_type) => event_type.display_name(),
+        }
+    }
+
     /// Returns the card's junk effect.
     pub fn junk_effect(&self) -> IconEffect {
         match self {
@@ -998,6 +1970,53 @@ impl PersonOrEventType<'_> {
         }
     }
 
+    /// Returns the card's own behavior when junked, if any.
+    pub fn on_junk(&self) -> Option<OnJunk> {
+        match self {
+            PersonOrEventType::Person(person_type) => person_type.on_junk,
+            PersonOrEventType::Event(event_type) => event_type.on_junk,
+        }
+    }
+
+    /// Returns whether the card can be junked given a game view.
+    pub fn can_junk(&self, game_view: &GameView) -> bool {
+        match self.on_junk() {
+            Some(on_junk) if on_junk.replaces_junk_effect => true,
+            _ => self.junk_effect().can_perform(game_view),
+        }
+    }
+
+    /// Returns a description of what junking the card does, for display.
+    pub fn describe_junk(&self, game_view: &GameView) -> String {
+        match self.on_junk() {
+            Some(on_junk) if on_junk.replaces_junk_effect => on_junk.description.to_string(),
+            Some(on_junk) => format!(
+                "{}, then {}",
+                self.junk_effect().describe(game_view),
+                on_junk.description
+            ),
+            None => self.junk_effect().describe(game_view),
+        }
+    }
+
+    /// Performs the effects of junking the card for the given view's player, once it's been
+    /// moved to the discard pile.
+    pub fn perform_junk<'g, 'ctype: 'g>(
+        &self,
+        game_view: GameViewMut<'g, 'ctype>,
+    ) -> Result<ChoiceFuture<'g, 'ctype>, GameResult> {
+        match self.on_junk() {
+            Some(on_junk) if on_junk.replaces_junk_effect => (on_junk.handler)(game_view),
+            Some(on_junk) => {
+                let player = game_view.player;
+                Ok(self.junk_effect().perform(game_view)?.then_future_chain(
+                    move |game_state, _| (on_junk.handler)(game_state.view_for_mut(player)),
+                ))
+            }
+            None => self.junk_effect().perform(game_view),
+        }
+    }
+
     /// Returns the water cost to play this card.
     pub fn cost(&self) -> u32 {
         match self {
@@ -1017,12 +2036,13 @@ impl StyledName for PersonOrEventType<'_> {
     }
 }
 
-// hash by address
+// hash by ID (see the `Hash` impls for the card type references)
 impl Hash for PersonOrEventType<'_> {
     fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
         match *self {
-            PersonOrEventType::Person(person_type) => ByAddress(person_type).hash(state),
-            PersonOrEventType::Event(event_type) => ByAddress(event_type).hash(state),
+            PersonOrEventType::Person(person_type) => person_type.hash(state),
+            PersonOrEventType::Event(event_type) => event_type.hash(state),
         }
     }
 }
@@ -1042,14 +2062,17 @@ impl PartialEq for PersonOrEventType<'_> {
     }
 }
 impl Eq for PersonOrEventType<'_> {}
+// compare by ID, so that anything ordered by card type (e.g. the cards in a hand, and the
+// options built from them) is in the same order in every run of the program; within a list of
+// card types, this agrees with comparing by address
 impl Ord for PersonOrEventType<'_> {
     fn cmp(&self, other: &Self) -> Ordering {
         match (*self, *other) {
             (PersonOrEventType::Person(person), PersonOrEventType::Person(other_person)) => {
-                ByAddress(person).cmp(&ByAddress(other_person))
+                person.id.cmp(&other_person.id)
             }
             (PersonOrEventType::Event(event), PersonOrEventType::Event(other_event)) => {
-                ByAddress(event).cmp(&ByAddress(other_event))
+                event.id.cmp(&other_event.id)
             }
             (PersonOrEventType::Person(_), PersonOrEventType::Event(_)) => Ordering::Less,
             (PersonOrEventType::Event(_), PersonOrEventType::Person(_)) => Ordering::Greater,
@@ -1062,29 +2085,53 @@ impl PartialOrd for PersonOrEventType<'_> {
     }
 }
 
-static RAIDERS_EVENT: EventType = EventType {
+/// Returns a description of an ability for display, noting what its icon effect (if any)
+/// would do.
+fn describe_ability(ability: &dyn Ability, game_view: &GameView) -> String {
+    match ability.icon_effect() {
+        Some(icon_effect) => icon_effect.describe(game_view),
+        None => ability.description(),
+    }
+}
+
+/// What using the raid effect does to a player's Raiders event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaidOutcome {
+    /// Raiders is played into the event queue.
+    Played,
+
+    /// Raiders moves forward one slot in the event queue.
+    Advanced,
+
+    /// Raiders resolves, and the opponent damages one of their camps.
+    Resolved,
+
+    /// Raiders can't be played or advanced, because there's no room for it.
+    Blocked,
+}
+
+impl fmt::Display for RaidOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            RaidOutcome::Played => "plays Raiders",
+            RaidOutcome::Advanced => "advances Raiders",
+            RaidOutcome::Resolved => "resolves Raiders",
+            RaidOutcome::Blocked => "Raiders is blocked",
+        })
+    }
+}
+
+pub static RAIDERS_EVENT: EventType = EventType {
+    id: usize::MAX, // Raiders is not in the list of event types
     name: "Raiders",
     num_in_deck: 0,                // Raiders is not a normal card in the deck
     junk_effect: IconEffect::Raid, // arbitrary; should never be junked
-    cost: 0,                       // arbitrary; should never be paid for
+    on_junk: None,
+    cost: 0, // arbitrary; should never be paid for
     resolve_turns: 2,
-    on_resolve: |game_view| {
-        // have the other player choose one of their (non-destroyed) camps to damage
-        let target_locs = game_view
-            .other_state()
-            .enumerate_camps()
-            .filter_map(|(location, camp)| {
-                if camp.is_destroyed() {
-                    None
-                } else {
-                    Some(location.for_player(game_view.player.other()))
-                }
-            })
-            .collect_vec();
-
-        // have the other player choose one of their (non-destroyed) camps to damage
-        Ok(DamageChoice::future(game_view.player.other(), false, target_locs).ignore_result())
-    },
+    description: "Your opponent damages one of their camps",
+    optional: false,
+    on_resolve: |game_view| Ok(game_view.opponent_damages_own_camp(false)),
 };
 
 /// Enum representing basic icon effects for abilities and junk effects.
@@ -1100,11 +2147,50 @@ pub enum IconEffect {
 }
 
 impl IconEffect {
+    /// All the icon effects.
+    pub const ALL: [IconEffect; 7] = [
+        IconEffect::Damage,
+        IconEffect::Injure,
+        IconEffect::Restore,
+        IconEffect::Draw,
+        IconEffect::Water,
+        IconEffect::GainPunk,
+        IconEffect::Raid,
+    ];
+
+    /// Returns a description of this icon effect for display, noting what it would do when
+    /// that depends on the game state.
+    pub fn describe(&self, game_view: &GameView) -> String {
+        match self {
+            IconEffect::Raid => format!("{} ({})", self.display_name(), game_view.raid_outcome()),
+            _ => self.display_name().to_string(),
+        }
+    }
+
+    /// Returns the name of this icon effect in the loaded locale.
+    pub fn display_name(&self) -> &'static str {
+        let name = match self {
+            IconEffect::Damage => "Damage",
+            IconEffect::Injure => "Injure",
+            IconEffect::Restore => "Restore",
+            IconEffect::Draw => "Draw",
+            IconEffect::Water => "Water",
+            IconEffect::GainPunk => "GainPunk",
+            IconEffect::Raid => "Raid",
+        };
+        translate(Section::Ability, name)
+    }
+
     /// Returns whether this icon effect can be performed given a game view.
     pub fn can_perform(&self, game_view: &GameView) -> bool {
         match self {
             IconEffect::Damage => true, // if there's nothing to damage, the game is over!
-            IconEffect::Injure => game_view.other_state().people().next().is_some(),
+            IconEffect::Injure => {
+                let other_state = game_view.other_state();
+                other_state
+                    .person_locs()
+                    .any(|loc| other_state.is_valid_injure_target(loc))
+            }
             IconEffect::Restore => game_view.my_state().has_restorable_card(),
             IconEffect::Draw => true, // it's always possible to draw a card
             IconEffect::Water => true, // it's always possible to gain water
@@ -1113,6 +2199,26 @@ impl IconEffect {
         }
     }
 
+    /// Performs the given effects in order for the current player.
+    ///
+    /// Panics if there are no effects.
+    pub fn perform_sequence<'g, 'ctype: 'g>(
+        effects: &[IconEffect],
+        game_view: GameViewMut<'g, 'ctype>,
+    ) -> Result<ChoiceFuture<'g, 'ctype>, GameResult> {
+        let player = game_view.player;
+        let (first, rest) = effects
+            .split_first()
+            .expect("An icon effect sequence should have at least one effect");
+        let mut future = first.perform(game_view)?;
+        for &effect in rest {
+            future = future.then_future_chain(move |game_state, ()| {
+                effect.perform(game_state.view_for_mut(player))
+            });
+        }
+        Ok(future)
+    }
+
     /// Performs the effect for the current player.
     pub fn perform<'g, 'ctype: 'g>(
         &self,
@@ -1135,12 +2241,709 @@ impl IconEffect {
                 game_view.game_state.gain_water();
             }
             IconEffect::GainPunk => {
-                return Ok(game_view.gain_punk());
+                return game_view.gain_punk();
             }
             IconEffect::Raid => {
-                return Ok(game_view.game_state.raid(game_view.player));
+                return Ok(game_view.game_state.raid(game_view.player).ignore_result());
             }
         }
         Ok(ChoiceFuture::immediate(game_view.game_state))
     }
 }
+
+/// Card-specific behavior for when a card is junked from its owner's hand, in addition to (or
+/// instead of) its junk effect. (Using a card's junk effect without junking it, e.g. from a
+/// revealed card, doesn't trigger it.)
+#[derive(Clone, Copy)]
+pub struct OnJunk {
+    /// The text of the behavior (in English).
+    pub description: &'static str,
+
+    /// Whether the behavior replaces the card's junk effect, rather than following it. A card
+    /// whose junk effect is replaced can always be junked.
+    pub replaces_junk_effect: bool,
+
+    /// The handler containing the logic of the behavior.
+    /// Takes a view from the perspective of the player who junked the card.
+    pub handler: &'static OnJunkHandler,
+}
+
+/// The logic of a card's behavior when junked (see `OnJunk`).
+pub type OnJunkHandler = dyn for<'g, 'ctype> Fn(GameViewMut<'g, 'ctype>) -> Result<ChoiceFuture<'g, 'ctype>, GameResult>
+    + Send
+    + Sync;
+
+impl OnJunk {
+    /// Creates the behavior of performing the given icon effects in order (e.g. for a card in a
+    /// card set file). Like the names of a card set's new cards, the effects and description are
+    /// leaked, since the card types live for the rest of the program.
+    ///
+    /// Panics if there are no effects.
+    pub fn icon_sequence(effects: Vec<IconEffect>, replaces_junk_effect: bool) -> Self {
+        assert!(
+            !effects.is_empty(),
+            "Tried to create an OnJunk with no effects"
+        );
+        let description = effects
+            .iter()
+            .map(|effect| effect.display_name())
+            .join(", ");
+        let effects: &'static [IconEffect] = Box::leak(effects.into_boxed_slice());
+
+        // gives the closure the handler's signature, which can't be inferred from `Box::new`
+        fn handler<F>(handler: F) -> F
+        where
+            F: for<'g, 'ctype> Fn(
+                GameViewMut<'g, 'ctype>,
+            ) -> Result<ChoiceFuture<'g, 'ctype>, GameResult>,
+        {
+            handler
+        }
+
+        OnJunk {
+            description: Box::leak(description.into_boxed_str()),
+            replaces_junk_effect,
+            handler: Box::leak(Box::new(handler(move |game_view| {
+                IconEffect::perform_sequence(effects, game_view)
+            }))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::locations::Player::{Player1, Player2};
+    use super::sandbox::testing::*;
+    use super::*;
+
+    /// Uses the raid effect for the given player, returning the next choice and, if the raid has
+    /// finished by then, its outcome.
+    fn raid<'ctype>(
+        game_state: &mut GameState<'ctype>,
+        player: Player,
+    ) -> (Option<RaidOutcome>, Choice<'ctype>) {
+        let outcome = Arc::new(Mutex::new(None));
+        let outcome_in_callback = outcome.clone();
+        let choice = game_state
+            .raid(player)
+            .then(move |game_state, raid_outcome| {
+                *outcome_in_callback.lock().unwrap() = Some(raid_outcome);
+                Ok(Choice::new_actions(game_state))
+            })
+            .expect("the raid shouldn't end the game");
+        let outcome = *outcome.lock().unwrap();
+        (outcome, choice)
+    }
+
+    #[test]
+    fn raid_plays_raiders() {
+        let (mut game_state, _) = position(&["turn p1"]);
+        assert_eq!(
+            game_state.view_for(Player1).raid_outcome(),
+            RaidOutcome::Played
+        );
+
+        let (outcome, _) = raid(&mut game_state, Player1);
+        assert_eq!(outcome, Some(RaidOutcome::Played));
+        assert_eq!(
+            game_state.player(Player1).events,
+            [None, Some(&RAIDERS_EVENT), None]
+        );
+    }
+
+    #[test]
+    fn raid_advances_raiders() {
+        let (mut game_state, _) = position(&["turn p1", "event p1 2 Raiders"]);
+
+        let (outcome, _) = raid(&mut game_state, Player1);
+        assert_eq!(outcome, Some(RaidOutcome::Advanced));
+        assert_eq!(
+            game_state.player(Player1).events,
+            [Some(&RAIDERS_EVENT), None, None]
+        );
+    }
+
+    #[test]
+    fn raid_resolves_raiders() {
+        let (mut game_state, _) = position(&["turn p1", "event p1 1 Raiders"]);
+
+        // Raiders leaves the queue, and the opponent chooses one of their camps to damage
+        let (outcome, choice) = raid(&mut game_state, Player1);
+        assert_eq!(outcome, None);
+        assert_eq!(game_state.player(Player1).events, [None, None, None]);
+        assert!(matches!(choice, Choice::Damage(_)));
+        assert_eq!(choice.chooser(&game_state), Player2);
+        assert_eq!(choice.num_options(), 3);
+
+        let target = card_loc(Player2, 1, 0);
+        let choice = choose_target(&mut game_state, &choice, target).unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(
+            game_state.player(Player2).columns[0].camp.status,
+            CampStatus::Damaged
+        );
+    }
+
+    #[test]
+    fn raid_is_blocked_behind_another_event() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "camp p1 1 Garage",
+            "event p1 1 Napalm",
+            "event p1 2 Raiders",
+        ]);
+        assert!(!game_state.view_for(Player1).can_raid());
+        assert!(labels(&game_state, &choice)
+            .iter()
+            .all(|label| !label.starts_with("Use Garage's ability")));
+
+        let events_before = game_state.player(Player1).events;
+        let (outcome, _) = raid(&mut game_state, Player1);
+        assert_eq!(outcome, Some(RaidOutcome::Blocked));
+        assert_eq!(game_state.player(Player1).events, events_before);
+    }
+
+    #[test]
+    fn raiders_against_an_opponent_with_no_camps_does_nothing() {
+        let (mut game_state, _) = position(&["turn p1", "event p1 1 Raiders"]);
+        for column in &mut game_state.player_mut(Player2).columns {
+            column.camp.status = CampStatus::Destroyed;
+        }
+
+        // there's no camp to damage, so no choice is made
+        let (outcome, choice) = raid(&mut game_state, Player1);
+        assert_eq!(outcome, Some(RaidOutcome::Resolved));
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(game_state.player(Player1).events, [None, None, None]);
+    }
+
+    /// Plays the given person for Player1 (see `GameViewMut::play_person`), returning the next
+    /// choice.
+    fn play_person(
+        game_state: &mut GameState<'static>,
+        person_type: &'static PersonType,
+        cost: Option<u32>,
+    ) -> Choice<'static> {
+        let person = Person::new_non_punk(person_type, &game_state.view_for(Player1));
+        game_state
+            .view_for_mut(Player1)
+            .play_person(person, cost)
+            .then(|game_state, ()| Ok(Choice::new_actions(game_state)))
+            .unwrap()
+    }
+
+    #[test]
+    fn a_person_with_nowhere_to_be_played_fizzles() {
+        let (mut game_state, _) = position(&[
+            "turn p1",
+            "person p1 1 1 punk",
+            "person p1 1 2 punk",
+            "person p1 2 1 punk",
+            "person p1 2 2 punk",
+            "person p1 3 1 punk",
+            "person p1 3 2 punk",
+        ]);
+        let columns_before = game_state.player(Player1).columns.clone();
+        let looter = person_type("Looter");
+
+        // no location is chosen; Looter goes straight to the discard pile
+        let choice = play_person(&mut game_state, looter, None);
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(game_state.player(Player1).columns, columns_before);
+        assert_eq!(
+            game_state.discard.top(),
+            Some(PersonOrEventType::Person(looter))
+        );
+    }
+
+    #[test]
+    fn a_person_with_no_location_at_the_given_cost_fizzles() {
+        let (mut game_state, _) = position(&["turn p1"]);
+        let holdout = person_type("Holdout");
+
+        // Holdout only costs 0 in the column of a destroyed camp, and there are none
+        let choice = play_person(&mut game_state, holdout, Some(0));
+        assert!(matches!(choice, Choice::Action(_)));
+        assert!(game_state.player(Player1).people().next().is_none());
+        assert_eq!(
+            game_state.discard.top(),
+            Some(PersonOrEventType::Person(holdout))
+        );
+    }
+
+    #[test]
+    fn gaining_a_punk_on_a_full_board_does_nothing() {
+        let (mut game_state, _) = position(&[
+            "turn p1",
+            "person p1 1 1 punk",
+            "person p1 1 2 punk",
+            "person p1 2 1 punk",
+            "person p1 2 2 punk",
+            "person p1 3 1 punk",
+            "person p1 3 2 punk",
+        ]);
+        let (deck_len, discard_len) = (game_state.deck.len(), game_state.discard.len());
+
+        // no card is drawn for the punk, and none is discarded
+        let choice = game_state
+            .view_for_mut(Player1)
+            .gain_punk()
+            .unwrap()
+            .then(|game_state, ()| Ok(Choice::new_actions(game_state)))
+            .unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(game_state.deck.len(), deck_len);
+        assert_eq!(game_state.discard.len(), discard_len);
+    }
+
+    /// Returns the name and cost of each PlayPerson action that Player 1 has.
+    fn play_person_actions(game_state: &GameState) -> Vec<(&'static str, u32)> {
+        let game_view = game_state.view_for(Player1);
+        game_view
+            .my_state()
+            .actions(&game_view)
+            .into_iter()
+            .filter_map(|action| match action {
+                Action::PlayPerson(person_type, cost) => Some((person_type.name, cost)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn person_costs_depend_on_the_column() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 5",
+            "camp p1 1 Oasis",
+            "status p1 3 destroyed",
+            "hand p1 add Muse",
+            "hand p1 add Holdout",
+        ]);
+        let costs = |game_state: &GameState, person_type| {
+            (0..3)
+                .map(|column| {
+                    game_state
+                        .view_for(Player1)
+                        .person_play_cost(person_type, ColumnIndex::from(column))
+                })
+                .collect_vec()
+        };
+        let (muse, holdout) = (person_type("Muse"), person_type("Holdout"));
+
+        // Oasis takes 1 off in its column, and Holdout is free in the destroyed camp's column
+        assert_eq!(costs(&game_state, muse), [0, 1, 1]);
+        assert_eq!(costs(&game_state, holdout), [1, 2, 0]);
+        assert_eq!(
+            play_person_actions(&game_state),
+            [
+                ("Holdout", 1),
+                ("Holdout", 2),
+                ("Holdout", 0),
+                ("Muse", 0),
+                ("Muse", 1)
+            ]
+        );
+        let play_labels = labels(&game_state, &choice)
+            .into_iter()
+            .filter(|label| label.starts_with("Play "))
+            .collect_vec();
+        assert_eq!(
+            play_labels,
+            [
+                "Play Holdout in column 0 (costs 1 water)",
+                "Play Holdout in column 1 (costs 2 water)",
+                "Play Holdout in column 2 (costs 0 water)",
+                "Play Muse in column 0 (costs 0 water)",
+                "Play Muse in column 1 or 2 (costs 1 water)",
+            ]
+        );
+
+        // the free Muse can only go into the Oasis column, which then has no discount
+        let choice = choose(&mut game_state, &choice, "Play Muse in column 0").unwrap();
+        assert!(matches!(choice, Choice::PlayLoc(_)));
+        assert_eq!(
+            labels(&game_state, &choice),
+            ["Play Muse at <column: 0, row: 1>"]
+        );
+        let choice = choice.choose(&mut game_state, 0).unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(game_state.cur_player_water, 5);
+        assert!(game_state.player(Player1).columns[0].person_slots[0].is_some());
+        assert_eq!(costs(&game_state, muse), [1, 1, 1]);
+        assert_eq!(costs(&game_state, holdout), [2, 2, 0]);
+        assert_eq!(
+            play_person_actions(&game_state),
+            [("Holdout", 2), ("Holdout", 0)]
+        );
+    }
+
+    #[test]
+    fn holdout_is_free_in_a_destroyed_oasis_column() {
+        let (game_state, _) = position(&[
+            "turn p1",
+            "water 5",
+            "camp p1 1 Oasis",
+            "status p1 1 destroyed",
+            "hand p1 add Muse",
+            "hand p1 add Holdout",
+        ]);
+
+        // a destroyed Oasis has no trait, and Holdout's trait doesn't go below 0
+        assert_eq!(
+            play_person_actions(&game_state),
+            [("Holdout", 0), ("Holdout", 2), ("Muse", 1)]
+        );
+    }
+
+    #[test]
+    fn cards_are_conserved_through_destroying_rescuing_and_fizzling() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 10",
+            "person p1 1 1 Rescue Team",
+            "person p1 1 2 punk",
+            "person p1 2 1 Looter",
+            "person p1 2 2 punk",
+            "person p1 3 1 punk",
+            "person p1 3 2 punk",
+            "person p2 1 1 punk",
+            "hand p1 add Looter",
+        ]);
+        let all_cards = game_state.all_cards();
+        let looter = PersonOrEventType::Person(person_type("Looter"));
+
+        // destroying a punk
+        let choice = choose(&mut game_state, &choice, "Use Looter's ability").unwrap();
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 1, 1)).unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert!(game_state.player(Player2).people().next().is_none());
+        assert_eq!(game_state.all_cards(), all_cards);
+
+        // a person from the hand fizzling on the full board
+        game_state.player_mut(Player1).hand.remove_one(looter);
+        let choice = play_person(&mut game_state, person_type("Looter"), None);
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(game_state.discard.top(), Some(looter));
+        assert_eq!(game_state.all_cards(), all_cards);
+
+        // rescuing a person
+        let choice = choose(&mut game_state, &choice, "Use Rescue Team's ability").unwrap();
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player1, 2, 1)).unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert!(game_state
+            .player(Player1)
+            .hand
+            .iter()
+            .any(|(card, _)| card == looter));
+        assert_eq!(game_state.all_cards(), all_cards);
+    }
+
+    #[test]
+    fn reactor_destroyed_mid_batch_resolves_after_the_batch() {
+        let (mut game_state, _) = position(&[
+            "turn p1",
+            "camp p2 2 Reactor",
+            "status p2 2 damaged",
+            "person p1 1 1 Looter",
+            "person p2 1 1 punk",
+            "person p2 1 2 Muse",
+        ]);
+        let discard_len = game_state.discard.len();
+
+        // the cards after Reactor in the batch are still there to be damaged, since its trigger
+        // waits for the whole batch (and for Muse to be shifted forward)
+        let result = game_state.damage_cards_at(
+            [
+                card_loc(Player2, 2, 0),
+                card_loc(Player2, 1, 1),
+                card_loc(Player1, 1, 1),
+            ],
+            false,
+        );
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            game_state.player(Player2).columns[1].camp.status,
+            CampStatus::Destroyed
+        );
+        assert!(game_state.player(Player1).people().next().is_none());
+        assert!(game_state.player(Player2).people().next().is_none());
+        // (the punk, Muse, and Looter)
+        assert_eq!(game_state.discard.len(), discard_len + 3);
+    }
+
+    #[test]
+    fn a_camp_trigger_can_destroy_another_camp_with_a_trigger() {
+        let (mut game_state, _) = position(&[
+            "turn p1",
+            "camp p2 1 Reactor",
+            "person p1 2 1 Looter",
+            "person p2 3 1 Muse",
+        ]);
+        // a made-up camp that destroys the opponent's camp across from it when it's destroyed
+        let fuse = CampType {
+            id: card_set().camp_types.len(),
+            name: "Test Fuse",
+            num_initial_cards: 0,
+            abilities: Vec::new(),
+            special_type: SpecialCampType::None,
+            on_damaged: None,
+            on_destroyed: Some(CampTrigger {
+                description: "When this card is destroyed, destroy the opposite camp",
+                handler: |game_state, camp_loc| {
+                    let opposite = CardLocation::new(
+                        camp_loc.column(),
+                        CardRowIndex::camp(),
+                        camp_loc.player().other(),
+                    );
+                    game_state.damage_card_at(opposite, true, true)
+                },
+            }),
+        };
+        let fuse: &'static CampType = Box::leak(Box::new(fuse));
+        game_state.player_mut(Player1).columns[0].camp.camp_type = fuse;
+
+        // the fuse destroys Reactor, whose own trigger then destroys everyone
+        let result = game_state.damage_card_at(card_loc(Player1, 1, 0), true, true);
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            game_state.player(Player2).columns[0].camp.status,
+            CampStatus::Destroyed
+        );
+        assert!(game_state.player(Player1).people().next().is_none());
+        assert!(game_state.player(Player2).people().next().is_none());
+    }
+
+    #[test]
+    fn the_turn_state_is_reset_before_events_resolve_at_the_start_of_a_turn() {
+        let (mut game_state, choice) = position(&[
+            "turn p2",
+            "water 5",
+            "hand p2 add Muse",
+            "person p2 1 1 Looter",
+            "event p1 1 Bombardment",
+        ]);
+
+        let choice = choose(&mut game_state, &choice, "Play Muse").unwrap();
+        let choice = choice.choose(&mut game_state, 0).unwrap();
+        let choice = choose(&mut game_state, &choice, "Use Looter's ability").unwrap();
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player1, 1, 0)).unwrap();
+        assert_eq!(game_state.turn_state().people_played(), 1);
+        assert_eq!(game_state.turn_state().abilities_used(), 1);
+        assert_eq!(game_state.turn_state().events_resolved(), 0);
+
+        // the Bombardment that resolves at the start of Player 1's turn counts for that turn
+        let choice = choose(&mut game_state, &choice, "End turn").unwrap();
+        assert_eq!(choice.chooser(&game_state), Player1);
+        assert_eq!(game_state.turn_state().people_played(), 0);
+        assert_eq!(game_state.turn_state().abilities_used(), 0);
+        assert_eq!(game_state.turn_state().events_resolved(), 1);
+    }
+
+    /// Leaves only the given number of cards in the deck (with the rest in the discard pile),
+    /// after the deck has already been reshuffled once, so that running out ends the game in a
+    /// tie.
+    fn set_up_last_deck(game_state: &mut GameState, num_cards: usize) {
+        let deck = mem::take(&mut game_state.deck);
+        let (discarded, kept) = deck.split_at(deck.len() - num_cards);
+        for &card in discarded {
+            game_state.discard.push(card);
+        }
+        game_state.deck = kept.to_vec();
+        game_state.has_reshuffled_deck = true;
+    }
+
+    #[test]
+    fn scientist_ties_when_the_deck_runs_out_again() {
+        // (with Obelisk destroyed, since it would win the game when the deck's last card is drawn)
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "person p1 1 1 Scientist",
+            "camp p2 1 Obelisk",
+            "status p2 1 destroyed",
+        ]);
+        set_up_last_deck(&mut game_state, 2);
+        let discard_len = game_state.discard.len();
+
+        // the two cards left are discarded before the third draw ends the game
+        let result = choose(&mut game_state, &choice, "Use Scientist's ability");
+        assert_eq!(result.err(), Some(GameResult::Tie));
+        assert!(game_state.deck.is_empty());
+        assert_eq!(game_state.discard.len(), discard_len + 2);
+    }
+
+    #[test]
+    fn obelisk_wins_when_the_last_card_of_the_deck_is_drawn() {
+        let obelisk = card_set()
+            .camp_types
+            .iter()
+            .find(|camp_type| camp_type.name == "Obelisk")
+            .unwrap();
+        // (the players with an Obelisk, and the result of drawing the last card)
+        let cases = [
+            (&[][..], None),
+            (&[Player1][..], Some(GameResult::P1Wins)),
+            (&[Player2][..], Some(GameResult::P2Wins)),
+            (&[Player1, Player2][..], Some(GameResult::Tie)),
+        ];
+        for (obelisk_players, expected) in cases {
+            // (the Obelisk that Player 2 starts with is destroyed, so it doesn't count)
+            let (mut game_state, _) =
+                position(&["turn p1", "camp p2 1 Obelisk", "status p2 1 destroyed"]);
+            for &player in obelisk_players {
+                game_state.player_mut(player).columns[1].camp.camp_type = obelisk;
+            }
+            set_up_last_deck(&mut game_state, 2);
+            game_state.has_reshuffled_deck = false;
+
+            // only the last card counts, and it wins even before the deck has been reshuffled
+            assert!(game_state.draw_card().is_ok(), "{obelisk_players:?}");
+            assert_eq!(
+                game_state.draw_card().err(),
+                expected,
+                "{obelisk_players:?}"
+            );
+            assert!(game_state.deck.is_empty());
+        }
+    }
+
+    #[test]
+    fn a_draw_junk_effect_ties_when_the_deck_runs_out_again() {
+        let (mut game_state, choice) = position(&["turn p1", "hand p1 add Cult Leader"]);
+        set_up_last_deck(&mut game_state, 0);
+        let discard_len = game_state.discard.len();
+
+        // the junked card is discarded before its draw ends the game
+        let result = choose(&mut game_state, &choice, "Junk Cult Leader");
+        assert_eq!(result.err(), Some(GameResult::Tie));
+        assert!(game_state.player(Player1).hand.is_empty());
+        assert_eq!(game_state.discard.len(), discard_len + 1);
+    }
+
+    #[test]
+    fn gaining_a_punk_ties_when_the_deck_runs_out_again() {
+        let (mut game_state, choice) = position(&["turn p1", "person p1 1 1 Rabble Rouser"]);
+        set_up_last_deck(&mut game_state, 0);
+
+        let result = choose(&mut game_state, &choice, "Use Rabble Rouser's ability");
+        assert_eq!(result.err(), Some(GameResult::Tie));
+        assert_eq!(game_state.player(Player1).people().count(), 1);
+    }
+
+    /// Starts a new game with the base card set and the default rules, in which the players
+    /// choose which of the camps they were dealt to keep.
+    fn new_drafted_game(seed: u64) -> (GameState<'static>, Choice<'static>) {
+        let card_set = card_set();
+        GameState::new_seeded(
+            &card_set.camp_types,
+            &card_set.person_types,
+            &card_set.event_types,
+            GameRules::default(),
+            seed,
+        )
+    }
+
+    #[test]
+    fn camp_draft_offers_every_set_of_3_dealt_camps() {
+        let (game_state, choice) = new_drafted_game(0);
+        let first_player = game_state.cur_player;
+
+        assert!(game_state.is_choosing_camps());
+        assert!(matches!(choice, Choice::CampSelect(_)));
+        assert_eq!(choice.chooser(&game_state), first_player);
+        assert_eq!(choice.num_options(), 20); // 6 choose 3
+
+        // until both have chosen, the boards show nothing of what either player was dealt
+        assert_eq!(
+            game_state.player(Player1).columns,
+            game_state.player(Player2).columns
+        );
+    }
+
+    #[test]
+    fn hands_are_dealt_only_after_both_players_keep_their_camps() {
+        let (mut game_state, choice) = new_drafted_game(0);
+        let first_player = game_state.cur_player;
+        let first_camps = match &choice {
+            Choice::CampSelect(camp_choice) => camp_choice.camp_sets()[3],
+            _ => unreachable!(),
+        };
+
+        let choice = choice.choose(&mut game_state, 3).unwrap();
+        assert_eq!(choice.chooser(&game_state), first_player.other());
+        assert!(game_state.player(Player1).hand.is_empty());
+        assert!(game_state.player(Player2).hand.is_empty());
+
+        let choice = choice.choose(&mut game_state, 0).unwrap();
+        assert!(!game_state.is_choosing_camps());
+        assert!(matches!(choice, Choice::Action(_)));
+        assert!(!game_state.player(Player1).hand.is_empty());
+        assert!(!game_state.player(Player2).hand.is_empty());
+        let kept = game_state
+            .player(first_player)
+            .columns
+            .iter()
+            .map(|column| column.camp.camp_type.name)
+            .collect_vec();
+        assert_eq!(kept, first_camps.map(|camp_type| camp_type.name));
+    }
+
+    #[test]
+    fn the_second_chooser_cant_observe_the_first_pick() {
+        let observe_after_first_pick = |option| {
+            let (mut game_state, choice) = new_drafted_game(0);
+            let second_player = game_state.cur_player.other();
+            let choice = choice.choose(&mut game_state, option).unwrap();
+            let observed =
+                observed_state::ObservedState::from_game_state(&game_state, &choice, second_player);
+            (observed, game_state.fingerprint())
+        };
+        let (observed_a, fingerprint_a) = observe_after_first_pick(0);
+        let (observed_b, fingerprint_b) = observe_after_first_pick(19);
+        assert!(observed_a == observed_b);
+        assert_ne!(fingerprint_a, fingerprint_b);
+    }
+
+    #[test]
+    fn determinization_re_deals_the_opponents_camps() {
+        let (mut game_state, choice) = new_drafted_game(0);
+        let second_player = game_state.cur_player.other();
+        let choice = choice.choose(&mut game_state, 0).unwrap();
+        let my_camp_sets = match &choice {
+            Choice::CampSelect(camp_choice) => camp_choice.camp_sets().to_vec(),
+            _ => unreachable!(),
+        };
+
+        let mut opponent_picks = std::collections::HashSet::new();
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut world = controllers::monte_carlo::randomize_unobserved_with(
+                &game_state.view_for(second_player),
+                &mut rng,
+            );
+            let camp_draft = world.camp_draft.as_ref().unwrap();
+            let [opponent_dealt, my_dealt] = match second_player {
+                Player1 => [&camp_draft.dealt[1], &camp_draft.dealt[0]],
+                Player2 => [&camp_draft.dealt[0], &camp_draft.dealt[1]],
+            };
+            assert_eq!(opponent_dealt.len(), 6);
+            assert!(opponent_dealt.iter().all(|camp| !my_dealt.contains(camp)));
+            let opponent_kept = camp_draft.kept[CampDraft::index(second_player.other())].unwrap();
+            assert!(opponent_kept
+                .iter()
+                .all(|camp| opponent_dealt.contains(camp)));
+            opponent_picks.insert(opponent_kept.map(|camp_type| camp_type.name));
+
+            // my own options are the same in every world
+            let choice = choice.choose(&mut world, 0);
+            assert!(choice.is_ok());
+            assert_eq!(
+                world.player(second_player).columns[0].camp.camp_type,
+                my_camp_sets[0][0]
+            );
+        }
+        assert!(opponent_picks.len() > 1);
+    }
+}
@@ -0,0 +1,129 @@
+//! This module contains an advisor for deciding whether to keep an opening hand.
+
+use rand::rngs::StdRng;
+
+use super::choices::Choice;
+use super::controllers::monte_carlo::compute_rollout_score;
+use super::controllers::random::RandomController;
+use super::locations::Player;
+use super::{GameState, IconEffect, PersonOrEventType, WATER_PER_TURN};
+
+/// How much better (as a win rate) mulliganing must look before it is recommended.
+/// Random rollouts are noisy, so ties go to keeping the hand.
+const MULLIGAN_MARGIN: f64 = 0.03;
+
+/// A keep/mulligan recommendation for an opening hand, along with the reasoning behind it.
+#[derive(Debug, Clone)]
+pub struct HandAdvice {
+    /// Whether the hand should be kept.
+    pub keep: bool,
+
+    /// Human-readable lines explaining the recommendation.
+    pub reasons: Vec<String>,
+}
+
+/// Evaluates the opening hand and camps of the player who must make the given mulligan choice,
+/// using `num_rollouts` random self-play games for each of keeping and mulliganing (randomized
+/// with `rng`).
+///
+/// Panics if `choice` is not a `Choice::Mulligan`.
+pub fn evaluate_opening_hand<'ctype>(
+    game_state: &GameState<'ctype>,
+    choice: &Choice<'ctype>,
+    num_rollouts: u32,
+    rng: &mut StdRng,
+) -> HandAdvice {
+    let player = match choice {
+        Choice::Mulligan(mulligan_choice) => mulligan_choice.chooser(),
+        _ => panic!("evaluate_opening_hand requires a mulligan choice"),
+    };
+
+    // estimate the win rates of keeping (option 0) and mulliganing (option 1)
+    let mut estimate_win_rate = |option_index| {
+        let total_score: u32 = (0..num_rollouts)
+            .map(|_| {
+                compute_rollout_score(
+                    player,
+                    game_state,
+                    choice,
+                    &|_, rng: &mut StdRng| RandomController::from_rng(rng),
+                    option_index,
+                    rng,
+                )
+            })
+            .sum();
+        (total_score as f64) / ((num_rollouts * 2) as f64)
+    };
+    let keep_win_rate = estimate_win_rate(0);
+    let mulligan_win_rate = estimate_win_rate(1);
+
+    let mut reasons = describe_hand(game_state, player);
+    reasons.push(format!(
+        "Simulations: keep wins {:.1}%, mulligan wins {:.1}% ({num_rollouts} games each)",
+        keep_win_rate * 100.0,
+        mulligan_win_rate * 100.0,
+    ));
+
+    HandAdvice {
+        keep: mulligan_win_rate < keep_win_rate + MULLIGAN_MARGIN,
+        reasons,
+    }
+}
+
+/// Returns lines describing the curve, early plays, and water efficiency of a player's hand.
+fn describe_hand(game_state: &GameState, player: Player) -> Vec<String> {
+    let hand = &game_state.player(player).hand;
+
+    // the cost curve of the people in the hand
+    let mut people_by_cost = [0; 3]; // 0-1 water, 2 water, 3+ water
+    let mut num_events = 0;
+    for (card, count) in hand.iter() {
+        match card {
+            PersonOrEventType::Person(person_type) => {
+                people_by_cost[(person_type.cost as usize).saturating_sub(1).min(2)] += count;
+            }
+            PersonOrEventType::Event(_) => num_events += count,
+        }
+    }
+
+    // the cards that can be played with a single turn's water
+    let num_early_plays: usize = hand
+        .iter()
+        .filter(|(card, _)| card.cost() <= WATER_PER_TURN)
+        .map(|(_, count)| count)
+        .sum();
+
+    // how much water the hand needs, and how much its junk effects can provide
+    let num_cards = hand.count();
+    let total_cost: u32 = hand
+        .iter()
+        .map(|(card, count)| card.cost() * (count as u32))
+        .sum();
+    let num_water_junks: usize = hand
+        .iter()
+        .filter(|(card, _)| card.junk_effect() == IconEffect::Water)
+        .map(|(_, count)| count)
+        .sum();
+
+    let camp_cards: u32 = game_state
+        .player(player)
+        .columns
+        .iter()
+        .map(|col| col.camp.camp_type.num_initial_cards)
+        .sum();
+
+    vec![
+        format!("Hand: {num_cards} cards from camps granting {camp_cards}"),
+        format!(
+            "Curve: {} cheap (0-1), {} mid (2), {} expensive (3+) people; {num_events} events",
+            people_by_cost[0], people_by_cost[1], people_by_cost[2],
+        ),
+        format!(
+            "Early plays: {num_early_plays} of {num_cards} cards cost {WATER_PER_TURN} water or less"
+        ),
+        format!(
+            "Water efficiency: {:.1} average cost; {num_water_junks} cards junk for water",
+            if num_cards == 0 { 0.0 } else { (total_cost as f64) / (num_cards as f64) },
+        ),
+    ]
+}
@@ -2,11 +2,17 @@ use std::collections::BTreeMap;
 
 use crate::cards::Cards;
 
-use super::choices::Choice;
+use super::camps::CampType;
+use super::choices::{Choice, SelectTarget};
 use super::events::EventType;
 use super::locations::Player;
 use super::player_state::CardColumn;
-use super::{GameState, PersonOrEventType};
+use super::turn_state::TurnState;
+use super::{CampDraft, GameState, PersonOrEventType};
+
+/// While the players are choosing which camps to keep: the camps I was dealt, the camps I chose
+/// (if I have), and whether my opponent has chosen yet (but not what they chose).
+type ObservedCampDraft<'ctype> = (Vec<&'ctype CampType>, Option<[&'ctype CampType; 3]>, bool);
 
 /// A hashable multiset of cards.
 #[derive(Clone, PartialEq, Eq, Hash, Default)]
@@ -41,7 +47,8 @@ impl<'ctype> From<&Cards<PersonOrEventType<'ctype>>> for HashableCards<'ctype> {
 /// Stores the game state observed by a single player.
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct ObservedStateFull<'ctype> {
-    undrawn_cards: HashableCards<'ctype>,
+    /// The cards in the deck and my opponent's hand, which I can't tell apart.
+    unaccounted_cards: HashableCards<'ctype>,
     discard: HashableCards<'ctype>,
 
     /// The cards I have in my hand.
@@ -58,10 +65,11 @@ pub struct ObservedStateFull<'ctype> {
 
     cur_player: Player,
     cur_player_water: u32,
-    other_player_has_water_silo: bool,
-    has_paid_to_draw: bool,
-    has_played_event: bool,
+    my_has_water_silo: bool,
+    opponent_has_water_silo: bool,
+    turn_state: TurnState,
     has_reshuffled_deck: bool,
+    camp_draft: Option<ObservedCampDraft<'ctype>>,
     // TODO: Does this struct need to include the current choice too?
     // I think it just needs to uniquely identify nodes in the game search tree.
     // edit: YES, it needs to include some info about the current choice.
@@ -69,6 +77,9 @@ pub struct ObservedStateFull<'ctype> {
     //       Asserting that option counts match should catch issues like this.
     choice_type: std::mem::Discriminant<Choice<'ctype>>,
     num_options: usize,
+    /// The targets selected so far in a multi-select choice, which only change the game state
+    /// once the selection is finished (just the number of them, for my opponent's selection).
+    choice_selected: (usize, Vec<SelectTarget<'ctype>>),
 }
 
 impl<'ctype> ObservedStateFull<'ctype> {
@@ -79,8 +90,8 @@ impl<'ctype> ObservedStateFull<'ctype> {
         player: Player,
     ) -> Self {
         ObservedStateFull {
-            undrawn_cards: (&game_state.deck).into(),
-            discard: (&game_state.discard).into(),
+            unaccounted_cards: (&game_state.view_for(player).unaccounted_cards()).into(),
+            discard: game_state.discard.as_slice().into(),
             my_hand: (&game_state.player(player).hand).into(),
             opponent_hand_known: HashableCards::default(), // TODO: track known cards
             opponent_hand_unknown_count: game_state.player(player.other()).hand.count(),
@@ -90,14 +101,30 @@ impl<'ctype> ObservedStateFull<'ctype> {
             opponent_events: game_state.player(player.other()).events,
             cur_player: game_state.cur_player,
             cur_player_water: game_state.cur_player_water,
-            other_player_has_water_silo: game_state
-                .player(game_state.cur_player.other())
-                .has_water_silo,
-            has_paid_to_draw: game_state.has_paid_to_draw,
-            has_played_event: game_state.has_played_event,
+            my_has_water_silo: game_state.player(player).has_water_silo,
+            opponent_has_water_silo: game_state.player(player.other()).has_water_silo,
+            turn_state: game_state.turn_state,
             has_reshuffled_deck: game_state.has_reshuffled_deck,
+            camp_draft: game_state.camp_draft.as_ref().map(|camp_draft| {
+                (
+                    camp_draft.dealt[CampDraft::index(player)].clone(),
+                    camp_draft.kept[CampDraft::index(player)],
+                    camp_draft.kept[CampDraft::index(player.other())].is_some(),
+                )
+            }),
             choice_type: std::mem::discriminant(choice),
-            num_options: choice.num_options(game_state),
+            num_options: choice.num_options(),
+            choice_selected: match choice {
+                Choice::MultiSelect(multi_select_choice) => {
+                    let selected = multi_select_choice.selected();
+                    if multi_select_choice.chooser() == player {
+                        (selected.len(), selected.to_vec())
+                    } else {
+                        (selected.len(), Vec::new())
+                    }
+                }
+                _ => (0, Vec::new()),
+            },
         }
     }
 }
@@ -117,4 +144,15 @@ impl<'ctype> ObservedState<'ctype> {
         ObservedStateFull::from_game_state(game_state, choice, player).hash(&mut hasher);
         ObservedState(hasher.finish(), std::marker::PhantomData)
     }
+
+    /// Returns the hash that identifies this observed state, for writing it to a file. (The
+    /// hash is stable across runs of the same build of the program.)
+    pub fn key(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the observed state identified by the given hash (see `key`).
+    pub fn from_key(key: u64) -> Self {
+        ObservedState(key, std::marker::PhantomData)
+    }
 }
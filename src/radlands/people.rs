@@ -1,4 +1,3 @@
-use std::collections::HashSet;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 
@@ -6,11 +5,14 @@ use by_address::ByAddress;
 use itertools::Itertools;
 use tui::text::Span;
 
+use crate::locale::{translate, Section};
+
 use super::abilities::*;
 use super::choices::*;
 use super::locations::PlayLocation;
+use super::player_state::PersonInteraction;
 use super::styles::*;
-use super::{GameResult, GameViewMut, IconEffect};
+use super::{GameResult, GameViewMut, IconEffect, OnJunk, PersonOrEventType};
 
 /// Type alias for on_enter_play handler functions.
 type OnEnterPlayHandler = for<'g, 'ctype> fn(
@@ -18,29 +20,76 @@ type OnEnterPlayHandler = for<'g, 'ctype> fn(
     PlayLocation,
 ) -> Result<ChoiceFuture<'g, 'ctype>, GameResult>;
 
-/// Enum for identifying "special" people that require special handling.
+/// A trait of a person type: a passive effect that the game-state queries it affects check for
+/// (rather than checking for particular people), so that a person with any combination of traits
+/// only needs data. Except for `FreeInDestroyedColumn` and `CopyAbility`, which are about the
+/// person itself, a trait affects its owner's whole board, and only while the person is
+/// uninjured.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum SpecialType {
-    None,
-    Holdout,
-    Mimic,
-    ArgoYesky,
-    ZetoKhan,
-    KarliBlaze,
-    VeraVosh,
+pub enum PassiveEffect {
+    /// Costs 0 to play in the column of a destroyed camp (Holdout).
+    FreeInDestroyedColumn,
+    /// Can use the ability of one of your ready people or any undamaged enemy (Mimic).
+    CopyAbility,
+    /// Gives the person's first ability to all your other people (Argo Yesky).
+    GrantAbility,
+    /// The first event you play each turn resolves instantly (Zeto Khan).
+    InstantFirstEvent,
+    /// All your people enter play ready (Karli Blaze).
+    PeopleEnterReady,
+    /// The first time you use a card's ability each turn, that card stays ready (Vera Vosh).
+    FirstUseStaysReady,
+}
+
+impl PassiveEffect {
+    /// All the passive effects.
+    pub const ALL: [PassiveEffect; 6] = [
+        PassiveEffect::FreeInDestroyedColumn,
+        PassiveEffect::CopyAbility,
+        PassiveEffect::GrantAbility,
+        PassiveEffect::InstantFirstEvent,
+        PassiveEffect::PeopleEnterReady,
+        PassiveEffect::FirstUseStaysReady,
+    ];
+
+    /// Returns the text of this trait (in English).
+    pub fn description(self) -> &'static str {
+        match self {
+            PassiveEffect::FreeInDestroyedColumn => {
+                "Costs 0 to play in the column of a destroyed camp"
+            }
+            PassiveEffect::CopyAbility => {
+                "Can use the ability of one of your ready people or any undamaged enemy"
+            }
+            PassiveEffect::GrantAbility => "All your people have this card's ability",
+            PassiveEffect::InstantFirstEvent => {
+                "The first event you play each turn resolves instantly"
+            }
+            PassiveEffect::PeopleEnterReady => "All your people enter play ready",
+            PassiveEffect::FirstUseStaysReady => {
+                "The first time you use a card's ability each turn, that card stays ready"
+            }
+        }
+    }
 }
 
 /// A type of person card.
 pub struct PersonType {
-    /// The person's name.
+    /// The person's name in English, which never changes (see `display_name`).
     pub name: &'static str,
 
+    /// The index of this person type in the list of person types, which identifies it.
+    pub id: usize,
+
     /// How many of this person type are in the deck.
     pub num_in_deck: u32,
 
     /// The person's junk effect.
     pub junk_effect: IconEffect,
 
+    /// The person's own behavior when junked, if any.
+    pub on_junk: Option<OnJunk>,
+
     /// The water cost to play this person.
     pub cost: u32,
 
@@ -50,18 +99,26 @@ pub struct PersonType {
     /// The person's on-enter-play handler, if any.
     pub on_enter_play: Option<OnEnterPlayHandler>,
 
+    /// The text of the person's enter-play effect (in English), if it has one.
+    pub enter_play_effect: Option<&'static str>,
+
+    /// Whether the person's enter-play effect is optional ("may"), so that its owner is asked
+    /// whether to resolve it (see `perform_optional_effect`). Otherwise, it's resolved as far as
+    /// possible.
+    pub enter_play_optional: bool,
+
     /// Whether this person enters play ready.
     pub enters_play_ready: bool,
 
-    /// The special identity of this person type (if any). Used for people that require special
-    /// handling elsewhere in the code.
-    pub special_type: SpecialType,
+    /// The person's traits (see `PassiveEffect`).
+    pub passive_effects: Vec<PassiveEffect>,
 }
 
-// hash references by address
+// hash references by ID, which is cheap, stable across runs of the program, and (within a list
+// of card types) agrees with comparing by address
 impl Hash for &PersonType {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        ByAddress(*self).hash(state);
+        self.id.hash(state);
     }
 }
 
@@ -79,10 +136,22 @@ impl fmt::Debug for PersonType {
     }
 }
 
+impl PersonType {
+    /// Returns the person's name in the loaded locale.
+    pub fn display_name(&self) -> &'static str {
+        translate(Section::Person, self.name)
+    }
+
+    /// Returns whether this person type has the given trait.
+    pub fn has_passive_effect(&self, passive_effect: PassiveEffect) -> bool {
+        self.passive_effects.contains(&passive_effect)
+    }
+}
+
 impl StyledName for PersonType {
     /// Returns this person's name, styled for display.
     fn styled_name(&self) -> Span<'static> {
-        Span::styled(self.name, *PERSON_READY)
+        Span::styled(self.display_name(), *PERSON_READY)
     }
 }
 
@@ -93,6 +162,9 @@ macro_rules! on_enter_play {
     (($game_view:ident) => $on_enter_play:expr) => {
         Some(|$game_view, _play_loc| $on_enter_play)
     };
+    ((mut $game_view:ident) => $on_enter_play:expr) => {
+        Some(|mut $game_view, _play_loc| $on_enter_play)
+    };
     (($game_view:ident, $play_loc:ident) => $on_enter_play:expr) => {
         Some(|$game_view, $play_loc| $on_enter_play)
     };
@@ -101,12 +173,30 @@ macro_rules! on_enter_play {
     };
 }
 
-macro_rules! special_type {
+macro_rules! enter_play_effect {
+    () => {
+        None
+    };
+    ($enter_play_effect:literal) => {
+        Some($enter_play_effect)
+    };
+}
+
+macro_rules! on_junk {
     () => {
-        SpecialType::None
+        None
     };
-    ($type:ident) => {
-        SpecialType::$type
+    ($on_junk:expr) => {
+        Some($on_junk)
+    };
+}
+
+macro_rules! enter_play_optional {
+    () => {
+        false
+    };
+    ($enter_play_optional:literal) => {
+        $enter_play_optional
     };
 }
 
@@ -126,32 +216,39 @@ macro_rules! person_type {
         name: $name:literal,
         num_in_deck: $num_in_deck:literal,
         junk_effect: $junk_effect:expr,
+        $(on_junk: $on_junk:expr,)?
         cost: $cost:literal,
         abilities: [$($ability:expr),* $(,)?],
         $(
+            enter_play_effect: $enter_play_effect:literal,
+            $(enter_play_optional: $enter_play_optional:literal,)?
             on_enter_play($($on_enter_play_param:ident $($on_enter_play_mut:ident)?),+)
                 => $on_enter_play_expr:expr,
         )?
         $(enters_play_ready: $enters_play_ready:literal,)?
-        $(special_type: $special_type:tt,)?
+        $(passive_effects: [$($passive_effect:ident),* $(,)?],)?
     } => {
         PersonType {
+            id: 0, // assigned by get_person_types
             name: $name,
             num_in_deck: $num_in_deck,
             junk_effect: $junk_effect,
+            on_junk: on_junk!($($on_junk)?),
             cost: $cost,
             abilities: vec![$($ability),*],
             on_enter_play: on_enter_play!(
                 $(($($on_enter_play_param $($on_enter_play_mut)?),+) => $on_enter_play_expr)?
             ),
+            enter_play_effect: enter_play_effect!($($enter_play_effect)?),
+            enter_play_optional: enter_play_optional!($($($enter_play_optional)?)?),
             enters_play_ready: enters_play_ready!($($enters_play_ready)?),
-            special_type: special_type!($($special_type)?),
+            passive_effects: vec![$($(PassiveEffect::$passive_effect),*)?],
         }
     };
 }
 
 pub fn get_person_types() -> Vec<PersonType> {
-    vec![
+    let mut person_types = vec![
         person_type! {
             name: "Cult Leader",
             num_in_deck: 2,
@@ -190,7 +287,7 @@ pub fn get_person_types() -> Vec<PersonType> {
             junk_effect: IconEffect::Raid,
             cost: 2,
             abilities: [icon_ability(1, IconEffect::Damage)],
-            special_type: Holdout, // costs 0 to play in the column of a destroyed camp
+            passive_effects: [FreeInDestroyedColumn],
         },
         person_type! {
             name: "Repair Bot",
@@ -198,8 +295,8 @@ pub fn get_person_types() -> Vec<PersonType> {
             junk_effect: IconEffect::Injure,
             cost: 1,
             abilities: [icon_ability(2, IconEffect::Restore)],
+            enter_play_effect: "Restore",
             on_enter_play(game_view) => {
-                // when this card enters play, restore
                 Ok(game_view.restore_card())
             },
         },
@@ -244,7 +341,7 @@ pub fn get_person_types() -> Vec<PersonType> {
             junk_effect: IconEffect::Injure,
             cost: 1,
             abilities: [], // mimic gets its abilities from other people
-            special_type: Mimic,
+            passive_effects: [CopyAbility],
         },
         person_type! {
             name: "Sniper",
@@ -268,35 +365,24 @@ pub fn get_person_types() -> Vec<PersonType> {
                 cost => 1;
                 can_perform => true;
                 perform(game_view) => {
-                    // discard the top 3 cards and collect the unique junk effects that can be used
-                    let junk_effects: HashSet<IconEffect> = (0..3)
-                        .filter_map(|_| {
-                            // draw a card, propagating any end-game condition
-                            let card_type = match game_view.game_state.draw_card() {
-                                Ok(card_type) => card_type,
-                                Err(game_result) => return Some(Err(game_result)),
-                            };
-
-                            // discard the card
+                    // discard the top 3 cards, propagating any end-game condition
+                    let revealed: Vec<PersonOrEventType> = (0..3)
+                        .map(|_| {
+                            let card_type = game_view.game_state.draw_card()?;
                             game_view.game_state.discard.push(card_type);
-
-                            // return the card's junk effect (if it can be performed)
-                            let effect = card_type.junk_effect();
-                            if effect.can_perform(&game_view.as_non_mut()) {
-                                Some(Ok(effect))
-                            } else {
-                                None
-                            }
+                            Ok(card_type)
                         })
                         .collect::<Result<_, GameResult>>()?;
 
-                    // ask the player which junk effect to use (if any)
-                    if junk_effects.is_empty() {
-                        Ok(game_view.immediate_future())
-                    } else {
-                        let junk_effects: Vec<IconEffect> = junk_effects.into_iter().collect();
-                        Ok(IconEffectChoice::future(game_view.player, junk_effects))
-                    }
+                    // ask the player which card's junk effect to use (if any), offering each
+                    // unique usable card once in the order the cards were discarded
+                    let usable = revealed
+                        .iter()
+                        .copied()
+                        .filter(|card| card.junk_effect().can_perform(&game_view.as_non_mut()))
+                        .unique()
+                        .collect_vec();
+                    Ok(RevealedJunkChoice::future(game_view.player, revealed, usable))
                 };
             }],
         },
@@ -314,7 +400,16 @@ pub fn get_person_types() -> Vec<PersonType> {
                         .then_future_chain(move |_game_state, _| {
                             Ok(IconEffectChoice::future(game_view.player, vec![IconEffect::Restore])
                                 .then_future(move |game_state, _| {
-                                    game_state.damage_card_at(card_loc, false, true)
+                                    // unless the damage destroyed this card too (e.g. by
+                                    // destroying a Reactor)
+                                    let is_in_play = game_state
+                                        .player(card_loc.player())
+                                        .card_locs()
+                                        .any(|loc| loc == card_loc.player_loc());
+                                    if is_in_play {
+                                        game_state.damage_card_at(card_loc, false, true)?;
+                                    }
+                                    Ok(())
                                 }))
                         })
                     )
@@ -339,7 +434,10 @@ pub fn get_person_types() -> Vec<PersonType> {
                 description => "Return one of your people to your hand";
                 cost => 0;
                 can_perform => true;
-                perform(game_view) => Ok(RescuePersonChoice::future(game_view.player));
+                perform(game_view) => {
+                    let locations = game_view.my_state().person_locs().collect_vec();
+                    Ok(RescuePersonChoice::future(game_view.player, locations))
+                };
             }],
             enters_play_ready: true,
         },
@@ -355,14 +453,17 @@ pub fn get_person_types() -> Vec<PersonType> {
                 perform(game_view) => {
                     let player = game_view.player;
                     Ok(game_view.damage_enemy().then_future_chain(move |game_state, _| {
+                        // the damage back follows the usual rules: the opponent chooses one of
+                        // this player's unprotected cards (and may end the game by destroying
+                        // this player's last camp)
                         let opponent_view = game_state.view_for(player.other());
                         Ok(opponent_view.damage_enemy().ignore_result())
                     }))
                 };
             }],
+            enter_play_effect: "Punk",
             on_enter_play(game_view) => {
-                // when this card enters play, punk
-                Ok(game_view.gain_punk())
+                game_view.gain_punk()
             },
         },
         person_type! {
@@ -405,8 +506,8 @@ pub fn get_person_types() -> Vec<PersonType> {
             junk_effect: IconEffect::Injure,
             cost: 1,
             abilities: [icon_ability(1, IconEffect::Damage)],
+            enter_play_effect: "Draw, then damage this card",
             on_enter_play(mut game_view, play_loc) => {
-                // when this card enters play, draw, then damage this card
                 game_view.draw_card_into_hand()?;
 
                 let play_loc = play_loc.for_player(game_view.player);
@@ -434,15 +535,13 @@ pub fn get_person_types() -> Vec<PersonType> {
                 can_perform(game_view) => game_view.other_state().has_event();
                 perform => IconEffect::Damage;
             }],
-            on_enter_play(game_view) => {
-                // when this card enters play, you may move all the opponent's events back 1
-                if game_view.other_state().has_event() {
-                    // the AI (and humans) pretty much always choose to move events back...
-                    // so should this even be a choice?
-                    Ok(MoveEventsChoice::future(game_view.player))
-                } else {
-                    Ok(game_view.immediate_future())
-                }
+            enter_play_effect: "You may move all the opponent's events back 1",
+            // the AI (and humans) pretty much always choose to move events back, but the card
+            // says "may", so this is a choice under either rules fidelity
+            enter_play_optional: true,
+            on_enter_play(mut game_view) => {
+                game_view.other_state_mut().move_events_back();
+                Ok(game_view.immediate_future())
             },
         },
         person_type! {
@@ -454,8 +553,10 @@ pub fn get_person_types() -> Vec<PersonType> {
                 description => "Destroy all damaged enemies";
                 cost => 1;
                 can_perform(game_view) => {
-                    // can perform if the opponent has any injured people
-                    game_view.other_state().people().any(|person| person.is_injured())
+                    // can perform if the opponent has any damaged (i.e. injured) people
+                    game_view
+                        .other_state()
+                        .has_person_counting_for(PersonInteraction::Damaged)
                 };
                 perform(mut game_view) => {
                     game_view.destroy_all_injured_enemies();
@@ -469,11 +570,11 @@ pub fn get_person_types() -> Vec<PersonType> {
             junk_effect: IconEffect::GainPunk,
             cost: 3,
             abilities: [icon_ability(1, IconEffect::Damage)],
+            enter_play_effect: "Punk",
             on_enter_play(game_view) => {
-                // when this card enters play, punk
-                Ok(game_view.gain_punk())
+                game_view.gain_punk()
             },
-            special_type: ArgoYesky, // Argo Yesky gives its ability to other people (when uninjured)
+            passive_effects: [GrantAbility],
         },
         person_type! {
             name: "Magnus Karv",
@@ -485,10 +586,15 @@ pub fn get_person_types() -> Vec<PersonType> {
                 cost => 2;
                 can_perform => true;
                 perform(game_view) => {
-                    let non_empty_cols = game_view.other_state().enumerate_columns()
-                        .filter(|(_, col)| !col.is_empty())
-                        .map(|(col_idx, _)| col_idx)
-                        .collect_vec();
+                    let non_empty_cols = DamageColumnChoice::target_columns(
+                        game_view.game_state,
+                        game_view.player,
+                        false,
+                    );
+                    if non_empty_cols.is_empty() {
+                        // nothing to damage (the game should already be over)
+                        return Ok(game_view.immediate_future());
+                    }
                     Ok(DamageColumnChoice::future(game_view.player, false, false, non_empty_cols))
                 };
             }],
@@ -507,7 +613,7 @@ pub fn get_person_types() -> Vec<PersonType> {
                     Ok(DiscardChoice::discard_n_future(game_view.game_state, game_view.player, None, 3))
                 };
             }],
-            special_type: ZetoKhan, // Trait: the first event you play each turn resolves instantly
+            passive_effects: [InstantFirstEvent],
         },
         person_type! {
             name: "Karli Blaze",
@@ -516,7 +622,7 @@ pub fn get_person_types() -> Vec<PersonType> {
             cost: 3,
             abilities: [icon_ability(1, IconEffect::Damage)],
             enters_play_ready: true,
-            special_type: KarliBlaze, // Trait: all your people enter play ready
+            passive_effects: [PeopleEnterReady],
         },
         person_type! {
             name: "Vera Vosh",
@@ -524,8 +630,7 @@ pub fn get_person_types() -> Vec<PersonType> {
             junk_effect: IconEffect::GainPunk,
             cost: 3,
             abilities: [icon_ability(1, IconEffect::Injure)],
-            special_type: VeraVosh, // Trait: the first time you use a card's ability each turn,
-                                    //        that card stays ready
+            passive_effects: [FirstUseStaysReady],
         },
         person_type! {
             name: "Molgur Stang",
@@ -539,5 +644,391 @@ pub fn get_person_types() -> Vec<PersonType> {
                 perform(game_view) => Ok(game_view.destroy_enemy_camp().ignore_result());
             }],
         },
-    ]
+    ];
+    for (id, person_type) in person_types.iter_mut().enumerate() {
+        person_type.id = id;
+    }
+    person_types
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::locations::Player::{Player1, Player2};
+    use super::super::locations::{ColumnIndex, PersonRowIndex};
+    use super::super::player_state::{CampStatus, NonPunkStatus, Person, PlayerState};
+    use super::super::sandbox::testing::*;
+    use super::super::GameState;
+    use super::*;
+
+    /// Returns the English name of the person in the given slot (`column` and `slot` numbered
+    /// from 1, like in the sandbox commands), or "nobody".
+    fn person_at(player_state: &PlayerState, column: usize, slot: usize) -> &'static str {
+        match player_state.columns[column - 1].person_slots[slot - 1].as_ref() {
+            None => "nobody",
+            Some(Person::Punk { .. }) => "Punk",
+            Some(Person::NonPunk { person_type, .. }) => person_type.name,
+        }
+    }
+
+    /// Returns the status of the (non-punk) person in the given slot (numbered as in
+    /// `person_at`).
+    fn status_at(player_state: &PlayerState, column: usize, slot: usize) -> NonPunkStatus {
+        match player_state.columns[column - 1].person_slots[slot - 1] {
+            Some(Person::NonPunk { status, .. }) => status,
+            _ => panic!("no person other than a punk in column {column}, slot {slot}"),
+        }
+    }
+
+    #[test]
+    fn magnus_karv_damages_every_card_in_the_column() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "person p1 1 1 Magnus Karv",
+            "person p2 1 1 punk",
+            "person p2 1 2 Looter",
+        ]);
+
+        let choice = choose(&mut game_state, &choice, "Use Magnus Karv's ability").unwrap();
+        let choice = choose(&mut game_state, &choice, "Damage opponent's column 0").unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+
+        // the punk is destroyed even though it was protected, the person in front of it is
+        // injured (and moves back), and the camp is damaged
+        let opponent = game_state.player(Player2);
+        assert_eq!(person_at(opponent, 1, 1), "Looter");
+        assert_eq!(person_at(opponent, 1, 2), "nobody");
+        assert!(opponent.columns[0].person_slots[0]
+            .as_ref()
+            .unwrap()
+            .is_injured());
+        assert_eq!(opponent.columns[0].camp.status, CampStatus::Damaged);
+    }
+
+    #[test]
+    fn magnus_karv_skips_columns_with_nothing_to_damage() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "person p1 1 1 Magnus Karv",
+            "status p2 2 destroyed",
+        ]);
+
+        // the destroyed camp's column is empty, so it can't be chosen
+        let choice = choose(&mut game_state, &choice, "Use Magnus Karv's ability").unwrap();
+        assert_eq!(
+            labels(&game_state, &choice),
+            ["Damage opponent's column 0", "Damage opponent's column 2"],
+        );
+        let targets = DamageColumnChoice::target_columns(&game_state, Player1, false);
+        assert_eq!(targets, [ColumnIndex::from(0), ColumnIndex::from(2)]);
+        assert!(DamageColumnChoice::target_columns(&game_state, Player1, true).is_empty());
+    }
+
+    #[test]
+    fn vanguard_damage_back_can_destroy_the_last_camp() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "person p1 1 1 Vanguard",
+            "status p1 1 destroyed",
+            "status p1 2 damaged",
+            "status p1 3 destroyed",
+        ]);
+
+        // Vanguard damages one of the opponent's camps
+        let choice = choose(&mut game_state, &choice, "Use Vanguard's ability").unwrap();
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 1, 0)).unwrap();
+        assert_eq!(
+            game_state.player(Player2).columns[0].camp.status,
+            CampStatus::Damaged
+        );
+
+        // then the opponent damages back, choosing from the usual unprotected cards
+        assert_eq!(choice.chooser(&game_state), Player2);
+        let targets = (0..choice.num_options())
+            .map(|option| choice.option_target(option, &game_state))
+            .collect_vec();
+        assert_eq!(
+            targets,
+            [Some(card_loc(Player1, 1, 1)), Some(card_loc(Player1, 2, 0))],
+        );
+
+        // destroying the last camp ends the game
+        let result = choose_target(&mut game_state, &choice, card_loc(Player1, 2, 0));
+        assert_eq!(result.err(), Some(GameResult::P2Wins));
+    }
+
+    #[test]
+    fn vanguard_damage_back_continues_the_turn() {
+        let (mut game_state, choice) = position(&["turn p1", "water 3", "person p1 1 1 Vanguard"]);
+
+        let choice = choose(&mut game_state, &choice, "Use Vanguard's ability").unwrap();
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 1, 0)).unwrap();
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player1, 1, 1)).unwrap();
+
+        // the damage back injured Vanguard, and it's the Vanguard player's turn again
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(choice.chooser(&game_state), Player1);
+        let vanguard = game_state.player(Player1).columns[0].person_slots[0].as_ref();
+        assert!(vanguard.unwrap().is_injured());
+    }
+
+    /// Returns the icon effects of the abilities granted to the person in the given slot
+    /// (numbered from 1, like in the sandbox commands).
+    fn granted_effects(
+        player_state: &PlayerState,
+        column: usize,
+        slot: usize,
+    ) -> Vec<Option<IconEffect>> {
+        let loc = PlayLocation::new(
+            ColumnIndex::from(column - 1),
+            PersonRowIndex::from(slot - 1),
+        );
+        player_state
+            .granted_abilities(loc)
+            .into_iter()
+            .map(|ability| ability.icon_effect())
+            .collect()
+    }
+
+    #[test]
+    fn argo_yesky_grants_his_ability_only_while_uninjured() {
+        let commands = [
+            "turn p1",
+            "person p1 1 1 Argo Yesky",
+            "person p1 2 1 punk",
+            "person p1 3 1 Looter",
+        ];
+        let (game_state, _) = position(&commands);
+        let player_state = game_state.player(Player1);
+        assert_eq!(granted_effects(player_state, 1, 1), []);
+        assert_eq!(
+            granted_effects(player_state, 2, 1),
+            [Some(IconEffect::Damage)]
+        );
+        assert_eq!(
+            granted_effects(player_state, 3, 1),
+            [Some(IconEffect::Damage)]
+        );
+
+        let (game_state, _) = position(&[&commands[..], &["status p1 1 1 injured"]].concat());
+        let player_state = game_state.player(Player1);
+        assert_eq!(granted_effects(player_state, 2, 1), []);
+        assert_eq!(granted_effects(player_state, 3, 1), []);
+    }
+
+    #[test]
+    fn two_argo_yeskys_grant_the_same_ability_once() {
+        let (mut game_state, _) =
+            position(&["turn p1", "person p1 1 1 Argo Yesky", "person p1 2 1 punk"]);
+        // copy the Argo Yesky on the board, so that both are the same person type (as in a real
+        // game, where every copy of a card shares one type)
+        let argo = game_state.player(Player1).columns[0].person_slots[0].clone();
+        game_state.player_mut(Player1).columns[2].person_slots[0] = argo;
+
+        // duplicates are found by ability, not by the person granting it, so each Argo Yesky
+        // is also not granted the other's copy of his own ability
+        let player_state = game_state.player(Player1);
+        assert_eq!(
+            granted_effects(player_state, 2, 1),
+            [Some(IconEffect::Damage)]
+        );
+        assert_eq!(granted_effects(player_state, 1, 1), []);
+        assert_eq!(granted_effects(player_state, 3, 1), []);
+    }
+
+    #[test]
+    fn an_instant_event_names_the_card_that_makes_it_instant() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "person p1 1 1 Zeto Khan",
+            "hand p1 add Napalm",
+        ]);
+        let play_label = |game_state: &GameState<'static>, choice: &Choice<'static>| {
+            labels(game_state, choice)
+                .into_iter()
+                .find(|label| label.starts_with("Play Napalm"))
+                .unwrap()
+        };
+        let label = play_label(&game_state, &choice);
+        assert!(
+            label.starts_with("Play Napalm (resolves immediately due to Zeto Khan)"),
+            "{label}"
+        );
+
+        // a made-up person with the same trait is named instead
+        let mut herald = person_type! {
+            name: "Test Herald",
+            num_in_deck: 1,
+            junk_effect: IconEffect::Water,
+            cost: 1,
+            abilities: [],
+            passive_effects: [InstantFirstEvent],
+        };
+        herald.id = card_set().person_types.len();
+        let herald: &'static PersonType = Box::leak(Box::new(herald));
+        let herald = Person::new_non_punk(herald, &game_state.view_for(Player1));
+        game_state.player_mut(Player1).columns[0].person_slots[0] = Some(herald);
+        let choice = Choice::new_actions(&mut game_state);
+        let label = play_label(&game_state, &choice);
+        assert!(
+            label.starts_with("Play Napalm (resolves immediately due to Test Herald)"),
+            "{label}"
+        );
+    }
+
+    #[test]
+    fn a_punk_gets_the_abilities_of_every_granting_person() {
+        let (mut game_state, _) =
+            position(&["turn p1", "person p1 1 1 Argo Yesky", "person p1 2 1 punk"]);
+        // a made-up person who grants a different ability than Argo Yesky's
+        let mut granter = person_type! {
+            name: "Test Granter",
+            num_in_deck: 1,
+            junk_effect: IconEffect::Water,
+            cost: 1,
+            abilities: [icon_ability(1, IconEffect::Restore)],
+            passive_effects: [GrantAbility],
+        };
+        // an ID that no real person type has, since abilities are identified by it
+        granter.id = card_set().person_types.len();
+        let granter: &'static PersonType = Box::leak(Box::new(granter));
+        let granter = Person::new_non_punk(granter, &game_state.view_for(Player1));
+        game_state.player_mut(Player1).columns[2].person_slots[0] = Some(granter);
+
+        let player_state = game_state.player(Player1);
+        assert_eq!(
+            granted_effects(player_state, 2, 1),
+            [Some(IconEffect::Damage), Some(IconEffect::Restore)],
+        );
+        assert_eq!(
+            granted_effects(player_state, 1, 1),
+            [Some(IconEffect::Restore)]
+        );
+        assert_eq!(
+            granted_effects(player_state, 3, 1),
+            [Some(IconEffect::Damage)]
+        );
+    }
+
+    #[test]
+    fn mimic_copies_ready_people_of_its_own_and_any_undamaged_enemy() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "person p1 1 1 Mimic",
+            "person p1 2 1 Muse",
+            "person p1 3 1 Scout",
+            "status p1 2 1 notready",
+            "status p1 3 1 injured",
+            "person p2 1 1 Looter",
+            "person p2 2 1 Gunner",
+            "person p2 3 1 Muse",
+            "status p2 1 1 notready",
+            "status p2 2 1 injured",
+        ]);
+
+        // only the undamaged enemies can be copied: it doesn't matter whether they're ready, but
+        // the player's own people must be
+        let choice = choose(&mut game_state, &choice, "Use Mimic").unwrap();
+        assert_eq!(
+            labels(&game_state, &choice),
+            [
+                "Copy the ability of opponent's Looter at <column: 0, row: 1>: Damage; if this \
+                 hits a camp, draw (costs 2 water)",
+                "Copy the ability of opponent's Muse at <column: 2, row: 1>: Water (costs 0 water)",
+            ]
+        );
+    }
+
+    #[test]
+    fn mimic_chooses_whose_ability_to_copy_then_uses_it() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "person p1 1 1 Mimic",
+            "person p1 2 1 Muse",
+            "person p2 1 1 Looter",
+        ]);
+
+        // using Mimic only offers one action, and then asks whose ability to copy
+        let mimic_labels = labels(&game_state, &choice)
+            .into_iter()
+            .filter(|label| label.contains("Mimic"))
+            .collect_vec();
+        assert_eq!(
+            mimic_labels,
+            ["Use Mimic's ability: copy another person's ability"]
+        );
+        let choice = choose(&mut game_state, &choice, "Use Mimic").unwrap();
+        assert!(matches!(choice, Choice::CopyAbility(_)));
+        assert_eq!(
+            labels(&game_state, &choice),
+            [
+                "Copy the ability of your Muse at <column: 1, row: 1>: Water (costs 0 water)",
+                "Copy the ability of opponent's Looter at <column: 0, row: 1>: Damage; if this \
+                 hits a camp, draw (costs 2 water)",
+            ]
+        );
+
+        // copying Looter's ability costs its cost, and then asks for its target
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 1, 1)).unwrap();
+        assert!(matches!(choice, Choice::Damage(_)));
+        assert_eq!(game_state.cur_player_water, 1);
+        let choice = choose_target(&mut game_state, &choice, card_loc(Player2, 2, 0)).unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(
+            game_state.player(Player2).columns[1].camp.status,
+            CampStatus::Damaged
+        );
+
+        // it was Mimic that was used, not Looter
+        assert_eq!(
+            status_at(game_state.player(Player1), 1, 1),
+            NonPunkStatus::NotReady
+        );
+        assert_eq!(
+            status_at(game_state.player(Player2), 1, 1),
+            NonPunkStatus::Ready
+        );
+    }
+
+    #[test]
+    fn doomsayer_only_asks_to_move_events_back_when_that_would_change_something() {
+        let (mut game_state, choice) = position(&[
+            "turn p1",
+            "water 3",
+            "hand p1 add Doomsayer",
+            "event p2 1 Napalm",
+        ]);
+
+        // the opponent's event can move back, so Doomsayer's owner chooses whether to
+        let choice = choose(&mut game_state, &choice, "Play Doomsayer").unwrap();
+        let choice = choice.choose(&mut game_state, 0).unwrap();
+        assert_eq!(
+            labels(&game_state, &choice),
+            [
+                "Decline Doomsayer's optional effect (resolving Doomsayer's enter-play effect)",
+                "Resolve Doomsayer's optional effect (resolving Doomsayer's enter-play effect)",
+            ]
+        );
+        let napalm = game_state.player(Player2).events[0];
+        let choice = choose(&mut game_state, &choice, "Resolve").unwrap();
+        assert!(matches!(choice, Choice::Action(_)));
+        assert_eq!(game_state.player(Player2).events, [None, napalm, None]);
+
+        // with the event already at the back, or no events at all, there's nothing to decline
+        for event_command in [Some("event p2 3 Napalm"), None] {
+            let mut commands = vec!["turn p1", "water 3", "hand p1 add Doomsayer"];
+            commands.extend(event_command);
+            let (mut game_state, choice) = position(&commands);
+            let events = game_state.player(Player2).events;
+            let choice = choose(&mut game_state, &choice, "Play Doomsayer").unwrap();
+            let choice = choice.choose(&mut game_state, 0).unwrap();
+            assert!(matches!(choice, Choice::Action(_)), "{event_command:?}");
+            assert_eq!(game_state.player(Player2).events, events);
+        }
+    }
 }
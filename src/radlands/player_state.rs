@@ -1,17 +1,19 @@
 use crate::cards::Cards;
+use crate::locale::{translate, Section};
 
-use super::people::SpecialType;
+use super::abilities::AbilityId;
+use super::camps::SpecialCampType;
+use super::people::PassiveEffect;
 use super::*;
 
 /// Represents the state of a player's board and hand.
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct PlayerState<'ctype> {
     /// The cards in the player's hand, not including Water Silo.
     pub hand: Cards<PersonOrEventType<'ctype>>,
 
-    /// When it is not this player's turn, whether this player has Water Silo
-    /// in their hand. (They are assumed to not have it in their hand when it
-    /// *is* this player's turn.)
+    /// Whether this player has Water Silo in their hand (rather than on their board). It is
+    /// taken into the hand at the end of a turn, and can be junked for 1 water on a later turn.
     pub has_water_silo: bool,
 
     /// The three columns of the player's board.
@@ -22,29 +24,31 @@ pub struct PlayerState<'ctype> {
 }
 
 impl<'v, 'g: 'v, 'ctype: 'g> PlayerState<'ctype> {
-    /// Creates a new `PlayerState` with the given camps, drawing an initial
-    /// hand from the given deck.
-    pub fn new(camps: &[&'ctype CampType], deck: &mut Vec<PersonOrEventType<'ctype>>) -> Self {
-        // determine the number of starting cards from the set of camps
-        assert_eq!(camps.len(), 3);
-        let hand_size: usize = camps.iter().map(|c| c.num_initial_cards as usize).sum();
-
-        // draw the top hand_size cards from the deck
-        let deck_cut_index = deck.len() - hand_size;
-        let hand = Cards::from_iter(deck.drain(deck_cut_index..));
-
+    /// Creates a new `PlayerState` with the given camps and an empty hand (see
+    /// `draw_opening_hand`).
+    pub fn new(camps: [&'ctype CampType; 3]) -> Self {
         PlayerState {
-            hand,
+            hand: Cards::new(),
             has_water_silo: false,
-            columns: [
-                CardColumn::new(camps[0]),
-                CardColumn::new(camps[1]),
-                CardColumn::new(camps[2]),
-            ],
+            columns: camps.map(CardColumn::new),
             events: [None, None, None],
         }
     }
 
+    /// Draws the player's opening hand from the top of the given deck, with as many cards as
+    /// their camps call for.
+    pub fn draw_opening_hand(&mut self, deck: &mut Vec<PersonOrEventType<'ctype>>) {
+        let hand_size: usize = self
+            .columns
+            .iter()
+            .map(|column| column.camp.camp_type.num_initial_cards as usize)
+            .sum();
+        let deck_cut_index = deck.len() - hand_size;
+        for card in deck.drain(deck_cut_index..) {
+            self.hand.add_one(card);
+        }
+    }
+
     /// Returns the column at the given index.
     pub fn column(&self, index: ColumnIndex) -> &CardColumn<'ctype> {
         &self.columns[index.as_usize()]
@@ -60,6 +64,15 @@ impl<'v, 'g: 'v, 'ctype: 'g> PlayerState<'ctype> {
         self.column(loc.column()).person_slot(loc.row())
     }
 
+    /// Returns the styled name of the card at the given location (or of the empty slot).
+    pub fn card_name_at(&self, loc: PlayerCardLocation) -> Span<'static> {
+        let column = self.column(loc.column());
+        match loc.row().to_person_index() {
+            Ok(row) => column.person_slots[row.as_usize()].styled_name(),
+            Err(()) => column.camp.styled_name(),
+        }
+    }
+
     /// Returns the person at the given location as mutable.
     pub fn person_mut_slot(&mut self, loc: PlayLocation) -> Option<&mut Person<'ctype>> {
         self.column_mut(loc.column()).person_mut_slot(loc.row())
@@ -126,23 +139,47 @@ impl<'v, 'g: 'v, 'ctype: 'g> PlayerState<'ctype> {
         person
     }
 
+    /// Moves the person at `from` to the location `to` on this player's board, shifting people
+    /// as if it were removed and then played there.
+    /// Panics if there is no person at `from`, or if there is no room at `to`.
+    pub fn move_person(&mut self, from: PlayLocation, to: PlayLocation) {
+        let person = self.remove_person_at(from);
+        self.column_mut(to.column()).place_person(person, to.row());
+    }
+
+    /// Returns the locations on this player's board that the person at `from` can be moved to
+    /// (see `move_person`): any slot where it could be played after being removed, except for
+    /// the one that would put it back where it is.
+    pub fn move_destinations(&self, from: PlayLocation) -> Vec<PlayLocation> {
+        let mut destinations = Vec::new();
+        for (col_index, col) in self.enumerate_columns() {
+            let mut num_people = col.people().count();
+            if col_index == from.column() {
+                num_people -= 1;
+            }
+            if num_people < 2 {
+                for row in 0..=num_people {
+                    let loc = PlayLocation::new(col_index, row.into());
+                    if loc != from {
+                        destinations.push(loc);
+                    }
+                }
+            }
+        }
+        destinations
+    }
+
     /// Returns whether this player has an empty person slot.
     pub fn has_empty_person_slot(&self) -> bool {
-        self.columns
-            .iter()
-            .flat_map(|col| &col.person_slots)
-            .any(|slot| slot.is_none())
+        self.columns.iter().any(|col| col.has_empty_person_slot())
     }
 
-    /// Returns whether this player has an empty person slot in a column where
-    /// `column.camp.is_destroyed() == camp_destroyed`. This is used to determine
-    /// valid locations to play the person "Holdout" for different costs.
-    pub fn has_empty_holdout_slot(&self, camp_destroyed: bool) -> bool {
+    /// Returns the number of this player's camps that are destroyed.
+    pub fn num_destroyed_camps(&self) -> u32 {
         self.columns
             .iter()
-            .filter(|col| col.camp.is_destroyed() == camp_destroyed)
-            .flat_map(|col| &col.person_slots)
-            .any(|slot| slot.is_none())
+            .filter(|col| col.camp.is_destroyed())
+            .count() as u32
     }
 
     /// Returns whether this player has a punk on their board.
@@ -169,23 +206,90 @@ impl<'v, 'g: 'v, 'ctype: 'g> PlayerState<'ctype> {
         self.columns.iter().flat_map(|col| col.people())
     }
 
+    /// Returns whether this player has a camp of the given SpecialCampType that isn't destroyed
+    /// (i.e. whose trait is active).
+    pub fn has_special_camp(&self, special_type: SpecialCampType) -> bool {
+        self.columns
+            .iter()
+            .any(|col| col.camp.has_active_special_type(special_type))
+    }
+
     /// Returns an iterator over the locations of this player's people.
     pub fn person_locs(&self) -> impl Iterator<Item = PlayLocation> + '_ {
         self.enumerate_people().map(|(loc, _)| loc)
     }
 
-    /// Returns whether this player has a person of the given SpecialType that is uninjured (i.e.
-    /// whose trait is active).
-    pub fn has_special_person(&self, special_type: SpecialType) -> bool {
-        self.people().any(|person| {
-            matches!(person,
-                Person::NonPunk { person_type, status, .. }
-                    if person_type.special_type == special_type
-                       && *status != NonPunkStatus::Injured
-            )
+    /// Returns whether this player has an uninjured person with the given trait (i.e. whether
+    /// the trait is active on this board).
+    pub fn has_active_passive_effect(&self, passive_effect: PassiveEffect) -> bool {
+        self.active_passive_effect_source(passive_effect).is_some()
+    }
+
+    /// Returns the type of the first uninjured person on this board with the given trait (i.e.
+    /// the card that makes the trait active), if there is one.
+    pub fn active_passive_effect_source(
+        &self,
+        passive_effect: PassiveEffect,
+    ) -> Option<&'ctype PersonType> {
+        self.people().find_map(|person| match *person {
+            Person::NonPunk {
+                person_type,
+                status,
+                ..
+            } if person_type.has_passive_effect(passive_effect)
+                && status != NonPunkStatus::Injured =>
+            {
+                Some(person_type)
+            }
+            _ => None,
         })
     }
 
+    /// Returns the abilities that the person at the given location is given by the other people
+    /// on this board (such as Argo Yesky's, while uninjured), besides their own. Duplicates are
+    /// found by ability rather than by the person granting it: an ability given by several
+    /// sources (e.g. two copies of Argo Yesky share one ability), or that the person already
+    /// has, is only returned once, while different abilities are all returned.
+    pub fn granted_abilities(&self, person_loc: PlayLocation) -> Vec<&'ctype dyn Ability> {
+        self.granted_abilities_with_ids(person_loc)
+            .into_iter()
+            .map(|(_, ability)| ability)
+            .collect()
+    }
+
+    /// Like `granted_abilities`, but with the ID of each ability.
+    fn granted_abilities_with_ids(
+        &self,
+        person_loc: PlayLocation,
+    ) -> Vec<(AbilityId, &'ctype dyn Ability)> {
+        let own_ability_ids = match self.person_slot(person_loc) {
+            Some(Person::NonPunk { person_type, .. }) => (0..person_type.abilities.len())
+                .map(|index| AbilityId::new(person_type, index))
+                .collect_vec(),
+            _ => Vec::new(),
+        };
+        let mut granted: Vec<(AbilityId, &'ctype dyn Ability)> = Vec::new();
+        let sources = self
+            .enumerate_people()
+            .filter(|&(loc, _)| loc != person_loc)
+            .filter_map(|(_, source)| source.granted_ability());
+        for (id, ability) in sources {
+            let is_duplicate = own_ability_ids.contains(&id)
+                || granted.iter().any(|&(other_id, _)| other_id == id);
+            if !is_duplicate {
+                granted.push((id, ability));
+            }
+        }
+        granted
+    }
+
+    /// Returns whether people (including punks) that this player plays enter play ready, i.e.
+    /// whether a trait like Karli Blaze's is active. The trait is inactive while its person is
+    /// injured.
+    pub fn people_enter_play_ready(&self) -> bool {
+        self.has_active_passive_effect(PassiveEffect::PeopleEnterReady)
+    }
+
     /// Returns an iterator over the locations of this player's cards (people
     /// and non-destroyed camps).
     pub fn card_locs(&self) -> impl Iterator<Item = PlayerCardLocation> + '_ {
@@ -206,11 +310,56 @@ impl<'v, 'g: 'v, 'ctype: 'g> PlayerState<'ctype> {
     /// Returns an iterator over the locations of this player's unprotected people.
     pub fn unprotected_person_locs(&self) -> impl Iterator<Item = PlayerCardLocation> + '_ {
         self.enumerate_columns().filter_map(|(col_index, col)| {
-            col.frontmost_person_row()
+            col.unprotected_person_row()
                 .map(move |row_index| PlayerCardLocation::new(col_index, row_index.into()))
         })
     }
 
+    /// Returns an iterator over the locations of this player's unprotected people that count for
+    /// the given interaction.
+    pub fn unprotected_people_for(
+        &self,
+        interaction: PersonInteraction,
+    ) -> impl Iterator<Item = PlayLocation> + '_ {
+        self.enumerate_columns()
+            .filter_map(move |(col_index, col)| {
+                let row_index = col.unprotected_person_row()?;
+                let person = col.person_slot(row_index)?;
+                person
+                    .counts_as_person_for(interaction)
+                    .then(|| PlayLocation::new(col_index, row_index))
+            })
+    }
+
+    /// Returns an iterator over the locations of this player's people that count for the given
+    /// interaction, whether or not they are protected.
+    pub fn people_for(
+        &self,
+        interaction: PersonInteraction,
+    ) -> impl Iterator<Item = PlayLocation> + '_ {
+        self.enumerate_people()
+            .filter(move |(_, person)| person.counts_as_person_for(interaction))
+            .map(|(loc, _)| loc)
+    }
+
+    /// Returns whether the person at the given location can be chosen as the target of an
+    /// injure effect: there is a person there, it is unprotected, and it counts as a person
+    /// for injuring.
+    pub fn is_valid_injure_target(&self, loc: PlayLocation) -> bool {
+        let col = self.column(loc.column());
+        col.unprotected_person_row() == Some(loc.row())
+            && matches!(
+                col.person_slot(loc.row()),
+                Some(person) if person.counts_as_person_for(PersonInteraction::Injure)
+            )
+    }
+
+    /// Returns whether this player has any person that counts for the given interaction.
+    pub fn has_person_counting_for(&self, interaction: PersonInteraction) -> bool {
+        self.people()
+            .any(|person| person.counts_as_person_for(interaction))
+    }
+
     /// Returns an iterator that enumerates the columns of this player's board with strongly-typed
     /// column indices.
     pub fn enumerate_columns(
@@ -246,94 +395,155 @@ impl<'v, 'g: 'v, 'ctype: 'g> PlayerState<'ctype> {
         })
     }
 
-    /// Returns the nth person (and its location) on this player's board, by some arbitrary
-    /// but stable ordering.
-    ///
-    /// Panics if `n` is greater or equal to the number of people this player has.
-    pub fn nth_person(&self, n: usize) -> (PlayLocation, &Person<'ctype>) {
-        self.enumerate_people()
-            .nth(n)
-            .expect("nth_person: n is too large")
-    }
-
     /// Returns the actions that this player can take given a view for them.
     pub fn actions(&self, game_view: &'v GameView<'g, 'ctype>) -> Vec<Action<'ctype>> {
         // this is a hot function, so pre-reserve enough capacity for most cases
         let mut actions = Vec::with_capacity(16);
 
         // actions to play or junk a card
-        let can_play_person = self.has_empty_person_slot();
         for card_type in self.hand.iter_unique() {
-            let can_afford = game_view.game_state.cur_player_water >= card_type.cost();
+            let water = game_view.game_state.cur_player_water;
             match card_type {
-                PersonOrEventType::Person(person_type)
-                    if person_type.special_type == SpecialType::Holdout =>
-                {
-                    // PlayPerson/PlayHoldout actions for "Holdout"
-                    if can_afford && self.has_empty_holdout_slot(false) {
-                        // there's an empty slot in a column with a non-destroyed camp
-                        // (and the player can afford Holdout's normal cost)
-                        actions.push(Action::PlayPerson(person_type));
-                    }
-                    if self.has_empty_holdout_slot(true) {
-                        // there's an empty slot in a column with a destroyed camp
-                        actions.push(Action::PlayHoldout(person_type));
-                    }
-                }
                 PersonOrEventType::Person(person_type) => {
-                    // PlayPerson actions for all other people
-                    if can_afford && can_play_person {
-                        actions.push(Action::PlayPerson(person_type));
+                    // PlayPerson actions; a person's cost can depend on the column it's played
+                    // into (e.g. "Holdout", or a column with "Oasis"), so it gets a separate
+                    // action for each distinct cost among the columns with an empty slot
+                    let mut costs = [0; 3];
+                    let mut num_costs = 0;
+                    for (col_index, col) in self.enumerate_columns() {
+                        if !col.has_empty_person_slot() {
+                            continue;
+                        }
+                        let cost = game_view.person_play_cost(person_type, col_index);
+                        if !costs[..num_costs].contains(&cost) {
+                            costs[num_costs] = cost;
+                            num_costs += 1;
+                            if water >= cost {
+                                actions.push(Action::PlayPerson(person_type, cost));
+                            }
+                        }
                     }
                 }
                 PersonOrEventType::Event(event_type) => {
                     // PlayEvent actions
-                    if can_afford && game_view.can_play_event(event_type.resolve_turns) {
+                    if water >= event_type.cost
+                        && game_view.can_play_event(event_type.resolve_turns)
+                    {
                         actions.push(Action::PlayEvent(event_type));
                     }
                 }
             }
 
             // JunkCard actions
-            if card_type.junk_effect().can_perform(game_view) {
+            if card_type.can_junk(game_view) {
                 actions.push(Action::JunkCard(card_type));
             }
         }
 
+        // action to junk Water Silo for water
+        if self.has_water_silo {
+            actions.push(Action::JunkWaterSilo);
+        }
+
         // action to pay 2 water to draw a card
         // (limited to 1 use per turn)
-        if game_view.game_state.cur_player_water >= 2 && !game_view.game_state.has_paid_to_draw {
+        if game_view.game_state.cur_player_water >= 2
+            && !game_view.game_state.turn_state().has_paid_to_draw()
+        {
             actions.push(Action::DrawCard);
         }
 
-        // actions to use a person's ability
+        // actions to use a person's or camp's ability, unless the player has used an ability
+        // that must be the only one they use this turn (e.g. Resonator's)
+        if !game_view.game_state.turn_state().abilities_locked() {
+            self.push_ability_actions(game_view, &mut actions);
+        }
 
-        let argo_yesky_ability = self
-            .people()
-            .filter_map(|person| match person {
-                Person::NonPunk {
-                    person_type,
-                    status,
-                    ..
-                } if person_type.special_type == SpecialType::ArgoYesky
-                    && *status != NonPunkStatus::Injured =>
-                {
-                    Some(person_type.abilities[0].as_ref())
+        // action to end turn (and take Water Silo if possible)
+        actions.push(Action::EndTurn);
+
+        actions
+    }
+
+    /// Returns the abilities that the Mimic at the given location can copy and use: those of
+    /// one of this player's ready people, or of any undamaged enemy, that the player can afford
+    /// and that would do something. Each is paired with the location of a person to copy it from.
+    /// Copying the same ability from a different person would have the same effect, so each
+    /// ability is only listed once, and not at all if it's given to the Mimic (e.g. by Argo
+    /// Yesky), since it can then be used without copying it.
+    pub fn copyable_abilities(
+        &self,
+        game_view: &'v GameView<'g, 'ctype>,
+        mimic_loc: PlayLocation,
+    ) -> Vec<(CardLocation, &'ctype dyn Ability)> {
+        let mimic_card_loc = mimic_loc.for_player(game_view.player);
+        let granted_ability_ids = self
+            .granted_abilities_with_ids(mimic_loc)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect_vec();
+        let mut copyable_ids: Vec<AbilityId> = Vec::new();
+        let mut copyable: Vec<(CardLocation, &'ctype dyn Ability)> = Vec::new();
+
+        // punks have no abilities of their own to copy, and neither do Mimics
+        let all_people = self
+            .enumerate_people()
+            .map(|(loc, person)| (loc.for_player(game_view.player), person))
+            .chain(
+                game_view
+                    .other_state()
+                    .enumerate_people()
+                    .map(|(loc, person)| (loc.for_player(game_view.player.other()), person)),
+            );
+        let copyable_people = all_people.filter(|(source_loc, person)| {
+            let interaction = if source_loc.player() == game_view.player {
+                PersonInteraction::CopyOwnAbilities
+            } else {
+                PersonInteraction::CopyEnemyAbilities
+            };
+            person.counts_as_person_for(interaction)
+        });
+        for (source_loc, person) in copyable_people {
+            if let Person::NonPunk { person_type, .. } = person {
+                for (index, ability) in person_type.abilities.iter().enumerate() {
+                    let id = AbilityId::new(person_type, index);
+                    let is_duplicate =
+                        granted_ability_ids.contains(&id) || copyable_ids.contains(&id);
+                    if !is_duplicate && ability.can_afford_and_perform_at(game_view, mimic_card_loc)
+                    {
+                        copyable_ids.push(id);
+                        copyable.push((source_loc, ability.as_ref()));
+                    }
                 }
-                _ => None,
-            })
-            .next()
-            .filter(|ability| ability.can_afford_and_perform(game_view));
+            }
+        }
+        copyable
+    }
 
-        // TODO: make this a little more DRY
+    /// Adds the actions to use the abilities of this player's ready people and camps to
+    /// `actions` (see `actions`).
+    fn push_ability_actions(
+        &self,
+        game_view: &'v GameView<'g, 'ctype>,
+        actions: &mut Vec<Action<'ctype>>,
+    ) {
+        // actions to use a person's ability
         for (loc, person) in self.enumerate_people() {
+            let card_loc = loc.for_player(game_view.player);
+            // abilities given by other cards (e.g. Argo Yesky), which punks have too
+            let granted_abilities = self.granted_abilities(loc);
+            let push_granted_abilities = |actions: &mut Vec<Action<'ctype>>| {
+                for &ability in &granted_abilities {
+                    if ability.can_afford_and_perform_at(game_view, card_loc) {
+                        actions.push(Action::UsePersonAbility(ability, loc));
+                    }
+                }
+            };
             match person {
                 Person::Punk { is_ready, .. } => {
                     // punks don't have abilities, unless they're given one by another card
                     if *is_ready {
-                        if let Some(argo_yesky_ability) = argo_yesky_ability {
-                            actions.push(Action::UsePersonAbility(argo_yesky_ability, loc));
-                        }
+                        push_granted_abilities(actions);
                     }
                 }
                 Person::NonPunk {
@@ -344,52 +554,17 @@ impl<'v, 'g: 'v, 'ctype: 'g> PlayerState<'ctype> {
                     if *status == NonPunkStatus::Ready {
                         // the person's own abilities
                         for ability in &person_type.abilities {
-                            if ability.can_afford_and_perform(game_view) {
+                            if ability.can_afford_and_perform_at(game_view, card_loc) {
                                 actions.push(Action::UsePersonAbility(ability.as_ref(), loc));
                             }
                         }
-
-                        // Argo Yesky's ability (if in effect)
-                        if person_type.special_type != SpecialType::ArgoYesky {
-                            // TODO: somehow dedup abilities better?
-                            if let Some(argo_yesky_ability) = argo_yesky_ability {
-                                actions.push(Action::UsePersonAbility(argo_yesky_ability, loc));
-                            }
-                        }
+                        push_granted_abilities(actions);
 
                         // mimic gets its abilities from other people
-                        if person_type.special_type == SpecialType::Mimic {
-                            // debug_assert that enemy people will always be either Ready or Injured
-                            #[cfg(debug_assertions)]
-                            for other_person in game_view.other_state().people() {
-                                #[rustfmt::skip]
-                                debug_assert!(matches!(other_person,
-                                    Person::Punk { is_ready: true, .. }
-                                    | Person::NonPunk {
-                                        status: NonPunkStatus::Ready | NonPunkStatus::Injured, ..
-                                    }
-                                ), "An enemy person was neither Ready nor Injured");
-                            }
-
-                            // either one of your own ready people, or any undamaged enemy (person)
-                            // TODO: deduplicate these abilities?
-                            let all_people = self.people().chain(game_view.other_state().people());
-                            for other_person in all_people {
-                                if let Person::NonPunk {
-                                    person_type: other_person_type,
-                                    status: NonPunkStatus::Ready,
-                                    ..
-                                } = other_person
-                                {
-                                    for ability in &other_person_type.abilities {
-                                        if ability.can_afford_and_perform(game_view) {
-                                            actions.push(Action::UsePersonAbility(
-                                                ability.as_ref(),
-                                                loc,
-                                            ));
-                                        }
-                                    }
-                                }
+                        if person_type.has_passive_effect(PassiveEffect::CopyAbility) {
+                            // the ability to copy (and who from) is chosen after using Mimic
+                            if !self.copyable_abilities(game_view, loc).is_empty() {
+                                actions.push(Action::UseMimicAbility(loc));
                             }
                         }
                     }
@@ -401,21 +576,17 @@ impl<'v, 'g: 'v, 'ctype: 'g> PlayerState<'ctype> {
         for (loc, camp) in self.enumerate_camps() {
             if camp.is_ready() {
                 for ability in &camp.camp_type.abilities {
-                    if ability.can_afford_and_perform(game_view) {
+                    let card_loc = loc.for_player(game_view.player);
+                    if ability.can_afford_and_perform_at(game_view, card_loc) {
                         actions.push(Action::UseCampAbility(ability.as_ref(), loc.column()));
                     }
                 }
             }
         }
-
-        // action to end turn (and take Water Silo if possible)
-        actions.push(Action::EndTurn);
-
-        actions
     }
 }
 
-#[derive(Clone, Hash, PartialEq, Eq)]
+#[derive(Clone, Hash, PartialEq, Eq, Debug)]
 pub struct CardColumn<'ctype> {
     /// The column's camp.
     pub camp: Camp<'ctype>,
@@ -434,6 +605,7 @@ impl<'ctype> CardColumn<'ctype> {
                 status: CampStatus::Undamaged,
                 is_ready: true,
                 times_used: 0,
+                position: 0,
             },
             person_slots: [None, None],
         }
@@ -454,6 +626,21 @@ impl<'ctype> CardColumn<'ctype> {
         &mut self.person_slots[loc.as_usize()]
     }
 
+    /// Places the person into the given slot. If there was already a person in the slot, they
+    /// are moved to the other slot (which must be empty).
+    ///
+    /// This only rearranges the column; it doesn't trigger any effects. (The UI uses it on a copy
+    /// of the column to preview where everyone would end up.)
+    pub fn place_person(&mut self, person: Person<'ctype>, loc: PersonRowIndex) {
+        let row_index = loc.as_usize();
+        if let Some(old_person) = self.person_slots[row_index].replace(person) {
+            // if there was a person already in the slot, move the old person to the other slot
+            let other_row_index = 1 - row_index;
+            let other_slot_old = self.person_slots[other_row_index].replace(old_person);
+            assert!(other_slot_old.is_none()); // the other slot should have been empty
+        }
+    }
+
     /// Returns an iterator over the people in the column.
     pub fn people(&self) -> impl Iterator<Item = &Person<'ctype>> {
         self.person_slots.iter().filter_map(|slot| slot.as_ref())
@@ -479,9 +666,17 @@ impl<'ctype> CardColumn<'ctype> {
             })
     }
 
+    /// Returns whether this column has an empty person slot.
+    pub fn has_empty_person_slot(&self) -> bool {
+        self.person_slots.iter().any(|slot| slot.is_none())
+    }
+
     /// Returns whether this column has any damaged cards that can be restored.
     pub fn has_restorable_card(&self) -> bool {
-        self.camp.is_restorable() || self.people().any(|person| person.is_injured())
+        self.camp.is_restorable()
+            || self
+                .people()
+                .any(|person| person.counts_as_person_for(PersonInteraction::Damaged))
     }
 
     /// Returns an iterator over the locations of any damaged and restorable cards in this column.
@@ -493,7 +688,7 @@ impl<'ctype> CardColumn<'ctype> {
                 .iter()
                 .enumerate()
                 .filter_map(|(row, slot)| {
-                    if matches!(slot, Some(person) if person.is_injured()) {
+                    if matches!(slot, Some(person) if person.counts_as_person_for(PersonInteraction::Damaged)) {
                         let row: PersonRowIndex = row.into();
                         Some(row.into())
                     } else {
@@ -505,29 +700,40 @@ impl<'ctype> CardColumn<'ctype> {
             .chain(restorable_person_rows)
     }
 
-    /// Returns the row index (0 or 1) of the frontmost person in the column, or None if there are
-    /// no people in the column.
-    pub fn frontmost_person_row(&self) -> Option<PersonRowIndex> {
+    /// Returns how far the card at the given row index is from the back of the column (0, 1, or
+    /// 2). This is the row index itself unless the camp has moved forward (see `Camp::position`),
+    /// in which case the person slots it has passed are behind it.
+    pub fn physical_row(&self, row: CardRowIndex) -> usize {
+        let camp_position = self.camp.position();
+        match row.to_person_index() {
+            Err(()) => camp_position,
+            Ok(person_row) if person_row.as_usize() < camp_position => person_row.as_usize(),
+            Ok(person_row) => person_row.as_usize() + 1,
+        }
+    }
+
+    /// Returns the row index (0 or 1) of the unprotected person in the column: the frontmost
+    /// person, unless the camp is in front of them. Returns None if there is no such person.
+    pub fn unprotected_person_row(&self) -> Option<PersonRowIndex> {
         self.person_slots
             .iter()
             .rposition(|person| person.is_some())
-            .map(|row| row.into())
+            .map(PersonRowIndex::from)
+            .filter(|&row| self.physical_row(row.into()) > self.camp.position())
     }
 
     /// Returns the row index (0 (camp), 1, or 2) of the frontmost card in the column, or None if
     /// there are no people in the column and the camp is destroyed.
     pub fn frontmost_card_row(&self) -> Option<CardRowIndex> {
-        if let Some(front_person_row) = self.frontmost_person_row() {
-            // there's a person in the column; return the row of the person
-            Some(front_person_row.into())
-        } else if let CampStatus::Destroyed = self.camp.status {
-            // there are no people and the camp is destroyed, so there are no unprotected cards in
-            // this column
-            None
-        } else {
-            // the (non-destroyed) camp is the only thing in the column
-            Some(CardRowIndex::camp())
-        }
+        self.card_rows().max_by_key(|&row| self.physical_row(row))
+    }
+
+    /// Returns an iterator over the row indices of the cards in the column that are hit by an
+    /// effect that damages the whole column: all the people, plus the camp (if not destroyed)
+    /// unless `people_only` is set.
+    pub fn column_damage_rows(&self, people_only: bool) -> impl Iterator<Item = CardRowIndex> + '_ {
+        self.card_rows()
+            .filter(move |row| !(people_only && row.is_camp()))
     }
 
     /// Returns an iterator over the row indices of the cards in the column (people or non-destroyed
@@ -556,7 +762,7 @@ impl<'ctype> CardColumn<'ctype> {
 }
 
 /// A camp on the board.
-#[derive(Clone, Hash, PartialEq, Eq)]
+#[derive(Clone, Hash, PartialEq, Eq, Debug)]
 pub struct Camp<'ctype> {
     /// The camp type.
     pub camp_type: &'ctype CampType,
@@ -569,6 +775,41 @@ pub struct Camp<'ctype> {
 
     /// The number of times an ability on this camp has been used this turn.
     times_used: u8,
+
+    /// How far the camp has moved forward from the back of its column (0, 1, or 2). Only
+    /// Juggernaut moves; the people it passes are behind it (see `CardColumn::physical_row`).
+    position: u8,
+}
+
+impl<'ctype> Camp<'ctype> {
+    /// Creates a camp with the given state (e.g. when decoding one; see `wire`).
+    pub(super) fn from_parts(
+        camp_type: &'ctype CampType,
+        status: CampStatus,
+        is_ready: bool,
+        times_used: u8,
+        position: u8,
+    ) -> Self {
+        Camp {
+            camp_type,
+            status,
+            is_ready,
+            times_used,
+            position,
+        }
+    }
+
+    /// Returns the camp's state as passed to `from_parts` (including whether a destroyed camp
+    /// was ready, which `is_ready` hides).
+    pub(super) fn parts(&self) -> (&'ctype CampType, CampStatus, bool, u8, u8) {
+        (
+            self.camp_type,
+            self.status,
+            self.is_ready,
+            self.times_used,
+            self.position,
+        )
+    }
 }
 
 impl Camp<'_> {
@@ -583,6 +824,29 @@ impl Camp<'_> {
             CampStatus::Damaged => self.status = CampStatus::Destroyed,
             CampStatus::Destroyed => {}
         }
+
+        // a destroyed camp stays at the back of its column
+        if self.status == CampStatus::Destroyed {
+            self.position = 0;
+        }
+    }
+
+    /// Returns how far the camp has moved forward from the back of its column (0, 1, or 2).
+    pub fn position(&self) -> usize {
+        self.position as usize
+    }
+
+    /// Moves the camp forward one space, past the person slot in front of it. If the camp is
+    /// already at the front, it moves back to the back of its column instead, and this returns
+    /// true (for Juggernaut, whose third move makes the opponent destroy a camp).
+    pub fn advance(&mut self) -> bool {
+        if self.position < 2 {
+            self.position += 1;
+            false
+        } else {
+            self.position = 0;
+            true
+        }
     }
 
     /// Restores the camp.
@@ -600,9 +864,17 @@ impl Camp<'_> {
         self.status == CampStatus::Destroyed
     }
 
+    /// Returns whether the camp is of the given SpecialCampType and isn't destroyed (i.e. whether
+    /// its trait is active).
+    pub fn has_active_special_type(&self, special_type: SpecialCampType) -> bool {
+        self.camp_type.special_type == special_type && !self.is_destroyed()
+    }
+
     /// Returns whether the camp is damaged and can be restored.
     pub fn is_restorable(&self) -> bool {
+        // Bonfire's trait: it can't be restored
         self.status == CampStatus::Damaged
+            && self.camp_type.special_type != SpecialCampType::Bonfire
     }
 
     /// Returns whether the camp is ready to use an ability.
@@ -637,9 +909,11 @@ impl StyledName for Camp<'_> {
     /// Returns this camps's name, styled for display.
     fn styled_name(&self) -> Span<'static> {
         match self.status {
-            CampStatus::Undamaged => Span::styled(self.camp_type.name, *CAMP),
-            CampStatus::Damaged => Span::styled(self.camp_type.name, *CAMP_DAMAGED),
-            CampStatus::Destroyed => Span::styled("<destroyed>", *CAMP_DESTROYED),
+            CampStatus::Undamaged => Span::styled(self.camp_type.display_name(), *CAMP),
+            CampStatus::Damaged => Span::styled(self.camp_type.display_name(), *CAMP_DAMAGED),
+            CampStatus::Destroyed => {
+                Span::styled(translate(Section::Ui, "<destroyed>"), *CAMP_DESTROYED)
+            }
         }
     }
 }
@@ -652,10 +926,38 @@ pub enum CampStatus {
     Destroyed,
 }
 
+/// A card that is face down, so that no player can look at it. It is left out of comparisons
+/// and hashes (all face-down cards are equal), so that states which only differ in which cards
+/// are face down where (e.g. the same observation, with different cards under the punks) are
+/// treated as the same.
+#[derive(Clone, Copy)]
+pub struct FaceDown<T>(pub T);
+
+impl<T> PartialEq for FaceDown<T> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<T> Eq for FaceDown<T> {}
+
+impl<T> Hash for FaceDown<T> {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+impl<T> fmt::Debug for FaceDown<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FaceDown(..)")
+    }
+}
+
 /// A person played on the board (a punk or face-up person).
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
 pub enum Person<'ctype> {
     Punk {
+        /// The card that the punk is: a card from the deck, placed face down.
+        card: FaceDown<PersonOrEventType<'ctype>>,
+
         /// Whether the punk is ready.
         is_ready: bool,
 
@@ -674,6 +976,29 @@ pub enum Person<'ctype> {
     },
 }
 
+/// A way that a card effect can interact with a person. Punks are people, but they have no
+/// abilities of their own and are destroyed by any damage, so whether a person counts for an
+/// effect depends on the interaction; see `Person::counts_as_person_for`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PersonInteraction {
+    /// Being injured (e.g. by the injure icon effect or Gunner). Every person counts; injuring a
+    /// punk or an injured person destroys it.
+    Injure,
+    /// Being destroyed (e.g. by Assassin, or when destroying one of your own people). Every
+    /// person counts.
+    Destroy,
+    /// Being damaged and restorable (e.g. for Exterminator or the restore icon effect). Only
+    /// injured people count; punks are never damaged, since any damage destroys them.
+    Damaged,
+    /// Having abilities that can be copied by Mimic, for one of the Mimic owner's own people.
+    /// Only ready people other than punks count, since punks have no abilities of their own
+    /// (even while Argo Yesky gives them one).
+    CopyOwnAbilities,
+    /// Having abilities that can be copied by Mimic, for an enemy. Only undamaged people other
+    /// than punks count; unlike the Mimic owner's own people, they don't need to be ready.
+    CopyEnemyAbilities,
+}
+
 /// Enum representing the damage/readiness of a non-punk person.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum NonPunkStatus {
@@ -686,13 +1011,15 @@ pub enum NonPunkStatus {
 }
 
 impl<'ctype> Person<'ctype> {
-    /// Creates a punk to be played onto the board.
+    /// Creates a punk to be played onto the board, from the given card placed face down.
     /// The punk will be ready iff Karli Blaze's trait is active; otherwise, it will be not ready.
-    pub(super) fn new_punk(game_view: &GameView<'_, 'ctype>) -> Self {
+    pub(super) fn new_punk(
+        card: PersonOrEventType<'ctype>,
+        game_view: &GameView<'_, 'ctype>,
+    ) -> Self {
         Person::Punk {
-            is_ready: game_view
-                .my_state()
-                .has_special_person(SpecialType::KarliBlaze),
+            card: FaceDown(card),
+            is_ready: game_view.my_state().people_enter_play_ready(),
             times_used: 0,
         }
     }
@@ -705,10 +1032,7 @@ impl<'ctype> Person<'ctype> {
         person_type: &'ctype PersonType,
         game_view: &GameView<'_, 'ctype>,
     ) -> Self {
-        let force_ready = game_view
-            .my_state()
-            .has_special_person(SpecialType::KarliBlaze);
-
+        let force_ready = game_view.my_state().people_enter_play_ready();
         Person::NonPunk {
             person_type,
             status: if force_ready || person_type.enters_play_ready {
@@ -725,6 +1049,26 @@ impl<'ctype> Person<'ctype> {
         matches!(self, Person::NonPunk { status, .. } if *status == NonPunkStatus::Injured)
     }
 
+    /// Returns whether this person counts as a person for the given interaction. Rules that
+    /// target or check for people should use this rather than matching on punks directly.
+    pub fn counts_as_person_for(&self, interaction: PersonInteraction) -> bool {
+        match interaction {
+            PersonInteraction::Injure | PersonInteraction::Destroy => true,
+            PersonInteraction::Damaged => self.is_injured(),
+            PersonInteraction::CopyOwnAbilities => matches!(
+                self,
+                Person::NonPunk {
+                    status: NonPunkStatus::Ready,
+                    ..
+                }
+            ),
+            PersonInteraction::CopyEnemyAbilities => matches!(
+                self,
+                Person::NonPunk { status, .. } if *status != NonPunkStatus::Injured
+            ),
+        }
+    }
+
     /// Restores this person.
     /// Panics if the person is not injured.
     pub fn restore(&mut self) {
@@ -740,6 +1084,20 @@ impl<'ctype> Person<'ctype> {
         }
     }
 
+    /// Sets this person to be ready. Has no effect if the person is injured or already ready.
+    pub fn set_ready(&mut self) {
+        match self {
+            Person::Punk { is_ready, .. } => {
+                *is_ready = true;
+            }
+            Person::NonPunk { status, .. } => {
+                if *status == NonPunkStatus::NotReady {
+                    *status = NonPunkStatus::Ready;
+                }
+            }
+        }
+    }
+
     /// Sets this person to be not ready. Has no effect if the person is injured or already not
     /// ready.
     pub fn set_not_ready(&mut self) {
@@ -763,6 +1121,26 @@ impl<'ctype> Person<'ctype> {
         }
     }
 
+    /// Returns the ability that this person gives to the other people on their player's board,
+    /// if they're giving one (e.g. Argo Yesky, while uninjured), with its ID.
+    pub fn granted_ability(&self) -> Option<(AbilityId, &'ctype dyn Ability)> {
+        match self {
+            Person::NonPunk {
+                person_type,
+                status,
+                ..
+            } if person_type.has_passive_effect(PassiveEffect::GrantAbility)
+                && *status != NonPunkStatus::Injured =>
+            {
+                Some((
+                    AbilityId::new(person_type, 0),
+                    person_type.abilities[0].as_ref(),
+                ))
+            }
+            _ => None,
+        }
+    }
+
     /// Returns the value of this person's times_used counter.
     pub fn times_used(&self) -> u8 {
         match self {
@@ -771,12 +1149,22 @@ impl<'ctype> Person<'ctype> {
         }
     }
 
+    /// Returns the card that this person is: the person card, or a punk's face-down card. This
+    /// is the card that goes to the discard pile or a hand when the person leaves the board.
+    pub fn card(&self) -> PersonOrEventType<'ctype> {
+        match self {
+            Person::Punk { card, .. } => card.0,
+            Person::NonPunk { person_type, .. } => PersonOrEventType::Person(person_type),
+        }
+    }
+
     /// Resets this person's readiness and use counter (used at the end of a turn).
     pub fn end_turn_reset(&mut self) {
         match self {
             Person::Punk {
                 is_ready,
                 times_used,
+                ..
             } => {
                 *is_ready = true;
                 *times_used = 0;
@@ -797,13 +1185,13 @@ impl StyledName for Person<'_> {
     /// Returns the name of the person, styled for display.
     fn styled_name(&self) -> Span<'static> {
         match self {
-            Person::Punk { .. } => Span::styled("Punk", *PUNK),
+            Person::Punk { .. } => Span::styled(translate(Section::Person, "Punk"), *PUNK),
             Person::NonPunk {
                 person_type,
                 status,
                 ..
             } => Span::styled(
-                person_type.name,
+                person_type.display_name(),
                 match status {
                     NonPunkStatus::Ready => *PERSON_READY,
                     NonPunkStatus::NotReady => *PERSON_NOT_READY,
@@ -819,7 +1207,198 @@ impl StyledName for Option<Person<'_>> {
     fn styled_name(&self) -> Span<'static> {
         match self {
             Some(person) => person.styled_name(),
-            None => Span::styled("<none>", *EMPTY),
+            None => Span::styled(translate(Section::Ui, "<none>"), *EMPTY),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::locations::Player::{Player1, Player2};
+    use super::super::sandbox::testing::*;
+    use super::*;
+
+    /// Returns whether the given person is ready.
+    fn is_ready(person: &Person) -> bool {
+        match person {
+            Person::Punk { is_ready, .. } => *is_ready,
+            Person::NonPunk { status, .. } => *status == NonPunkStatus::Ready,
+        }
+    }
+
+    #[test]
+    fn people_enter_play_ready_only_with_an_uninjured_karli_blaze() {
+        let looter = person_type("Looter");
+        let cases: [(&[&str], bool); 3] = [
+            (&[], false),
+            (&["person p1 1 1 Karli Blaze"], true),
+            (
+                &["person p1 1 1 Karli Blaze", "status p1 1 1 injured"],
+                false,
+            ),
+        ];
+        for (karli_commands, expected) in cases {
+            let mut commands = vec!["turn p1"];
+            commands.extend_from_slice(karli_commands);
+            let (game_state, _) = position(&commands);
+            let game_view = game_state.view_for(Player1);
+
+            assert_eq!(
+                game_view.my_state().people_enter_play_ready(),
+                expected,
+                "{karli_commands:?}"
+            );
+            let punk = Person::new_punk(PersonOrEventType::Person(looter), &game_view);
+            assert_eq!(is_ready(&punk), expected, "punk, {karli_commands:?}");
+            let person = Person::new_non_punk(looter, &game_view);
+            assert_eq!(is_ready(&person), expected, "Looter, {karli_commands:?}");
+        }
+    }
+
+    #[test]
+    fn counts_as_person_for_each_interaction() {
+        use PersonInteraction::*;
+
+        let (game_state, _) = position(&[
+            "turn p1",
+            "person p1 1 1 punk",
+            "person p1 1 2 Argo Yesky",
+            "person p1 2 1 Looter",
+            "status p1 2 1 notready",
+            "person p1 3 1 Looter",
+            "status p1 3 1 injured",
+        ]);
+        let interactions = [
+            Injure,
+            Destroy,
+            Damaged,
+            CopyOwnAbilities,
+            CopyEnemyAbilities,
+        ];
+        // (column, slot, description, whether the person counts for each interaction)
+        let cases = [
+            (
+                1,
+                1,
+                "punk given Argo Yesky's ability",
+                [true, true, false, false, false],
+            ),
+            (1, 2, "ready person", [true, true, false, true, true]),
+            (2, 1, "not-ready person", [true, true, false, false, true]),
+            (3, 1, "injured person", [true, true, true, false, false]),
+        ];
+        let player_state = game_state.player(Player1);
+        for (column, slot, description, expected) in cases {
+            let person = player_state.columns[column - 1].person_slots[slot - 1]
+                .as_ref()
+                .unwrap();
+            for (interaction, expected) in interactions.into_iter().zip(expected) {
+                assert_eq!(
+                    person.counts_as_person_for(interaction),
+                    expected,
+                    "{description}, {interaction:?}"
+                );
+            }
+        }
+        let punk_loc = PlayLocation::new(ColumnIndex::from(0), PersonRowIndex::from(0));
+        assert!(!player_state.granted_abilities(punk_loc).is_empty());
+    }
+
+    #[test]
+    fn mimic_can_copy_each_different_ability_once() {
+        let (game_state, _) = position(&[
+            "turn p1",
+            "water 3",
+            "person p1 1 1 Mimic",
+            "person p2 1 1 Looter",
+            "person p2 2 1 Gunner",
+            "person p2 3 1 Looter",
+        ]);
+        let game_view = game_state.view_for(Player1);
+        let mimic_loc = PlayLocation::new(ColumnIndex::from(0), PersonRowIndex::from(0));
+        let copyable = game_view
+            .my_state()
+            .copyable_abilities(&game_view, mimic_loc)
+            .into_iter()
+            .map(|(source_loc, ability)| (source_loc, ability.description()))
+            .collect_vec();
+
+        // Looter's and Gunner's abilities are both custom abilities, but they're still told
+        // apart, while the second Looter's ability is the same as the first's
+        let looter = person_type("Looter").abilities[0].description();
+        let gunner = person_type("Gunner").abilities[0].description();
+        assert_eq!(
+            copyable,
+            [
+                (card_loc(Player2, 1, 1), looter),
+                (card_loc(Player2, 2, 1), gunner),
+            ]
+        );
+    }
+
+    /// Returns the names of the people in each of the player's columns, from back to front
+    /// ("punk" for a punk, and "" for an empty slot).
+    fn column_people(player_state: &PlayerState) -> [[&'static str; 2]; 3] {
+        player_state.columns.clone().map(|column| {
+            column.person_slots.map(|slot| match slot {
+                Some(Person::Punk { .. }) => "punk",
+                Some(Person::NonPunk { person_type, .. }) => person_type.name,
+                None => "",
+            })
+        })
+    }
+
+    #[test]
+    fn move_destinations_are_the_slots_that_change_the_board() {
+        let (game_state, _) = position(&[
+            "turn p1",
+            "person p1 1 1 Looter",
+            "person p1 1 2 punk",
+            "person p1 2 1 Muse",
+            "person p1 3 1 Scout",
+            "person p1 3 2 Gunner",
+        ]);
+        let player_state = game_state.player(Player1);
+        let loc = |column: usize, row: usize| {
+            PlayLocation::new(ColumnIndex::from(column), PersonRowIndex::from(row))
+        };
+
+        // Looter can swap with the punk, or go to either side of Muse, but not into the full
+        // column
+        assert_eq!(
+            player_state.move_destinations(loc(0, 0)),
+            [loc(0, 1), loc(1, 0), loc(1, 1)]
+        );
+        // Muse would end up where it is in its own column, and the others are full
+        assert_eq!(player_state.move_destinations(loc(1, 0)), []);
+    }
+
+    #[test]
+    fn moving_a_person_shifts_people_as_removing_and_playing_it_would() {
+        let (game_state, _) = position(&[
+            "turn p1",
+            "person p1 1 1 Looter",
+            "person p1 1 2 punk",
+            "person p1 2 1 Muse",
+        ]);
+        let loc = |column: usize, row: usize| {
+            PlayLocation::new(ColumnIndex::from(column), PersonRowIndex::from(row))
+        };
+
+        // within its column, Looter swaps places with the punk
+        let mut player_state = game_state.player(Player1).clone();
+        player_state.move_person(loc(0, 0), loc(0, 1));
+        assert_eq!(
+            column_people(&player_state),
+            [["punk", "Looter"], ["Muse", ""], ["", ""]]
+        );
+
+        // into another column, the punk shifts back, and Looter pushes Muse forward
+        let mut player_state = game_state.player(Player1).clone();
+        player_state.move_person(loc(0, 0), loc(1, 0));
+        assert_eq!(
+            column_people(&player_state),
+            [["punk", ""], ["Looter", "Muse"], ["", ""]]
+        );
+    }
+}
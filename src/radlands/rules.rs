@@ -0,0 +1,65 @@
+//! The rules that a game is played with, for the parts of the rules that can vary.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// How closely a game sticks to the official rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RulesFidelity {
+    /// The official rules, plus any house conveniences that were asked for (such as mulligans).
+    #[default]
+    Casual,
+
+    /// Only the official rules, for tournament play and AI training. Any house conveniences
+    /// that were asked for are ignored.
+    Strict,
+}
+
+impl fmt::Display for RulesFidelity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RulesFidelity::Casual => "casual",
+            RulesFidelity::Strict => "strict",
+        })
+    }
+}
+
+impl FromStr for RulesFidelity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "casual" => Ok(RulesFidelity::Casual),
+            "strict" => Ok(RulesFidelity::Strict),
+            _ => Err(format!("invalid rules fidelity: {s:?}")),
+        }
+    }
+}
+
+/// The variable parts of the rules of a game, which are fixed for the whole game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct GameRules {
+    /// Whether the players were asked to be allowed to mulligan their opening hands.
+    /// See `allows_mulligans`.
+    pub mulligans: bool,
+
+    /// How closely the game sticks to the official rules.
+    pub fidelity: RulesFidelity,
+}
+
+impl GameRules {
+    /// Returns the casual rules, with or without mulligans.
+    pub fn casual(mulligans: bool) -> Self {
+        GameRules {
+            mulligans,
+            fidelity: RulesFidelity::Casual,
+        }
+    }
+
+    /// Returns whether each player is asked whether to keep their opening hand before the
+    /// first turn begins.
+    pub fn allows_mulligans(&self) -> bool {
+        // the official rules have no mulligans; they're a house rule to soften bad opening hands
+        self.mulligans && self.fidelity == RulesFidelity::Casual
+    }
+}
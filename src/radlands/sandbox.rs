@@ -0,0 +1,630 @@
+//! Setting up a game position by hand, to play from it (with `--sandbox`).
+//!
+//! A sandbox starts from the setup of a new game, which is then edited with text commands:
+//!
+//! - `turn <player>`: make it the given player's turn (`p1` or `p2`)
+//! - `water <amount>`: set the current player's water
+//! - `camp <player> <column> <camp>`: put a camp in a column (1 to 3), moving the camp that was
+//!   there to where the given camp was
+//! - `person <player> <column> <slot> <person>`: put a person (or `punk`) in a slot (1 is the
+//!   back, 2 is the front)
+//! - `remove <player> <column> <slot>`: remove a person, moving any person in front of it back
+//! - `status <player> <column> [<slot>] <status>`: set a camp's status (`undamaged`, `damaged`,
+//!   or `destroyed`) or a person's status (`ready`, `notready`, or `injured`)
+//! - `hand <player> add|remove <card>` or `hand <player> clear`: change a player's hand
+//! - `silo <player> yes|no`: set whether Water Silo is in the player's hand
+//! - `event <player> <slot> <event>|none`: set an event slot (1 resolves next)
+//! - `start`: play from the position (handled by the UI, which calls `Sandbox::start`)
+//!
+//! Card names are matched without regard to case. Every card put into play or into a hand is
+//! taken from the deck, and every card removed is put back, so the position always has the
+//! usual set of cards. The position is played from the middle of the current player's turn
+//! (after their draw), once `Sandbox::start` finds it valid.
+
+use std::mem;
+
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use super::camps::CampType;
+use super::choices::Choice;
+use super::events::EventType;
+use super::locations::{ColumnIndex, PersonRowIndex, PlayLocation, Player};
+use super::people::PersonType;
+use super::player_state::{CampStatus, FaceDown, NonPunkStatus, Person, PlayerState};
+use super::rules::GameRules;
+use super::{CampDraft, GameState, PersonOrEventType, RAIDERS_EVENT, WATER_PER_TURN};
+
+/// The most water the current player can be given. This is far more than a turn can provide,
+/// but keeps typos from making a position that is silly to search.
+const MAX_WATER: u32 = 20;
+
+/// A summary of the edit commands, for display.
+pub const COMMAND_HELP: &[&str] = &[
+    "turn <player>",
+    "water <amount>",
+    "camp <player> <column> <camp>",
+    "person <player> <column> <slot> <person>|punk",
+    "remove <player> <column> <slot>",
+    "status <player> <column> [<slot>] <status>",
+    "hand <player> add|remove <card>, hand <player> clear",
+    "silo <player> yes|no",
+    "event <player> <slot> <event>|none",
+    "start",
+];
+
+/// A game position being edited.
+pub struct Sandbox<'ctype> {
+    game_state: GameState<'ctype>,
+    camp_types: &'ctype [CampType],
+    person_types: &'ctype [PersonType],
+    event_types: &'ctype [EventType],
+}
+
+impl<'ctype> Sandbox<'ctype> {
+    /// Creates a sandbox starting from the setup of a new game with the given rules and seed
+    /// (the same setup as `GameState::new_seeded`). The rules' mulligans are ignored, since the
+    /// game starts from the edited position, and if there are camps to choose from, each player
+    /// keeps the first 3 they were dealt.
+    pub fn new(
+        camp_types: &'ctype [CampType],
+        person_types: &'ctype [PersonType],
+        event_types: &'ctype [EventType],
+        rules: GameRules,
+        seed: u64,
+    ) -> Self {
+        let rng = StdRng::seed_from_u64(seed);
+        let mut game_state = GameState::set_up(camp_types, person_types, event_types, rules, rng);
+        if let Some(camp_draft) = game_state.camp_draft.take() {
+            for player in [Player::Player1, Player::Player2] {
+                let dealt = &camp_draft.dealt[CampDraft::index(player)];
+                *game_state.player_mut(player) = PlayerState::new([dealt[0], dealt[1], dealt[2]]);
+            }
+            game_state.deal_opening_hands(game_state.cur_player);
+        }
+        game_state.cur_player_water = WATER_PER_TURN;
+        Sandbox {
+            game_state,
+            camp_types,
+            person_types,
+            event_types,
+        }
+    }
+
+    /// Returns the position as edited so far.
+    pub fn game_state(&self) -> &GameState<'ctype> {
+        &self.game_state
+    }
+
+    /// Applies one edit command to the position. Returns a description of the change, or a
+    /// message explaining why the command is invalid (in which case nothing is changed).
+    pub fn apply(&mut self, command: &str) -> Result<String, String> {
+        let words = command.split_whitespace().collect_vec();
+        let (&verb, args) = match words.split_first() {
+            Some(split) => split,
+            None => return Err("enter a command".to_string()),
+        };
+        match verb.to_lowercase().as_str() {
+            "turn" => {
+                let player = match args {
+                    [player] => parse_player(player)?,
+                    _ => return Err("usage: turn <player>".to_string()),
+                };
+                self.game_state.cur_player = player;
+                Ok(format!("It is now {player:?}'s turn"))
+            }
+            "water" => {
+                let water = match args {
+                    [water] => water
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|&water| water <= MAX_WATER)
+                        .ok_or_else(|| format!("water must be from 0 to {MAX_WATER}"))?,
+                    _ => return Err("usage: water <amount>".to_string()),
+                };
+                self.game_state.cur_player_water = water;
+                Ok(format!(
+                    "{:?} has {water} water",
+                    self.game_state.cur_player
+                ))
+            }
+            "camp" => match args {
+                [player, column, name @ ..] if !name.is_empty() => {
+                    let player = parse_player(player)?;
+                    let column = parse_column(column)?;
+                    self.set_camp(player, column, &name.join(" "))
+                }
+                _ => Err("usage: camp <player> <column> <camp>".to_string()),
+            },
+            "person" => match args {
+                [player, column, slot, name @ ..] if !name.is_empty() => {
+                    let player = parse_player(player)?;
+                    let loc = PlayLocation::new(parse_column(column)?, parse_slot(slot)?);
+                    self.set_person(player, loc, &name.join(" "))
+                }
+                _ => Err("usage: person <player> <column> <slot> <person>".to_string()),
+            },
+            "remove" => match args {
+                [player, column, slot] => {
+                    let player = parse_player(player)?;
+                    let loc = PlayLocation::new(parse_column(column)?, parse_slot(slot)?);
+                    self.remove_person(player, loc)
+                }
+                _ => Err("usage: remove <player> <column> <slot>".to_string()),
+            },
+            "status" => match args {
+                [player, column, status] => {
+                    let player = parse_player(player)?;
+                    let column = parse_column(column)?;
+                    self.set_camp_status(player, column, status)
+                }
+                [player, column, slot, status] => {
+                    let player = parse_player(player)?;
+                    let loc = PlayLocation::new(parse_column(column)?, parse_slot(slot)?);
+                    self.set_person_status(player, loc, status)
+                }
+                _ => Err("usage: status <player> <column> [<slot>] <status>".to_string()),
+            },
+            "hand" => match args {
+                [player, action, name @ ..] => {
+                    let player = parse_player(player)?;
+                    match (action.to_lowercase().as_str(), name) {
+                        ("clear", []) => Ok(self.clear_hand(player)),
+                        ("add", name) if !name.is_empty() => {
+                            self.add_to_hand(player, &name.join(" "))
+                        }
+                        ("remove", name) if !name.is_empty() => {
+                            self.remove_from_hand(player, &name.join(" "))
+                        }
+                        _ => Err(
+                            "usage: hand <player> add|remove <card>, or hand <player> clear"
+                                .to_string(),
+                        ),
+                    }
+                }
+                _ => Err(
+                    "usage: hand <player> add|remove <card>, or hand <player> clear".to_string(),
+                ),
+            },
+            "silo" => {
+                let (player, has_water_silo) = match args {
+                    [player, answer] => (
+                        parse_player(player)?,
+                        match answer.to_lowercase().as_str() {
+                            "yes" => true,
+                            "no" => false,
+                            _ => return Err("usage: silo <player> yes|no".to_string()),
+                        },
+                    ),
+                    _ => return Err("usage: silo <player> yes|no".to_string()),
+                };
+                self.game_state.player_mut(player).has_water_silo = has_water_silo;
+                Ok(format!(
+                    "Water Silo is {} {player:?}'s hand",
+                    if has_water_silo { "in" } else { "not in" }
+                ))
+            }
+            "event" => match args {
+                [player, slot, name @ ..] if !name.is_empty() => {
+                    let player = parse_player(player)?;
+                    let slot = parse_number(slot, 3, "event slot")?;
+                    self.set_event(player, slot, &name.join(" "))
+                }
+                _ => Err("usage: event <player> <slot> <event>|none".to_string()),
+            },
+            _ => Err(format!("unknown command {verb:?}")),
+        }
+    }
+
+    /// Checks that the position can be played from, and if so, returns a copy of it (with the
+    /// deck shuffled) along with the current player's choice of actions. Otherwise, returns a
+    /// message explaining what needs to be fixed.
+    pub fn start(&self) -> Result<(GameState<'ctype>, Choice<'ctype>), String> {
+        for player in [Player::Player1, Player::Player2] {
+            let player_state = self.game_state.player(player);
+            if player_state
+                .columns
+                .iter()
+                .all(|col| col.camp.is_destroyed())
+            {
+                return Err(format!("{player:?} has no camps left, so the game is over"));
+            }
+        }
+
+        let mut game_state = self.game_state.clone();
+        game_state.deck.shuffle(&mut game_state.rng);
+        let choice = Choice::new_actions(&mut game_state);
+        Ok((game_state, choice))
+    }
+
+    fn set_camp(
+        &mut self,
+        player: Player,
+        column: ColumnIndex,
+        name: &str,
+    ) -> Result<String, String> {
+        let camp_type = find_by_name(self.camp_types, name, |camp_type| camp_type.name)
+            .ok_or_else(|| format!("there is no camp named {name:?}"))?;
+        // every camp type is in play in every game, so the camps trade places
+        let other_location = [Player::Player1, Player::Player2]
+            .into_iter()
+            .cartesian_product(0..3)
+            .find(|&(p, col)| self.game_state.player(p).columns[col].camp.camp_type == camp_type);
+        let old_type = self.game_state.player(player).column(column).camp.camp_type;
+        self.game_state
+            .player_mut(player)
+            .column_mut(column)
+            .camp
+            .camp_type = camp_type;
+
+        let message = format!(
+            "{player:?}'s column {} has {}",
+            column.as_usize() + 1,
+            camp_type.name
+        );
+        match other_location {
+            Some((other_player, other_col)) if old_type != camp_type => {
+                let other_column = &mut self.game_state.player_mut(other_player).columns[other_col];
+                other_column.camp.camp_type = old_type;
+                Ok(format!(
+                    "{message}, and {other_player:?}'s column {} has {}",
+                    other_col + 1,
+                    old_type.name
+                ))
+            }
+            _ => Ok(message),
+        }
+    }
+
+    fn set_person(
+        &mut self,
+        player: Player,
+        loc: PlayLocation,
+        name: &str,
+    ) -> Result<String, String> {
+        let column = self.game_state.player(player).column(loc.column());
+        if loc.row().as_usize() == 1 && column.person_slot(0.into()).is_none() {
+            return Err("the back slot (1) must be filled before the front slot (2)".to_string());
+        }
+
+        let person = if name.eq_ignore_ascii_case("punk") {
+            let card = self
+                .game_state
+                .deck
+                .pop()
+                .ok_or_else(|| "the deck has no card left for a punk".to_string())?;
+            Person::Punk {
+                card: FaceDown(card),
+                is_ready: true,
+                times_used: 0,
+            }
+        } else {
+            let person_type = find_by_name(self.person_types, name, |person_type| person_type.name)
+                .ok_or_else(|| format!("there is no person named {name:?}"))?;
+            self.take_from_deck(PersonOrEventType::Person(person_type))?;
+            Person::NonPunk {
+                person_type,
+                status: NonPunkStatus::Ready,
+                times_used: 0,
+            }
+        };
+
+        let slot = self.game_state.player_mut(player).person_slot_mut(loc);
+        let old_person = slot.replace(person);
+        self.return_person_to_deck(old_person);
+        Ok(format!(
+            "{player:?}'s column {} slot {} has {}",
+            loc.column().as_usize() + 1,
+            loc.row().as_usize() + 1,
+            name_of(self.game_state.player(player).person_slot(loc)),
+        ))
+    }
+
+    fn remove_person(&mut self, player: Player, loc: PlayLocation) -> Result<String, String> {
+        if self.game_state.player(player).person_slot(loc).is_none() {
+            return Err("there is no person in that slot".to_string());
+        }
+        let person = self.game_state.player_mut(player).remove_person_at(loc);
+        let name = name_of(Some(&person));
+        self.return_person_to_deck(Some(person));
+        Ok(format!("Removed {name} from {player:?}'s board"))
+    }
+
+    fn set_camp_status(
+        &mut self,
+        player: Player,
+        column: ColumnIndex,
+        status: &str,
+    ) -> Result<String, String> {
+        let status = match status.to_lowercase().as_str() {
+            "undamaged" => CampStatus::Undamaged,
+            "damaged" => CampStatus::Damaged,
+            "destroyed" => CampStatus::Destroyed,
+            _ => return Err("a camp's status is undamaged, damaged, or destroyed".to_string()),
+        };
+        let camp = &mut self.game_state.player_mut(player).column_mut(column).camp;
+        camp.status = status;
+        Ok(format!("{} is {status:?}", camp.camp_type.name))
+    }
+
+    fn set_person_status(
+        &mut self,
+        player: Player,
+        loc: PlayLocation,
+        new_status: &str,
+    ) -> Result<String, String> {
+        let new_status = match new_status.to_lowercase().as_str() {
+            "ready" => NonPunkStatus::Ready,
+            "notready" => NonPunkStatus::NotReady,
+            "injured" => NonPunkStatus::Injured,
+            _ => return Err("a person's status is ready, notready, or injured".to_string()),
+        };
+        match self.game_state.player_mut(player).person_mut_slot(loc) {
+            None => Err("there is no person in that slot".to_string()),
+            Some(Person::Punk { .. }) if new_status == NonPunkStatus::Injured => {
+                Err("punks can't be injured (any damage destroys them)".to_string())
+            }
+            Some(Person::Punk { is_ready, .. }) => {
+                *is_ready = new_status == NonPunkStatus::Ready;
+                Ok(format!("Punk is {new_status:?}"))
+            }
+            Some(Person::NonPunk {
+                person_type,
+                status,
+                ..
+            }) => {
+                *status = new_status;
+                Ok(format!("{} is {new_status:?}", person_type.name))
+            }
+        }
+    }
+
+    fn clear_hand(&mut self, player: Player) -> String {
+        let hand = mem::take(&mut self.game_state.player_mut(player).hand);
+        for (card, count) in hand.iter() {
+            self.game_state
+                .deck
+                .extend(std::iter::repeat(card).take(count));
+        }
+        format!("Returned {player:?}'s hand to the deck")
+    }
+
+    fn add_to_hand(&mut self, player: Player, name: &str) -> Result<String, String> {
+        let card = self.find_card(name)?;
+        self.take_from_deck(card)?;
+        self.game_state.player_mut(player).hand.add_one(card);
+        Ok(format!("Added {} to {player:?}'s hand", card.name()))
+    }
+
+    fn remove_from_hand(&mut self, player: Player, name: &str) -> Result<String, String> {
+        let card = self.find_card(name)?;
+        let hand = &mut self.game_state.player_mut(player).hand;
+        if !hand.iter_unique().any(|hand_card| hand_card == card) {
+            return Err(format!("{player:?} has no {} in their hand", card.name()));
+        }
+        hand.remove_one(card);
+        self.game_state.deck.push(card);
+        Ok(format!("Removed {} from {player:?}'s hand", card.name()))
+    }
+
+    fn set_event(&mut self, player: Player, slot: usize, name: &str) -> Result<String, String> {
+        let event_type = if name.eq_ignore_ascii_case("none") {
+            None
+        } else if name.eq_ignore_ascii_case(RAIDERS_EVENT.name) {
+            let has_raiders = self
+                .game_state
+                .player(player)
+                .events
+                .iter()
+                .any(|event| matches!(event, Some(event) if *event == &RAIDERS_EVENT));
+            if has_raiders {
+                return Err(format!("{player:?} already has Raiders in play"));
+            }
+            Some(&RAIDERS_EVENT)
+        } else {
+            let event_type = find_by_name(self.event_types, name, |event_type| event_type.name)
+                .ok_or_else(|| format!("there is no event named {name:?}"))?;
+            if event_type.resolve_turns == 0 {
+                return Err(format!(
+                    "{} resolves immediately when played",
+                    event_type.name
+                ));
+            }
+            self.take_from_deck(PersonOrEventType::Event(event_type))?;
+            Some(event_type)
+        };
+
+        let old_event = mem::replace(
+            &mut self.game_state.player_mut(player).events[slot],
+            event_type,
+        );
+        if let Some(old_event) = old_event {
+            // Raiders isn't a card in the deck
+            if old_event != &RAIDERS_EVENT {
+                self.game_state
+                    .deck
+                    .push(PersonOrEventType::Event(old_event));
+            }
+        }
+        Ok(format!(
+            "{player:?}'s event slot {} has {}",
+            slot + 1,
+            event_type.map_or("no event", |event_type| event_type.name)
+        ))
+    }
+
+    /// Returns the person or event card with the given name.
+    fn find_card(&self, name: &str) -> Result<PersonOrEventType<'ctype>, String> {
+        let person = find_by_name(self.person_types, name, |person_type| person_type.name)
+            .map(PersonOrEventType::Person);
+        let event = find_by_name(self.event_types, name, |event_type| event_type.name)
+            .map(PersonOrEventType::Event);
+        person
+            .or(event)
+            .ok_or_else(|| format!("there is no person or event named {name:?}"))
+    }
+
+    /// Removes one copy of the given card from the deck, or returns an error if there are none
+    /// left.
+    fn take_from_deck(&mut self, card: PersonOrEventType<'ctype>) -> Result<(), String> {
+        let deck = &mut self.game_state.deck;
+        let index = deck
+            .iter()
+            .position(|&deck_card| deck_card == card)
+            .ok_or_else(|| format!("there are no copies of {} left in the deck", card.name()))?;
+        deck.remove(index);
+        Ok(())
+    }
+
+    /// Puts the card of a person removed from the board back into the deck.
+    fn return_person_to_deck(&mut self, person: Option<Person<'ctype>>) {
+        if let Some(person) = person {
+            self.game_state.deck.push(person.card());
+        }
+    }
+}
+
+/// Returns the card type with the given name (ignoring case), if any.
+fn find_by_name<'ctype, T>(
+    types: &'ctype [T],
+    name: &str,
+    get_name: impl Fn(&T) -> &str,
+) -> Option<&'ctype T> {
+    types
+        .iter()
+        .find(|card_type| get_name(card_type).eq_ignore_ascii_case(name))
+}
+
+/// Returns the name of a person (or of an empty slot), in English.
+fn name_of(person: Option<&Person>) -> &'static str {
+    match person {
+        None => "nobody",
+        Some(Person::Punk { .. }) => "Punk",
+        Some(Person::NonPunk { person_type, .. }) => person_type.name,
+    }
+}
+
+fn parse_player(s: &str) -> Result<Player, String> {
+    match s.to_lowercase().as_str() {
+        "p1" | "1" => Ok(Player::Player1),
+        "p2" | "2" => Ok(Player::Player2),
+        _ => Err(format!("expected a player (p1 or p2), found {s:?}")),
+    }
+}
+
+/// Parses a number from 1 to `max`, returning it as a 0-based index.
+fn parse_number(s: &str, max: usize, what: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(n) if (1..=max).contains(&n) => Ok(n - 1),
+        _ => Err(format!("expected a {what} from 1 to {max}, found {s:?}")),
+    }
+}
+
+fn parse_column(s: &str) -> Result<ColumnIndex, String> {
+    parse_number(s, 3, "column").map(ColumnIndex::from)
+}
+
+fn parse_slot(s: &str) -> Result<PersonRowIndex, String> {
+    parse_number(s, 2, "slot").map(PersonRowIndex::from)
+}
+
+/// Helpers for tests that set up a position with sandbox commands and play it out.
+#[cfg(test)]
+pub mod testing {
+    use lazy_static::lazy_static;
+
+    use super::*;
+    use crate::radlands::card_set::CardSet;
+    use crate::radlands::locations::{CardLocation, CardRowIndex};
+    use crate::radlands::GameResult;
+
+    lazy_static! {
+        /// The base card set, shared by every test, so that the cards of a position and the card
+        /// types that a test looks up (see `person_type`) are the same.
+        static ref CARD_SET: CardSet = CardSet::standard();
+    }
+
+    /// Returns the base card set, which games made from it can borrow for the rest of the test.
+    pub fn card_set() -> &'static CardSet {
+        &CARD_SET
+    }
+
+    /// Returns the person type with the given English name from the base card set.
+    pub fn person_type(name: &str) -> &'static PersonType {
+        card_set()
+            .person_types
+            .iter()
+            .find(|person_type| person_type.name == name)
+            .unwrap_or_else(|| panic!("no person named {name:?}"))
+    }
+
+    /// Sets up a position from a new game with both hands emptied, applying the given sandbox
+    /// commands (panicking if one is invalid), and starts playing from it.
+    pub fn position(commands: &[&str]) -> (GameState<'static>, Choice<'static>) {
+        let card_set = card_set();
+        let mut sandbox = Sandbox::new(
+            &card_set.camp_types,
+            &card_set.person_types,
+            &card_set.event_types,
+            GameRules::casual(false),
+            0,
+        );
+        for command in ["hand p1 clear", "hand p2 clear"].iter().chain(commands) {
+            if let Err(message) = sandbox.apply(command) {
+                panic!("invalid sandbox command {command:?}: {message}");
+            }
+        }
+        sandbox.start().expect("the position can't be played from")
+    }
+
+    /// Returns the location of a card, numbered like in the sandbox commands: `column` is 1 to
+    /// 3, and `slot` is 0 for the camp or 1 to 2 for a person.
+    pub fn card_loc(player: Player, column: usize, slot: usize) -> CardLocation {
+        let row = match slot {
+            0 => CardRowIndex::camp(),
+            slot => PersonRowIndex::from(slot - 1).into(),
+        };
+        CardLocation::new(ColumnIndex::from(column - 1), row, player)
+    }
+
+    /// Returns the labels of the choice's options.
+    pub fn labels<'ctype>(game_state: &GameState<'ctype>, choice: &Choice<'ctype>) -> Vec<String> {
+        (0..choice.num_options())
+            .map(|option| choice.option_label(option, game_state))
+            .collect()
+    }
+
+    /// Chooses the first option whose label starts with the given text, panicking if there is
+    /// none.
+    pub fn choose<'ctype>(
+        game_state: &mut GameState<'ctype>,
+        choice: &Choice<'ctype>,
+        label: &str,
+    ) -> Result<Choice<'ctype>, GameResult> {
+        let labels = labels(game_state, choice);
+        let option = labels
+            .iter()
+            .position(|option_label| option_label.starts_with(label))
+            .unwrap_or_else(|| panic!("no option starts with {label:?} in {labels:#?}"));
+        choice.choose(game_state, option)
+    }
+
+    /// Chooses the option that targets the given card, panicking if there is none.
+    pub fn choose_target<'ctype>(
+        game_state: &mut GameState<'ctype>,
+        choice: &Choice<'ctype>,
+        target: CardLocation,
+    ) -> Result<Choice<'ctype>, GameResult> {
+        let option = (0..choice.num_options())
+            .find(|&option| choice.option_target(option, game_state) == Some(target))
+            .unwrap_or_else(|| {
+                panic!(
+                    "no option targets {target:?} in {:#?}",
+                    labels(game_state, choice)
+                )
+            });
+        choice.choose(game_state, option)
+    }
+}
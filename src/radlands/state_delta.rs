@@ -0,0 +1,610 @@
+//! Structured differences between two game states ("what changed"), as returned by
+//! `GameState::diff`: camps damaged, restored, destroyed, or moved, people placed, removed, or
+//! changed in their slots, events added, moved, or resolved, and changes to the water, hands,
+//! deck, discard pile, and turn.
+//!
+//! The comparison is by position, not by identity: a person who moves to another slot shows up
+//! as removed from one slot and placed in another, and an event is matched with an event of the
+//! same type in the same slot or a slot in front of it. A punk is compared by its face-down card
+//! too (which `Person`'s own comparison leaves out; see `FaceDown`), so a punk replaced by
+//! another punk shows up. The per-turn flags (such as whether the current player has paid to
+//! draw) and the game's RNG aren't compared.
+
+use std::fmt;
+
+use super::camps::CampType;
+use super::events::EventType;
+use super::locations::{CardLocation, CardRowIndex, PersonRowIndex, Player};
+use super::player_state::{CampStatus, NonPunkStatus, Person, PlayerState};
+use super::styles::StyledName;
+use super::GameState;
+
+/// The differences between two game states, in a fixed order: the turn, the water, the deck
+/// and discard pile, then each player's board, events, and hand.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateDelta<'ctype> {
+    changes: Vec<StateChange<'ctype>>,
+}
+
+/// One difference between two game states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateChange<'ctype> {
+    /// The turn number (and so possibly the current player) changed.
+    Turn {
+        before: u32,
+        after: u32,
+        cur_player: Player,
+    },
+
+    /// The current player's water changed.
+    Water { before: u32, after: u32 },
+
+    /// The number of cards in the deck changed.
+    DeckSize { before: usize, after: usize },
+
+    /// The number of cards in the discard pile changed.
+    DiscardSize { before: usize, after: usize },
+
+    /// A camp was damaged, restored, or destroyed.
+    CampStatus {
+        location: CardLocation,
+        camp_type: &'ctype CampType,
+        before: CampStatus,
+        after: CampStatus,
+    },
+
+    /// A camp moved forward or back in its column (see Juggernaut).
+    CampPosition {
+        location: CardLocation,
+        camp_type: &'ctype CampType,
+        before: usize,
+        after: usize,
+    },
+
+    /// A camp became ready or not ready.
+    CampReadiness {
+        location: CardLocation,
+        camp_type: &'ctype CampType,
+        is_ready: bool,
+    },
+
+    /// A person was placed in an empty slot.
+    PersonPlaced {
+        location: CardLocation,
+        person: Person<'ctype>,
+    },
+
+    /// A person left a slot, which is now empty (e.g. they were destroyed, returned to a hand,
+    /// or moved to another slot).
+    PersonRemoved {
+        location: CardLocation,
+        person: Person<'ctype>,
+    },
+
+    /// A slot now holds a different person than before.
+    PersonReplaced {
+        location: CardLocation,
+        before: Person<'ctype>,
+        after: Person<'ctype>,
+    },
+
+    /// The person in a slot was injured, restored, readied, or used.
+    PersonStatus {
+        location: CardLocation,
+        before: Person<'ctype>,
+        after: Person<'ctype>,
+    },
+
+    /// An event was put in an event slot (e.g. played).
+    EventAdded {
+        player: Player,
+        slot: usize,
+        event_type: &'ctype EventType,
+    },
+
+    /// An event moved to another slot (e.g. forward at the start of a turn).
+    EventMoved {
+        player: Player,
+        event_type: &'ctype EventType,
+        from: usize,
+        to: usize,
+    },
+
+    /// An event left the event slots (e.g. it was resolved or destroyed).
+    EventRemoved {
+        player: Player,
+        slot: usize,
+        event_type: &'ctype EventType,
+    },
+
+    /// The number of cards in a player's hand (not counting Water Silo) changed.
+    HandSize {
+        player: Player,
+        before: usize,
+        after: usize,
+    },
+
+    /// Water Silo moved between a player's board and their hand.
+    WaterSilo { player: Player, in_hand: bool },
+}
+
+impl<'ctype> StateDelta<'ctype> {
+    /// Returns the differences between the two states.
+    pub(super) fn between(before: &GameState<'ctype>, after: &GameState<'ctype>) -> Self {
+        let mut changes = Vec::new();
+        if before.turn_number != after.turn_number {
+            changes.push(StateChange::Turn {
+                before: before.turn_number,
+                after: after.turn_number,
+                cur_player: after.cur_player,
+            });
+        }
+        if before.cur_player_water != after.cur_player_water {
+            changes.push(StateChange::Water {
+                before: before.cur_player_water,
+                after: after.cur_player_water,
+            });
+        }
+        if before.deck().len() != after.deck().len() {
+            changes.push(StateChange::DeckSize {
+                before: before.deck().len(),
+                after: after.deck().len(),
+            });
+        }
+        if before.discard().len() != after.discard().len() {
+            changes.push(StateChange::DiscardSize {
+                before: before.discard().len(),
+                after: after.discard().len(),
+            });
+        }
+        for player in [Player::Player1, Player::Player2] {
+            diff_player(
+                player,
+                before.player(player),
+                after.player(player),
+                &mut changes,
+            );
+        }
+        StateDelta { changes }
+    }
+
+    /// Returns whether the states are the same (as far as the comparison goes).
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the differences, in order.
+    pub fn changes(&self) -> &[StateChange<'ctype>] {
+        &self.changes
+    }
+}
+
+/// Adds the differences between a player's state before and after to `changes`.
+fn diff_player<'ctype>(
+    player: Player,
+    before: &PlayerState<'ctype>,
+    after: &PlayerState<'ctype>,
+    changes: &mut Vec<StateChange<'ctype>>,
+) {
+    for (column_index, (column_before, column_after)) in
+        before.columns.iter().zip(&after.columns).enumerate()
+    {
+        let location = |row: CardRowIndex| CardLocation::new(column_index.into(), row, player);
+
+        let (camp_before, camp_after) = (&column_before.camp, &column_after.camp);
+        if camp_before.status != camp_after.status {
+            changes.push(StateChange::CampStatus {
+                location: location(CardRowIndex::camp()),
+                camp_type: camp_after.camp_type,
+                before: camp_before.status,
+                after: camp_after.status,
+            });
+        }
+        if camp_before.position() != camp_after.position() {
+            changes.push(StateChange::CampPosition {
+                location: location(CardRowIndex::camp()),
+                camp_type: camp_after.camp_type,
+                before: camp_before.position(),
+                after: camp_after.position(),
+            });
+        }
+        // a destroyed camp is never ready, so its readiness only changes with its status
+        if camp_before.is_ready() != camp_after.is_ready() && !camp_after.is_destroyed() {
+            changes.push(StateChange::CampReadiness {
+                location: location(CardRowIndex::camp()),
+                camp_type: camp_after.camp_type,
+                is_ready: camp_after.is_ready(),
+            });
+        }
+
+        let slots = column_before
+            .person_slots
+            .iter()
+            .zip(&column_after.person_slots);
+        for (row_index, slot) in slots.enumerate() {
+            let location = location(PersonRowIndex::from(row_index).into());
+            match slot {
+                (None, Some(person)) => changes.push(StateChange::PersonPlaced {
+                    location,
+                    person: person.clone(),
+                }),
+                (Some(person), None) => changes.push(StateChange::PersonRemoved {
+                    location,
+                    person: person.clone(),
+                }),
+                (Some(before), Some(after)) if before != after || !is_same_card(before, after) => {
+                    let change = if is_same_card(before, after) {
+                        StateChange::PersonStatus {
+                            location,
+                            before: before.clone(),
+                            after: after.clone(),
+                        }
+                    } else {
+                        StateChange::PersonReplaced {
+                            location,
+                            before: before.clone(),
+                            after: after.clone(),
+                        }
+                    };
+                    changes.push(change);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    diff_events(player, &before.events, &after.events, changes);
+
+    if before.hand.count() != after.hand.count() {
+        changes.push(StateChange::HandSize {
+            player,
+            before: before.hand.count(),
+            after: after.hand.count(),
+        });
+    }
+    if before.has_water_silo != after.has_water_silo {
+        changes.push(StateChange::WaterSilo {
+            player,
+            in_hand: after.has_water_silo,
+        });
+    }
+}
+
+/// Returns whether the two people are the same card (punks with the same face-down card, or the
+/// same person type), perhaps with a different status.
+fn is_same_card(a: &Person, b: &Person) -> bool {
+    match (a, b) {
+        (Person::Punk { card: card_a, .. }, Person::Punk { card: card_b, .. }) => {
+            card_a.0 == card_b.0
+        }
+        (
+            Person::NonPunk {
+                person_type: type_a,
+                ..
+            },
+            Person::NonPunk {
+                person_type: type_b,
+                ..
+            },
+        ) => type_a == type_b,
+        _ => false,
+    }
+}
+
+/// Adds the differences between a player's event slots before and after to `changes`. Each event
+/// before is matched with an event of the same type after, preferring the same slot and then the
+/// nearest slot in front of it (since events only move forward).
+fn diff_events<'ctype>(
+    player: Player,
+    before: &[Option<&'ctype EventType>; 3],
+    after: &[Option<&'ctype EventType>; 3],
+    changes: &mut Vec<StateChange<'ctype>>,
+) {
+    let mut matched = [false; 3];
+    for (from, event_type) in before
+        .iter()
+        .enumerate()
+        .filter_map(|(slot, event)| event.map(|event_type| (slot, event_type)))
+    {
+        let is_match = |to: &usize| !matched[*to] && after[*to] == Some(event_type);
+        let to = (0..=from)
+            .rev()
+            .find(is_match)
+            .or_else(|| (from + 1..3).find(is_match));
+        match to {
+            Some(to) => {
+                matched[to] = true;
+                if to != from {
+                    changes.push(StateChange::EventMoved {
+                        player,
+                        event_type,
+                        from,
+                        to,
+                    });
+                }
+            }
+            None => changes.push(StateChange::EventRemoved {
+                player,
+                slot: from,
+                event_type,
+            }),
+        }
+    }
+    for (slot, event_type) in after.iter().enumerate() {
+        if let (Some(event_type), false) = (event_type, matched[slot]) {
+            changes.push(StateChange::EventAdded {
+                player,
+                slot,
+                event_type,
+            });
+        }
+    }
+}
+
+/// Returns the name of the person with their status, e.g. "Looter (ready, used 1×)".
+fn describe_person(person: &Person) -> String {
+    let name = person.styled_name().content;
+    let status = match person {
+        Person::Punk { is_ready: true, .. }
+        | Person::NonPunk {
+            status: NonPunkStatus::Ready,
+            ..
+        } => "ready",
+        Person::Punk { .. }
+        | Person::NonPunk {
+            status: NonPunkStatus::NotReady,
+            ..
+        } => "not ready",
+        Person::NonPunk {
+            status: NonPunkStatus::Injured,
+            ..
+        } => "injured",
+    };
+    match person.times_used() {
+        0 => format!("{name} ({status})"),
+        times_used => format!("{name} ({status}, used {times_used}×)"),
+    }
+}
+
+impl fmt::Display for StateChange<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateChange::Turn {
+                before,
+                after,
+                cur_player,
+            } => write!(f, "turn {before} → {after} ({cur_player:?}'s turn)"),
+            StateChange::Water { before, after } => write!(f, "water {before} → {after}"),
+            StateChange::DeckSize { before, after } => {
+                write!(f, "deck {before} → {after} cards")
+            }
+            StateChange::DiscardSize { before, after } => {
+                write!(f, "discard pile {before} → {after} cards")
+            }
+            StateChange::CampStatus {
+                location,
+                camp_type,
+                before,
+                after,
+            } => write!(
+                f,
+                "{} at {location}: {before:?} → {after:?}",
+                camp_type.display_name()
+            ),
+            StateChange::CampPosition {
+                location,
+                camp_type,
+                before,
+                after,
+            } => write!(
+                f,
+                "{} at {location}: position {before} → {after}",
+                camp_type.display_name()
+            ),
+            StateChange::CampReadiness {
+                location,
+                camp_type,
+                is_ready,
+            } => write!(
+                f,
+                "{} at {location}: {}",
+                camp_type.display_name(),
+                if *is_ready { "ready" } else { "not ready" }
+            ),
+            StateChange::PersonPlaced { location, person } => {
+                write!(f, "{} placed at {location}", describe_person(person))
+            }
+            StateChange::PersonRemoved { location, person } => {
+                write!(f, "{} removed from {location}", describe_person(person))
+            }
+            StateChange::PersonReplaced {
+                location,
+                before,
+                after,
+            }
+            | StateChange::PersonStatus {
+                location,
+                before,
+                after,
+            } => write!(
+                f,
+                "{} → {} at {location}",
+                describe_person(before),
+                describe_person(after)
+            ),
+            StateChange::EventAdded {
+                player,
+                slot,
+                event_type,
+            } => write!(
+                f,
+                "{} added to {player:?}'s event slot {}",
+                event_type.display_name(),
+                slot + 1
+            ),
+            StateChange::EventMoved {
+                player,
+                event_type,
+                from,
+                to,
+            } => write!(
+                f,
+                "{} moved from {player:?}'s event slot {} to {}",
+                event_type.display_name(),
+                from + 1,
+                to + 1
+            ),
+            StateChange::EventRemoved {
+                player,
+                slot,
+                event_type,
+            } => write!(
+                f,
+                "{} removed from {player:?}'s event slot {}",
+                event_type.display_name(),
+                slot + 1
+            ),
+            StateChange::HandSize {
+                player,
+                before,
+                after,
+            } => write!(f, "{player:?}'s hand {before} → {after} cards"),
+            StateChange::WaterSilo { player, in_hand } => write!(
+                f,
+                "{player:?}'s Water Silo {}",
+                if *in_hand { "taken into their hand" } else { "put back on their board" }
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::locations::Player::{Player1, Player2};
+    use super::super::player_state::FaceDown;
+    use super::super::sandbox::testing::*;
+    use super::super::PersonOrEventType;
+    use super::*;
+
+    /// Returns the person at the given location (numbered as in `card_loc`).
+    fn person_at<'ctype>(
+        game_state: &GameState<'ctype>,
+        player: Player,
+        column: usize,
+        slot: usize,
+    ) -> Person<'ctype> {
+        game_state.player(player).columns[column - 1].person_slots[slot - 1]
+            .clone()
+            .unwrap()
+    }
+
+    #[test]
+    fn damaging_a_person_changes_only_their_status() {
+        let (before, _) = position(&["turn p1", "person p2 1 1 Looter"]);
+        let mut after = before.clone();
+        after
+            .damage_card_at(card_loc(Player2, 1, 1), false, true)
+            .unwrap();
+
+        assert_eq!(
+            before.diff(&after).changes(),
+            [StateChange::PersonStatus {
+                location: card_loc(Player2, 1, 1),
+                before: person_at(&before, Player2, 1, 1),
+                after: person_at(&after, Player2, 1, 1),
+            }]
+        );
+    }
+
+    #[test]
+    fn destroying_a_person_shifts_the_one_in_front_back() {
+        let (before, _) = position(&["turn p1", "person p2 1 1 punk", "person p2 1 2 Looter"]);
+        let mut after = before.clone();
+        after
+            .damage_card_at(card_loc(Player2, 1, 1), true, true)
+            .unwrap();
+
+        // compared by slot, Looter replaced the punk, and left the slot in front
+        let discard_len = before.discard().len();
+        assert_eq!(
+            before.diff(&after).changes(),
+            [
+                StateChange::DiscardSize {
+                    before: discard_len,
+                    after: discard_len + 1,
+                },
+                StateChange::PersonReplaced {
+                    location: card_loc(Player2, 1, 1),
+                    before: person_at(&before, Player2, 1, 1),
+                    after: person_at(&before, Player2, 1, 2),
+                },
+                StateChange::PersonRemoved {
+                    location: card_loc(Player2, 1, 2),
+                    person: person_at(&before, Player2, 1, 2),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn advancing_an_event_moves_it() {
+        let (before, _) = position(&["turn p1", "event p1 2 Napalm"]);
+        let mut after = before.clone();
+        let _ = after.advance_event(Player1, 1).unwrap();
+
+        let napalm = before.player(Player1).events[1].unwrap();
+        assert_eq!(
+            before.diff(&after).changes(),
+            [StateChange::EventMoved {
+                player: Player1,
+                event_type: napalm,
+                from: 1,
+                to: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn spending_water_changes_only_the_water() {
+        let (before, _) = position(&["turn p1", "water 3"]);
+        let mut after = before.clone();
+        after.spend_water(2);
+
+        assert_eq!(
+            before.diff(&after).changes(),
+            [StateChange::Water {
+                before: 3,
+                after: 1
+            }]
+        );
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn a_punk_replaced_by_another_punk_is_a_change() {
+        let (before, _) = position(&["turn p1", "person p2 1 1 punk"]);
+        let mut after = before.clone();
+        let punk_card = match &mut after.player_mut(Player2).columns[0].person_slots[0] {
+            Some(Person::Punk { card, .. }) => card,
+            _ => unreachable!(),
+        };
+        // (a card other than the one that was under the punk)
+        let muse = PersonOrEventType::Person(person_type("Muse"));
+        let looter = PersonOrEventType::Person(person_type("Looter"));
+        *punk_card = FaceDown(if punk_card.0 == muse { looter } else { muse });
+
+        // the punks compare as equal people, since their cards are face down
+        assert_eq!(
+            person_at(&before, Player2, 1, 1),
+            person_at(&after, Player2, 1, 1)
+        );
+        assert_eq!(
+            before.diff(&after).changes(),
+            [StateChange::PersonReplaced {
+                location: card_loc(Player2, 1, 1),
+                before: person_at(&before, Player2, 1, 1),
+                after: person_at(&after, Player2, 1, 1),
+            }]
+        );
+    }
+}
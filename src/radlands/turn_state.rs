@@ -0,0 +1,69 @@
+//! What has happened so far in the current turn, for the rules and the cards that depend on it
+//! (e.g. "if this is your only ability this turn").
+
+/// The state of the current turn, which is reset when the turn ends (see `GameState::end_turn`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct TurnState {
+    /// Whether the current player has used the generic "pay 2 water to draw a card"
+    /// ability this turn.
+    pub(super) has_paid_to_draw: bool,
+
+    /// Whether the current player has played an event this turn.
+    pub(super) has_played_event: bool,
+
+    /// Whether the current player has used an ability that must be the only one they use this
+    /// turn (e.g. Resonator's), so they can't use any more abilities.
+    pub(super) abilities_locked: bool,
+
+    /// The number of people the current player has played from their hand this turn.
+    pub(super) people_played: u32,
+
+    /// The number of person or camp abilities the current player has used this turn.
+    pub(super) abilities_used: u32,
+
+    /// The number of events (of either player) that have resolved this turn, including at its
+    /// start.
+    pub(super) events_resolved: u32,
+}
+
+impl TurnState {
+    /// Returns whether the current player has paid 2 water to draw a card this turn.
+    pub fn has_paid_to_draw(&self) -> bool {
+        self.has_paid_to_draw
+    }
+
+    /// Returns whether the current player has played an event this turn.
+    pub fn has_played_event(&self) -> bool {
+        self.has_played_event
+    }
+
+    /// Returns whether the current player can't use any more abilities this turn.
+    pub fn abilities_locked(&self) -> bool {
+        self.abilities_locked
+    }
+
+    /// Returns the number of people the current player has played from their hand this turn.
+    pub fn people_played(&self) -> u32 {
+        self.people_played
+    }
+
+    /// Returns the number of abilities the current player has used this turn.
+    pub fn abilities_used(&self) -> u32 {
+        self.abilities_used
+    }
+
+    /// Returns whether the current player has used the ability of a person or camp this turn.
+    pub fn has_used_ability(&self) -> bool {
+        self.abilities_used > 0
+    }
+
+    /// Returns the number of events that have resolved this turn.
+    pub fn events_resolved(&self) -> u32 {
+        self.events_resolved
+    }
+
+    /// Returns whether any event has resolved this turn.
+    pub fn event_resolved(&self) -> bool {
+        self.events_resolved > 0
+    }
+}
@@ -0,0 +1,105 @@
+//! A compact summary of where a game is within the current turn (the turn number, whose turn it
+//! is, their water, and what the pending choice is part of), for status lines such as the UI's
+//! status bar and the plain game's `--verbose` output.
+
+use itertools::Itertools;
+use tui::text::Spans;
+
+use crate::make_spans;
+
+use super::choices::{Choice, ChoiceContext};
+use super::events::EventType;
+use super::locations::Player;
+use super::styles::*;
+use super::GameState;
+
+/// What part of the turn a pending choice belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurnPhase<'ctype> {
+    /// The players are choosing which of the camps they were dealt to keep, before the first
+    /// turn.
+    CampSelect,
+
+    /// The players are deciding whether to mulligan their opening hands, before the first turn.
+    Mulligan,
+
+    /// The current player is choosing an action, and can take the given number of actions
+    /// besides ending the turn.
+    Main { num_actions: usize },
+
+    /// The choice is part of resolving the given event.
+    Event(&'ctype EventType),
+
+    /// The choice is part of resolving an action that was taken (e.g. picking the target of an
+    /// ability or an enter-play effect).
+    Action,
+}
+
+/// A summary of the current turn at a pending choice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TurnStatus<'ctype> {
+    /// The number of the turn, starting from 1.
+    pub turn_number: u32,
+
+    /// The player whose turn it is.
+    pub cur_player: Player,
+
+    /// The amount of water that the current player has available.
+    pub water: u32,
+
+    /// What the pending choice is part of.
+    pub phase: TurnPhase<'ctype>,
+}
+
+impl<'ctype> TurnStatus<'ctype> {
+    /// Returns the status of the turn in the given game state, at the given pending choice.
+    pub fn new(game_state: &GameState<'ctype>, choice: &Choice<'ctype>) -> Self {
+        let phase = match choice {
+            Choice::CampSelect(_) => TurnPhase::CampSelect,
+            Choice::Mulligan(_) => TurnPhase::Mulligan,
+            Choice::Action(action_choice) => TurnPhase::Main {
+                num_actions: action_choice.num_actions_besides_end_turn(),
+            },
+            _ => match choice.context() {
+                Some(ChoiceContext::ResolveEvent(event_type)) => TurnPhase::Event(event_type),
+                Some(ChoiceContext::EnterPlay(_)) | None => TurnPhase::Action,
+            },
+        };
+        TurnStatus {
+            turn_number: game_state.turn_number,
+            cur_player: game_state.cur_player,
+            water: game_state.cur_player_water,
+            phase,
+        }
+    }
+
+    /// Formats this status as a single line for human-readable display, e.g.
+    /// "Turn 3 | Player 1 | 2 water | main phase: 4 actions besides ending the turn".
+    pub fn format(&self) -> Spans<'static> {
+        let mut spans = make_spans!(
+            format!("Turn {}", self.turn_number),
+            " | ",
+            format!("Player {}", self.cur_player.number()),
+            " | ",
+            Span::styled(format!("{} water", self.water), *WATER),
+            " | ",
+        );
+        spans.0.extend(match self.phase {
+            TurnPhase::CampSelect => make_spans!("choosing camps").0,
+            TurnPhase::Mulligan => make_spans!("choosing opening hands").0,
+            TurnPhase::Main { num_actions: 0 } => {
+                make_spans!("main phase: nothing left to do but end the turn").0
+            }
+            TurnPhase::Main { num_actions } => {
+                make_spans!(format!(
+                    "main phase: {num_actions} {} besides ending the turn",
+                    if num_actions == 1 { "action" } else { "actions" },
+                ))
+                .0
+            }
+            TurnPhase::Event(event_type) => make_spans!("resolving ", event_type.styled_name()).0,
+            TurnPhase::Action => make_spans!("resolving an action").0,
+        });
+        spans
+    }
+}
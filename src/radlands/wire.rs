@@ -0,0 +1,810 @@
+//! A compact binary encoding of what a player can see of a game (an "observation"), for event
+//! stream subscribers (see `event_stream`) that export self-play data at scale, where JSON is
+//! too bulky and slow to parse. (Remote play has its own protocol; see `controllers::remote`.)
+//!
+//! Cards are written as their IDs in the lists of card types (see `get_person_types` etc.), so
+//! an observation can only be decoded with the same lists, i.e. by the same version of the
+//! card definitions. Every observation starts with the magic bytes `RB` and the format version
+//! (`WIRE_VERSION`), which is bumped whenever the layout or the card lists change, so a reader
+//! can reject data it doesn't understand instead of misreading it.
+//!
+//! After the header, the fields are written in this order. Unsigned integers are LEB128
+//! varints ("uint"), and booleans and small enums are single bytes:
+//!
+//! - the turn number (uint), the current player (0 or 1), and their water (uint)
+//! - a byte of flags: paid to draw (1), played an event (2), reshuffled the deck (4), can't use
+//!   any more abilities (8); then the numbers of people played, abilities used, and events
+//!   resolved this turn (uints)
+//! - the deck size (uint), then the discard pile as a count and that many cards, bottom first
+//! - for each player: a byte of flags (hand shown (1), has Water Silo (2)), the hand size
+//!   (uint), and if the hand is shown, the number of distinct cards and a (card, count) pair for
+//!   each; then three columns of a camp and two person slots (back first), and three event slots
+//! - the choice: 0 and the game result (0 = Player 1 wins, 1 = Player 2 wins, 2 = tie) if the
+//!   game is over, or 1, the chooser, the choice kind (an index into `CHOICE_KINDS`), its
+//!   context, the number of options, and whether the option labels follow (only if the
+//!   chooser's hand is shown, as in the event stream), then the labels as UTF-8 strings
+//!
+//! A card is `2 * id` for a person or `2 * id + 1` for an event. An event slot is 0 if empty, 1
+//! for Raiders, or `id + 2`. A camp is its ID, a byte with its status (0 = undamaged, 1 =
+//! damaged, 2 = destroyed) plus 4 if it's ready plus 8 times its position (how far it has moved
+//! forward; see Juggernaut), and its use count. A person slot is 0 if empty, 1 for a punk
+//! (followed by whether it's ready and its use count, but not its face-down card), or 2 for a
+//! person card (followed by its ID, its status (0 = ready, 1 = not ready, 2 = injured), and its
+//! use count).
+//! A context is 0 for none, 1 and a person ID for an enter-play effect, or 2 and an event slot
+//! value for an event resolving. A string is its length in bytes (uint) and the bytes.
+//!
+//! `--fuzz-wire <GAMES>` checks that every observation of random games round-trips exactly, and
+//! that the decoder rejects corrupted data without panicking.
+
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::cards::Cards;
+
+use super::camps::CampType;
+use super::choices::{Choice, ChoiceContext};
+use super::events::EventType;
+use super::locations::Player;
+use super::people::PersonType;
+use super::player_state::{Camp, CampStatus, CardColumn, FaceDown, NonPunkStatus, Person};
+use super::rules::GameRules;
+use super::{GameResult, GameState, PersonOrEventType, RAIDERS_EVENT};
+
+/// The version of the format, written after the magic bytes.
+pub const WIRE_VERSION: u8 = 23;
+
+/// The bytes that every encoded observation starts with.
+const MAGIC: [u8; 2] = *b"RB";
+
+/// The names of the kinds of choices, indexed by how they're written.
+pub const CHOICE_KINDS: [&str; 18] = [
+    "action",
+    "play_location",
+    "damage",
+    "restore",
+    "icon_effect",
+    "rescue_person",
+    "optional_effect",
+    "damage_column",
+    "discard",
+    "discard_drawn",
+    "revealed_junk",
+    "mulligan",
+    "camp_select",
+    "advance_event",
+    "move_person",
+    "move_destination",
+    "copy_ability",
+    "multi_select",
+];
+
+/// Why an observation couldn't be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WireError {
+    /// The data ended in the middle of the observation.
+    Truncated,
+
+    /// The data doesn't start with the magic bytes, so it isn't an observation.
+    BadMagic,
+
+    /// The observation was written in a version of the format that this build can't read.
+    UnsupportedVersion(u8),
+
+    /// A field had a value that isn't allowed, such as an unknown card ID.
+    Invalid(&'static str),
+
+    /// There were bytes left over after the observation.
+    TrailingBytes(usize),
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireError::Truncated => write!(f, "the data ended unexpectedly"),
+            WireError::BadMagic => write!(f, "the data isn't an encoded observation"),
+            WireError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported format version {version} (this build reads version {WIRE_VERSION})"
+            ),
+            WireError::Invalid(what) => write!(f, "invalid {what}"),
+            WireError::TrailingBytes(count) => {
+                write!(f, "{count} unexpected bytes after the observation")
+            }
+        }
+    }
+}
+
+/// What one player (or a spectator) can see of a game at a choice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Observation<'ctype> {
+    pub turn_number: u32,
+    pub cur_player: Player,
+    pub cur_player_water: u32,
+    pub has_paid_to_draw: bool,
+    pub has_played_event: bool,
+    pub abilities_locked: bool,
+    pub has_reshuffled_deck: bool,
+    pub people_played: u32,
+    pub abilities_used: u32,
+    pub events_resolved: u32,
+    pub deck_size: u32,
+
+    /// The discard pile, in the order it was discarded.
+    pub discard: Vec<PersonOrEventType<'ctype>>,
+
+    /// What can be seen of Player 1 and Player 2, in that order.
+    pub players: [ObservedPlayer<'ctype>; 2],
+
+    /// The choice to be made, or the result if the game is over.
+    pub choice: Result<ObservedChoice<'ctype>, GameResult>,
+}
+
+/// What can be seen of one player's cards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObservedPlayer<'ctype> {
+    /// The player's hand (not including Water Silo), if it's shown.
+    pub hand: Option<Cards<PersonOrEventType<'ctype>>>,
+
+    /// The number of cards in the player's hand, which is always shown.
+    pub hand_size: u32,
+
+    pub has_water_silo: bool,
+    pub columns: [CardColumn<'ctype>; 3],
+    pub events: [Option<&'ctype EventType>; 3],
+}
+
+/// What can be seen of a choice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObservedChoice<'ctype> {
+    pub chooser: Player,
+
+    /// The kind of choice (one of `CHOICE_KINDS`).
+    pub kind: &'static str,
+
+    pub context: Option<ChoiceContext<'ctype>>,
+    pub num_options: u32,
+
+    /// The labels of the options, if the chooser's hand is shown.
+    pub option_labels: Option<Vec<String>>,
+}
+
+impl<'ctype> Observation<'ctype> {
+    /// Returns what can be seen of the game at the given choice (or result), showing the hands
+    /// of the players for which `shows_hand` (indexed like `players`) is true.
+    pub fn new(
+        game_state: &GameState<'ctype>,
+        choice: &Result<Choice<'ctype>, GameResult>,
+        shows_hand: [bool; 2],
+    ) -> Self {
+        let players = [Player::Player1, Player::Player2].map(|player| {
+            let player_state = game_state.player(player);
+            ObservedPlayer {
+                hand: shows_hand[player_index(player)].then(|| player_state.hand.clone()),
+                hand_size: player_state.hand.count() as u32,
+                has_water_silo: player_state.has_water_silo,
+                columns: player_state.columns.clone(),
+                events: player_state.events,
+            }
+        });
+        let choice = match choice {
+            Ok(choice) => {
+                let chooser = choice.chooser(game_state);
+                Ok(ObservedChoice {
+                    chooser,
+                    kind: CHOICE_KINDS[choice_kind_index(choice)],
+                    context: choice.context(),
+                    num_options: choice.num_options() as u32,
+                    option_labels: shows_hand[player_index(chooser)].then(|| {
+                        (0..choice.num_options())
+                            .map(|option| choice.option_label(option, game_state))
+                            .collect()
+                    }),
+                })
+            }
+            Err(game_result) => Err(*game_result),
+        };
+        let turn_state = game_state.turn_state();
+        Observation {
+            turn_number: game_state.turn_number,
+            cur_player: game_state.cur_player,
+            cur_player_water: game_state.cur_player_water,
+            has_paid_to_draw: turn_state.has_paid_to_draw(),
+            has_played_event: turn_state.has_played_event(),
+            abilities_locked: turn_state.abilities_locked(),
+            has_reshuffled_deck: game_state.has_reshuffled_deck,
+            people_played: turn_state.people_played(),
+            abilities_used: turn_state.abilities_used(),
+            events_resolved: turn_state.events_resolved(),
+            deck_size: game_state.deck().len() as u32,
+            discard: game_state.discard().as_slice().to_vec(),
+            players,
+            choice,
+        }
+    }
+
+    /// Encodes this observation.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(256);
+        bytes.extend(MAGIC);
+        bytes.push(WIRE_VERSION);
+
+        write_uint(&mut bytes, self.turn_number.into());
+        bytes.push(player_index(self.cur_player) as u8);
+        write_uint(&mut bytes, self.cur_player_water.into());
+        bytes.push(
+            u8::from(self.has_paid_to_draw)
+                | u8::from(self.has_played_event) << 1
+                | u8::from(self.has_reshuffled_deck) << 2
+                | u8::from(self.abilities_locked) << 3,
+        );
+        write_uint(&mut bytes, self.people_played.into());
+        write_uint(&mut bytes, self.abilities_used.into());
+        write_uint(&mut bytes, self.events_resolved.into());
+        write_uint(&mut bytes, self.deck_size.into());
+        write_uint(&mut bytes, self.discard.len() as u64);
+        for &card in &self.discard {
+            write_card(&mut bytes, card);
+        }
+
+        for player in &self.players {
+            bytes.push(u8::from(player.hand.is_some()) | u8::from(player.has_water_silo) << 1);
+            write_uint(&mut bytes, player.hand_size.into());
+            if let Some(hand) = &player.hand {
+                write_uint(&mut bytes, hand.count_unique() as u64);
+                for (card, count) in hand.iter() {
+                    write_card(&mut bytes, card);
+                    write_uint(&mut bytes, count as u64);
+                }
+            }
+            for column in &player.columns {
+                write_column(&mut bytes, column);
+            }
+            for &event in &player.events {
+                write_uint(&mut bytes, event_slot_value(event));
+            }
+        }
+
+        match &self.choice {
+            Err(game_result) => {
+                bytes.push(0);
+                bytes.push(match game_result {
+                    GameResult::P1Wins => 0,
+                    GameResult::P2Wins => 1,
+                    GameResult::Tie => 2,
+                });
+            }
+            Ok(choice) => {
+                bytes.push(1);
+                bytes.push(player_index(choice.chooser) as u8);
+                let kind = CHOICE_KINDS.iter().position(|&kind| kind == choice.kind);
+                bytes.push(kind.expect("unknown choice kind") as u8);
+                match choice.context {
+                    None => bytes.push(0),
+                    Some(ChoiceContext::EnterPlay(person_type)) => {
+                        bytes.push(1);
+                        write_uint(&mut bytes, person_type.id as u64);
+                    }
+                    Some(ChoiceContext::ResolveEvent(event_type)) => {
+                        bytes.push(2);
+                        write_uint(&mut bytes, event_slot_value(Some(event_type)));
+                    }
+                }
+                write_uint(&mut bytes, choice.num_options.into());
+                bytes.push(u8::from(choice.option_labels.is_some()));
+                for label in choice.option_labels.iter().flatten() {
+                    write_uint(&mut bytes, label.len() as u64);
+                    bytes.extend(label.as_bytes());
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Decodes an observation, looking up the cards in the given lists of card types (which
+    /// must be the lists it was encoded with).
+    pub fn decode(
+        bytes: &[u8],
+        camp_types: &'ctype [CampType],
+        person_types: &'ctype [PersonType],
+        event_types: &'ctype [EventType],
+    ) -> Result<Self, WireError> {
+        let mut reader = Reader {
+            bytes,
+            camp_types,
+            person_types,
+            event_types,
+        };
+        if reader.take(2)? != MAGIC {
+            return Err(WireError::BadMagic);
+        }
+        let version = reader.byte()?;
+        if version != WIRE_VERSION {
+            return Err(WireError::UnsupportedVersion(version));
+        }
+
+        let turn_number = reader.u32("turn number")?;
+        let cur_player = reader.player()?;
+        let cur_player_water = reader.u32("water")?;
+        let flags = reader.flags(4, "turn flags")?;
+        let people_played = reader.u32("people played")?;
+        let abilities_used = reader.u32("abilities used")?;
+        let events_resolved = reader.u32("events resolved")?;
+        if flags[3] && abilities_used == 0 {
+            // abilities can only be locked by using one
+            return Err(WireError::Invalid("turn flags"));
+        }
+        let deck_size = reader.u32("deck size")?;
+        let discard = (0..reader.count()?)
+            .map(|_| reader.card())
+            .collect::<Result<_, _>>()?;
+
+        let mut read_player = || -> Result<ObservedPlayer<'ctype>, WireError> {
+            let flags = reader.flags(2, "player flags")?;
+            let hand_size = reader.u32("hand size")?;
+            let hand = if flags[0] {
+                let mut hand = Cards::new();
+                for _ in 0..reader.count()? {
+                    let card = reader.card()?;
+                    let count = reader.count()?;
+                    if count == 0 || hand.iter_unique().any(|other| other == card) {
+                        return Err(WireError::Invalid("hand"));
+                    }
+                    hand.add(card, count);
+                }
+                if hand.count() != hand_size as usize {
+                    return Err(WireError::Invalid("hand size"));
+                }
+                Some(hand)
+            } else {
+                None
+            };
+            Ok(ObservedPlayer {
+                hand,
+                hand_size,
+                has_water_silo: flags[1],
+                columns: [reader.column()?, reader.column()?, reader.column()?],
+                events: [
+                    reader.event_slot()?,
+                    reader.event_slot()?,
+                    reader.event_slot()?,
+                ],
+            })
+        };
+        let players = [read_player()?, read_player()?];
+
+        let choice = match reader.byte()? {
+            0 => Err(match reader.byte()? {
+                0 => GameResult::P1Wins,
+                1 => GameResult::P2Wins,
+                2 => GameResult::Tie,
+                _ => return Err(WireError::Invalid("game result")),
+            }),
+            1 => {
+                let chooser = reader.player()?;
+                let kind = *CHOICE_KINDS
+                    .get(reader.byte()? as usize)
+                    .ok_or(WireError::Invalid("choice kind"))?;
+                let context = match reader.byte()? {
+                    0 => None,
+                    1 => Some(ChoiceContext::EnterPlay(reader.person_type()?)),
+                    2 => Some(ChoiceContext::ResolveEvent(
+                        reader
+                            .event_slot()?
+                            .ok_or(WireError::Invalid("choice context"))?,
+                    )),
+                    _ => return Err(WireError::Invalid("choice context")),
+                };
+                let num_options = reader.u32("number of options")?;
+                let option_labels = match reader.byte()? {
+                    0 => None,
+                    1 => Some(
+                        (0..num_options)
+                            .map(|_| reader.string())
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    _ => return Err(WireError::Invalid("option labels flag")),
+                };
+                Ok(ObservedChoice {
+                    chooser,
+                    kind,
+                    context,
+                    num_options,
+                    option_labels,
+                })
+            }
+            _ => return Err(WireError::Invalid("choice")),
+        };
+
+        if !reader.bytes.is_empty() {
+            return Err(WireError::TrailingBytes(reader.bytes.len()));
+        }
+        Ok(Observation {
+            turn_number,
+            cur_player,
+            cur_player_water,
+            has_paid_to_draw: flags[0],
+            has_played_event: flags[1],
+            has_reshuffled_deck: flags[2],
+            abilities_locked: flags[3],
+            people_played,
+            abilities_used,
+            events_resolved,
+            deck_size,
+            discard,
+            players,
+            choice,
+        })
+    }
+}
+
+/// Returns the index of the player in `Observation::players`.
+fn player_index(player: Player) -> usize {
+    match player {
+        Player::Player1 => 0,
+        Player::Player2 => 1,
+    }
+}
+
+/// Returns the index of the choice's kind in `CHOICE_KINDS`.
+fn choice_kind_index(choice: &Choice) -> usize {
+    match choice {
+        Choice::Action(_) => 0,
+        Choice::PlayLoc(_) => 1,
+        Choice::Damage(_) => 2,
+        Choice::Restore(_) => 3,
+        Choice::IconEffect(_) => 4,
+        Choice::RescuePerson(_) => 5,
+        Choice::OptionalEffect(_) => 6,
+        Choice::DamageColumn(_) => 7,
+        Choice::Discard(_) => 8,
+        Choice::DiscardDrawn(_) => 9,
+        Choice::RevealedJunk(_) => 10,
+        Choice::Mulligan(_) => 11,
+        Choice::CampSelect(_) => 12,
+        Choice::AdvanceEvent(_) => 13,
+        Choice::MovePerson(_) => 14,
+        Choice::MoveDestination(_) => 15,
+        Choice::CopyAbility(_) => 16,
+        Choice::MultiSelect(_) => 17,
+    }
+}
+
+/// Writes an unsigned integer as a LEB128 varint.
+fn write_uint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn write_card(bytes: &mut Vec<u8>, card: PersonOrEventType) {
+    write_uint(
+        bytes,
+        match card {
+            PersonOrEventType::Person(person_type) => 2 * person_type.id as u64,
+            PersonOrEventType::Event(event_type) => 2 * event_type.id as u64 + 1,
+        },
+    );
+}
+
+/// Returns the value written for an event slot.
+fn event_slot_value(event: Option<&EventType>) -> u64 {
+    match event {
+        None => 0,
+        Some(event_type) if event_type == &RAIDERS_EVENT => 1,
+        Some(event_type) => event_type.id as u64 + 2,
+    }
+}
+
+fn write_column(bytes: &mut Vec<u8>, column: &CardColumn) {
+    let (camp_type, status, is_ready, times_used, position) = column.camp.parts();
+    write_uint(bytes, camp_type.id as u64);
+    let status = match status {
+        CampStatus::Undamaged => 0,
+        CampStatus::Damaged => 1,
+        CampStatus::Destroyed => 2,
+    };
+    bytes.extend([status | u8::from(is_ready) << 2 | position << 3, times_used]);
+
+    for slot in &column.person_slots {
+        match slot {
+            None => bytes.push(0),
+            Some(Person::Punk {
+                is_ready,
+                times_used,
+                ..
+            }) => bytes.extend([1, u8::from(*is_ready), *times_used]),
+            Some(Person::NonPunk {
+                person_type,
+                status,
+                times_used,
+            }) => {
+                bytes.push(2);
+                write_uint(bytes, person_type.id as u64);
+                let status = match status {
+                    NonPunkStatus::Ready => 0,
+                    NonPunkStatus::NotReady => 1,
+                    NonPunkStatus::Injured => 2,
+                };
+                bytes.extend([status, *times_used]);
+            }
+        }
+    }
+}
+
+/// Reads the fields of an observation from the front of `bytes`.
+struct Reader<'b, 'ctype> {
+    bytes: &'b [u8],
+    camp_types: &'ctype [CampType],
+    person_types: &'ctype [PersonType],
+    event_types: &'ctype [EventType],
+}
+
+impl<'b, 'ctype> Reader<'b, 'ctype> {
+    fn take(&mut self, len: usize) -> Result<&'b [u8], WireError> {
+        if self.bytes.len() < len {
+            return Err(WireError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, WireError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self, what: &'static str) -> Result<bool, WireError> {
+        match self.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(WireError::Invalid(what)),
+        }
+    }
+
+    /// Reads a byte of `num_flags` flags, rejecting any other bits.
+    fn flags(&mut self, num_flags: u32, what: &'static str) -> Result<[bool; 8], WireError> {
+        let byte = self.byte()?;
+        if byte >> num_flags != 0 {
+            return Err(WireError::Invalid(what));
+        }
+        Ok(std::array::from_fn(|bit| byte >> bit & 1 == 1))
+    }
+
+    fn uint(&mut self) -> Result<u64, WireError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(WireError::Invalid("integer"))
+    }
+
+    fn u32(&mut self, what: &'static str) -> Result<u32, WireError> {
+        u32::try_from(self.uint()?).map_err(|_| WireError::Invalid(what))
+    }
+
+    /// Reads the length of a list, which can't be longer than the remaining data (since every
+    /// item takes at least a byte), so corrupted data can't make the reader allocate too much.
+    fn count(&mut self) -> Result<usize, WireError> {
+        let count = self.uint()?;
+        if count > self.bytes.len() as u64 {
+            return Err(WireError::Truncated);
+        }
+        Ok(count as usize)
+    }
+
+    fn player(&mut self) -> Result<Player, WireError> {
+        match self.byte()? {
+            0 => Ok(Player::Player1),
+            1 => Ok(Player::Player2),
+            _ => Err(WireError::Invalid("player")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, WireError> {
+        let len = self.count()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| WireError::Invalid("string"))
+    }
+
+    fn person_type(&mut self) -> Result<&'ctype PersonType, WireError> {
+        let id = self.uint()?;
+        self.person_types
+            .get(id as usize)
+            .ok_or(WireError::Invalid("person ID"))
+    }
+
+    /// Returns the card to put under a decoded punk. A punk's face-down card isn't sent (no
+    /// player can see it), and face-down cards are all equal, so any card will do.
+    fn punk_stand_in(&self) -> Result<PersonOrEventType<'ctype>, WireError> {
+        self.person_types
+            .first()
+            .map(PersonOrEventType::Person)
+            .ok_or(WireError::Invalid("person ID"))
+    }
+
+    fn card(&mut self) -> Result<PersonOrEventType<'ctype>, WireError> {
+        let value = self.uint()?;
+        let card = if value % 2 == 0 {
+            self.person_types
+                .get((value / 2) as usize)
+                .map(PersonOrEventType::Person)
+        } else {
+            self.event_types
+                .get((value / 2) as usize)
+                .map(PersonOrEventType::Event)
+        };
+        card.ok_or(WireError::Invalid("card"))
+    }
+
+    fn event_slot(&mut self) -> Result<Option<&'ctype EventType>, WireError> {
+        match self.uint()? {
+            0 => Ok(None),
+            1 => Ok(Some(&RAIDERS_EVENT)),
+            value => self
+                .event_types
+                .get((value - 2) as usize)
+                .map(Some)
+                .ok_or(WireError::Invalid("event ID")),
+        }
+    }
+
+    fn column(&mut self) -> Result<CardColumn<'ctype>, WireError> {
+        let camp_type = self
+            .camp_types
+            .get(self.uint()? as usize)
+            .ok_or(WireError::Invalid("camp ID"))?;
+        let byte = self.byte()?;
+        let status = match byte & 3 {
+            0 => CampStatus::Undamaged,
+            1 => CampStatus::Damaged,
+            2 => CampStatus::Destroyed,
+            _ => return Err(WireError::Invalid("camp status")),
+        };
+        let position = byte >> 3;
+        if position > 2 || (position != 0 && status == CampStatus::Destroyed) {
+            return Err(WireError::Invalid("camp position"));
+        }
+        let camp = Camp::from_parts(camp_type, status, byte & 4 != 0, self.byte()?, position);
+
+        let mut person_slots = [None, None];
+        for slot in &mut person_slots {
+            *slot = match self.byte()? {
+                0 => None,
+                1 => Some(Person::Punk {
+                    card: FaceDown(self.punk_stand_in()?),
+                    is_ready: self.bool("punk readiness")?,
+                    times_used: self.byte()?,
+                }),
+                2 => Some(Person::NonPunk {
+                    person_type: self.person_type()?,
+                    status: match self.byte()? {
+                        0 => NonPunkStatus::Ready,
+                        1 => NonPunkStatus::NotReady,
+                        2 => NonPunkStatus::Injured,
+                        _ => return Err(WireError::Invalid("person status")),
+                    },
+                    times_used: self.byte()?,
+                }),
+                _ => return Err(WireError::Invalid("person slot")),
+            };
+        }
+        Ok(CardColumn { camp, person_slots })
+    }
+}
+
+/// Plays `num_games` games with uniformly random moves, and checks that every observation of
+/// every choice (from each player's view, a spectator's, and with both hands shown) decodes to
+/// exactly what was encoded. Each encoding is also corrupted in random ways (truncated, or with
+/// bytes changed), which the decoder must reject or decode without panicking. Prints any
+/// mismatches and a summary, and returns whether there were no mismatches.
+pub fn fuzz_round_trip(
+    num_games: u64,
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+) -> bool {
+    const SHOWN_HANDS: [[bool; 2]; 4] =
+        [[true, false], [false, true], [false, false], [true, true]];
+
+    let mut num_observations = 0;
+    let mut num_bytes = 0;
+    let mut num_mismatches = 0;
+    let mut num_corrupted_accepted = 0;
+    for seed in 0..num_games {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (mut game_state, choice) = GameState::new_seeded(
+            camp_types,
+            person_types,
+            event_types,
+            GameRules::casual(seed % 2 == 1),
+            seed,
+        );
+        let mut choice = Ok(choice);
+        loop {
+            for shows_hand in SHOWN_HANDS {
+                let observation = Observation::new(&game_state, &choice, shows_hand);
+                let bytes = observation.encode();
+                num_observations += 1;
+                num_bytes += bytes.len();
+                match Observation::decode(&bytes, camp_types, person_types, event_types) {
+                    Ok(decoded) if decoded == observation => {}
+                    Ok(_) => {
+                        num_mismatches += 1;
+                        println!(
+                            "Game {seed}, turn {}: decoded a different observation",
+                            game_state.turn_number
+                        );
+                    }
+                    Err(error) => {
+                        num_mismatches += 1;
+                        println!(
+                            "Game {seed}, turn {}: failed to decode: {error}",
+                            game_state.turn_number
+                        );
+                    }
+                }
+
+                // a truncated observation must be rejected; a changed byte may still decode to
+                // a valid (different) observation, but mustn't panic
+                let truncated = &bytes[..rng.gen_range(0..bytes.len())];
+                if Observation::decode(truncated, camp_types, person_types, event_types).is_ok() {
+                    num_corrupted_accepted += 1;
+                    println!(
+                        "Game {seed}, turn {}: accepted a truncated observation",
+                        game_state.turn_number
+                    );
+                }
+                let mut changed = bytes.clone();
+                for _ in 0..rng.gen_range(1..=3) {
+                    let index = rng.gen_range(0..changed.len());
+                    changed[index] = rng.gen();
+                }
+                let _ = Observation::decode(&changed, camp_types, person_types, event_types);
+            }
+
+            let next = match &choice {
+                Ok(choice) => {
+                    choice.choose(&mut game_state, rng.gen_range(0..choice.num_options()))
+                }
+                Err(_) => break,
+            };
+            choice = next;
+        }
+    }
+
+    println!(
+        "{num_observations} observations from {num_games} games round-tripped with \
+         {num_mismatches} mismatches ({:.0} bytes on average)",
+        num_bytes as f64 / num_observations.max(1) as f64,
+    );
+    if num_corrupted_accepted > 0 {
+        println!("{num_corrupted_accepted} truncated observations were accepted");
+    }
+    num_mismatches == 0 && num_corrupted_accepted == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::radlands::sandbox::testing::card_set;
+
+    #[test]
+    fn observations_of_random_games_round_trip() {
+        let card_set = card_set();
+        assert!(fuzz_round_trip(
+            10,
+            &card_set.camp_types,
+            &card_set.person_types,
+            &card_set.event_types,
+        ));
+    }
+}
@@ -0,0 +1,97 @@
+//! Reloading the MCTS AI's search state at one of its decisions, to step through the search
+//! (with `--trace-search`) when debugging why it misjudged a position.
+//!
+//! With `--dump-search-tree <DIR>`, the MCTS AI writes a search state file (`<name>.state`)
+//! alongside each dumped tree, if the game is autosaved. The file gives the game up to the
+//! decision (in the autosave format), the player deciding, and the search's ply, followed by
+//! one line for each explored state: the hash that identifies it (see `ObservedState`), its
+//! rollout count, the ply it was last visited in, and each option's rollout count and total
+//! score (2 per win and 1 per tie, for the chooser):
+//!
+//! ```text
+//! game seed=42 ai_seed=7 mulligans=false fidelity=casual p1=mcts:3 p2=human:auto moves=0,2,1
+//! player=1
+//! ply=2
+//! key=00a3f1c29b6e4d10 rollouts=5120 last_visit=2 options=4000:5210,1100:1320,20:11
+//! ```
+//!
+//! Tracing replays the game to the decision, loads the explored states, and runs the given
+//! number of search iterations, printing the PUCT computation (win rate plus exploration bonus)
+//! for every option at each explored state that the selection phase passes through. Each
+//! iteration updates the loaded states as the search would, so later iterations see its
+//! result. The rollouts are played by the same rollout controllers as the player's AI (see
+//! `ControllerSpec::build_mcts`), but unlike the search, the hidden information and the
+//! rollouts are randomized with an RNG seeded with the given seed, so a trace can be
+//! reproduced exactly. The states are
+//! identified by hashes, which are only stable within one build of the program, so a state
+//! file should be traced with the build that wrote it.
+
+use std::fs;
+use std::path::Path;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::autosave::SavedGame;
+use crate::golden::parse_fields;
+use crate::radlands::camps::CampType;
+use crate::radlands::events::EventType;
+use crate::radlands::locations::Player;
+use crate::radlands::people::PersonType;
+
+/// Loads the search state file at the given path, and prints a trace of `num_iterations`
+/// search iterations from it, randomized with the given seed. Returns a message describing the
+/// problem if the file is invalid.
+pub fn run(
+    path: &Path,
+    num_iterations: usize,
+    seed: u64,
+    camp_types: &[CampType],
+    person_types: &[PersonType],
+    event_types: &[EventType],
+) -> Result<(), String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    let mut lines = contents.lines();
+
+    // replay the game up to the decision
+    let game = lines
+        .next()
+        .and_then(|line| line.strip_prefix("game "))
+        .ok_or("The search state file doesn't start with a game line")?;
+    let game: SavedGame = game.parse()?;
+    let (game_state, choice) = game.replay(camp_types, person_types, event_types, |_, _, _| {})?;
+    let choice = choice.map_err(|_| "The game is already over at the decision".to_string())?;
+
+    let [player] = parse_fields(lines.next().unwrap_or_default(), ["player"])?;
+    let player = match player {
+        "1" => Player::Player1,
+        "2" => Player::Player2,
+        _ => return Err(format!("invalid player: {player:?}")),
+    };
+    if choice.chooser(&game_state) != player {
+        return Err(format!(
+            "Player {} isn't the one choosing at the end of the game",
+            player.number()
+        ));
+    }
+
+    // load the explored states into the player's controller, which only searches when traced
+    let spec = match player {
+        Player::Player1 => game.p1,
+        Player::Player2 => game.p2,
+    };
+    let mut controller = spec
+        .build_mcts(player, seed)
+        .ok_or_else(|| format!("Player {} isn't an MCTS AI ({spec})", player.number()))?;
+    controller.load_state_dump(lines)?;
+
+    let game_view = game_state.view_for(player);
+    let mut rng = StdRng::seed_from_u64(seed);
+    for iteration in 1..=num_iterations {
+        let mut trace = String::new();
+        controller.trace_sample(&game_view, &choice, &mut rng, &mut trace);
+        println!("Iteration {iteration}:\n{trace}");
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+//! Quick analysis of the displayed position on demand (the A key): a short MCTS search from the
+//! choosing player's view, whose option ranking is shown in the stats pane. It runs on a worker
+//! thread owned by the UI, separately from the game's controllers, so it doesn't affect the game.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+
+use crate::radlands::choices::Choice;
+use crate::radlands::controllers::mcts::MCTSController;
+use crate::radlands::controllers::random::RandomController;
+use crate::radlands::controllers::{PlayerController, SearchBudget};
+use crate::radlands::GameState;
+
+/// The name of the thread that runs analyses. Stats shown from this thread go to the analysis
+/// in the stats pane, rather than to the stats of the player whose choice is being analyzed.
+pub const ANALYSIS_THREAD_NAME: &str = "analysis worker";
+
+/// How long each analysis searches for.
+const ANALYSIS_TIME: Duration = Duration::from_secs(1);
+
+/// The ID of the analysis being run, which tags the stats it shows.
+static CURRENT_ANALYSIS: AtomicU64 = AtomicU64::new(0);
+
+/// A position to analyze, with an ID to match the results with the request.
+pub(super) struct AnalysisRequest<'ctype> {
+    pub id: u64,
+    pub game_state: GameState<'ctype>,
+    pub choice: Choice<'ctype>,
+}
+
+/// Returns whether the current thread is the analysis worker.
+pub fn is_analysis_thread() -> bool {
+    thread::current().name() == Some(ANALYSIS_THREAD_NAME)
+}
+
+/// Returns the ID of the analysis that the worker is running.
+pub(super) fn current_analysis() -> u64 {
+    CURRENT_ANALYSIS.load(Ordering::Relaxed)
+}
+
+/// Runs the analysis worker until the channel of requests is closed. If several requests are
+/// waiting, only the latest is analyzed, since the others are out of date.
+pub(super) fn worker_main(requests: mpsc::Receiver<AnalysisRequest>) {
+    while let Ok(mut request) = requests.recv() {
+        while let Ok(newer_request) = requests.try_recv() {
+            request = newer_request;
+        }
+        CURRENT_ANALYSIS.store(request.id, Ordering::Relaxed);
+
+        // the search shows its stats as it goes, as for an AI player
+        let chooser = request.choice.chooser(&request.game_state);
+        let mut ai = MCTSController::new(
+            chooser,
+            SearchBudget::Time(ANALYSIS_TIME),
+            |_, rng: &mut StdRng| RandomController::from_rng(rng),
+            rand::random(),
+        );
+        ai.choose_option(&request.game_state.view_for(chooser), &request.choice);
+    }
+}
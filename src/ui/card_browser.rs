@@ -0,0 +1,228 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use itertools::Itertools;
+use tui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Widget},
+};
+
+use crate::locale::{translate, Section};
+use crate::radlands::{styles::StyledName, GameState, PersonOrEventType};
+
+use super::layout::Layout;
+
+/// A pile of cards that can be browsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pile {
+    Discard,
+    Deck,
+}
+
+/// The state of the overlay for browsing the discard pile (and, when all hidden information is
+/// shown, the deck).
+pub struct CardBrowser {
+    /// The pile being browsed.
+    pile: Pile,
+    /// Only cards whose names contain this (case-insensitively) are listed.
+    filter: String,
+    /// The index of the first listed card that is visible.
+    scroll: usize,
+}
+
+impl CardBrowser {
+    /// Creates a browser showing the discard pile.
+    pub fn new() -> Self {
+        CardBrowser {
+            pile: Pile::Discard,
+            filter: String::new(),
+            scroll: 0,
+        }
+    }
+
+    /// Handles a KeyEvent. If `can_see_deck` is false, the deck can't be browsed. Returns true
+    /// if the browser should be closed.
+    pub fn handle_key_event(&mut self, key: KeyEvent, can_see_deck: bool) -> bool {
+        match key.code {
+            KeyCode::Esc => return true,
+            KeyCode::Tab if can_see_deck => {
+                self.pile = match self.pile {
+                    Pile::Discard => Pile::Deck,
+                    Pile::Deck => Pile::Discard,
+                };
+                self.scroll = 0;
+            }
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll += 1,
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll += 10,
+            KeyCode::Home => self.scroll = 0,
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.scroll = 0;
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.scroll = 0;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Returns whether the card matches the filter.
+    fn matches(&self, card: &PersonOrEventType) -> bool {
+        card.display_name()
+            .to_lowercase()
+            .contains(&self.filter.to_lowercase())
+    }
+
+    /// Returns the listed cards of the pile being browsed: each card type once, by name, with
+    /// how many copies are in the pile. The deck is listed the same way as the discard pile, so
+    /// that browsing it doesn't reveal the order the cards will be drawn in.
+    fn listed_cards<'ctype>(
+        &self,
+        game_state: &GameState<'ctype>,
+    ) -> Vec<(PersonOrEventType<'ctype>, usize)> {
+        let cards = match self.pile {
+            Pile::Discard => game_state.discard().as_slice(),
+            Pile::Deck => game_state.deck(),
+        };
+        cards
+            .iter()
+            .copied()
+            .filter(|card| self.matches(card))
+            .counts()
+            .into_iter()
+            .sorted_by_key(|(card, _)| card.display_name())
+            .collect()
+    }
+
+    /// Returns the list items for the listed cards of the pile being browsed.
+    fn items(&self, game_state: &GameState) -> Vec<ListItem<'static>> {
+        self.listed_cards(game_state)
+            .into_iter()
+            .map(|(card, count)| {
+                ListItem::new(Spans(vec![
+                    Span::raw(format!("{count:>3}x  ")),
+                    card.styled_name(),
+                ]))
+            })
+            .collect()
+    }
+}
+
+/// A widget that renders a CardBrowser as an overlay over the given area.
+pub struct CardBrowserWidget<'a, 'g, 'ctype: 'g> {
+    pub browser: &'a mut CardBrowser,
+    pub game_state: &'g GameState<'ctype>,
+    /// Whether the deck can be browsed.
+    pub can_see_deck: bool,
+}
+
+impl Widget for CardBrowserWidget<'_, '_, '_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // use the middle of the area, leaving the rest visible around the edges
+        let [_, middle, _] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(20),
+                Constraint::Percentage(60),
+                Constraint::Percentage(20),
+            ])
+            .split(area);
+        let [_, area, _] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(10),
+                Constraint::Percentage(80),
+                Constraint::Percentage(10),
+            ])
+            .split(middle);
+        Clear.render(area, buf);
+
+        let (pile_name, num_cards) = match self.browser.pile {
+            Pile::Discard => ("Discard pile", self.game_state.discard().len()),
+            Pile::Deck => ("Deck", self.game_state.deck().len()),
+        };
+        let pile_name = translate(Section::Ui, pile_name);
+        let cards = translate(Section::Ui, "cards");
+        let block = Block::default()
+            .title(format!(" {pile_name} ({num_cards} {cards}) "))
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL);
+        let inner_area = block.inner(area);
+        block.render(area, buf);
+
+        let [filter_rect, list_rect, help_rect] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(2),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(inner_area);
+
+        // render the filter box
+        let filter = Paragraph::new(Spans(vec![
+            Span::raw(format!("{} ", translate(Section::Ui, "Filter:"))),
+            Span::styled(
+                self.browser.filter.clone(),
+                Style::default().fg(Color::Yellow),
+            ),
+        ]));
+        filter.render(filter_rect, buf);
+
+        // render the visible part of the list, keeping the scroll position in range
+        let items = self.browser.items(self.game_state);
+        let max_scroll = items.len().saturating_sub(list_rect.height.into());
+        self.browser.scroll = self.browser.scroll.min(max_scroll);
+        let items = items.into_iter().skip(self.browser.scroll).collect_vec();
+        if items.is_empty() {
+            Paragraph::new(translate(Section::Ui, "(no cards)")).render(list_rect, buf);
+        } else {
+            List::new(items).render(list_rect, buf);
+        }
+
+        // render the key hints
+        let help = if self.can_see_deck {
+            "Type to filter, arrows/PgUp/PgDn to scroll, Tab to switch piles, Esc to close"
+        } else {
+            "Type to filter, arrows/PgUp/PgDn to scroll, Esc to close"
+        };
+        let help = translate(Section::Ui, help);
+        Paragraph::new(Span::styled(help, Style::default().fg(Color::DarkGray)))
+            .render(help_rect, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::radlands::sandbox::testing::*;
+
+    #[test]
+    fn the_deck_is_listed_by_name_rather_than_in_draw_order() {
+        let (game_state, _) = position(&[]);
+        let mut browser = CardBrowser::new();
+        browser.pile = Pile::Deck;
+        let listed = browser.listed_cards(&game_state);
+
+        // every card in the deck is counted once, under its name
+        let names = listed
+            .iter()
+            .map(|(card, _)| card.display_name())
+            .collect_vec();
+        assert_eq!(names, names.iter().cloned().sorted().collect_vec());
+        assert!(names.iter().all_unique());
+        let total: usize = listed.iter().map(|(_, count)| count).sum();
+        assert_eq!(total, game_state.deck().len());
+
+        // filtering still applies
+        browser.filter = "looter".into();
+        let listed = browser.listed_cards(&game_state);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].0.display_name(), "Looter");
+    }
+}
@@ -1,3 +1,5 @@
+use std::mem;
+
 use itertools::{zip_eq, Itertools};
 use lazy_static::lazy_static;
 use tui::{
@@ -8,15 +10,19 @@ use tui::{
     widgets::{Block, BorderType, Borders, List, ListItem, Row, Table, Widget},
 };
 
+use unicode_width::UnicodeWidthStr;
+
 use crate::{
+    locale::{translate, Section},
     make_spans,
     radlands::{
-        choices::Choice,
+        choices::{Choice, SelectTarget},
         events::get_event_types,
-        locations::{CardRowIndex, ColumnIndex, Player},
+        locations::{CardRowIndex, ColumnIndex, PersonRowIndex, Player},
         people::get_person_types,
+        player_state::CardColumn,
         styles::*,
-        Action, GameState,
+        GameState,
     },
     ui::layout::Layout,
 };
@@ -25,9 +31,29 @@ pub struct GameStateWidget<'a, 'ctype, 'str> {
     pub block: Block<'str>,
     pub game_state: &'a GameState<'ctype>,
     pub choice: Option<&'a Choice<'ctype>>,
+    /// If set, only what this player can see is shown (i.e. the other player's hand is hidden).
+    pub viewer: Option<Player>,
+    /// The option currently entered in the input box, if any. For a PlayChoice, the column it
+    /// would play into is shown as it would look afterward.
+    pub preview_option: Option<usize>,
 }
 
 impl GameStateWidget<'_, '_, '_> {
+    /// Returns the height needed to render the given game state.
+    pub fn desired_height(game_state: &GameState) -> usize {
+        let max_player_height = [Player::Player1, Player::Player2]
+            .into_iter()
+            .map(|player| {
+                let player_state = game_state.player(player);
+                let hand_len =
+                    player_state.hand.count_unique() + (player_state.has_water_silo as usize);
+                usize::max(hand_len, num_board_rows(game_state, player) + 1) + 5
+            })
+            .max()
+            .unwrap();
+        max_player_height * 2 + 2 // plus the line for the deck and discard pile
+    }
+
     fn render_player(&self, area: Rect, buf: &mut Buffer, player: Player) {
         // get the player's title line
         let n = player.number();
@@ -35,16 +61,20 @@ impl GameStateWidget<'_, '_, '_> {
         let title = if is_cur_player {
             // current player
             make_spans!(
-                format!(" Player {n} ("),
+                format!(" {} {n} (", translate(Section::Ui, "Player")),
                 Span::styled(
-                    format!("{} water", self.game_state.cur_player_water),
+                    format!(
+                        "{} {}",
+                        self.game_state.cur_player_water,
+                        translate(Section::Ui, "water")
+                    ),
                     *WATER
                 ),
                 ") ",
             )
         } else {
             // other player
-            Spans::from(format!(" Player {n} "))
+            Spans::from(format!(" {} {n} ", translate(Section::Ui, "Player")))
         };
 
         // draw the title + border
@@ -69,12 +99,12 @@ impl GameStateWidget<'_, '_, '_> {
         #[rustfmt::skip]
         lazy_static! {
             static ref MAX_PERSON_NAME_LEN: u16 = get_person_types().iter()
-                .map(|person_type| person_type.name.len())
+                .map(|person_type| person_type.display_name().width())
                 .max().unwrap()
                 .try_into().unwrap();
 
             static ref MAX_EVENT_NAME_LEN: u16 = get_event_types().iter()
-                .map(|event_type| event_type.name.len())
+                .map(|event_type| event_type.display_name().width())
                 .max().unwrap()
                 .try_into().unwrap();
 
@@ -99,24 +129,34 @@ impl GameStateWidget<'_, '_, '_> {
 
     fn render_hand(&self, area: Rect, buf: &mut Buffer, player: Player) {
         let player_state = self.game_state.player(player);
-        let mut items = player_state
-            .hand
-            .iter()
-            .map(|(card_type, count)| {
-                make_spans!(
-                    card_type.styled_name(),
-                    if count > 1 { format!(" (x{count})") } else { "".to_string() }
-                )
-            })
-            .collect_vec();
+        let mut items = if self.viewer.map_or(false, |viewer| viewer != player) {
+            // only show the number of cards in the hand
+            let num_cards = player_state.hand.count();
+            vec![Span::styled(
+                format!("<{num_cards} {}>", translate(Section::Ui, "hidden cards")),
+                *EMPTY,
+            )
+            .into()]
+        } else {
+            player_state
+                .hand
+                .iter()
+                .map(|(card_type, count)| {
+                    make_spans!(
+                        card_type.styled_name(),
+                        if count > 1 { format!(" (x{count})") } else { "".to_string() }
+                    )
+                })
+                .collect_vec()
+        };
         if player_state.has_water_silo {
             items.push(Span::styled("Water Silo", *WATER).into());
-        } else if player_state.hand.is_empty() {
-            items.push(Span::styled("<none>", *EMPTY).into());
+        } else if items.is_empty() {
+            items.push(Span::styled(translate(Section::Ui, "<none>"), *EMPTY).into());
         }
 
         List::new(items.into_iter().map(ListItem::new).collect_vec())
-            .block(Block::default().title("Hand"))
+            .block(Block::default().title(translate(Section::Ui, "Hand")))
             .render(area, buf);
     }
 
@@ -127,79 +167,91 @@ impl GameStateWidget<'_, '_, '_> {
                 format!("[{}] ", i + 1),
                 match event {
                     Some(event) => event.styled_name(),
-                    None => Span::styled("<none>", *EMPTY),
+                    None => Span::styled(translate(Section::Ui, "<none>"), *EMPTY),
                 }
             )
         });
 
         List::new(items.into_iter().map(ListItem::new).collect_vec())
-            .block(Block::default().title("Events"))
+            .block(Block::default().title(translate(Section::Ui, "Events")))
             .render(area, buf);
     }
 
     fn render_board(&self, area: Rect, buf: &mut Buffer, player: Player) {
-        // get the columns
-        let table_columns = self.game_state.player(player).columns.iter().map(|col| {
-            [
-                col.person_slots[1].styled_name(),
-                col.person_slots[0].styled_name(),
-                col.camp.styled_name(),
-            ]
-            .into_iter()
-            .map(Spans::from)
-            .collect_vec()
-        });
-        let mut table_columns = table_columns.collect_vec();
+        // get the columns, and which rows have something in them (the camps always do)
+        let num_rows = num_board_rows(self.game_state, player);
+        let columns = &self.game_state.player(player).columns;
+        let cell_index = |col: ColumnIndex, row: CardRowIndex| {
+            num_rows - 1 - columns[col.as_usize()].physical_row(row)
+        };
+        let mut table_columns = columns
+            .iter()
+            .map(|col| column_cells(col, num_rows))
+            .collect_vec();
+        if self.game_state.is_choosing_camps() {
+            // the camps on the board are only placeholders until both players have chosen
+            for (col_index, column) in table_columns.iter_mut().enumerate() {
+                column[cell_index(col_index.into(), CardRowIndex::camp())] =
+                    Span::styled(translate(Section::Ui, "<choosing>"), *EMPTY).into();
+            }
+        }
+        let mut row_has_content = vec![false; num_rows];
+        for (col_index, col) in self.game_state.player(player).enumerate_columns() {
+            row_has_content[cell_index(col_index, CardRowIndex::camp())] = true;
+            for (row, _) in col.enumerate_people() {
+                row_has_content[cell_index(col_index, row.into())] = true;
+            }
+        }
 
         let min_column_widths = table_columns
             .iter()
             .map(|column| column.iter().map(|s| s.width()).max().unwrap() + 4)
             .collect_vec();
 
+        // if a play location is selected, show its column as it would be after playing there
+        let mut preview_loc = None;
+        if let Some(Choice::PlayLoc(choice)) = self.choice {
+            let selected_loc = self.preview_option.and_then(|i| choice.locations().get(i));
+            if let (true, Some(&loc)) = (player == choice.chooser(), selected_loc) {
+                let mut column = self.game_state.player(player).column(loc.column()).clone();
+                column.place_person(choice.person().clone(), loc.row());
+                for (row, _) in column.enumerate_people() {
+                    row_has_content[cell_index(loc.column(), row.into())] = true;
+                }
+
+                let cells = &mut table_columns[loc.column().as_usize()];
+                *cells = column_cells(&column, num_rows);
+                let ghost_cell = &mut cells[cell_index(loc.column(), loc.row().into())];
+                for span in &mut ghost_cell.0 {
+                    span.style = span
+                        .style
+                        .add_modifier(Modifier::ITALIC | Modifier::REVERSED);
+                }
+                preview_loc = Some(loc);
+            }
+        }
+
         // tag board items with associated option numbers based on the type of Choice
         let mut tag_location = |row: CardRowIndex, col: ColumnIndex, i: usize| {
             let tag = Span::from(format!("({}) ", i + 1));
-            let cell = &mut table_columns[col.as_usize()][2 - row.as_usize()];
+            let cell = &mut table_columns[col.as_usize()][cell_index(col, row)];
             cell.0.insert(0, tag);
+            row_has_content[cell_index(col, row)] = true;
         };
         match self.choice {
-            Some(Choice::Action(choice)) if player == self.game_state.cur_player => {
-                for (i, action) in choice.actions().iter().enumerate().rev() {
-                    match action {
-                        Action::UsePersonAbility(_ability, loc) => {
-                            tag_location(loc.row().into(), loc.column(), i);
-                        }
-                        Action::UseCampAbility(_ability, col_index) => {
-                            tag_location(CardRowIndex::camp(), *col_index, i);
-                        }
-                        _ => {}
-                    }
-                }
-            }
             Some(Choice::PlayLoc(choice)) if player == choice.chooser() => {
                 for (i, loc) in choice.locations().iter().enumerate().rev() {
-                    // TODO: shift and make gaps
-                    tag_location(loc.row().into(), loc.column(), i);
-                }
-            }
-            Some(Choice::Damage(choice)) => {
-                for (i, loc) in choice.locations().iter().enumerate().rev() {
-                    if player == loc.player() {
-                        tag_location(loc.row(), loc.column(), i);
+                    match preview_loc {
+                        // the previewed column only tags the person being played
+                        Some(preview_loc) if preview_loc.column() == loc.column() => {
+                            if preview_loc == *loc {
+                                tag_location(loc.row().into(), loc.column(), i);
+                            }
+                        }
+                        _ => tag_location(loc.row().into(), loc.column(), i),
                     }
                 }
             }
-            Some(Choice::Restore(choice)) if player == choice.chooser() => {
-                for (i, loc) in choice.locations().iter().enumerate().rev() {
-                    tag_location(loc.row(), loc.column(), i);
-                }
-            }
-            Some(Choice::RescuePerson(choice)) if player == choice.chooser() => {
-                let locations = self.game_state.player(choice.chooser()).person_locs();
-                for (i, loc) in locations.collect_vec().into_iter().enumerate().rev() {
-                    tag_location(loc.row().into(), loc.column(), i);
-                }
-            }
             Some(Choice::DamageColumn(choice)) if player == choice.chooser().other() => {
                 for (i, col) in choice.columns().iter().enumerate().rev() {
                     if !choice.people_only() {
@@ -215,7 +267,32 @@ impl GameStateWidget<'_, '_, '_> {
                     }
                 }
             }
-            _ => {}
+            Some(choice) => {
+                // tag the cards on this player's board that options target
+                for i in (0..choice.num_options()).rev() {
+                    match choice.option_target(i, self.game_state) {
+                        Some(target) if target.player() == player => {
+                            tag_location(target.row(), target.column(), i);
+                        }
+                        _ => {}
+                    }
+                }
+
+                // mark the cards that are already selected
+                if let Choice::MultiSelect(choice) = choice {
+                    for target in choice.selected() {
+                        match target {
+                            SelectTarget::Card(loc) if loc.player() == player => {
+                                let cell = &mut table_columns[loc.column().as_usize()]
+                                    [cell_index(loc.column(), loc.row())];
+                                cell.0.insert(0, Span::from("(selected) "));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            None => {}
         }
 
         // center the cells in their columns
@@ -236,21 +313,28 @@ impl GameStateWidget<'_, '_, '_> {
             }
         }
 
-        // transpose it into a list of rows
-        let mut table_columns = table_columns
-            .into_iter()
-            .map(|col| col.into_iter())
+        // transpose it into a list of rows, leaving out the rows of empty slots if the board is
+        // too tall for its area (since none of them can be acted on)
+        let is_compact = num_rows + 1 > area.height as usize;
+        let table_rows = (0..num_rows)
+            .filter(|&row| !is_compact || row_has_content[row])
+            .map(|row| {
+                Row::new(
+                    table_columns
+                        .iter_mut()
+                        .map(|col| mem::take(&mut col[row]))
+                        .collect_vec(),
+                )
+            })
             .collect_vec();
-        let table_rows =
-            (0..3).map(|_| Row::new(table_columns.iter_mut().map(|col| col.next().unwrap())));
 
         // build and render the final table
         Table::new(table_rows)
-            .block(Block::default().title("Board"))
+            .block(Block::default().title(translate(Section::Ui, "Board")))
             .widths(
                 &column_widths
                     .into_iter()
-                    .map(|w| Constraint::Length(w.try_into().unwrap()))
+                    .map(|w| Constraint::Length(u16::try_from(w).unwrap_or(u16::MAX)))
                     .collect_vec(),
             )
             .column_spacing(2)
@@ -258,6 +342,66 @@ impl GameStateWidget<'_, '_, '_> {
     }
 }
 
+/// Returns the number of rows in a player's board: the most person slots in any of their
+/// columns, plus the row of camps.
+fn num_board_rows(game_state: &GameState, player: Player) -> usize {
+    let columns = &game_state.player(player).columns;
+    columns
+        .iter()
+        .map(|col| col.person_slots.len())
+        .max()
+        .unwrap()
+        + 1
+}
+
+/// Returns the table cells of a column from the top down (the front card first, and usually the
+/// camp last), padded at the top to `num_rows` cells.
+fn column_cells<'ctype>(column: &CardColumn<'ctype>, num_rows: usize) -> Vec<Spans<'ctype>> {
+    let mut cells = vec![Spans::default(); num_rows];
+    cells[num_rows - 1 - column.physical_row(CardRowIndex::camp())] =
+        column.camp.styled_name().into();
+    for (row, slot) in column.person_slots.iter().enumerate() {
+        let physical_row = column.physical_row(PersonRowIndex::from(row).into());
+        cells[num_rows - 1 - physical_row] = slot.styled_name().into();
+    }
+    cells
+}
+
+impl GameStateWidget<'_, '_, '_> {
+    /// Renders the number of cards in the deck, and the discard pile's most recent cards.
+    fn render_piles(&self, area: Rect, buf: &mut Buffer) {
+        let deck_len = self.game_state.deck().len();
+        let discard = self.game_state.discard();
+        let cards = translate(Section::Ui, "cards");
+        let mut spans = vec![Span::raw(format!(
+            " {}: {deck_len} {cards}   {} ({} {cards}): ",
+            translate(Section::Ui, "Deck"),
+            translate(Section::Ui, "Discard pile"),
+            discard.len(),
+        ))];
+
+        if discard.is_empty() {
+            spans.push(Span::styled(translate(Section::Ui, "<none>"), *EMPTY));
+        } else {
+            // list the most recent discards first, as many as fit while leaving room for ", ..."
+            let mut width = spans[0].width();
+            for (i, card) in discard.iter_recent().enumerate() {
+                let name = card.styled_name();
+                let separator = if i == 0 { "" } else { ", " };
+                if width + separator.len() + name.width() + 5 > area.width.into() {
+                    spans.push(Span::raw(format!("{separator}...")));
+                    break;
+                }
+                width += separator.len() + name.width();
+                spans.push(Span::raw(separator));
+                spans.push(name);
+            }
+        }
+
+        buf.set_spans(area.x, area.y, &Spans(spans), area.width);
+    }
+}
+
 impl Widget for GameStateWidget<'_, '_, '_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
         // first, render the block
@@ -269,10 +413,14 @@ impl Widget for GameStateWidget<'_, '_, '_> {
         }
 
         // render the game state
+        let [players_rect, piles_rect] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(inner_area);
         let player_rects = Layout::default()
             .direction(Direction::Vertical)
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(inner_area);
+            .split(players_rect);
         for (i, player_rect) in player_rects.into_iter().enumerate() {
             let player = match i {
                 0 => Player::Player1,
@@ -283,6 +431,6 @@ impl Widget for GameStateWidget<'_, '_, '_> {
             self.render_player(player_rect, buf, player);
         }
 
-        // TODO: show the number of cards in the deck/discard?
+        self.render_piles(piles_rect, buf);
     }
 }
@@ -1,56 +1,145 @@
-use std::{
-    sync::{mpsc, Arc, Mutex},
-    time::Duration,
-};
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::Scope;
 
-use super::{HistoryEntry, RedrawEvent};
+use super::{show_caption, HistoryEntry, LatestUpdate, RedrawEvent};
 use crate::{
-    do_one_choice,
+    autosave::{Autosave, SavedGame},
+    decision_times::GameTimes,
+    demo::Demo,
+    do_one_choice_or_end_game, event_stream,
     radlands::{
         choices::Choice,
-        controllers::{human::HumanController, mcts::MCTSController, random::RandomController},
+        controllers::{scripted::ScriptedController, EndGameRequest, PlayerController},
         locations::Player,
-        GameResult, GameState,
+        EarlyEnding, GameResult, GameState,
     },
 };
 
-/// The main function that runs on the game thread.
-pub(super) fn game_thread_main(
-    initial_state: GameState<'static>,
-    initial_choice: Result<Choice<'static>, GameResult>,
+/// The main function that runs on the game thread. Guarded AI controllers run on threads in the
+/// given scope. If `autosave` is true, the game is autosaved after every choice. If `demo` is
+/// given, both players are played from its script instead of by the saved game's controllers.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn game_thread_main<'scope, 'ctype: 'scope>(
+    scope: &'scope Scope<'scope, '_>,
+    initial_state: GameState<'ctype>,
+    initial_choice: Result<Choice<'ctype>, GameResult>,
+    saved_game: SavedGame,
+    autosave: bool,
+    demo: Option<Demo>,
     event_tx: mpsc::Sender<RedrawEvent>,
-    game_history: Arc<Mutex<Vec<HistoryEntry<'static>>>>,
+    game_history: Arc<Mutex<Vec<HistoryEntry<'ctype>>>>,
+    latest_update: Arc<LatestUpdate<'ctype>>,
 ) {
     let mut game_state = initial_state;
     let mut cur_choice = initial_choice;
 
-    let p1 = &mut MCTSController::new(Player::Player1, Duration::from_secs_f64(3.0), |_| {
-        RandomController
-    });
-    let p2 = &mut HumanController;
+    let (mut p1, mut p2): (Box<dyn PlayerController>, Box<dyn PlayerController>) = match demo {
+        Some(demo) => {
+            let script = Arc::new(Mutex::new(VecDeque::from(demo.steps)));
+            (
+                Box::new(ScriptedController::new(script.clone(), demo.pace)),
+                Box::new(ScriptedController::new(script, demo.pace)),
+            )
+        }
+        None => (
+            saved_game.p1.build_guarded(
+                Player::Player1,
+                saved_game.controller_seed(Player::Player1),
+                scope,
+            ),
+            saved_game.p2.build_guarded(
+                Player::Player2,
+                saved_game.controller_seed(Player::Player2),
+                scope,
+            ),
+        ),
+    };
+    let mut autosave = autosave.then(|| Autosave::start(saved_game));
+    let mut times = GameTimes::default();
+    let mut early_ending = None;
+    event_stream::publish(&game_state, &cur_choice);
 
     while let Ok(choice) = &cur_choice {
         // save the game state and choice for the history entry
         let history_game_state = game_state.clone();
         let history_choice = choice.clone();
 
-        // do one choice, updating the GameState and Choice
-        let (chosen_option, new_choice) = do_one_choice(&mut game_state, choice, p1, p2);
+        // do one choice, updating the GameState and Choice, unless the chooser asks to end the
+        // game instead
+        let decision = do_one_choice_or_end_game(
+            &mut game_state,
+            choice,
+            p1.as_mut(),
+            p2.as_mut(),
+            Some(&mut times),
+        );
+        let (chosen_option, new_choice) = match decision {
+            Ok(decision) => decision,
+            Err(request) => {
+                let chooser = choice.chooser(&game_state);
+                let opponent = match chooser {
+                    Player::Player1 => p2.as_mut(),
+                    Player::Player2 => p1.as_mut(),
+                };
+                let ending = match request {
+                    EndGameRequest::Resign => EarlyEnding::Resigned(chooser),
+                    EndGameRequest::OfferTie => {
+                        show_caption(format!("Player {} offers a tie", chooser.number()));
+                        let opponent_view = game_state.view_for(chooser.other());
+                        if !opponent.accepts_tie(&opponent_view, choice) {
+                            // keep playing from the same choice
+                            show_caption(format!(
+                                "Player {} declines the tie",
+                                chooser.other().number()
+                            ));
+                            continue;
+                        }
+                        EarlyEnding::TieAgreed {
+                            offered_by: chooser,
+                        }
+                    }
+                };
+                show_caption(ending.describe());
+                early_ending = Some(ending);
+                cur_choice = Err(ending.result());
+                event_stream::publish(&game_state, &cur_choice);
+                *latest_update.lock().unwrap() = Some((game_state.clone(), cur_choice.clone()));
+                event_tx
+                    .send(RedrawEvent::GameUpdate)
+                    .expect("Failed to send GameUpdate event");
+                break;
+            }
+        };
         cur_choice = new_choice;
+        if let Some(autosave) = &mut autosave {
+            autosave.record_move(chosen_option);
+        }
 
         // add a history entry
         game_history.lock().unwrap().push(HistoryEntry {
             game_state: history_game_state,
             choice: history_choice,
             chosen_option,
+            notes: Vec::new(),
         });
 
-        // update the UI's state and choice
+        // update the UI's state and choice, and any external UIs
+        event_stream::publish(&game_state, &cur_choice);
+        *latest_update.lock().unwrap() = Some((game_state.clone(), cur_choice.clone()));
         event_tx
-            .send(RedrawEvent::GameUpdate(Box::new((
-                game_state.clone(),
-                cur_choice.clone(),
-            ))))
+            .send(RedrawEvent::GameUpdate)
             .expect("Failed to send GameUpdate event");
     }
+
+    if let Some(autosave) = autosave {
+        autosave.finish();
+    }
+
+    // summarize the controllers' decision times in the log
+    show_caption(format!("Player 1's decisions: {}", times.p1));
+    show_caption(format!("Player 2's decisions: {}", times.p2));
+    event_tx
+        .send(RedrawEvent::GameOver(times, early_ending))
+        .expect("Failed to send GameOver event");
 }
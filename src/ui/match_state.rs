@@ -0,0 +1,314 @@
+use std::fmt::Write;
+
+use crate::autosave::SavedGame;
+use crate::decision_times::{DecisionTimes, GameTimes};
+use crate::locale::{translate, Section};
+use crate::radlands::{
+    camps::CampType, choices::Choice, events::EventType, locations::Player, people::PersonType,
+    EarlyEnding, GameResult, GameState,
+};
+
+/// Returns the player who won a game with the given result, if either did.
+fn game_winner(result: GameResult) -> Option<Player> {
+    match result {
+        GameResult::P1Wins => Some(Player::Player1),
+        GameResult::P2Wins => Some(Player::Player2),
+        GameResult::Tie => None,
+    }
+}
+
+/// A game of a match that has ended.
+#[derive(Debug, Clone, Copy)]
+struct MatchGame {
+    result: GameResult,
+    /// How the players ended the game, if they ended it early.
+    early_ending: Option<EarlyEnding>,
+    first_player: Player,
+    num_choices: usize,
+    times: GameTimes,
+}
+
+/// A best-of-N match between the same two controllers, played as a series of new deals. The
+/// first player alternates from game to game, and a tied game doesn't count toward either
+/// player, so the match lasts until one player has won a majority of the N games.
+pub struct MatchState<'ctype> {
+    best_of: u32,
+
+    /// The games that have ended, in order.
+    games: Vec<MatchGame>,
+
+    /// The number of games that have been started (including one in progress).
+    num_started: usize,
+
+    /// The first player of the latest game.
+    cur_first_player: Player,
+
+    camp_types: &'ctype [CampType],
+    person_types: &'ctype [PersonType],
+    event_types: &'ctype [EventType],
+}
+
+impl<'ctype> MatchState<'ctype> {
+    /// Starts a best-of-`best_of` match, whose first game (already dealt) is started by
+    /// `first_player`. Later games are dealt from the given card types.
+    pub fn new(
+        best_of: u32,
+        first_player: Player,
+        camp_types: &'ctype [CampType],
+        person_types: &'ctype [PersonType],
+        event_types: &'ctype [EventType],
+    ) -> Self {
+        MatchState {
+            best_of,
+            games: Vec::new(),
+            num_started: 1,
+            cur_first_player: first_player,
+            camp_types,
+            person_types,
+            event_types,
+        }
+    }
+
+    /// Records the result of the latest game (and how the players ended it, if they ended it
+    /// early), with the number of choices it took and the controllers' decision times.
+    pub fn record_game(
+        &mut self,
+        result: GameResult,
+        early_ending: Option<EarlyEnding>,
+        num_choices: usize,
+        times: GameTimes,
+    ) {
+        self.games.push(MatchGame {
+            result,
+            early_ending,
+            first_player: self.cur_first_player,
+            num_choices,
+            times,
+        });
+    }
+
+    /// Returns the number of games the given player has won.
+    pub fn wins(&self, player: Player) -> usize {
+        self.games
+            .iter()
+            .filter(|game| game_winner(game.result) == Some(player))
+            .count()
+    }
+
+    /// Returns the player who has won the match, if either has.
+    pub fn winner(&self) -> Option<Player> {
+        [Player::Player1, Player::Player2]
+            .into_iter()
+            .find(|&player| self.wins(player) > self.best_of as usize / 2)
+    }
+
+    /// Returns whether the latest game has ended and the match hasn't, so the next game can be
+    /// started.
+    pub fn is_between_games(&self) -> bool {
+        self.games.len() == self.num_started && self.winner().is_none()
+    }
+
+    /// Deals the next game of the match, with the same rules and controllers as the given
+    /// game, and the other player going first. Returns the new game, with its initial state
+    /// and choice.
+    pub fn deal_next_game(
+        &mut self,
+        last_game: &SavedGame,
+    ) -> (SavedGame, GameState<'ctype>, Choice<'ctype>) {
+        let first_player = self.cur_first_player.other();
+        self.cur_first_player = first_player;
+        self.num_started += 1;
+
+        // the first player is picked by the game's seed, so deal until the right player is
+        // picked (which keeps each game replayable from its seed, e.g. by the autosave)
+        loop {
+            let saved_game = SavedGame::new_game(last_game.rules, last_game.p1, last_game.p2);
+            let (game_state, choice) = GameState::new_seeded(
+                self.camp_types,
+                self.person_types,
+                self.event_types,
+                saved_game.rules,
+                saved_game.seed,
+            );
+            if game_state.cur_player == first_player {
+                return (saved_game, game_state, choice);
+            }
+        }
+    }
+
+    /// Returns the score line shown above the game, e.g.
+    /// "Match (best of 3), game 2: Player 1 1 – 0 Player 2".
+    pub fn header(&self) -> String {
+        format!(
+            "{} ({} {}), {} {}: {} 1 {} – {} {} 2",
+            translate(Section::Ui, "Match"),
+            translate(Section::Ui, "best of"),
+            self.best_of,
+            translate(Section::Ui, "game"),
+            self.num_started,
+            translate(Section::Ui, "Player"),
+            self.wins(Player::Player1),
+            self.wins(Player::Player2),
+            translate(Section::Ui, "Player"),
+        )
+    }
+
+    /// Returns the match statistics (the result, each player's wins and decision times, how
+    /// many games were ended early, and how long the games were), as lines of text.
+    pub fn summary(&self) -> Vec<String> {
+        let ui = |text: &'static str| translate(Section::Ui, text);
+        let mut lines = Vec::new();
+
+        let score = format!(
+            "{} – {}",
+            self.wins(Player::Player1),
+            self.wins(Player::Player2)
+        );
+        lines.push(match self.winner() {
+            Some(winner) => format!(
+                "{} {} {}, {score}",
+                ui("Player"),
+                winner.number(),
+                ui("wins the match"),
+            ),
+            None => format!("{} {score}", ui("The match was left unfinished at")),
+        });
+
+        for player in [Player::Player1, Player::Player2] {
+            let wins_going_first = self
+                .games
+                .iter()
+                .filter(|game| {
+                    game_winner(game.result) == Some(player) && game.first_player == player
+                })
+                .count();
+            let mut times = DecisionTimes::default();
+            for game in &self.games {
+                times.merge(game.times.player(player));
+            }
+            let wins = self.wins(player);
+            lines.push(format!(
+                "{} {}: {wins} {} ({wins_going_first} {}); {times}",
+                ui("Player"),
+                player.number(),
+                ui(if wins == 1 { "win" } else { "wins" }),
+                ui("going first"),
+            ));
+        }
+
+        let num_ties = self
+            .games
+            .iter()
+            .filter(|game| game.result == GameResult::Tie)
+            .count();
+        let num_resigned = self
+            .games
+            .iter()
+            .filter(|game| matches!(game.early_ending, Some(EarlyEnding::Resigned(_))))
+            .count();
+        let num_agreed_ties = self
+            .games
+            .iter()
+            .filter(|game| matches!(game.early_ending, Some(EarlyEnding::TieAgreed { .. })))
+            .count();
+        let total_choices: usize = self.games.iter().map(|game| game.num_choices).sum();
+        let num_games = self.games.len();
+        let mut games_line = format!(
+            "{num_games} {}",
+            ui(if num_games == 1 { "game" } else { "games" })
+        );
+        let mut notes = Vec::new();
+        if num_ties > 0 {
+            notes.push(format!("{num_ties} {}", ui("tied")));
+        }
+        if num_agreed_ties > 0 {
+            notes.push(format!("{num_agreed_ties} {}", ui("by agreement")));
+        }
+        if num_resigned > 0 {
+            notes.push(format!("{num_resigned} {}", ui("resigned")));
+        }
+        if !notes.is_empty() {
+            write!(games_line, " ({})", notes.join(", ")).unwrap();
+        }
+        if let Some(mean_choices) = total_choices.checked_div(num_games) {
+            let average = ui("choices per game on average");
+            write!(games_line, ", {mean_choices} {average}").unwrap();
+        }
+        lines.push(games_line);
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::radlands::controllers::ControllerSpec;
+    use crate::radlands::rules::GameRules;
+    use crate::radlands::sandbox::testing::card_set;
+
+    /// Returns a new best-of-3 match, whose first game Player 1 starts.
+    fn best_of_3() -> MatchState<'static> {
+        let card_set = card_set();
+        MatchState::new(
+            3,
+            Player::Player1,
+            &card_set.camp_types,
+            &card_set.person_types,
+            &card_set.event_types,
+        )
+    }
+
+    #[test]
+    fn a_match_ends_once_a_player_wins_a_majority_and_ties_dont_count() {
+        let mut match_state = best_of_3();
+        let game = SavedGame::new_game(
+            GameRules::casual(false),
+            ControllerSpec::Random,
+            ControllerSpec::Random,
+        );
+        assert!(!match_state.is_between_games());
+
+        let results = [GameResult::P1Wins, GameResult::Tie, GameResult::P1Wins];
+        for (i, result) in results.into_iter().enumerate() {
+            if i > 0 {
+                // the first player alternates
+                let (_, game_state, _) = match_state.deal_next_game(&game);
+                let expected = if i % 2 == 0 { Player::Player1 } else { Player::Player2 };
+                assert_eq!(game_state.cur_player, expected);
+                assert!(!match_state.is_between_games());
+            }
+            match_state.record_game(result, None, 10 * (i + 1), GameTimes::default());
+            assert_eq!(match_state.winner().is_some(), i == 2);
+            assert_eq!(match_state.is_between_games(), i < 2);
+        }
+        assert_eq!(match_state.winner(), Some(Player::Player1));
+        assert_eq!(match_state.wins(Player::Player1), 2);
+        assert_eq!(match_state.wins(Player::Player2), 0);
+        assert_eq!(
+            match_state.header(),
+            "Match (best of 3), game 3: Player 1 2 – 0 Player 2"
+        );
+    }
+
+    #[test]
+    fn the_summary_counts_wins_and_how_games_ended() {
+        let mut match_state = best_of_3();
+        let resigned = Some(EarlyEnding::Resigned(Player::Player2));
+        match_state.record_game(GameResult::P1Wins, resigned, 30, GameTimes::default());
+        let agreed = Some(EarlyEnding::TieAgreed {
+            offered_by: Player::Player1,
+        });
+        match_state.record_game(GameResult::Tie, agreed, 10, GameTimes::default());
+
+        assert_eq!(
+            match_state.summary(),
+            [
+                "The match was left unfinished at 1 – 0",
+                "Player 1: 1 win (1 going first); no decisions",
+                "Player 2: 0 wins (0 going first); no decisions",
+                "2 games (1 tied, 1 by agreement, 1 resigned), 20 choices per game on average",
+            ]
+        );
+    }
+}
@@ -1,16 +1,21 @@
+mod analysis;
+mod card_browser;
 mod game_state;
 mod game_thread;
 mod layout;
+mod match_state;
+mod unseen_cards;
 
 use std::{
     collections::VecDeque,
-    io, mem, panic,
+    fs, io, mem, panic,
+    path::PathBuf,
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         mpsc, Arc, Mutex,
     },
-    thread,
-    time::Instant,
+    thread::{self, Scope, ScopedJoinHandle},
+    time::{Duration, Instant},
 };
 
 use crossterm::{
@@ -27,37 +32,66 @@ use tui::{
     layout::{Alignment, Constraint, Corner, Direction, Rect},
     style::{Color, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Widget},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Widget, Wrap},
     Frame, Terminal,
 };
 use unicode_width::UnicodeWidthStr;
 
+use crate::autosave::{self, Note, SavedGame};
+use crate::decision_times::GameTimes;
+use crate::demo::Demo;
+use crate::game_trace;
+use crate::locale::{translate, Section};
+use crate::metrics;
 use crate::radlands::{
-    camps::{get_camp_types, CampType},
-    choices::Choice,
-    controllers::ControllerStats,
-    events::{get_event_types, EventType},
+    card_set::CardSet,
+    choices::{Choice, OptionGroup},
+    controllers::{self, combinators::TIME_LIMIT_THREAD_NAME, ControllerSpec, ControllerStats},
     locations::Player,
-    people::{get_person_types, PersonType},
-    GameResult, GameState,
+    sandbox::{self, Sandbox},
+    turn_status::TurnStatus,
+    EarlyEnding, GameResult, GameState,
 };
 
-use self::{game_state::GameStateWidget, layout::Layout};
+use self::{
+    analysis::{AnalysisRequest, ANALYSIS_THREAD_NAME},
+    card_browser::{CardBrowser, CardBrowserWidget},
+    game_state::GameStateWidget,
+    layout::Layout,
+    match_state::MatchState,
+    unseen_cards::UnseenCardsWidget,
+};
+
+pub use self::analysis::is_analysis_thread;
+
+/// How often the input event thread checks whether the UI has exited.
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The smallest terminal size (in columns and rows) that the panes are laid out in. Below it,
+/// only a message asking for a bigger terminal is shown.
+const MIN_TERMINAL_SIZE: (u16, u16) = (80, 24);
+
+/// Returns the tallest that the options pane can be in a terminal of the given height: half of
+/// it, so that a terminal that shrinks still has room for the game state.
+fn max_options_height(terminal_height: u16) -> u16 {
+    terminal_height / 2
+}
 
-/// Spawns a new thread along with a monitor thread that will send a RedrawEvent::Abort
-/// if the main spawned thread panics.
-fn spawn_monitored_thread<T: Send + 'static>(
+/// Spawns a new thread in the given scope along with a monitor thread that will send a
+/// RedrawEvent::Abort if the main spawned thread panics.
+fn spawn_monitored_thread<'scope, T: Send + 'scope>(
+    scope: &'scope Scope<'scope, '_>,
     name: &str,
     event_tx: mpsc::Sender<RedrawEvent>,
-    f: impl FnOnce() -> T + Send + 'static,
-) -> io::Result<thread::JoinHandle<T>> {
+    f: impl FnOnce() -> T + Send + 'scope,
+) -> io::Result<ScopedJoinHandle<'scope, T>> {
     // spawn the main work thread
     let builder = thread::Builder::new().name(name.into());
-    let join_handle = builder.spawn(f)?;
+    let join_handle = builder.spawn_scoped(scope, f)?;
 
     // spawn the monitoring thread
     let builder = thread::Builder::new().name(format!("panic monitor: {name}"));
-    let join_handle2 = builder.spawn(move || {
+    let join_handle2 = builder.spawn_scoped(scope, move || {
         match join_handle.join() {
             Ok(value) => value, // forward the value
             Err(_) => {
@@ -87,17 +121,59 @@ pub fn get_user_input() -> String {
 
 static STATS_TX: Mutex<Option<mpsc::Sender<RedrawEvent>>> = Mutex::new(None);
 
-// Sets the contents of the stats display for the given player.
+// Sets the contents of the stats display for the given player (or of the analysis, if called
+// from the analysis worker). If called while a game is being traced, the stats go to the trace.
+// Does nothing if the UI isn't running (e.g. when AIs play each other in the arena).
 pub fn set_controller_stats(stats: Option<Box<dyn ControllerStats + Send>>, player: Player) {
-    STATS_TX
-        .lock()
-        .unwrap()
-        .as_ref()
-        .expect("STATS_TX is not initialized")
-        .send(RedrawEvent::StatsUpdate(stats, player))
-        .expect("Failed to send StatsUpdate");
+    if game_trace::is_trace_thread() {
+        game_trace::record_stats(stats);
+    } else if let Some(stats_tx) = STATS_TX.lock().unwrap().as_ref() {
+        let event = if analysis::is_analysis_thread() {
+            RedrawEvent::AnalysisUpdate(analysis::current_analysis(), stats)
+        } else {
+            RedrawEvent::StatsUpdate(stats, player)
+        };
+        stats_tx.send(event).expect("Failed to send StatsUpdate");
+    }
+}
+
+/// How often AI controllers update their live stats while thinking.
+static STATS_INTERVAL: Mutex<Duration> = Mutex::new(Duration::from_millis(100));
+
+/// The shortest time between two frames of the UI, which caps its frame rate.
+static MIN_FRAME_INTERVAL: Mutex<Duration> = Mutex::new(Duration::from_millis(1000 / 30));
+
+/// Sets how often AI controllers update their live stats while thinking.
+pub fn set_stats_interval(interval: Duration) {
+    *STATS_INTERVAL.lock().unwrap() = interval;
 }
 
+/// Returns how often AI controllers should update their live stats while thinking.
+pub fn stats_interval() -> Duration {
+    *STATS_INTERVAL.lock().unwrap()
+}
+
+/// Caps the UI's frame rate at the given number of frames per second. Updates that arrive
+/// faster than that (such as live stats from several threads) are drawn together in one frame.
+pub fn set_max_fps(max_fps: u32) {
+    *MIN_FRAME_INTERVAL.lock().unwrap() = Duration::from_secs(1) / max_fps;
+}
+
+/// Shows a caption in the log, after the moves made so far (e.g. to narrate a demo). While the
+/// UI is running, this is also where controllers' captions are shown.
+/// Does nothing if the UI isn't running.
+fn show_caption(caption: String) {
+    if let Some(stats_tx) = STATS_TX.lock().unwrap().as_ref() {
+        stats_tx
+            .send(RedrawEvent::Caption(caption))
+            .expect("Failed to send Caption");
+    }
+}
+
+/// Messages from panics in guarded AI controllers, which don't end the game, so they are printed
+/// after the UI exits rather than immediately.
+static CONTROLLER_PANICS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
 /// How many times the debug key has been pressed.
 static DEBUG_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
@@ -110,6 +186,8 @@ struct HistoryEntry<'ctype> {
     game_state: GameState<'ctype>,
     choice: Choice<'ctype>,
     chosen_option: usize,
+    /// The notes the user has attached to this entry, shown below it in the log
+    notes: Vec<String>,
 }
 
 impl<'ctype> HistoryEntry<'ctype> {
@@ -117,8 +195,88 @@ impl<'ctype> HistoryEntry<'ctype> {
         // TODO: this function shouldn't require &mut self
         // The issue is with GameView - make GameViewMut?
         self.choice
-            .format_option(self.chosen_option, &self.game_state)
+            .format_chosen_option(self.chosen_option, &self.game_state)
     }
+
+    /// Returns whether the choice was forced (i.e. there was only one option).
+    fn is_forced(&self) -> bool {
+        self.choice.num_options() == 1
+    }
+
+    /// Returns whether the choice was part of resolving an earlier choice (e.g. targeting for a
+    /// person's enter-play effect).
+    fn is_follow_up(&self) -> bool {
+        self.choice.context().is_some()
+    }
+}
+
+/// Formats the game history for the log pane, most recent entry first, with each caption (and
+/// the number of history entries before it) on its own line, and each entry's notes on lines
+/// below it.
+/// If `verbose` is false, each chain of forced choices and follow-up choices (such as the
+/// targeting for an enter-play effect) is folded into the entry before it.
+fn format_history(
+    history: &mut [HistoryEntry],
+    captions: &[(usize, String)],
+    verbose: bool,
+) -> Vec<ListItem<'static>> {
+    let caption_line = |caption: &String| {
+        let style = Style::default().fg(Color::Yellow);
+        (None, Spans::from(Span::styled(caption.clone(), style)))
+    };
+    let note_line = |note: &String| {
+        let style = Style::default().fg(Color::Cyan);
+        (
+            None,
+            Spans::from(Span::styled(
+                format!("  {}: {note}", translate(Section::Ui, "Note")),
+                style,
+            )),
+        )
+    };
+
+    let mut lines: Vec<(Option<Player>, Spans<'static>)> = Vec::new();
+    // the line of the last entry, which later steps can be folded into (past its notes)
+    let mut last_entry_line = None;
+    let mut captions = captions.iter().peekable();
+    for (index, entry) in history.iter_mut().enumerate() {
+        while let Some((_, caption)) = captions.next_if(|(before, _)| *before <= index) {
+            lines.push(caption_line(caption));
+            last_entry_line = None;
+        }
+
+        let chooser = entry.choice.chooser(&entry.game_state);
+        let spans = entry.format();
+        match last_entry_line.map(|line: usize| &mut lines[line]) {
+            Some((Some(first_chooser), composite))
+                if !verbose && (entry.is_forced() || entry.is_follow_up()) =>
+            {
+                // append this step to the previous entry
+                composite.0.push(Span::raw(" → "));
+                if chooser != *first_chooser {
+                    composite.0.push(Span::raw(format!("{chooser:?}: ")));
+                }
+                composite.0.extend(spans.0);
+            }
+            _ => {
+                last_entry_line = Some(lines.len());
+                lines.push((Some(chooser), spans));
+            }
+        }
+        lines.extend(entry.notes.iter().map(note_line));
+    }
+    lines.extend(captions.map(|(_, caption)| caption_line(caption)));
+
+    lines
+        .into_iter()
+        .rev()
+        .map(|(chooser, mut spans)| {
+            if let Some(chooser) = chooser {
+                spans.0.insert(0, Span::raw(format!("{chooser:?}:  ")));
+            }
+            ListItem::new(spans)
+        })
+        .collect()
 }
 
 enum InputMode {
@@ -126,15 +284,36 @@ enum InputMode {
     Editing,
 }
 
+/// The latest game state and choice from the game thread, which the UI takes when it gets a
+/// `RedrawEvent::GameUpdate`.
+type LatestUpdate<'ctype> = Mutex<Option<(GameState<'ctype>, Result<Choice<'ctype>, GameResult>)>>;
+
 /// An event that triggers a redraw.
 enum RedrawEvent {
     Input(Event),
-    GameUpdate(Box<(GameState<'static>, Result<Choice<'static>, GameResult>)>),
+    /// The game thread has put a new game state and choice in the latest update.
+    GameUpdate,
     StatsUpdate(Option<Box<dyn ControllerStats + Send>>, Player),
+    /// New stats from the analysis with the given ID.
+    AnalysisUpdate(u64, Option<Box<dyn ControllerStats + Send>>),
+    /// A caption to show in the log after the moves made so far.
+    Caption(String),
+    /// The game thread has finished the game, with the given decision times (and how the
+    /// players ended it, if they ended it early).
+    GameOver(GameTimes, Option<EarlyEnding>),
     Abort,
 }
 
-struct AppState {
+/// An analysis of the displayed position, shown in the stats pane in place of the players' stats.
+struct Analysis {
+    id: u64,
+    /// The player whose choice is being analyzed
+    player: Player,
+    /// The latest stats from the search, once it has shown any
+    stats: Option<Box<dyn ControllerStats + Send>>,
+}
+
+struct AppState<'ctype> {
     frame_num: usize,
 
     /// Current value of the input box
@@ -144,20 +323,70 @@ struct AppState {
 
     p1_stats: Option<Box<dyn ControllerStats + Send>>,
     p2_stats: Option<Box<dyn ControllerStats + Send>>,
+    /// The analysis of the displayed position, if one was requested since it was reached
+    analysis: Option<Analysis>,
+    /// The number of analyses requested so far, which gives each one its ID
+    num_analyses: u64,
+    /// The channel for sending positions to the analysis worker, while the UI is running
+    analysis_tx: Option<mpsc::Sender<AnalysisRequest<'ctype>>>,
 
-    game_history: Arc<Mutex<Vec<HistoryEntry<'static>>>>,
+    game_history: Arc<Mutex<Vec<HistoryEntry<'ctype>>>>,
+    /// The captions shown in the log, with the number of history entries before each one
+    captions: Vec<(usize, String)>,
+    /// The latest update from the game thread, if the UI hasn't taken it yet
+    latest_update: Arc<LatestUpdate<'ctype>>,
     log_messages: Vec<String>,
     options_height: u16,
+    /// Whether to show every choice in the log, rather than folding forced choices together
+    verbose_log: bool,
+    /// The deck/discard browser overlay, if it is open
+    card_browser: Option<CardBrowser>,
+    /// Whether to show the panel of cards that the human player can't see
+    show_unseen_cards: bool,
+    /// The note being written in the input box, if the user is writing one
+    note: Option<String>,
+    /// The file to export the game to (with its notes) when the UI exits, if any
+    export_path: Option<PathBuf>,
+
+    /// The position being set up with `--sandbox`, until the game starts from it
+    sandbox: Option<Sandbox<'ctype>>,
+    /// The result of the last sandbox command: what changed, or why the command was invalid
+    sandbox_feedback: Option<Result<String, String>>,
 
-    cur_state: GameState<'static>,
-    cur_choice: Result<Choice<'static>, GameResult>,
+    /// The game being played
+    saved_game: SavedGame,
+    /// Whether the game thread has been started for the game being played
+    game_started: bool,
+    /// The match that the game is part of, if playing a best-of-N match
+    match_state: Option<MatchState<'ctype>>,
+    /// The demo to play, until the game thread takes it
+    demo: Option<Demo>,
+    /// Whether the game thread keeps the game autosaved (a game started from a sandbox position
+    /// can't be replayed from its seed, so it isn't, and neither is a demo)
+    autosave: bool,
+    /// If set, only show what this player can see (used when the opponent is a remote human)
+    viewer: Option<Player>,
+    cur_state: GameState<'ctype>,
+    cur_choice: Result<Choice<'ctype>, GameResult>,
+    /// The groups of equivalent options for the current choice.
+    cur_option_groups: Vec<OptionGroup>,
 }
 
-impl AppState {
+impl<'ctype> AppState<'ctype> {
     fn run(&mut self) -> io::Result<()> {
+        // the game thread borrows the card types, so it's scoped to this function; this lets the
+        // card types be dropped (or reloaded) once the game is over
+        thread::scope(|scope| self.run_in_scope(scope))
+    }
+
+    fn run_in_scope<'scope>(&mut self, scope: &'scope Scope<'scope, '_>) -> io::Result<()>
+    where
+        'ctype: 'scope,
+    {
         // create a channel for sending events to the UI to trigger redraws
         let (event_tx, event_rx) = mpsc::channel();
         *STATS_TX.lock().unwrap() = Some(event_tx.clone());
+        controllers::set_caption_sink(Some(Box::new(show_caption)));
 
         // setup terminal
         enable_raw_mode()?;
@@ -171,6 +400,14 @@ impl AppState {
         // set a hook that restores the terminal in case of a panic
         let original_hook = std::panic::take_hook();
         panic::set_hook(Box::new(move |panic_info| {
+            if thread::current().name() == Some(TIME_LIMIT_THREAD_NAME) {
+                // a random move will be made instead, so the game goes on
+                CONTROLLER_PANICS
+                    .lock()
+                    .unwrap()
+                    .push(panic_info.to_string());
+                return;
+            }
             restore_terminal().expect("error restoring terminal in panic hook");
             original_hook(panic_info);
         }));
@@ -179,15 +416,31 @@ impl AppState {
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
 
-        // spawn a thread to generate RedrawEvents for terminal input events
+        // spawn a thread to generate RedrawEvents for terminal input events, until the UI exits
+        let ui_exited = Arc::new(AtomicBool::new(false));
+        let ui_exited2 = ui_exited.clone();
         let event_tx2 = event_tx.clone();
-        spawn_monitored_thread("input event thread", event_tx.clone(), move || loop {
-            let event = event::read().expect("Failed to read crossterm event");
-            event_tx2
-                .send(RedrawEvent::Input(event))
-                .expect("Failed to send crossterm event");
+        spawn_monitored_thread(scope, "input event thread", event_tx.clone(), move || {
+            while !ui_exited2.load(Ordering::Relaxed) {
+                if event::poll(INPUT_POLL_INTERVAL).expect("Failed to poll crossterm events") {
+                    let event = event::read().expect("Failed to read crossterm event");
+                    event_tx2
+                        .send(RedrawEvent::Input(event))
+                        .expect("Failed to send crossterm event");
+                }
+            }
         })?;
 
+        // spawn the analysis worker, which runs until the UI drops its channel
+        let (analysis_tx, analysis_rx) = mpsc::channel();
+        self.analysis_tx = Some(analysis_tx);
+        spawn_monitored_thread(scope, ANALYSIS_THREAD_NAME, event_tx.clone(), move || {
+            analysis::worker_main(analysis_rx)
+        })?;
+
+        let mut game_thread = None;
+        let min_frame_interval = *MIN_FRAME_INTERVAL.lock().unwrap();
+
         let was_aborted = 'main_loop: loop {
             // update the app state
             self.frame_num += 1;
@@ -197,81 +450,220 @@ impl AppState {
             terminal.draw(|f| ui(f, self))?;
             self.log_messages
                 .push(format!("Frame took {:?}", start.elapsed()));
+            let next_frame_time = start + min_frame_interval;
 
-            if self.frame_num == 1 {
-                // launch the game thread after drawing the first frame
-                // (this makes panic messages nicer if it immediately panics)
+            if !self.game_started && self.sandbox.is_none() {
+                // launch the game thread after drawing the first frame, or once the sandbox
+                // position is ready (this makes panic messages nicer if it immediately panics)
                 let game_history = self.game_history.clone();
+                let latest_update = self.latest_update.clone();
                 let initial_state = self.cur_state.clone();
                 let initial_choice = self.cur_choice.clone();
+                let saved_game = self.saved_game.clone();
+                let autosave = self.autosave;
+                let demo = self.demo.take();
                 let event_tx2 = event_tx.clone();
-                spawn_monitored_thread("game thread", event_tx.clone(), move || {
-                    game_thread::game_thread_main(
-                        initial_state,
-                        initial_choice,
-                        event_tx2,
-                        game_history,
-                    )
-                })?;
+                self.game_started = true;
+                game_thread = Some(spawn_monitored_thread(
+                    scope,
+                    "game thread",
+                    event_tx.clone(),
+                    move || {
+                        game_thread::game_thread_main(
+                            scope,
+                            initial_state,
+                            initial_choice,
+                            saved_game,
+                            autosave,
+                            demo,
+                            event_tx2,
+                            game_history,
+                            latest_update,
+                        )
+                    },
+                )?);
             }
 
-            // wait for events and handle them
-            let mut event = event_rx.recv().expect("event channel disconnected");
+            // wait for events and handle them, until one of them changes what's shown; then keep
+            // handling the events that arrive before the next frame is due, so that a burst of
+            // updates is drawn in one frame (a frame is drawn right away if it's already due, so
+            // input isn't delayed when the UI is idle)
+            let mut dirty = false;
             loop {
+                let event = if dirty {
+                    let timeout = next_frame_time.saturating_duration_since(Instant::now());
+                    match event_rx.recv_timeout(timeout) {
+                        Ok(event) => event,
+                        Err(mpsc::RecvTimeoutError::Timeout) => break,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => {
+                            panic!("event channel disconnected");
+                        }
+                    }
+                } else {
+                    event_rx.recv().expect("event channel disconnected")
+                };
+
                 // handle the event
                 match event {
+                    RedrawEvent::Input(Event::Resize(width, height)) => {
+                        // resize the terminal's buffers now, so the next frame is drawn in full
+                        // at the new size
+                        terminal.resize(Rect::new(0, 0, width, height))?;
+                        self.options_height = self.options_height.min(max_options_height(height));
+                        dirty = true;
+                    }
                     RedrawEvent::Input(event) => {
                         if let Event::Key(key) = event {
                             if self.handle_key_event(key) {
                                 break 'main_loop false;
                             }
+                            dirty = true;
                         }
                     }
-                    RedrawEvent::GameUpdate(update_data) => {
-                        let (new_state, new_choice) = *update_data;
-                        self.cur_state = new_state;
-                        self.cur_choice = new_choice;
+                    RedrawEvent::GameUpdate => {
+                        // an earlier event may have already taken this update
+                        let update = self.latest_update.lock().unwrap().take();
+                        if let Some((new_state, new_choice)) = update {
+                            self.cur_option_groups = match &new_choice {
+                                Ok(choice) => choice.option_groups(&new_state),
+                                Err(_) => Vec::new(),
+                            };
+                            self.cur_state = new_state;
+                            self.cur_choice = new_choice;
+                            self.analysis = None;
+                            dirty = true;
+                        }
                     }
-                    RedrawEvent::StatsUpdate(stats, player) => match player {
-                        Player::Player1 => self.p1_stats = stats,
-                        Player::Player2 => self.p2_stats = stats,
-                    },
-                    RedrawEvent::Abort => break 'main_loop true,
-                }
-
-                // get the next event (if any is available now)
-                event = match event_rx.try_recv() {
-                    Ok(event) => event,
-                    Err(mpsc::TryRecvError::Empty) => break,
-                    Err(mpsc::TryRecvError::Disconnected) => {
-                        panic!("event channel disconnected");
+                    RedrawEvent::StatsUpdate(stats, player) => {
+                        match player {
+                            Player::Player1 => self.p1_stats = stats,
+                            Player::Player2 => self.p2_stats = stats,
+                        }
+                        dirty = true;
+                    }
+                    RedrawEvent::AnalysisUpdate(id, stats) => {
+                        // ignore the stats of analyses of earlier positions
+                        if let Some(analysis) = self.analysis.as_mut().filter(|a| a.id == id) {
+                            analysis.stats = stats;
+                            dirty = true;
+                        }
+                    }
+                    RedrawEvent::Caption(caption) => {
+                        let num_entries = self.game_history.lock().unwrap().len();
+                        self.captions.push((num_entries, caption));
+                        dirty = true;
                     }
+                    RedrawEvent::GameOver(times, early_ending) => {
+                        self.record_match_game(times, early_ending);
+                        dirty = true;
+                    }
+                    RedrawEvent::Abort => break 'main_loop true,
                 }
             }
         };
 
+        ui_exited.store(true, Ordering::Relaxed);
+        self.analysis_tx = None;
         let _ = panic::take_hook();
 
         // restore terminal
         restore_terminal()?;
+        self.export_game();
+
+        for message in CONTROLLER_PANICS.lock().unwrap().drain(..) {
+            eprintln!("An AI controller panicked (a random move was made instead): {message}");
+        }
+        if let Some(match_state) = &self.match_state {
+            for line in match_state.summary() {
+                println!("{line}");
+            }
+        }
 
         if was_aborted {
             std::process::exit(1);
         }
+        if game_thread.map_or(false, |handle| !handle.is_finished()) {
+            // the game thread can't be interrupted in the middle of a choice (it may be waiting
+            // for a human or remote player), so the scope can't be left until the game ends;
+            // quitting mid-game ends the program instead, and the autosave lets it be resumed
+            metrics::print_report();
+            std::process::exit(0);
+        }
         Ok(())
     }
 
     /// Handles a KeyEvent. Returns true if the app should quit.
     fn handle_key_event(&mut self, key: KeyEvent) -> bool {
+        if let Some(card_browser) = &mut self.card_browser {
+            // the browser takes all input while it is open
+            if card_browser.handle_key_event(key, self.viewer.is_none()) {
+                self.card_browser = None;
+            }
+            return false;
+        }
+        if let Some(note) = &mut self.note {
+            // the note takes all input while it is being written
+            match key.code {
+                KeyCode::Enter => {
+                    let note = self.note.take().unwrap_or_default();
+                    self.add_note(note.trim());
+                }
+                KeyCode::Char(c) => note.push(c),
+                KeyCode::Backspace => {
+                    note.pop();
+                }
+                KeyCode::Esc => self.note = None,
+                _ => {}
+            }
+            return false;
+        }
+
         match self.input_mode {
             InputMode::Normal => match key.code {
-                KeyCode::Enter if !USER_INPUT_REQUESTS.lock().unwrap().is_empty() => {
+                KeyCode::Enter
+                    if self.sandbox.is_some()
+                        || !USER_INPUT_REQUESTS.lock().unwrap().is_empty() =>
+                {
                     self.input_mode = InputMode::Editing;
                 }
                 KeyCode::Char('s') => {
                     // shrink the options pane to fit
                     self.options_height = 0;
                 }
+                KeyCode::Char('v') => {
+                    // toggle folding forced choices in the log
+                    self.verbose_log = !self.verbose_log;
+                }
+                KeyCode::Char('b') => {
+                    // open the deck/discard browser
+                    self.card_browser = Some(CardBrowser::new());
+                }
+                KeyCode::Char('a') if self.analysis.is_some() => {
+                    // close the analysis, showing the players' stats again
+                    self.analysis = None;
+                }
+                KeyCode::Char('a') => {
+                    // analyze the displayed position
+                    self.request_analysis();
+                }
+                KeyCode::Char('u') => {
+                    // toggle the panel of cards that the human player can't see
+                    self.show_unseen_cards = !self.show_unseen_cards;
+                }
+                KeyCode::Char('n')
+                    if self
+                        .match_state
+                        .as_ref()
+                        .map_or(false, MatchState::is_between_games) =>
+                {
+                    self.start_next_match_game();
+                }
+                KeyCode::Char('n')
+                    if self.sandbox.is_none() && !self.game_history.lock().unwrap().is_empty() =>
+                {
+                    // start writing a note on the last entry in the log
+                    self.note = Some(String::new());
+                }
                 KeyCode::Char('d') => {
                     // increment the debug counter
                     DEBUG_COUNTER.fetch_add(1, Ordering::Relaxed);
@@ -283,6 +675,10 @@ impl AppState {
                 _ => {}
             },
             InputMode::Editing => match key.code {
+                KeyCode::Enter if !self.input.is_empty() && self.sandbox.is_some() => {
+                    let command = mem::take(&mut self.input);
+                    self.handle_sandbox_command(command.trim());
+                }
                 KeyCode::Enter if !self.input.is_empty() => {
                     let mut input_requests = USER_INPUT_REQUESTS.lock().unwrap();
                     if let Some(tx) = input_requests.pop_front() {
@@ -304,9 +700,181 @@ impl AppState {
         }
         false // don't quit the app
     }
+
+    /// Asks the analysis worker to analyze the displayed position, if it's a choice with more
+    /// than one option that can be analyzed without seeing hidden information.
+    fn request_analysis(&mut self) {
+        let choice = match &self.cur_choice {
+            Ok(choice) if self.sandbox.is_none() && choice.num_options() > 1 => choice,
+            _ => return,
+        };
+        let chooser = choice.chooser(&self.cur_state);
+        if self.viewer.map_or(false, |viewer| viewer != chooser) {
+            // the search would see the opponent's hand
+            return;
+        }
+        let analysis_tx = match &self.analysis_tx {
+            Some(analysis_tx) => analysis_tx,
+            None => return,
+        };
+
+        self.num_analyses += 1;
+        let request = AnalysisRequest {
+            id: self.num_analyses,
+            game_state: self.cur_state.clone(),
+            choice: choice.clone(),
+        };
+        analysis_tx
+            .send(request)
+            .expect("Failed to send an analysis request");
+        self.analysis = Some(Analysis {
+            id: self.num_analyses,
+            player: chooser,
+            stats: None,
+        });
+    }
+
+    /// Attaches a note to the last entry in the game history (and to the autosaved game).
+    fn add_note(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let mut game_history = self.game_history.lock().unwrap();
+        let move_index = match game_history.len().checked_sub(1) {
+            Some(move_index) => move_index,
+            None => return,
+        };
+        game_history[move_index].notes.push(text.to_string());
+        autosave::add_note(Note {
+            move_index,
+            text: text.to_string(),
+        });
+    }
+
+    /// Writes the game played so far, with its notes, to the export file (if there is one).
+    fn export_game(&self) {
+        let path = match &self.export_path {
+            Some(path) => path,
+            None => return,
+        };
+        let game_history = self.game_history.lock().unwrap();
+        let saved_game = SavedGame {
+            moves: game_history
+                .iter()
+                .map(|entry| entry.chosen_option)
+                .collect(),
+            notes: game_history
+                .iter()
+                .enumerate()
+                .flat_map(|(move_index, entry)| {
+                    entry.notes.iter().map(move |text| Note {
+                        move_index,
+                        text: text.clone(),
+                    })
+                })
+                .collect(),
+            ..self.saved_game.clone()
+        };
+        match fs::write(path, saved_game.to_file_contents()) {
+            Ok(()) => println!("Exported the game to {}", path.display()),
+            Err(err) => eprintln!("Failed to export the game to {}: {err}", path.display()),
+        }
+    }
+
+    /// Records the result of the game that just ended in the match (if playing one), and shows
+    /// how to start the next game, or the match statistics if the match is over.
+    fn record_match_game(&mut self, times: GameTimes, early_ending: Option<EarlyEnding>) {
+        let match_state = match &mut self.match_state {
+            Some(match_state) => match_state,
+            None => return,
+        };
+        let result = match self.cur_choice {
+            Ok(_) => return, // the game thread only finishes once the game is over
+            Err(result) => result,
+        };
+        let num_entries = self.game_history.lock().unwrap().len();
+        match_state.record_game(result, early_ending, num_entries, times);
+
+        let captions = if match_state.is_between_games() {
+            vec![format!(
+                "{}. Press N to start the next game.",
+                match_state.header()
+            )]
+        } else {
+            match_state.summary()
+        };
+        self.captions
+            .extend(captions.into_iter().map(|caption| (num_entries, caption)));
+    }
+
+    /// Deals the next game of the match and starts it, clearing the last game's log and stats.
+    fn start_next_match_game(&mut self) {
+        let match_state = match &mut self.match_state {
+            Some(match_state) => match_state,
+            None => return,
+        };
+        let (saved_game, game_state, choice) = match_state.deal_next_game(&self.saved_game);
+
+        self.game_history.lock().unwrap().clear();
+        self.captions.clear();
+        *self.latest_update.lock().unwrap() = None;
+        self.p1_stats = None;
+        self.p2_stats = None;
+        self.analysis = None;
+        self.cur_option_groups = choice.option_groups(&game_state);
+        self.cur_state = game_state;
+        self.cur_choice = Ok(choice);
+        self.saved_game = saved_game;
+        self.game_started = false;
+    }
+
+    /// Applies a command to the sandbox position, or starts the game from it if the command is
+    /// `start` and the position is valid.
+    fn handle_sandbox_command(&mut self, command: &str) {
+        let sandbox = match &mut self.sandbox {
+            Some(sandbox) => sandbox,
+            None => return,
+        };
+        if !command.eq_ignore_ascii_case("start") {
+            self.sandbox_feedback = Some(sandbox.apply(command));
+            return;
+        }
+
+        match sandbox.start() {
+            Ok((game_state, choice)) => {
+                self.cur_option_groups = choice.option_groups(&game_state);
+                self.cur_state = game_state;
+                self.cur_choice = Ok(choice);
+                self.sandbox = None;
+                self.sandbox_feedback = None;
+                self.input_mode = InputMode::Normal;
+            }
+            Err(message) => self.sandbox_feedback = Some(Err(message)),
+        }
+    }
 }
 
 fn ui<B: Backend>(f: &mut Frame<B>, app: &mut AppState) {
+    let size = f.size();
+    let (min_width, min_height) = MIN_TERMINAL_SIZE;
+    if size.width < min_width || size.height < min_height {
+        // the panes can't be laid out in so little space, so just ask for more
+        let message = format!(
+            "{} {min_width}×{min_height} ({}×{}).",
+            translate(
+                Section::Ui,
+                "The terminal is too small for RadBot; please enlarge it to at least"
+            ),
+            size.width,
+            size.height,
+        );
+        let paragraph = Paragraph::new(message)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, size);
+        return;
+    }
+
     // compute the top-level layout rects
     let [left_rect, right_rect] = Layout::default()
         .direction(Direction::Horizontal)
@@ -314,31 +882,50 @@ fn ui<B: Backend>(f: &mut Frame<B>, app: &mut AppState) {
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(f.size());
 
-    let max_player_height = [Player::Player1, Player::Player2]
-        .into_iter()
-        .map(|player| {
-            let player_state = app.cur_state.player(player);
-            let hand_len =
-                player_state.hand.count_unique() + (player_state.has_water_silo as usize);
-            usize::max(hand_len, 4) + 5
-        })
-        .max()
-        .unwrap();
-    let game_state_height = max_player_height * 2 + 1;
+    // while a sandbox position is being set up, show it instead of the game
+    let (cur_state, cur_choice) = match &app.sandbox {
+        Some(sandbox) => (sandbox.game_state(), None),
+        None => (&app.cur_state, app.cur_choice.as_ref().ok()),
+    };
+
+    let game_state_height = GameStateWidget::desired_height(cur_state);
 
     let [log_rect, stats_rect] = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(right_rect);
+    let (stats_rect, unseen_cards_rect) = if app.show_unseen_cards {
+        let [stats_rect, unseen_cards_rect] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(stats_rect);
+        (stats_rect, Some(unseen_cards_rect))
+    } else {
+        (stats_rect, None)
+    };
 
     // render the log pane
     let mut options = Vec::new();
-    if let Ok(choice) = &app.cur_choice {
-        let num_options = choice.num_options(&app.cur_state);
-        options = (0..num_options)
-            .map(|i| {
-                let mut spans = choice.format_option(i, &app.cur_state);
-                let num_string = format!("({})", i + 1);
+    if app.sandbox.is_some() {
+        // list the edit commands, with the result of the last one below them
+        options.extend(app.sandbox_feedback.iter().map(|feedback| match feedback {
+            Ok(message) => ListItem::new(message.clone()).style(Style::default().fg(Color::Green)),
+            Err(message) => ListItem::new(message.clone()).style(Style::default().fg(Color::Red)),
+        }));
+        options.extend(
+            sandbox::COMMAND_HELP
+                .iter()
+                .rev()
+                .map(|&usage| ListItem::new(format!("  {usage}"))),
+        );
+    } else if let Some(choice) = cur_choice {
+        // only list one option out of each group of equivalent options
+        options = app
+            .cur_option_groups
+            .iter()
+            .map(|&group| {
+                let mut spans = choice.format_option_group(group, cur_state);
+                let num_string = format!("({})", group.option + 1);
                 spans.0.insert(0, Span::raw(format!("{num_string:>5}  ")));
                 ListItem::new(spans)
             })
@@ -348,16 +935,7 @@ fn ui<B: Backend>(f: &mut Frame<B>, app: &mut AppState) {
 
     let mut history_items = {
         let mut game_history = app.game_history.lock().unwrap();
-        game_history
-            .iter_mut()
-            .rev()
-            .map(|entry| {
-                let mut spans = entry.format();
-                let chooser = entry.choice.chooser(&entry.game_state);
-                spans.0.insert(0, Span::raw(format!("{chooser:?}:  ")));
-                ListItem::new(spans)
-            })
-            .collect_vec()
+        format_history(&mut game_history, &app.captions, app.verbose_log)
     };
     if let Err(game_result) = app.cur_choice {
         let message = match game_result {
@@ -365,23 +943,27 @@ fn ui<B: Backend>(f: &mut Frame<B>, app: &mut AppState) {
             GameResult::P2Wins => "Player 2 wins!",
             GameResult::Tie => "The game ends in a tie!",
         };
-        history_items.insert(0, ListItem::new(message));
+        history_items.insert(0, ListItem::new(translate(Section::Ui, message)));
     }
 
-    let desired_options_height: u16 = (options.len() + 1).try_into().unwrap();
-    app.options_height = app.options_height.max(desired_options_height);
+    let desired_options_height = u16::try_from(options.len() + 1).unwrap_or(u16::MAX);
+    app.options_height = app
+        .options_height
+        .max(desired_options_height)
+        .min(max_options_height(size.height));
 
-    let [game_state_rect, options_rect, input_rect] = Layout::default()
+    let [game_state_rect, options_rect, status_rect, input_rect] = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Min(game_state_height.try_into().unwrap()),
+            Constraint::Min(u16::try_from(game_state_height).unwrap_or(u16::MAX)),
             Constraint::Length(app.options_height),
+            Constraint::Length(1),
             Constraint::Length(3),
         ])
         .split(left_rect);
 
     let block = Block::default()
-        .title(" Log ")
+        .title(format!(" {} ", translate(Section::Ui, "Log")))
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL - Borders::BOTTOM);
     let logs = List::new(history_items)
@@ -389,8 +971,9 @@ fn ui<B: Backend>(f: &mut Frame<B>, app: &mut AppState) {
         .start_corner(Corner::BottomLeft);
     f.render_widget(logs, log_rect);
 
+    let options_title = if app.sandbox.is_some() { "Sandbox" } else { "Options" };
     let block = Block::default()
-        .title(" Options ")
+        .title(format!(" {} ", translate(Section::Ui, options_title)))
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL - Borders::BOTTOM);
     let options = List::new(options)
@@ -398,68 +981,99 @@ fn ui<B: Backend>(f: &mut Frame<B>, app: &mut AppState) {
         .start_corner(Corner::BottomLeft);
     f.render_widget(options, options_rect);
 
-    // render the input box
-    let input = Paragraph::new(app.input.as_ref())
-        .style(match app.input_mode {
-            InputMode::Normal => Style::default(),
-            InputMode::Editing => Style::default().fg(Color::Yellow),
-        })
+    // render the status bar, summarizing the turn at the current choice
+    if let Some(choice) = cur_choice {
+        let mut status = TurnStatus::new(cur_state, choice).format();
+        status.0.insert(0, Span::raw(" "));
+        f.render_widget(Paragraph::new(status), status_rect);
+    }
+
+    // render the input box (or the note being written, in its place)
+    let (input_text, input_title, editing) = match &app.note {
+        Some(note) => (note, "Note", true),
+        None => (
+            &app.input,
+            "Input",
+            matches!(app.input_mode, InputMode::Editing),
+        ),
+    };
+    let input = Paragraph::new(input_text.as_ref())
+        .style(if editing { Style::default().fg(Color::Yellow) } else { Style::default() })
         .block(
             Block::default()
-                .title(" Input ")
+                .title(format!(" {} ", translate(Section::Ui, input_title)))
                 .title_alignment(Alignment::Center)
                 .borders(Borders::ALL),
         );
     f.render_widget(input, input_rect);
-    match app.input_mode {
-        // in Normal mode, hide the cursor (Frame's default)
-        InputMode::Normal => {}
-
-        // in Editing mode, make the cursor visible and ask tui to put it at
-        // the specified coordinates after rendering
-        InputMode::Editing => f.set_cursor(
+    if editing {
+        // make the cursor visible (it's hidden by default) and ask tui to put it at the
+        // specified coordinates after rendering
+        f.set_cursor(
             // put cursor past the end of the input text
-            input_rect.x + app.input.width() as u16 + 1,
+            input_rect.x + input_text.width() as u16 + 1,
             // move one line down, from the border to the input line
             input_rect.y + 1,
-        ),
+        );
     }
 
     // render the game state pane
+    let game_state_title = match &app.match_state {
+        Some(match_state) => format!(
+            " {} — {} ",
+            translate(Section::Ui, "Game State"),
+            match_state.header()
+        ),
+        None => format!(" {} ", translate(Section::Ui, "Game State")),
+    };
     let block = Block::default()
-        .title(" Game State ")
+        .title(game_state_title)
         .title_alignment(Alignment::Center)
         .borders(Borders::NONE);
     f.render_widget(
         GameStateWidget {
             block,
-            game_state: &app.cur_state,
-            choice: app.cur_choice.as_ref().ok(),
+            game_state: cur_state,
+            choice: cur_choice,
+            viewer: app.viewer,
+            preview_option: match app.input.trim().parse::<usize>() {
+                Ok(option_number) => option_number.checked_sub(1),
+                Err(_) => None,
+            },
         },
         game_state_rect,
     );
 
-    // render the stats pane
-    let p1_stats = app.p1_stats.as_mut().map(|s| (s, Player::Player1));
-    let p2_stats = app.p2_stats.as_mut().map(|s| (s, Player::Player2));
+    // render the stats pane, with the analysis of the displayed position in place of the
+    // players' stats if there is one
     let cur_player = match &app.cur_choice {
         Ok(choice) => choice.chooser(&app.cur_state),
         Err(_) => app.cur_state.cur_player,
     };
-    let stats_info = match cur_player {
-        Player::Player1 => p1_stats.or(p2_stats),
-        Player::Player2 => p2_stats.or(p1_stats),
-    };
-    let (stats_widget, stats_player) = match stats_info {
-        Some((w, p)) => (Some(w), Some(p)),
-        None => (None, None),
+    let (stats_title, stats_widget) = match &mut app.analysis {
+        Some(analysis) => (
+            match analysis.player {
+                Player::Player1 => "Analysis (Player 1)",
+                Player::Player2 => "Analysis (Player 2)",
+            },
+            analysis.stats.as_mut(),
+        ),
+        None => {
+            let p1_stats = app.p1_stats.as_mut().map(|s| (s, Player::Player1));
+            let p2_stats = app.p2_stats.as_mut().map(|s| (s, Player::Player2));
+            let stats_info = match cur_player {
+                Player::Player1 => p1_stats.or(p2_stats),
+                Player::Player2 => p2_stats.or(p1_stats),
+            };
+            match stats_info {
+                Some((w, Player::Player1)) => ("Stats (Player 1)", Some(w)),
+                Some((w, Player::Player2)) => ("Stats (Player 2)", Some(w)),
+                None => ("Stats", None),
+            }
+        }
     };
     let block = Block::default()
-        .title(match stats_player {
-            None => " Stats ",
-            Some(Player::Player1) => " Stats (Player 1) ",
-            Some(Player::Player2) => " Stats (Player 2) ",
-        })
+        .title(format!(" {} ", translate(Section::Ui, stats_title)))
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL);
     let inner_area = block.inner(stats_rect);
@@ -467,6 +1081,78 @@ fn ui<B: Backend>(f: &mut Frame<B>, app: &mut AppState) {
     if let Some(stats_widget) = stats_widget {
         f.render_widget(StatsWidget(stats_widget.as_mut()), inner_area);
     }
+
+    // render the unseen cards panel, from the human player's view (or the current player's, if
+    // neither or both players are human)
+    if let Some(unseen_cards_rect) = unseen_cards_rect {
+        let human_players = [
+            (Player::Player1, app.saved_game.p1),
+            (Player::Player2, app.saved_game.p2),
+        ]
+        .into_iter()
+        .filter(|(_, spec)| matches!(spec, ControllerSpec::Human { .. }))
+        .map(|(player, _)| player)
+        .collect_vec();
+        let player = match (app.viewer, human_players.as_slice()) {
+            (Some(viewer), _) => viewer,
+            (None, &[human_player]) => human_player,
+            _ => cur_player,
+        };
+        f.render_widget(
+            UnseenCardsWidget {
+                game_view: cur_state.view_for(player),
+            },
+            unseen_cards_rect,
+        );
+    }
+
+    // render the deck/discard browser over everything else
+    if let Some(card_browser) = &mut app.card_browser {
+        f.render_widget(
+            CardBrowserWidget {
+                browser: card_browser,
+                game_state: cur_state,
+                // like the opponent's hand, the deck is only hidden when there is a viewer
+                can_see_deck: app.viewer.is_none(),
+            },
+            f.size(),
+        );
+    }
+}
+
+/// Renders the game state as seen by the given player into lines of plain text, `width`
+/// columns wide.
+pub fn render_game_view_text<'ctype>(
+    game_state: &GameState<'ctype>,
+    choice: &Choice<'ctype>,
+    viewer: Player,
+    width: u16,
+) -> Vec<String> {
+    let height = GameStateWidget::desired_height(game_state)
+        .try_into()
+        .unwrap();
+    let area = Rect::new(0, 0, width, height);
+    let mut buf = Buffer::empty(area);
+    GameStateWidget {
+        block: Block::default(),
+        game_state,
+        choice: Some(choice),
+        viewer: Some(viewer),
+        preview_option: None,
+    }
+    .render(area, &mut buf);
+
+    (0..height)
+        .map(|y| {
+            let line: String = (0..width).map(|x| buf.get(x, y).symbol.as_str()).collect();
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Returns the plain text of the given spans, without styling.
+pub fn spans_to_string(spans: &Spans) -> String {
+    spans.0.iter().map(|span| span.content.as_ref()).collect()
 }
 
 struct StatsWidget<'a>(&'a mut dyn ControllerStats);
@@ -476,13 +1162,46 @@ impl Widget for StatsWidget<'_> {
     }
 }
 
-pub(crate) fn main() -> io::Result<()> {
-    lazy_static! {
-        static ref CAMP_TYPES: Vec<CampType> = get_camp_types();
-        static ref PERSON_TYPES: Vec<PersonType> = get_person_types();
-        static ref EVENT_TYPES: Vec<EventType> = get_event_types();
+/// Runs the UI for the given (new or resumed) game, which is played with the given card set.
+/// If `sandbox` is true, the user first sets up the position to play from, starting from the
+/// new game's setup. If `demo` is given, its script is played instead (and `saved_game` should
+/// be its game). If `best_of` is given, a best-of-N match is played, starting with this game.
+/// If `export_path` is given, the game is written to it (with the notes attached to it) when the
+/// UI exits. Returns once the game (or match) is over and the user quits; quitting mid-game ends
+/// the program.
+pub(crate) fn main(
+    saved_game: SavedGame,
+    sandbox: bool,
+    demo: Option<Demo>,
+    best_of: Option<u32>,
+    export_path: Option<PathBuf>,
+    card_set: &CardSet,
+) -> io::Result<()> {
+    let CardSet {
+        camp_types,
+        person_types,
+        event_types,
+    } = card_set;
+    // replay the game's moves so far, recording them in the history
+    let mut game_history = Vec::new();
+    let (game_state, choice) = saved_game
+        .replay(
+            camp_types,
+            person_types,
+            event_types,
+            |game_state, choice, chosen_option| {
+                game_history.push(HistoryEntry {
+                    game_state: game_state.clone(),
+                    choice: choice.clone(),
+                    chosen_option,
+                    notes: Vec::new(),
+                });
+            },
+        )
+        .expect("Failed to replay the saved game");
+    for note in &saved_game.notes {
+        game_history[note.move_index].notes.push(note.text.clone());
     }
-    let (game_state, choice) = GameState::new(&CAMP_TYPES, &PERSON_TYPES, &EVENT_TYPES);
 
     let mut app = AppState {
         frame_num: 0,
@@ -490,11 +1209,53 @@ pub(crate) fn main() -> io::Result<()> {
         input_mode: InputMode::Normal,
         p1_stats: None,
         p2_stats: None,
-        game_history: Arc::new(Mutex::new(Vec::new())),
+        analysis: None,
+        num_analyses: 0,
+        analysis_tx: None,
+        game_history: Arc::new(Mutex::new(game_history)),
+        captions: Vec::new(),
+        latest_update: Arc::new(Mutex::new(None)),
         log_messages: Vec::new(),
         options_height: 0,
+        verbose_log: false,
+        card_browser: None,
+        show_unseen_cards: false,
+        note: None,
+        export_path,
+        cur_option_groups: match &choice {
+            Ok(choice) => choice.option_groups(&game_state),
+            Err(_) => Vec::new(),
+        },
+        viewer: match saved_game.p2 {
+            ControllerSpec::Remote { .. } => Some(Player::Player1),
+            _ => None,
+        },
+        sandbox: sandbox.then(|| {
+            let rules = saved_game.rules;
+            Sandbox::new(
+                camp_types,
+                person_types,
+                event_types,
+                rules,
+                saved_game.seed,
+            )
+        }),
+        sandbox_feedback: None,
+        autosave: !sandbox && demo.is_none(),
+        demo,
+        saved_game,
+        game_started: false,
+        match_state: best_of.map(|best_of| {
+            MatchState::new(
+                best_of,
+                game_state.cur_player,
+                camp_types,
+                person_types,
+                event_types,
+            )
+        }),
         cur_state: game_state,
-        cur_choice: Ok(choice),
+        cur_choice: choice,
     };
 
     app.run()
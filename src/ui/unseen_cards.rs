@@ -0,0 +1,47 @@
+use itertools::Itertools;
+use tui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, Widget},
+};
+
+use crate::locale::{translate, Section};
+use crate::radlands::{locations::Player, styles::StyledName, GameView};
+
+/// A widget that lists the cards a player can't see (the deck, their opponent's hand, and the
+/// punks' face-down cards, combined), with how many copies of each card type are among them.
+pub struct UnseenCardsWidget<'g, 'ctype: 'g> {
+    pub game_view: GameView<'g, 'ctype>,
+}
+
+impl Widget for UnseenCardsWidget<'_, '_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let unseen_cards = self.game_view.unaccounted_cards();
+
+        let title = translate(
+            Section::Ui,
+            match self.game_view.player() {
+                Player::Player1 => "Unseen by Player 1",
+                Player::Player2 => "Unseen by Player 2",
+            },
+        );
+        let cards = translate(Section::Ui, "cards");
+        let block = Block::default()
+            .title(format!(" {title} ({} {cards}) ", unseen_cards.count()))
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL);
+
+        let items = unseen_cards
+            .iter()
+            .sorted_by_key(|(card, _)| card.display_name())
+            .map(|(card, count)| {
+                ListItem::new(Spans(vec![
+                    Span::raw(format!("{count:>3}x  ")),
+                    card.styled_name(),
+                ]))
+            })
+            .collect_vec();
+        List::new(items).block(block).render(area, buf);
+    }
+}